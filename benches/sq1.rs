@@ -0,0 +1,71 @@
+//! Benchmarks of moves, scramble generation
+//! and solving, run with
+//! cargo bench --bench sq1.
+//!
+//! Criterion reads the arguments after --,
+//! such as a name filter, --save-baseline
+//! NAME to store the results and --baseline
+//! NAME to compare with stored results.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sq1_scrambler::cube::SqOne;
+use sq1_scrambler::{solver, Move, Scramble, ScrambleOptions};
+
+/// Benchmarks single moves and the
+/// copies a search makes.
+fn moves(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(585);
+    let mut scrambled = SqOne::new();
+    let scramble = scrambled.scramble_with_rng(&ScrambleOptions::new(), &mut rng);
+
+    // Twisting both layers by a half turn
+    // and slashing from a solved cube can
+    // be repeated forever.
+    let mut cube = SqOne::new();
+    c.bench_function("twist", |b| b.iter(|| cube.apply_move(black_box(Move::Twist(6, 6)))));
+    let mut cube = SqOne::new();
+    c.bench_function("slash", |b| b.iter(|| cube.apply_move(black_box(Move::Slash))));
+    c.bench_function("clone", |b| b.iter(|| scrambled.clone()));
+
+    // Like a search expanding a node,
+    // trying each twist on a copy.
+    c.bench_function("expand_node", |b| {
+        b.iter(|| {
+            (-5..=6)
+                .filter_map(|top| {
+                    let mut child = scrambled.clone();
+                    child.apply_move(Move::Twist(top, 0)).ok()?;
+                    child.apply_move(Move::Slash).ok()
+                })
+                .count()
+        })
+    });
+    c.bench_function("apply_scramble", |b| b.iter(|| scramble.verify(&scrambled)));
+    c.bench_function("format_scramble", |b| b.iter(|| scramble.to_string()));
+}
+
+/// Benchmarks generating scrambles
+/// and solving.
+fn scrambles(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(585);
+    let mut scrambled = SqOne::new();
+    scrambled.scramble_with_rng(&ScrambleOptions::new(), &mut rng);
+
+    c.bench_function("scramble_random_moves", |b| {
+        b.iter(|| SqOne::new().scramble_with_rng(&ScrambleOptions::new(), &mut rng))
+    });
+    let random_state = ScrambleOptions::new().random_state(true);
+    c.bench_function("scramble_random_state", |b| b.iter(|| SqOne::new().scramble_with_rng(&random_state, &mut rng)));
+    c.bench_function("solve", |b| b.iter(|| solver::solve(&scrambled)));
+    c.bench_function("solve_n_5", |b| b.iter(|| solver::solve_n(&scrambled, 5)));
+    c.bench_function("generate_batch_100", |b| {
+        b.iter(|| Scramble::generate_batch_seeded(100, &ScrambleOptions::new(), 585).unwrap())
+    });
+}
+
+criterion_group!(benches, moves, scrambles);
+criterion_main!(benches);
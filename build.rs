@@ -0,0 +1,69 @@
+//! Build script finding the solver tables
+//! built into the binary with the
+//! embedded-tables feature. Tables are
+//! read from the file named by SQ1_TABLES
+//! if it is set, and otherwise written by
+//! building the scrambler for the host
+//! and running scrambler --write-tables.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SQ1_TABLES");
+    if env::var_os("CARGO_FEATURE_EMBEDDED_TABLES").is_none() {
+        return;
+    }
+
+    let manifest = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let tables = match env::var_os("SQ1_TABLES") {
+        Some(path) => manifest.join(path),
+        None => write_tables(&manifest, &PathBuf::from(env::var_os("OUT_DIR").unwrap())),
+    };
+    println!("cargo:rerun-if-changed={}", tables.display());
+    println!("cargo:rustc-env=SQ1_TABLES_FILE={}", tables.display());
+}
+
+/// Builds the scrambler without the
+/// embedded-tables feature in its own
+/// target directory and runs it to write
+/// the tables into out, returning the
+/// path of the file.
+fn write_tables(manifest: &Path, out: &Path) -> PathBuf {
+    let tables = out.join("sq1-tables.bin");
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    // The tables are the same on every
+    // target, so they are written by a
+    // build for the host, which the flags
+    // and features of this build must
+    // not reach.
+    let mut command = Command::new(cargo);
+    for (name, _) in env::vars_os() {
+        if name.to_str().is_some_and(|name| name.starts_with("CARGO_FEATURE_")) {
+            command.env_remove(name);
+        }
+    }
+    let status = command
+        .args(["run", "--release", "--quiet", "--bin", "scrambler"])
+        .args(["--no-default-features", "--features", "cli solver"])
+        .arg("--manifest-path")
+        .arg(manifest.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(out.join("tables"))
+        .arg("--")
+        .arg("--write-tables")
+        .arg(&tables)
+        .env_remove("CARGO_BUILD_TARGET")
+        .env_remove("CARGO_ENCODED_RUSTFLAGS")
+        .env_remove("CARGO_TARGET_DIR")
+        .status()
+        .expect("cargo runs to write the solver tables");
+    if !status.success() || !tables.exists() {
+        panic!("writing the solver tables failed, set SQ1_TABLES to tables written with scrambler --write-tables");
+    }
+
+    tables
+}
@@ -0,0 +1,48 @@
+"""Tests of the sq1_scrambler extension, built with maturin develop."""
+
+import unittest
+
+from sq1_scrambler import Scramble, SqOne
+
+
+class ScrambleTest(unittest.TestCase):
+    def test_parse(self):
+        scramble = Scramble("(1,0) / (-3,2) / (0,-6)")
+        self.assertEqual(scramble.turns, [(1, 0), (-3, 2), (0, 6)])
+        self.assertEqual(scramble.slashes, 2)
+        self.assertEqual(scramble, Scramble("(1, 0)/(-3, 2)/(0, 6)"))
+        self.assertEqual(repr(scramble), "Scramble('(1,0) / (-3,2) / (0,6)')")
+
+    def test_reject_invalid(self):
+        with self.assertRaises(ValueError):
+            Scramble("(7,0)")
+
+    def test_generate_seeded(self):
+        scramble = Scramble.generate(random_state=True, parity="even", seed=7)
+        self.assertEqual(scramble, Scramble.generate(random_state=True, parity="even", seed=7))
+        self.assertNotEqual(scramble, Scramble.generate(random_state=True, parity="even", seed=8))
+        with self.assertRaises(ValueError):
+            Scramble.generate(parity="both")
+
+
+class SqOneTest(unittest.TestCase):
+    def test_solve(self):
+        cube = SqOne().apply("(1,0) / (-3,2) / (0,3)")
+        self.assertFalse(cube.is_solved())
+        self.assertEqual(SqOne(cube.state), cube)
+        self.assertTrue(cube.apply(cube.solve()).is_solved())
+
+    def test_solved_state(self):
+        self.assertEqual(SqOne().state, SqOne.SOLVED)
+        self.assertEqual(hash(SqOne(SqOne.SOLVED)), hash(SqOne()))
+        self.assertTrue(SqOne().svg().startswith("<svg"))
+
+    def test_reject_invalid(self):
+        with self.assertRaises(ValueError):
+            SqOne(SqOne.SOLVED[1:])
+        with self.assertRaises(ValueError):
+            SqOne().apply("(1,0) / (0,0) / (1,0) /")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,394 @@
+//! Module holding a database of Square
+//! One algorithms for the cases of each
+//! phase of a solve, looked up by the
+//! case a cube is in.
+
+use crate::analysis::Phase;
+use crate::cases::{self, CornerCase, LayerCase, PblCase};
+use crate::cstimer;
+use crate::cube::SqOne;
+use crate::shape::{self, Shape, ShapeState};
+use crate::Scramble;
+
+/// Pieces of a layer that belong in the
+/// other layer, up to turns of the layer.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Orientation {
+    Solved,
+    One,
+
+    /// Two pieces next to each other.
+    Adjacent,
+
+    /// Two pieces across the layer.
+    Opposite,
+    Three,
+    All,
+}
+
+/// Case of a phase of a solve.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum AlgCase {
+
+    /// Shape to bring into cube shape. A
+    /// shape and its upside down version
+    /// share a case, listed by the smaller
+    /// of the two.
+    Shape(ShapeState),
+
+    /// Corners of each layer that
+    /// belong in the other layer.
+    CornerOrientation { top: Orientation, bottom: Orientation },
+
+    /// Edges of each layer that
+    /// belong in the other layer.
+    EdgeOrientation { top: Orientation, bottom: Orientation },
+
+    /// Permutation of the corners
+    /// of each layer.
+    CornerPermutation { top: CornerCase, bottom: CornerCase },
+
+    /// Permutation of the edges, with
+    /// the corners solved. Cases with an
+    /// odd layer need a parity algorithm.
+    EdgePermutation(PblCase),
+}
+
+impl AlgCase {
+
+    /// Returns the phase the
+    /// case belongs to.
+    pub fn phase(&self) -> Phase {
+        match self {
+            AlgCase::Shape(_) => Phase::CubeShape,
+            AlgCase::CornerOrientation { .. } => Phase::CornerOrientation,
+            AlgCase::EdgeOrientation { .. } => Phase::EdgeOrientation,
+            AlgCase::CornerPermutation { .. } => Phase::CornerPermutation,
+            AlgCase::EdgePermutation(_) => Phase::EdgePermutation,
+        }
+    }
+
+    /// Returns true if the case
+    /// has parity.
+    pub fn is_parity(&self) -> bool {
+        match self {
+            AlgCase::EdgePermutation(pbl) => {
+                matches!(pbl.top, LayerCase::Parity(_)) != matches!(pbl.bottom, LayerCase::Parity(_))
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the case of the first phase
+    /// the cube has not finished, or None
+    /// if the cube is solved apart from
+    /// its middle layer.
+    pub fn recognize(cube: &SqOne) -> Option<AlgCase> {
+        let phase = Phase::all().into_iter().find(|phase| !phase.done(cube))?;
+        let (top, bottom, _) = cube.slots();
+        let case = match phase {
+            Phase::CubeShape => {
+                let state = shape::classify(cube);
+                AlgCase::Shape(state.min(state.flipped()))
+            },
+            Phase::CornerOrientation => AlgCase::CornerOrientation {
+                top: orientation(&top, true, true),
+                bottom: orientation(&bottom, true, false),
+            },
+            Phase::EdgeOrientation => AlgCase::EdgeOrientation {
+                top: orientation(&top, false, true),
+                bottom: orientation(&bottom, false, false),
+            },
+            Phase::CornerPermutation => {
+                let pbl = cases::identify(cube)?;
+                AlgCase::CornerPermutation { top: pbl.top.corners(), bottom: pbl.bottom.corners() }
+            },
+            Phase::EdgePermutation => {
+                let pbl = cases::identify(cube)?;
+                if pbl == (PblCase { top: LayerCase::Solved, bottom: LayerCase::Solved }) {
+                    return None;
+                }
+                AlgCase::EdgePermutation(pbl)
+            },
+        };
+
+        Some(case)
+    }
+}
+
+/// Algorithm for a case.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Alg {
+    pub case: AlgCase,
+
+    /// Moves of the algorithm in
+    /// WCA notation.
+    pub moves: &'static str,
+}
+
+impl Alg {
+
+    /// Returns the moves of
+    /// the algorithm.
+    pub fn scramble(&self) -> Scramble {
+        cstimer::parse(self.moves).unwrap()
+    }
+}
+
+/// Returns every algorithm in the
+/// database, in the order of the phases.
+pub fn all() -> &'static [Alg] {
+    &ALGS
+}
+
+/// Returns the algorithms for
+/// the cases of a phase.
+pub fn for_phase(phase: Phase) -> impl Iterator<Item = &'static Alg> {
+    ALGS.iter().filter(move |alg| alg.case.phase() == phase)
+}
+
+/// Returns the algorithm for
+/// the case, if there is one.
+pub fn find(case: AlgCase) -> Option<&'static Alg> {
+    ALGS.iter().find(|alg| alg.case == case)
+}
+
+/// Returns the algorithm for the case
+/// of the first phase the cube has not
+/// finished. Each algorithm solves its
+/// case from one alignment of the layers,
+/// so a layer turn may be needed first,
+/// and shape algorithms may need the
+/// cube held upside down.
+pub fn lookup(cube: &SqOne) -> Option<&'static Alg> {
+    find(AlgCase::recognize(cube)?)
+}
+
+/// Returns the orientation of the corners
+/// or edges of a cube shaped layer, where
+/// top tells which layer it is.
+fn orientation(layer: &[u8; 12], corners: bool, top: bool) -> Orientation {
+    let in_top = |piece: u8| piece < 4 || (8..12).contains(&piece);
+    let wrong: Vec<bool> = (0..12)
+        .filter(|&i| layer[i] != layer[(i + 11) % 12] && (layer[i] < 8) == corners)
+        .map(|i| in_top(layer[i]) != top)
+        .collect();
+
+    match wrong.iter().filter(|&&wrong| wrong).count() {
+        0 => Orientation::Solved,
+        1 => Orientation::One,
+        2 if (0..4).any(|i| wrong[i] && wrong[(i + 1) % 4]) => Orientation::Adjacent,
+        2 => Orientation::Opposite,
+        3 => Orientation::Three,
+        _ => Orientation::All,
+    }
+}
+
+
+/// Returns the case of a shape.
+const fn shape(top: Shape, bottom: Shape) -> AlgCase {
+    AlgCase::Shape(ShapeState { top, bottom })
+}
+
+/// Returns the case of a PBL.
+const fn pbl(top: LayerCase, bottom: LayerCase) -> AlgCase {
+    AlgCase::EdgePermutation(PblCase { top, bottom })
+}
+
+/// Layer with its edges needing
+/// a parity algorithm.
+const PARITY: LayerCase = LayerCase::Parity(CornerCase::Solved);
+
+/// Algorithm for each case. Every
+/// algorithm ends on a solved cube. The
+/// shape and orientation algorithms are
+/// shortest in slashes, and the others
+/// are found by the two-phase solver
+/// from a cube with only the case left.
+const ALGS: [Alg; 146] = [
+    Alg { case: shape(Shape::Kite, Shape::Kite), moves: "(0,0)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::Barrel), moves: "(0,0)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::LeftPawn, Shape::RightPawn), moves: "(0,0)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Scallop, Shape::Scallop), moves: "(0,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Mushroom, Shape::Mushroom), moves: "(0,0)/ (5,3)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::Shield), moves: "(0,0)/ (5,0)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::Shield), moves: "(0,0)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::Barrel), moves: "(0,0)/ (3,0)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::Mushroom), moves: "(0,0)/ (1,3)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::Scallop), moves: "(0,0)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::LeftFist, Shape::RightFist), moves: "(0,0)/ (1,-4)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Star, Shape::TwoCorners { gap: 4 }), moves: "(0,0)/ (4,4)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Star, Shape::TwoCorners { gap: 0 }), moves: "(0,0)/ (4,2)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [0, 0, 6] }), moves: "(0,0)/ (4,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [0, 4, 2] }), moves: "(0,0)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [0, 2, 4] }), moves: "(0,0)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::Scallop), moves: "(0,0)/ (1,6)/ (5,3)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [2, 2, 2] }), moves: "(0,0)/ (4,2)/ (5,0)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::RightFist, Shape::RightPawn), moves: "(0,0)/ (4,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [1, 3, 2] }), moves: "(0,0)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [1, 2, 3] }), moves: "(0,0)/ (2,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::LeftFist, Shape::LeftPawn), moves: "(0,0)/ (0,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::Mushroom), moves: "(0,0)/ (4,3)/ (3,0)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [0, 2, 4] }), moves: "(0,0)/ (4,3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [0, 1, 5] }), moves: "(0,0)/ (4,0)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [0, 5, 1] }), moves: "(0,0)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [1, 1, 4] }), moves: "(0,0)/ (4,6)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [0, 4, 2] }), moves: "(0,0)/ (2,3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [0, 1, 5] }), moves: "(0,0)/ (2,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [0, 5, 1] }), moves: "(0,0)/ (2,6)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Star, Shape::TwoCorners { gap: 2 }), moves: "(0,0)/ (4,2)/ (1,-4)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [1, 1, 4] }), moves: "(0,0)/ (4,0)/ (1,-4)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [0, 3, 3] }), moves: "(0,0)/ (4,6)/ (1,-4)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::LeftFist, Shape::LeftFist), moves: "(0,0)/ (4,3)/ (4,4)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::RightFist, Shape::RightFist), moves: "(0,0)/ (4,1)/ (4,4)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [0, 0, 6] }), moves: "(0,0)/ (4,3)/ (4,2)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::Scallop), moves: "(0,0)/ (4,2)/ (4,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Mushroom, Shape::RightFist), moves: "(0,0)/ (4,1)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [0, 3, 3] }), moves: "(0,0)/ (4,-5)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::RightFist), moves: "(0,0)/ (2,1)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [1, 2, 3] }), moves: "(0,0)/ (2,-5)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::Shield), moves: "(0,0)/ (0,2)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [0, 5, 1] }), moves: "(0,0)/ (0,-5)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::LeftFist), moves: "(0,0)/ (4,5)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [1, 3, 2] }), moves: "(0,0)/ (4,-1)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Mushroom, Shape::LeftFist), moves: "(0,0)/ (2,5)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [0, 1, 5] }), moves: "(0,0)/ (0,5)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [0, 4, 2] }), moves: "(0,0)/ (3,-2)/ (4,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [1, 3, 2] }), moves: "(0,0)/ (0,-2)/ (4,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::RightFist), moves: "(0,0)/ (5,-4)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [1, 2, 3] }), moves: "(0,0)/ (2,0)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::LeftFist, Shape::RightPawn), moves: "(0,0)/ (2,-4)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::Shield), moves: "(0,0)/ (0,2)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::RightFist, Shape::LeftPawn), moves: "(0,0)/ (4,-2)/ (2,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::LeftFist), moves: "(0,0)/ (1,-2)/ (2,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [0, 2, 4] }), moves: "(0,0)/ (3,-4)/ (0,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Pair, Shape::ThreeCorners { gaps: [0, 3, 3] }), moves: "(0,0)/ (5,-2)/ (4,3)/ (3,0)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Scallop, Shape::Mushroom), moves: "(0,0)/ (4,6)/ (4,3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::LeftFist), moves: "(0,0)/ (3,6)/ (4,3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Scallop, Shape::LeftPawn), moves: "(0,0)/ (3,4)/ (4,0)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [1, 1, 4] }), moves: "(0,0)/ (3,-2)/ (4,0)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Scallop, Shape::RightPawn), moves: "(0,0)/ (3,6)/ (4,0)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::Scallop), moves: "(0,0)/ (0,-2)/ (4,0)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Scallop, Shape::RightFist), moves: "(0,0)/ (3,4)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [0, 0, 6] }), moves: "(0,0)/ (3,2)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::RightFist), moves: "(0,0)/ (1,0)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::RightFist), moves: "(0,0)/ (1,-2)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Scallop, Shape::LeftFist), moves: "(0,0)/ (5,4)/ (2,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::LeftFist), moves: "(0,0)/ (5,6)/ (2,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::RightPawn), moves: "(0,0)/ (1,2)/ (4,6)/ (1,-4)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Shield, Shape::LeftPawn), moves: "(0,0)/ (1,0)/ (4,6)/ (1,-4)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::Barrel), moves: "(0,0)/ (4,4)/ (4,3)/ (4,4)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::RightPawn), moves: "(0,0)/ (2,-5)/ (4,1)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Mushroom, Shape::LeftPawn), moves: "(0,0)/ (2,6)/ (4,1)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::RightPawn), moves: "(0,0)/ (2,-3)/ (2,1)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Mushroom, Shape::RightPawn), moves: "(0,0)/ (2,6)/ (2,1)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::Mushroom), moves: "(0,0)/ (4,5)/ (0,2)/ (4,6)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::LeftPawn), moves: "(0,0)/ (3,-2)/ (4,5)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::LeftPawn), moves: "(0,0)/ (5,-2)/ (2,5)/ (2,0)/ (5,4)/ (3,3)/ (5,4)" },
+    Alg { case: shape(Shape::L, Shape::ThreeCorners { gaps: [2, 2, 2] }), moves: "(0,0)/ (0,-5)/ (0,-2)/ (4,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::RightPawn), moves: "(0,0)/ (4,2)/ (5,-4)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::LeftPawn, Shape::LeftPawn), moves: "(0,0)/ (3,4)/ (2,-4)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Barrel, Shape::Mushroom), moves: "(0,0)/ (4,-3)/ (0,2)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::RightPawn, Shape::RightPawn), moves: "(0,0)/ (2,6)/ (0,2)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Line, Shape::ThreeCorners { gaps: [2, 2, 2] }), moves: "(0,0)/ (0,3)/ (0,2)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Kite, Shape::LeftPawn), moves: "(0,0)/ (4,5)/ (1,-2)/ (2,3)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Star, Shape::TwoCorners { gap: 3 }), moves: "(0,0)/ (4,-4)/ (3,4)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Star, Shape::TwoCorners { gap: 1 }), moves: "(0,0)/ (4,6)/ (3,4)/ (4,-3)/ (1,2)/ (3,0)/ (5,4)" },
+    Alg { case: shape(Shape::Square, Shape::Kite), moves: "(0,0)/ (4,0)/ (3,4)/ (2,-4)/ (2,4)/ (3,2)/ (3,0)/ (5,4)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::Adjacent, bottom: Orientation::Adjacent }, moves: "(0,0)/ (5,3)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::Opposite, bottom: Orientation::Opposite }, moves: "(0,0)/ (4,4)/ (5,3)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::One, bottom: Orientation::One }, moves: "(0,0)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::Three, bottom: Orientation::Three }, moves: "(0,0)/ (4,-5)/ (5,3)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::All, bottom: Orientation::All }, moves: "(0,0)/ (-5,-5)/ (5,3)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::Adjacent, bottom: Orientation::Opposite }, moves: "(0,0)/ (5,-4)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::CornerOrientation { top: Orientation::Opposite, bottom: Orientation::Adjacent }, moves: "(0,0)/ (3,0)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::Opposite, bottom: Orientation::Opposite }, moves: "(0,0)/ (1,1)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::All, bottom: Orientation::All }, moves: "(0,0)/ (-2,-2)/ (5,5)/ (4,4)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::Adjacent, bottom: Orientation::Adjacent }, moves: "(0,0)/ (1,-2)/ (5,5)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::One, bottom: Orientation::One }, moves: "(0,0)/ (0,3)/ (4,-5)/ (5,2)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::Opposite, bottom: Orientation::Adjacent }, moves: "(0,0)/ (-1,2)/ (4,-5)/ (5,2)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::Three, bottom: Orientation::Three }, moves: "(0,0)/ (1,4)/ (3,6)/ (5,2)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::EdgeOrientation { top: Orientation::Adjacent, bottom: Orientation::Opposite }, moves: "(0,0)/ (0,3)/ (3,6)/ (5,2)/ (4,1)/ (5,3)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Solved, bottom: CornerCase::Adjacent }, moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,-5)/ (3,0)/ (2,-4)/ (0,3)/ (0,-3)/ (4,1)/ (2,-1)/ (-3,3)/ (3,4)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Solved, bottom: CornerCase::Opposite }, moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (1,0)/ (2,-4)/ (4,-2)/ (-4,0)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Adjacent, bottom: CornerCase::Solved }, moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,-5)/ (2,-1)/ (1,4)/ (-1,5)/ (0,-3)/ (4,-2)/ (2,-1)/ (0,3)/ (0,-5)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Adjacent, bottom: CornerCase::Adjacent }, moves: "(3,-4)/ (0,3)/ (4,4)/ (-1,2)/ (3,-2)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Adjacent, bottom: CornerCase::Opposite }, moves: "(0,-1)/ (1,4)/ (0,-3)/ (-1,5)/ (0,3)/ (1,-2)/ (-1,5)/ (0,-3)/ (0,-2)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Opposite, bottom: CornerCase::Solved }, moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,-5)/ (2,2)/ (1,1)/ (2,-4)/ (3,-5)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Opposite, bottom: CornerCase::Adjacent }, moves: "(0,-1)/ (1,-5)/ (0,3)/ (-1,5)/ (3,6)/ (0,-3)/ (4,1)/ (-1,2)/ (3,-5)" },
+    Alg { case: AlgCase::CornerPermutation { top: CornerCase::Opposite, bottom: CornerCase::Opposite }, moves: "(0,2)/ (1,-5)/ (2,2)/ (-3,3)/ (0,-2)" },
+    Alg { case: pbl(LayerCase::Solved, PARITY), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (0,-3)/ (3,3)/ (3,6)/ (1,4)/ (0,-3)/ (0,3)/ (2,-1)/ (-2,1)/ (2,0)" },
+    Alg { case: pbl(LayerCase::Solved, LayerCase::Ub), moves: "(0,-1)/ (1,1)/ (2,5)/ (0,3)/ (4,1)/ (-1,2)/ (3,0)/ (1,4)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::Solved, LayerCase::Ua), moves: "(0,-1)/ (1,1)/ (3,0)/ (3,6)/ (-1,-4)/ (4,-5)/ (3,6)/ (-1,-4)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::Solved, LayerCase::Z), moves: "(0,-1)/ (1,1)/ (-1,-4)/ (1,1)/ (-1,2)/ (1,1)/ (-1,0)" },
+    Alg { case: pbl(LayerCase::Solved, LayerCase::H), moves: "(0,-1)/ (1,-5)/ (-1,5)/ (0,3)/ (1,-5)/ (-1,5)/ (0,-2)" },
+    Alg { case: pbl(PARITY, LayerCase::Solved), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,-5)/ (-1,-4)/ (0,-3)/ (4,-5)/ (-1,5)/ (3,6)/ (0,-3)/ (6,-3)/ (0,1)" },
+    Alg { case: pbl(PARITY, PARITY), moves: "(1,-3)/ (-1,2)/ (1,1)/ (0,-3)/ (-1,3)" },
+    Alg { case: pbl(PARITY, LayerCase::Ub), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,4)/ (0,6)/ (2,5)/ (1,-2)/ (2,2)/ (0,-3)/ (0,3)/ (0,-3)/ (6,-5)" },
+    Alg { case: pbl(PARITY, LayerCase::Ua), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (0,3)/ (0,6)/ (3,-3)/ (4,1)/ (0,3)/ (-1,5)/ (3,-3)/ (0,3)/ (0,-5)" },
+    Alg { case: pbl(PARITY, LayerCase::Z), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,-5)/ (3,0)/ (-1,2)/ (4,-5)/ (2,-4)/ (3,0)/ (1,-2)/ (2,-1)/ (0,1)" },
+    Alg { case: pbl(PARITY, LayerCase::H), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,4)/ (-1,2)/ (1,-2)/ (-1,-4)/ (4,4)/ (3,0)/ (0,-3)/ (6,-3)/ (-4,0)" },
+    Alg { case: pbl(LayerCase::Ua, LayerCase::Solved), moves: "(0,-1)/ (1,1)/ (0,3)/ (0,-3)/ (-1,-4)/ (1,-2)/ (0,-3)/ (-1,-4)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::Ua, PARITY), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (1,1)/ (2,-1)/ (0,-3)/ (3,3)/ (4,1)/ (0,-3)/ (3,3)/ (-1,2)/ (0,4)" },
+    Alg { case: pbl(LayerCase::Ua, LayerCase::Ub), moves: "(0,-1)/ (1,1)/ (-1,5)/ (1,1)/ (0,3)/ (-1,2)/ (1,1)/ (0,-3)/ (-1,3)" },
+    Alg { case: pbl(LayerCase::Ua, LayerCase::Ua), moves: "(0,-1)/ (1,1)/ (-1,5)/ (0,3)/ (1,1)/ (0,-3)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::Ua, LayerCase::Z), moves: "(0,-1)/ (0,3)/ (1,-2)/ (-1,-4)/ (1,4)/ (-1,5)/ (1,4)/ (-1,2)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::Ua, LayerCase::H), moves: "(0,-1)/ (0,-3)/ (1,4)/ (-1,-4)/ (1,-5)/ (-1,-4)/ (1,4)/ (0,-3)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::Ub, LayerCase::Solved), moves: "(0,-1)/ (1,1)/ (-1,-4)/ (0,3)/ (1,4)/ (-1,2)/ (0,3)/ (1,4)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::Ub, PARITY), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (0,3)/ (0,6)/ (3,-3)/ (4,1)/ (0,3)/ (2,2)/ (1,-2)/ (-4,2)/ (3,4)" },
+    Alg { case: pbl(LayerCase::Ub, LayerCase::Ub), moves: "(0,-1)/ (1,1)/ (0,6)/ (-1,-4)/ (1,1)/ (-1,2)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::Ub, LayerCase::Ua), moves: "(0,-1)/ (1,1)/ (-1,5)/ (1,1)/ (-1,-4)/ (0,-3)/ (1,1)/ (-1,2)/ (0,-2)" },
+    Alg { case: pbl(LayerCase::Ub, LayerCase::Z), moves: "(0,-1)/ (1,1)/ (0,-3)/ (-1,2)/ (1,4)/ (-1,2)/ (0,3)/ (1,4)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::Ub, LayerCase::H), moves: "(1,0)/ (0,3)/ (-1,-4)/ (1,4)/ (-1,5)/ (1,4)/ (-1,-4)/ (0,3)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::Z, LayerCase::Solved), moves: "(0,-1)/ (1,1)/ (2,-1)/ (1,1)/ (2,5)/ (1,1)/ (5,6)" },
+    Alg { case: pbl(LayerCase::Z, PARITY), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (1,0)/ (-1,2)/ (0,-3)/ (0,3)/ (4,4)/ (-1,2)/ (4,-2)/ (-1,2)/ (-2,1)/ (5,6)" },
+    Alg { case: pbl(LayerCase::Z, LayerCase::Ub), moves: "(0,-1)/ (3,0)/ (1,-5)/ (-1,2)/ (4,-5)/ (2,-1)/ (1,-5)/ (0,3)/ (2,6)" },
+    Alg { case: pbl(LayerCase::Z, LayerCase::Ua), moves: "(0,-1)/ (1,1)/ (2,-1)/ (4,-5)/ (-1,-4)/ (4,-5)/ (3,6)/ (-1,-4)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::Z, LayerCase::Z), moves: "(1,3)/ (-1,2)/ (1,1)/ (2,-4)/ (1,1)/ (-3,0)/ (-1,-3)" },
+    Alg { case: pbl(LayerCase::Z, LayerCase::H), moves: "(0,-1)/ (1,1)/ (2,-1)/ (3,-3)/ (1,1)/ (3,-3)/ (-1,-4)/ (-5,-5)/ (-1,0)" },
+    Alg { case: pbl(LayerCase::H, LayerCase::Solved), moves: "(0,-1)/ (1,-5)/ (-1,5)/ (3,0)/ (1,-5)/ (-1,5)/ (-3,1)" },
+    Alg { case: pbl(LayerCase::H, PARITY), moves: "(-5,-4)/ (-3,-3)/ (-5,-4)/ (-4,2)/ (-2,4)/ (-5,-4)/ (-3,-3)/ (0,-1)/ (4,1)/ (0,-3)/ (0,-3)/ (-1,5)/ (3,6)/ (3,0)/ (1,-5)/ (3,6)/ (5,0)" },
+    Alg { case: pbl(LayerCase::H, LayerCase::Ub), moves: "(1,0)/ (3,0)/ (2,5)/ (1,4)/ (-1,5)/ (4,1)/ (2,5)/ (0,3)/ (0,-5)" },
+    Alg { case: pbl(LayerCase::H, LayerCase::Ua), moves: "(0,-1)/ (3,6)/ (1,4)/ (2,5)/ (1,-5)/ (-1,-4)/ (4,1)/ (0,-3)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::H, LayerCase::Z), moves: "(0,-1)/ (1,1)/ (-1,2)/ (1,1)/ (-1,2)/ (3,-3)/ (1,1)/ (-3,3)/ (-1,6)" },
+    Alg { case: pbl(LayerCase::H, LayerCase::H), moves: "(0,-1)/ (1,1)/ (-1,5)/ (3,-3)/ (1,1)/ (-3,3)/ (-1,6)" },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Undoing each algorithm from a solved
+    // cube gives back its case.
+    #[test]
+    fn algs_solve_their_cases() {
+        for alg in all() {
+            let mut cube = SqOne::new();
+            cube.apply(&alg.scramble().inverse()).unwrap();
+            assert_eq!(AlgCase::recognize(&cube), Some(alg.case), "{}", alg.moves);
+            assert_eq!(lookup(&cube), Some(alg));
+            cube.apply(&alg.scramble()).unwrap();
+            assert!(cube.is_solved());
+        }
+    }
+
+    #[test]
+    fn cover_every_phase() {
+        assert_eq!(for_phase(Phase::CubeShape).count(), shape::all_shapes().len() - 1);
+        assert_eq!(for_phase(Phase::CornerOrientation).count(), 7);
+        assert_eq!(for_phase(Phase::EdgeOrientation).count(), 7);
+        assert_eq!(for_phase(Phase::CornerPermutation).count(), 8);
+        assert_eq!(for_phase(Phase::EdgePermutation).filter(|alg| alg.case.is_parity()).count(), 10);
+        for (i, alg) in all().iter().enumerate() {
+            assert!(all()[i + 1..].iter().all(|other| other.case != alg.case));
+        }
+
+        assert_eq!(lookup(&SqOne::new()), None);
+        let mut cube = SqOne::new();
+        cube.try_twist(1, 0).unwrap();
+        cube.try_slash().unwrap();
+        cube.try_twist(2, 0).unwrap();
+        cube.try_slash().unwrap();
+        assert_eq!(lookup(&cube).unwrap().case.phase(), Phase::CubeShape);
+    }
+}
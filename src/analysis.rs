@@ -0,0 +1,573 @@
+//! Module for measuring how difficult
+//! a scrambled cube is to solve.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::cases::{self, CornerCase};
+use crate::cube::{Layer, PieceId, SqOne};
+use crate::shape::{self, ShapeState};
+use crate::solver::Position;
+use crate::policy::MovePolicy;
+use crate::{solver, Metric, Move, Scramble, ScrambleOptions, VerifyError};
+
+/// Measures of the difficulty of a
+/// scrambled cube.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Grade {
+
+    /// Shapes of the layers.
+    pub shape: ShapeState,
+
+    /// Slashes needed to return to
+    /// cube shape.
+    pub shape_distance: u8,
+
+    /// Corners in the top layer.
+    pub top_corners: u8,
+
+    /// Corners in the bottom layer.
+    pub bottom_corners: u8,
+
+    /// Whether the cube has parity, if
+    /// it is in cube shape.
+    pub parity: Option<bool>,
+
+    /// Slashes in the solution found by
+    /// the two-phase solver. The optimal
+    /// solver is too slow for scrambled
+    /// cubes, so this is an upper bound
+    /// on the optimal solution.
+    pub solution_slashes: usize,
+}
+
+/// Returns the difficulty measures
+/// of the cube.
+pub fn grade(cube: &SqOne) -> Grade {
+    let state = shape::classify(cube);
+    let shape_distance = shape::all_shapes()
+        .into_iter()
+        .find(|&(class, _)| class == state || class == state.flipped())
+        .map(|(_, depth)| depth)
+        .unwrap();
+
+    // A corner starts in each slot that
+    // holds a different corner than the
+    // slot before it.
+    let (top, bottom, _) = cube.slots();
+    let corners = |layer: [u8; 12]| {
+        (0..12).filter(|&i| layer[i] < 8 && layer[i] != layer[(i + 11) % 12]).count() as u8
+    };
+
+    Grade {
+        shape: state,
+        shape_distance,
+        top_corners: corners(top),
+        bottom_corners: corners(bottom),
+        parity: cube.has_parity(),
+        solution_slashes: solver::solve(cube).len_in(Metric::Slash),
+    }
+}
+
+/// Results of a breadth first search
+/// over the shapes of the cube, where a
+/// shape includes how far each layer is
+/// twisted.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ShapeReport {
+
+    /// Number of shapes at each distance
+    /// from the shape of a solved cube.
+    pub distribution: Vec<usize>,
+
+    /// Number of shape classes found,
+    /// ignoring how far each layer is
+    /// twisted. A class and its upside
+    /// down version count separately.
+    pub classes: usize,
+
+    /// Classes of the shapes farthest
+    /// from the shape of a solved cube.
+    pub antipodes: Vec<ShapeState>,
+}
+
+impl ShapeReport {
+
+    /// Returns the number of shapes
+    /// reachable from a solved cube.
+    pub fn states(&self) -> usize {
+        self.distribution.iter().sum()
+    }
+
+    /// Returns the distance of the
+    /// farthest shapes, the God's number
+    /// of the shape graph.
+    pub fn diameter(&self) -> usize {
+        self.distribution.len() - 1
+    }
+}
+
+/// Runs a breadth first search from the
+/// shape of a solved cube to every
+/// reachable shape, counting moves in
+/// the metric. In the slash metric twists
+/// are free, so shapes only differing by
+/// a twist are at the same distance.
+pub fn explore_shapes(metric: Metric) -> ShapeReport {
+    let start = Position::solved();
+    let mut seen = HashSet::from([start.shape()]);
+    let mut frontier = vec![start];
+    let mut distribution = vec![];
+    let mut classes = BTreeSet::new();
+    let mut last = vec![];
+
+    while !frontier.is_empty() {
+
+        // With the slash metric, every
+        // twist of a shape in the frontier
+        // joins it at the same distance.
+        if metric == Metric::Slash {
+            for position in frontier.clone() {
+                for (top, bottom) in twists() {
+                    let next = position.twist(top, bottom);
+                    if seen.insert(next.shape()) {
+                        frontier.push(next);
+                    }
+                }
+            }
+        }
+
+        distribution.push(frontier.len());
+        let mut next_frontier = vec![];
+        for position in &frontier {
+            classes.insert(shape::classify_slots(&position.top, &position.bottom));
+            let mut neighbours = vec![];
+            if position.can_slash() {
+                neighbours.push(position.slash());
+            }
+            if metric == Metric::Twist {
+                neighbours.extend(twists().map(|(top, bottom)| position.twist(top, bottom)));
+            }
+            for next in neighbours {
+                if seen.insert(next.shape()) {
+                    next_frontier.push(next);
+                }
+            }
+        }
+        last = frontier;
+        frontier = next_frontier;
+    }
+
+    let antipodes: BTreeSet<ShapeState> = last
+        .iter()
+        .map(|position| shape::classify_slots(&position.top, &position.bottom))
+        .collect();
+
+    ShapeReport {
+        distribution,
+        classes: classes.len(),
+        antipodes: antipodes.into_iter().collect(),
+    }
+}
+
+/// Distributions over a sample
+/// of scrambles.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SampleReport {
+
+    /// Number of scrambles made.
+    pub samples: usize,
+
+    /// Number of scrambled cubes of each
+    /// shape class. A class and its upside
+    /// down version count separately.
+    pub shapes: BTreeMap<ShapeState, usize>,
+
+    /// Number of scrambled cubes in
+    /// cube shape.
+    pub cube_shape: usize,
+
+    /// Number of scrambled cubes in
+    /// cube shape with parity.
+    pub parity: usize,
+
+    /// Mean slashes in a scramble.
+    pub mean_slashes: f64,
+
+    /// Mean slashes needed to solve the
+    /// scrambled cubes, as found by
+    /// solver::distance.
+    pub mean_distance: f64,
+
+    /// Number of turns of the top and
+    /// bottom layers by each amount, with
+    /// an amount of -5 at index 0.
+    pub top_turns: [usize; 12],
+    pub bottom_turns: [usize; 12],
+}
+
+impl SampleReport {
+
+    /// Returns the share of cubes in cube
+    /// shape that have parity.
+    pub fn parity_rate(&self) -> f64 {
+        self.parity as f64 / self.cube_shape.max(1) as f64
+    }
+
+    /// Returns the share of scrambled
+    /// cubes with the shape.
+    pub fn shape_rate(&self, state: ShapeState) -> f64 {
+        self.shapes.get(&state).copied().unwrap_or(0) as f64 / self.samples.max(1) as f64
+    }
+}
+
+/// Makes n scrambles with the options
+/// and reports how the scrambles and
+/// the cubes they give are distributed,
+/// for comparing random move scrambles
+/// with random state scrambles.
+pub fn sample(n: usize, options: &ScrambleOptions) -> SampleReport {
+    sample_from(n, |cube| cube.scramble_with(options))
+}
+
+/// Makes n random move scrambles with
+/// the policy picking each turn and
+/// reports them like sample, for
+/// comparing policies.
+pub fn sample_policy<P: MovePolicy>(n: usize, policy: &mut P) -> SampleReport {
+    let mut rng = rand::thread_rng();
+    sample_from(n, |cube| cube.scramble_with_policy(policy, &mut rng))
+}
+
+/// Reports on n scrambles, each made by
+/// scrambling a solved cube.
+fn sample_from(n: usize, mut scramble: impl FnMut(&mut SqOne) -> Scramble) -> SampleReport {
+    let mut report = SampleReport {
+        samples: n,
+        shapes: BTreeMap::new(),
+        cube_shape: 0,
+        parity: 0,
+        mean_slashes: 0.0,
+        mean_distance: 0.0,
+        top_turns: [0; 12],
+        bottom_turns: [0; 12],
+    };
+
+    let (mut slashes, mut distance) = (0, 0);
+    for _ in 0..n {
+        let mut cube = SqOne::new();
+        let scramble = scramble(&mut cube);
+        for &(top, bottom) in &scramble.turns {
+            report.top_turns[(top + 5) as usize % 12] += 1;
+            report.bottom_turns[(bottom + 5) as usize % 12] += 1;
+        }
+        slashes += scramble.len_in(Metric::Slash);
+        distance += solver::distance(&cube, Metric::Slash);
+
+        let state = shape::classify(&cube);
+        *report.shapes.entry(state).or_insert(0) += 1;
+        if state.is_cube() {
+            report.cube_shape += 1;
+            report.parity += (cube.has_parity() == Some(true)) as usize;
+        }
+    }
+    report.mean_slashes = slashes as f64 / n.max(1) as f64;
+    report.mean_distance = distance as f64 / n.max(1) as f64;
+
+    report
+}
+
+/// How far a piece travels under a
+/// scramble, from displacement.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PieceTravel {
+    pub piece: PieceId,
+
+    /// Slots between where the piece
+    /// starts and ends, going the short
+    /// way around, from 0 to 6. Slots of
+    /// the bottom layer are counted as
+    /// seen from above.
+    pub slots: usize,
+
+    /// Whether the piece ends in the
+    /// other layer.
+    pub other_layer: bool,
+
+    /// Slashes that took the piece
+    /// from one layer to the other.
+    pub layer_changes: usize,
+}
+
+impl PieceTravel {
+
+    /// Returns how far the piece ends
+    /// from home, from 0 for a piece back
+    /// in its own slot to 1 for a piece in
+    /// the other layer half a turn away.
+    pub fn score(&self) -> f64 {
+        (self.slots as f64 / 6.0 + self.other_layer as u8 as f64) / 2.0
+    }
+}
+
+/// Travel of every piece under a
+/// scramble, from displacement.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Displacement {
+
+    /// Travel of each piece in order
+    /// of index.
+    pub pieces: Vec<PieceTravel>,
+}
+
+impl Displacement {
+
+    /// Returns the mean score of the
+    /// pieces, from 0 for a solved cube
+    /// to 1 when every piece ends as far
+    /// from home as it can.
+    pub fn score(&self) -> f64 {
+        self.pieces.iter().map(PieceTravel::score).sum::<f64>() / self.pieces.len() as f64
+    }
+}
+
+/// Returns how far each piece travels
+/// when the scramble is applied to a
+/// solved cube, or an error if a slash
+/// of it is blocked. Comparing the
+/// pieces over many scrambles shows
+/// whether a generator leaves some
+/// pieces near home.
+pub fn displacement(scramble: &Scramble) -> Result<Displacement, VerifyError> {
+    scramble.is_legal_from(&SqOne::new()).map_err(|at| VerifyError::IllegalSlash(at.turn))?;
+
+    let solved = SqOne::new();
+    let home: Vec<(Layer, usize)> = PieceId::all().map(|piece| solved.position_of(piece)).collect();
+    let mut layers: Vec<Layer> = home.iter().map(|&(layer, _)| layer).collect();
+    let mut changes = [0; 16];
+    let mut cube = solved;
+    for (next, after) in scramble.replay(&SqOne::new()) {
+        if next == Move::Slash {
+            for (i, piece) in PieceId::all().enumerate() {
+                let (layer, _) = after.position_of(piece);
+                if layer != layers[i] {
+                    layers[i] = layer;
+                    changes[i] += 1;
+                }
+            }
+        }
+        cube = after;
+    }
+
+    let pieces = PieceId::all()
+        .zip(home)
+        .map(|(piece, (home_layer, home_slot))| {
+            let (layer, slot) = cube.position_of(piece);
+            let apart = (slot + 12 - home_slot) % 12;
+            PieceTravel {
+                piece,
+                slots: apart.min(12 - apart),
+                other_layer: layer != home_layer,
+                layer_changes: changes[piece.index() as usize],
+            }
+        })
+        .collect();
+
+    Ok(Displacement { pieces })
+}
+
+/// Phases of a solve in the order
+/// they are finished. Each phase keeps
+/// the ones before it done.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Phase {
+
+    /// Both layers are squares.
+    CubeShape,
+
+    /// Every corner is in its
+    /// own layer.
+    CornerOrientation,
+
+    /// Every edge is in its
+    /// own layer.
+    EdgeOrientation,
+
+    /// The corners of each layer are
+    /// in order, up to a layer turn.
+    CornerPermutation,
+
+    /// The cube is solved, including
+    /// any parity.
+    EdgePermutation,
+}
+
+impl Phase {
+
+    /// Returns every phase in order.
+    pub fn all() -> [Phase; 5] {
+        [
+            Phase::CubeShape,
+            Phase::CornerOrientation,
+            Phase::EdgeOrientation,
+            Phase::CornerPermutation,
+            Phase::EdgePermutation,
+        ]
+    }
+
+    /// Returns true if the cube has
+    /// finished the phase.
+    pub fn done(&self, cube: &SqOne) -> bool {
+        match self {
+            Phase::CubeShape => shape::classify(cube).is_cube(),
+            Phase::CornerOrientation => {
+                Phase::CubeShape.done(cube) && cube.pieces_by_layer_color().corners_oriented()
+            },
+            Phase::EdgeOrientation => cases::identify(cube).is_some(),
+            Phase::CornerPermutation => cases::identify(cube).is_some_and(|case| {
+                case.top.corners() == CornerCase::Solved && case.bottom.corners() == CornerCase::Solved
+            }),
+            Phase::EdgePermutation => cube.is_solved(),
+        }
+    }
+}
+
+/// Point in a solution where a
+/// phase is finished.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PhaseMark {
+    pub phase: Phase,
+
+    /// Number of moves made when
+    /// the phase is finished.
+    pub index: usize,
+}
+
+/// Walks the moves from the start state,
+/// marking where each phase is finished.
+/// Phases already done at the start are
+/// left out, and a move may finish several
+/// phases at once. Returns the index of
+/// the first move that cannot be made.
+pub fn phases(moves: &[Move], start: &SqOne) -> Result<Vec<PhaseMark>, usize> {
+    let mut cube = start.clone();
+    let all = Phase::all();
+    let mut next_phase = all.iter().position(|phase| !phase.done(&cube)).unwrap_or(all.len());
+    let mut marks = vec![];
+    for (i, &next) in moves.iter().enumerate() {
+        cube.apply_move(next).map_err(|_| i)?;
+        while let Some(&phase) = all.get(next_phase) {
+            if !phase.done(&cube) {
+                break;
+            }
+            marks.push(PhaseMark { phase, index: i + 1 });
+            next_phase += 1;
+        }
+    }
+
+    Ok(marks)
+}
+
+/// Returns every twist other than (0, 0).
+fn twists() -> impl Iterator<Item = (i8, i8)> {
+    (-5..=6)
+        .flat_map(|top| (-5..=6).map(move |bottom| (top, bottom)))
+        .filter(|&twist| twist != (0, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::UniformTurns;
+    use crate::shape::Shape;
+
+    #[test]
+    fn grade_solved_cube() {
+        let grade = grade(&SqOne::new());
+        assert_eq!(grade.shape, ShapeState { top: Shape::Square, bottom: Shape::Square });
+        assert_eq!(grade.shape_distance, 0);
+        assert_eq!((grade.top_corners, grade.bottom_corners), (4, 4));
+        assert_eq!(grade.parity, Some(false));
+        assert_eq!(grade.solution_slashes, 0);
+    }
+
+    #[test]
+    fn grade_slashed_cube() {
+        let mut cube = SqOne::new();
+        cube.try_twist(1, 0).unwrap();
+        cube.try_slash().unwrap();
+        cube.try_twist(2, 0).unwrap();
+        cube.try_slash().unwrap();
+        let grade = grade(&cube);
+        assert!(grade.shape_distance <= 2);
+        assert!(grade.solution_slashes >= grade.shape_distance as usize);
+        assert_eq!(grade.top_corners + grade.bottom_corners, 8);
+    }
+
+    // The slash metric distances match
+    // the shape classes, and both metrics
+    // reach the same shapes.
+    #[test]
+    fn explore_shape_graph() {
+        let slash = explore_shapes(Metric::Slash);
+        let deepest = shape::all_shapes().iter().map(|&(_, depth)| depth as usize).max().unwrap();
+        assert_eq!(slash.diameter(), deepest);
+        assert_eq!(slash.classes, 170);
+        assert!(slash.antipodes.iter().all(|state| !state.is_cube()));
+
+        let twist = explore_shapes(Metric::Twist);
+        assert_eq!(twist.states(), slash.states());
+        assert!(twist.diameter() > slash.diameter());
+    }
+
+    #[test]
+    fn sample_scrambles() {
+        let report = sample(6, &ScrambleOptions::new().require_parity(true));
+        assert_eq!(report.cube_shape, 6);
+        assert_eq!(report.parity_rate(), 1.0);
+        assert_eq!(report.shapes.values().sum::<usize>(), 6);
+        let turns: usize = report.top_turns.iter().sum();
+        assert_eq!(turns, report.bottom_turns.iter().sum::<usize>());
+        assert_eq!(turns as f64, report.mean_slashes * 6.0 + 6.0);
+        assert!(report.mean_distance > 0.0);
+
+        let report = sample_policy(4, &mut UniformTurns::default());
+        assert_eq!((report.samples, report.mean_slashes), (4, 19.0));
+
+        let empty = sample(0, &ScrambleOptions::new());
+        assert_eq!((empty.mean_slashes, empty.shape_rate(ShapeState { top: Shape::Kite, bottom: Shape::Kite })), (0.0, 0.0));
+    }
+
+    #[test]
+    fn mark_phases() {
+        let scramble = SqOne::new().scramble();
+        let mut cube = SqOne::new();
+        cube.apply(&scramble).unwrap();
+        let solution = scramble.inverse().moves();
+        let marks = phases(&solution, &cube).unwrap();
+        assert_eq!(marks.last(), Some(&PhaseMark { phase: Phase::EdgePermutation, index: solution.len() }));
+        assert!(marks.windows(2).all(|pair| pair[0].phase < pair[1].phase && pair[0].index <= pair[1].index));
+
+        assert_eq!(phases(&[Move::Twist(0, 3)], &SqOne::new()), Ok(vec![]));
+        assert_eq!(phases(&[Move::Twist(1, 0), Move::Slash, Move::Twist(1, 0), Move::Slash], &SqOne::new()), Err(3));
+    }
+
+    #[test]
+    fn displace_pieces() {
+        let solved = displacement(&Scramble { turns: vec![(0, 0)] }).unwrap();
+        assert_eq!(solved.score(), 0.0);
+
+        // A slash and a half turn of the
+        // top sends the right half down and
+        // the left half round to the right.
+        let scramble = Scramble { turns: vec![(1, 0), (6, 0)] };
+        let moved = displacement(&scramble).unwrap();
+        assert_eq!(moved.pieces.len(), 16);
+        let changed = moved.pieces.iter().filter(|travel| travel.layer_changes == 1).count();
+        assert_eq!(changed, moved.pieces.iter().filter(|travel| travel.other_layer).count());
+        assert!(changed > 0);
+        assert!(moved.score() > 0.0 && moved.score() <= 1.0);
+
+        let blocked = Scramble { turns: vec![(2, 0), (0, 0)] };
+        assert_eq!(displacement(&blocked), Err(VerifyError::IllegalSlash(0)));
+    }
+}
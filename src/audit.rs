@@ -0,0 +1,418 @@
+//! Module for audit logs of generated
+//! scrambles, so delegates can show the
+//! scrambles of a competition came from
+//! a fixed seed in order rather than
+//! being picked by hand.
+//!
+//! Each scramble is generated like the
+//! ScrambleGenerator with the same seed,
+//! and the log records its seed, how much
+//! randomness it drew, the options and
+//! its fingerprint. The JSON of the log
+//! is signed with HMAC-SHA256 under a key
+//! kept by the organizers, so any edit to
+//! the log after it was written shows.
+
+use std::error::Error;
+use std::fmt::{self, Write};
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::cube::SqOne;
+use crate::fingerprint::{self, sha256};
+use crate::json::{self, json_string, Json};
+use crate::{cstimer, splitmix, Scramble, ScrambleOptions};
+
+/// Version written at the start of
+/// the JSON of a log. Version 1 logs
+/// were drawn from StdRng rather than
+/// ChaCha20 and no longer replay, and
+/// version 2 logs wrote the options
+/// with Debug and had fingerprints
+/// of 64 bits.
+pub const AUDIT_VERSION: u32 = 3;
+
+/// Record of one generated scramble.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct AuditEntry {
+
+    /// Seed of the generator the
+    /// scramble was drawn with.
+    pub seed: u64,
+
+    /// Number of 32 bit words drawn
+    /// from the generator.
+    pub draws: u64,
+
+    /// SHA-256 of every byte drawn, in
+    /// hex, so a replay can be checked
+    /// to draw the same numbers.
+    pub draw_digest: String,
+
+    /// Options written with
+    /// options_text.
+    pub options: String,
+    pub scramble: Scramble,
+
+    /// Fingerprint from
+    /// fingerprint::fingerprint.
+    pub fingerprint: String,
+}
+
+/// Scrambles generated in order from
+/// one seed, each with its record.
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    key: Vec<u8>,
+    seed: u64,
+    entries: Vec<AuditEntry>,
+}
+
+/// Reasons an audit log fails to
+/// check out.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum AuditError {
+
+    /// The text is not JSON, failing at
+    /// this byte offset.
+    InvalidJson(usize),
+
+    /// A field is missing or holds
+    /// the wrong kind of value.
+    InvalidField(&'static str),
+
+    /// The signature does not match the
+    /// key, so the log was edited or
+    /// signed with another key.
+    BadSignature,
+
+    /// The entry at this index does not
+    /// follow from the seed of the log or
+    /// does not match its fingerprint.
+    Entry(usize),
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::InvalidJson(offset) => write!(f, "invalid JSON at byte {}", offset),
+            AuditError::InvalidField(name) => write!(f, "invalid field \"{}\"", name),
+            AuditError::BadSignature => write!(f, "signature does not match"),
+            AuditError::Entry(i) => write!(f, "entry {} does not match its record", i),
+        }
+    }
+}
+
+impl Error for AuditError {}
+
+/// Generator that passes on the numbers
+/// of another and keeps a hash of them.
+struct Recorder<R> {
+    rng: R,
+    draws: u64,
+    bytes: Vec<u8>,
+}
+
+impl<R: RngCore> RngCore for Recorder<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        self.draws += 1;
+        self.bytes.extend(value.to_le_bytes());
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let value = self.rng.next_u64();
+        self.draws += 2;
+        self.bytes.extend(value.to_le_bytes());
+        value
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest);
+        self.draws += dest.len().div_ceil(4) as u64;
+        self.bytes.extend_from_slice(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl AuditLog {
+
+    /// Creates an empty log whose scrambles
+    /// are drawn from seed and whose JSON
+    /// is signed with key.
+    pub fn new(key: &[u8], seed: u64) -> AuditLog {
+        AuditLog { key: key.to_vec(), seed, entries: vec![] }
+    }
+
+    /// Returns the seed of the log.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the records in the order
+    /// the scrambles were generated.
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Generates the next scramble with the
+    /// options and records it. The scramble
+    /// is the one a ScrambleGenerator with
+    /// the same seed gives at this index.
+    pub fn generate(&mut self, options: &ScrambleOptions) -> Scramble {
+        let seed = splitmix(self.seed.wrapping_add(self.entries.len() as u64));
+        let (scramble, draws, draw_digest) = draw(options, seed);
+        let fingerprint = fingerprint::fingerprint(&scramble).expect("generated scrambles are legal");
+
+        self.entries.push(AuditEntry {
+            seed,
+            draws,
+            draw_digest,
+            options: options_text(options),
+            scramble: scramble.clone(),
+            fingerprint,
+        });
+
+        scramble
+    }
+
+    /// Returns the log as signed JSON.
+    /// The signature is the last field and
+    /// covers all the text before it.
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self.entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{\"seed\":{},\"draws\":{},\"draw_digest\":\"{}\",\"options\":{},\"scramble\":{},\"fingerprint\":\"{}\"}}",
+                    entry.seed,
+                    entry.draws,
+                    entry.draw_digest,
+                    json_string(&entry.options),
+                    json_string(&entry.scramble.to_string()),
+                    entry.fingerprint,
+                )
+            })
+            .collect();
+        let body = format!("{{\"version\":{},\"seed\":{},\"entries\":[{}]", AUDIT_VERSION, self.seed, entries.join(","));
+
+        format!("{},\"signature\":\"{}\"}}", body, hex(&hmac(&self.key, body.as_bytes())))
+    }
+}
+
+/// Checks the JSON of a log against the
+/// key: that the signature matches, that
+/// each entry has the seed that follows
+/// from the seed of the log and that each
+/// scramble matches its fingerprint.
+/// Returns the number of entries.
+pub fn verify(json: &str, key: &[u8]) -> Result<usize, AuditError> {
+    let split = json.rfind(",\"signature\":").ok_or(AuditError::InvalidField("signature"))?;
+    let document = json::parse_json(json).map_err(AuditError::InvalidJson)?;
+    let signature = match document.field("signature") {
+        Some(Json::String(signature)) => signature,
+        _ => return Err(AuditError::InvalidField("signature")),
+    };
+    let signature = unhex(signature).ok_or(AuditError::BadSignature)?;
+    if !same_bytes(&signature, &hmac(key, &json.as_bytes()[..split])) {
+        return Err(AuditError::BadSignature);
+    }
+
+    let seed: u64 = number(&document, "seed")?;
+    let entries = match document.field("entries") {
+        Some(Json::Array(entries)) => entries,
+        _ => return Err(AuditError::InvalidField("entries")),
+    };
+    for (i, entry) in entries.iter().enumerate() {
+        let scramble = match entry.field("scramble") {
+            Some(Json::String(text)) => cstimer::parse(text).map_err(|_| AuditError::Entry(i))?,
+            _ => return Err(AuditError::InvalidField("scramble")),
+        };
+        let fingerprint = match entry.field("fingerprint") {
+            Some(Json::String(fingerprint)) => fingerprint,
+            _ => return Err(AuditError::InvalidField("fingerprint")),
+        };
+        let expected = splitmix(seed.wrapping_add(i as u64));
+        if number::<u64>(entry, "seed")? != expected || !fingerprint::verify(&scramble, fingerprint) {
+            return Err(AuditError::Entry(i));
+        }
+    }
+
+    Ok(entries.len())
+}
+
+/// Returns true if generating with the
+/// options from the seed of the entry
+/// draws the same numbers and gives the
+/// same scramble, for logs whose options
+/// are known to the checker.
+pub fn replay(entry: &AuditEntry, options: &ScrambleOptions) -> bool {
+    let (scramble, draws, draw_digest) = draw(options, entry.seed);
+    scramble == entry.scramble && draws == entry.draws && draw_digest == entry.draw_digest
+}
+
+/// Generates a scramble from the seed,
+/// returning it with the number of words
+/// drawn and the hash of them in hex.
+fn draw(options: &ScrambleOptions, seed: u64) -> (Scramble, u64, String) {
+    let mut rng = Recorder { rng: ChaCha20Rng::seed_from_u64(seed), draws: 0, bytes: vec![] };
+    let scramble = SqOne::new().scramble_with_rng(options, &mut rng);
+
+    (scramble, rng.draws, hex(&sha256(&rng.bytes)))
+}
+
+/// Returns the options as text that is
+/// the same in every run, naming each
+/// requirement. A filter is a function
+/// pointer whose address changes from
+/// run to run, so only whether one is
+/// set is written, and the generator
+/// is left out since a log always
+/// draws from its own seed.
+fn options_text(options: &ScrambleOptions) -> String {
+    format!(
+        "parity={:?} middle={:?} random_state={} min_slashes={} suffix={:?} layer={:?} pieces={:?} cube_shape={} difficulty={:?} filter={}",
+        options.parity,
+        options.middle,
+        options.random_state,
+        options.min_slashes,
+        options.suffix,
+        options.layer,
+        options.pieces,
+        options.cube_shape,
+        options.difficulty,
+        options.filter.is_some(),
+    )
+}
+
+/// Returns a whole number field
+/// of an object.
+fn number<T: std::str::FromStr>(json: &Json, name: &'static str) -> Result<T, AuditError> {
+    match json.field(name) {
+        Some(Json::Number(number)) => number.parse().map_err(|_| AuditError::InvalidField(name)),
+        _ => Err(AuditError::InvalidField(name)),
+    }
+}
+
+/// Returns the HMAC-SHA256 of the
+/// message under the key, as in
+/// RFC 2104.
+fn hmac(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0; 64];
+    if key.len() > block.len() {
+        block[..32].copy_from_slice(&sha256(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner: Vec<u8> = block.iter().map(|byte| byte ^ 0x36).collect();
+    inner.extend_from_slice(message);
+    let mut outer: Vec<u8> = block.iter().map(|byte| byte ^ 0x5c).collect();
+    outer.extend(sha256(&inner));
+
+    sha256(&outer)
+}
+
+/// Returns true if the slices hold the
+/// same bytes, taking the same time
+/// wherever they first differ so a
+/// signature cannot be guessed a byte
+/// at a time.
+fn same_bytes(a: &[u8], b: &[u8]) -> bool {
+    let diff = a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y));
+    a.len() == b.len() && core::hint::black_box(diff) == 0
+}
+
+/// Reads hex in either case as bytes.
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    let digits: Vec<u8> = text.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>()?;
+    Some(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect())
+}
+
+/// Returns the bytes as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::new();
+    for byte in bytes {
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fingerprint::FINGERPRINT_LEN;
+    use crate::{ScrambleGenerator, SuffixPolicy};
+
+    // The HMAC matches the test cases of
+    // RFC 4231 with a short key, and with
+    // a key longer than a block that is
+    // hashed first.
+    #[test]
+    fn hmac_sha256() {
+        let cases: [(&[u8], &[u8], &str); 4] = [
+            (&[0x0b; 20], b"Hi There", "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+            (b"Jefe", b"what do ya want for nothing?", "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+            (
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+            (
+                &[0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ),
+        ];
+        for (key, message, mac) in cases {
+            assert_eq!(hex(&hmac(key, message)), mac);
+        }
+    }
+
+    #[test]
+    fn signed_log() {
+        let options = ScrambleOptions::new();
+        let mut log = AuditLog::new(b"delegate key", 635);
+        let scrambles: Vec<Scramble> = (0..3).map(|_| log.generate(&options)).collect();
+        assert_eq!(scrambles, ScrambleGenerator::new(options, 635).generate_batch(3));
+        assert!(log.entries().iter().all(|entry| entry.draws > 0 && replay(entry, &options)));
+
+        let json = log.to_json();
+        assert_eq!(verify(&json, b"delegate key"), Ok(3));
+        assert_eq!(verify(&json, b"other key"), Err(AuditError::BadSignature));
+
+        let edited = json.replacen(&log.entries()[1].fingerprint, &"0".repeat(FINGERPRINT_LEN), 1);
+        assert_eq!(verify(&edited, b"delegate key"), Err(AuditError::BadSignature));
+
+        let (body, signature) = json.rsplit_once(":\"").unwrap();
+        let upper = format!("{}:\"{}", body, signature.to_uppercase());
+        assert_eq!(verify(&upper, b"delegate key"), Ok(3));
+        let short = format!("{}:\"{}", body, &signature[2..]);
+        assert_eq!(verify(&short, b"delegate key"), Err(AuditError::BadSignature));
+        let odd = format!("{}:\"{}", body, &signature[1..]);
+        assert_eq!(verify(&odd, b"delegate key"), Err(AuditError::BadSignature));
+    }
+
+    // Options with a filter write the same
+    // text whatever its address.
+    #[test]
+    fn stable_options() {
+        let options = ScrambleOptions::wca().filter(|_, _| true).suffix(SuffixPolicy::Twist);
+        assert_eq!(
+            options_text(&options),
+            "parity=None middle=None random_state=true min_slashes=11 suffix=Twist layer=None pieces=None cube_shape=false difficulty=None filter=true",
+        );
+        assert_eq!(options_text(&options), options_text(&ScrambleOptions::wca().filter(|_, _| false).suffix(SuffixPolicy::Twist)));
+    }
+}
@@ -0,0 +1,9 @@
+use sq1_scrambler::server;
+
+fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| "127.0.0.1:8080".to_string());
+    println!("serving scrambles on {}", addr);
+    if let Err(error) = server::serve(&addr) {
+        eprintln!("{}", error);
+    }
+}
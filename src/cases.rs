@@ -0,0 +1,271 @@
+//! Module for recognizing the PBL
+//! (permutation of both layers) case
+//! of a cube in cube shape with every
+//! piece in its own layer.
+
+use crate::cube::SqOne;
+use crate::shape;
+
+/// Permutation of the corners of a
+/// layer, ignoring the edges.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CornerCase {
+    Solved,
+    Adjacent,
+    Opposite,
+}
+
+/// Permutation of a single layer up to
+/// turns of the layer before and after.
+/// Even permutations are named after the
+/// matching 3x3 PLL.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum LayerCase {
+    Solved,
+    Aa,
+    Ab,
+    E,
+    F,
+    Ga,
+    Gb,
+    Gc,
+    Gd,
+    H,
+    Ja,
+    Jb,
+    Na,
+    Nb,
+    Ra,
+    Rb,
+    T,
+    Ua,
+    Ub,
+    V,
+    Y,
+    Z,
+
+    /// An odd permutation, which has no
+    /// 3x3 equivalent and needs a parity
+    /// algorithm to solve.
+    Parity(CornerCase),
+}
+
+/// Cases of both layers of a cube.
+/// The bottom layer is read as seen
+/// from below, like the top layer is
+/// read from above.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct PblCase {
+    pub top: LayerCase,
+    pub bottom: LayerCase,
+}
+
+/// Corner and edge permutation of each
+/// 3x3 PLL, listing the position each
+/// piece came from, with corners and
+/// edges numbered clockwise from the
+/// back left corner and back edge.
+const PLLS: [(LayerCase, [u8; 4], [u8; 4]); 22] = [
+    (LayerCase::Solved, [0, 1, 2, 3], [0, 1, 2, 3]),
+    (LayerCase::Aa, [2, 0, 1, 3], [0, 1, 2, 3]),
+    (LayerCase::Ab, [0, 2, 3, 1], [0, 1, 2, 3]),
+    (LayerCase::E, [3, 2, 1, 0], [0, 1, 2, 3]),
+    (LayerCase::F, [0, 2, 1, 3], [2, 1, 0, 3]),
+    (LayerCase::Ga, [0, 2, 1, 3], [3, 2, 0, 1]),
+    (LayerCase::Gb, [0, 2, 1, 3], [2, 3, 1, 0]),
+    (LayerCase::Gc, [0, 2, 1, 3], [2, 0, 3, 1]),
+    (LayerCase::Gd, [0, 2, 1, 3], [1, 3, 0, 2]),
+    (LayerCase::H, [0, 1, 2, 3], [2, 3, 0, 1]),
+    (LayerCase::Ja, [1, 2, 0, 3], [1, 3, 2, 0]),
+    (LayerCase::Jb, [3, 0, 2, 1], [3, 0, 2, 1]),
+    (LayerCase::Na, [0, 3, 2, 1], [0, 3, 2, 1]),
+    (LayerCase::Nb, [2, 1, 0, 3], [0, 3, 2, 1]),
+    (LayerCase::Ra, [3, 0, 2, 1], [0, 3, 1, 2]),
+    (LayerCase::Rb, [2, 1, 3, 0], [1, 3, 2, 0]),
+    (LayerCase::T, [0, 2, 1, 3], [0, 3, 2, 1]),
+    (LayerCase::Ua, [0, 1, 2, 3], [0, 2, 3, 1]),
+    (LayerCase::Ub, [0, 1, 2, 3], [0, 3, 1, 2]),
+    (LayerCase::V, [2, 1, 0, 3], [1, 0, 2, 3]),
+    (LayerCase::Y, [2, 1, 0, 3], [3, 1, 2, 0]),
+    (LayerCase::Z, [3, 0, 1, 2], [2, 1, 0, 3]),
+];
+
+impl LayerCase {
+
+    /// Returns the permutation of the
+    /// corners of the layer.
+    pub fn corners(&self) -> CornerCase {
+        match self {
+            LayerCase::Parity(corners) => *corners,
+            case => {
+                let (_, corners, _) = PLLS.iter().find(|(c, _, _)| c == case).unwrap();
+                corner_case(corners)
+            },
+        }
+    }
+}
+
+/// Returns the PBL case of the cube, or
+/// None if the cube is not in cube shape
+/// or a piece is in the wrong layer.
+pub fn identify(cube: &SqOne) -> Option<PblCase> {
+    if !shape::classify(cube).is_cube() {
+        return None;
+    }
+
+    let (top, bottom, _) = cube.slots();
+    let (solved_top, solved_bottom, _) = SqOne::new().slots();
+    let mut bottom = bottom;
+    let mut solved_bottom = solved_bottom;
+    bottom.reverse();
+    solved_bottom.reverse();
+
+    Some(PblCase {
+        top: layer_case(&top, &solved_top)?,
+        bottom: layer_case(&bottom, &solved_bottom)?,
+    })
+}
+
+/// Returns the corners and edges of a
+/// layer in order, starting from the
+/// first corner.
+fn read_layer(layer: &[u8; 12]) -> ([u8; 4], [u8; 4]) {
+    let start = (0..12)
+        .find(|&i| layer[i] < 8 && layer[i] != layer[(i + 11) % 12])
+        .unwrap();
+    let mut corners = [0; 4];
+    let mut edges = [0; 4];
+    let (mut c, mut e) = (0, 0);
+    for i in (start..start + 12).map(|i| i % 12) {
+        if layer[i] == layer[(i + 11) % 12] {
+            continue;
+        }
+        if layer[i] < 8 {
+            corners[c] = layer[i];
+            c += 1;
+        } else {
+            edges[e] = layer[i];
+            e += 1;
+        }
+    }
+
+    (corners, edges)
+}
+
+/// Returns the case of a layer in cube
+/// shape compared to the solved layer,
+/// or None if it holds a piece from
+/// the other layer.
+fn layer_case(layer: &[u8; 12], solved: &[u8; 12]) -> Option<LayerCase> {
+    let (corners, edges) = read_layer(layer);
+    let (solved_corners, solved_edges) = read_layer(solved);
+
+    // Number each piece by the position
+    // it holds in the solved layer.
+    let mut corner_perm = [0; 4];
+    let mut edge_perm = [0; 4];
+    for i in 0..4 {
+        corner_perm[i] = solved_corners.iter().position(|&c| c == corners[i])? as u8;
+        edge_perm[i] = solved_edges.iter().position(|&e| e == edges[i])? as u8;
+    }
+
+    let key = case_key(&corner_perm, &edge_perm);
+    let case = PLLS
+        .iter()
+        .find(|(_, corners, edges)| case_key(corners, edges) == key)
+        .map(|&(case, _, _)| case)
+        .unwrap_or(LayerCase::Parity(corner_case(&corner_perm)));
+
+    Some(case)
+}
+
+/// Returns the pieces after turning the
+/// layer before and after the case,
+/// renumbered by their new positions.
+fn turned(pieces: &[u8; 4], before: u8, after: u8) -> [u8; 4] {
+    let mut turned = [0; 4];
+    for (i, piece) in turned.iter_mut().enumerate() {
+        *piece = (pieces[(i + before as usize) % 4] + 8 - before + after) % 4;
+    }
+
+    turned
+}
+
+/// Returns the smallest encoding of the
+/// permutation over every turn of the
+/// layer before and after it, so every
+/// permutation of a case has the same key.
+fn case_key(corners: &[u8; 4], edges: &[u8; 4]) -> [u8; 8] {
+    let mut best = [u8::MAX; 8];
+    for before in 0..4 {
+        for after in 0..4 {
+            let corners = turned(corners, before, after);
+            let edges = turned(edges, before, after);
+            let mut key = [0; 8];
+            key[..4].copy_from_slice(&corners);
+            key[4..].copy_from_slice(&edges);
+            best = best.min(key);
+        }
+    }
+
+    best
+}
+
+/// Returns the permutation of the corners
+/// up to turns of the layer.
+fn corner_case(corners: &[u8; 4]) -> CornerCase {
+    let key = |corners: &[u8; 4]| {
+        (0..4)
+            .flat_map(|before| (0..4).map(move |after| (before, after)))
+            .map(|(before, after)| turned(corners, before, after))
+            .min()
+            .unwrap()
+    };
+
+    if key(corners) == key(&[0, 1, 2, 3]) {
+        CornerCase::Solved
+    } else if key(corners) == key(&[0, 2, 1, 3]) {
+        CornerCase::Adjacent
+    } else {
+        CornerCase::Opposite
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identify_solved_cube() {
+        let case = identify(&SqOne::new()).unwrap();
+        assert_eq!(case, PblCase { top: LayerCase::Solved, bottom: LayerCase::Solved });
+        assert_eq!(case.top.corners(), CornerCase::Solved);
+    }
+
+    #[test]
+    fn identify_needs_cube_shape() {
+        let mut cube = SqOne::new();
+        cube.try_slash().unwrap();
+        assert_eq!(identify(&cube), None);
+    }
+
+    // Every 3x3 PLL has its own key, and
+    // the key ignores turns of the layer.
+    #[test]
+    fn pll_keys_are_distinct() {
+        for (i, (_, corners, edges)) in PLLS.iter().enumerate() {
+            for (_, other_corners, other_edges) in &PLLS[i + 1..] {
+                assert_ne!(case_key(corners, edges), case_key(other_corners, other_edges));
+            }
+        }
+        assert_eq!(case_key(&[1, 2, 3, 0], &[1, 2, 3, 0]), case_key(&[0, 1, 2, 3], &[0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn classify_corner_cases() {
+        assert_eq!(LayerCase::T.corners(), CornerCase::Adjacent);
+        assert_eq!(LayerCase::Na.corners(), CornerCase::Opposite);
+        assert_eq!(LayerCase::H.corners(), CornerCase::Solved);
+        assert_eq!(LayerCase::E.corners(), CornerCase::Opposite);
+    }
+}
@@ -0,0 +1,158 @@
+//! Module for checking moves entered one
+//! at a time against a scramble, so
+//! beginners learning to apply scrambles
+//! are told as soon as they go wrong and
+//! how to get back on track.
+
+use crate::cube::SqOne;
+use crate::{Move, Scramble, VerifyError};
+
+/// Response to a move given to
+/// a Checker.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Feedback {
+
+    /// The cube is where the scramble has
+    /// it, either after the move expected
+    /// next or, once back on track, after
+    /// the moves so far.
+    Correct,
+
+    /// The move cannot be made, as with a
+    /// blocked slash, so the cube is left
+    /// as it was. Holds the move expected
+    /// next, or None once the scramble
+    /// is done.
+    WrongMove { expected: Option<Move> },
+
+    /// The cube is not where the scramble
+    /// has it. Holds moves that take the
+    /// cube back to the scramble after the
+    /// moves made correctly so far.
+    OffTrack { fix: Scramble },
+}
+
+/// Stateful checker of the moves made
+/// while applying a scramble to a
+/// solved cube, created by follow.
+#[derive(Clone, Debug)]
+pub struct Checker {
+    moves: Vec<Move>,
+
+    /// Cube after each number of moves
+    /// of the scramble, from 0 to all.
+    states: Vec<SqOne>,
+    done: usize,
+    cube: SqOne,
+}
+
+/// Returns a checker for applying the
+/// scramble to a solved cube, or an
+/// error if a slash of it is blocked.
+pub fn follow(scramble: &Scramble) -> Result<Checker, VerifyError> {
+    scramble.is_legal_from(&SqOne::new()).map_err(|at| VerifyError::IllegalSlash(at.turn))?;
+
+    let mut states = vec![SqOne::new()];
+    states.extend(scramble.replay(&SqOne::new()).map(|(_, cube)| cube));
+
+    Ok(Checker {
+        moves: scramble.moves(),
+        states,
+        done: 0,
+        cube: SqOne::new(),
+    })
+}
+
+impl Checker {
+
+    /// Makes the move on the cube and
+    /// checks it against the scramble.
+    pub fn push(&mut self, next: Move) -> Feedback {
+        if self.cube.apply_move(next).is_err() {
+            return Feedback::WrongMove { expected: self.expected() };
+        }
+
+        // Moves are checked by the cube they
+        // give, so a twist split into a turn
+        // of each layer is correct once both
+        // layers are turned.
+        if self.done < self.moves.len() && self.cube == self.states[self.done + 1] {
+            self.done += 1;
+            return Feedback::Correct;
+        }
+        if self.cube == self.states[self.done] {
+            return Feedback::Correct;
+        }
+
+        Feedback::OffTrack { fix: Scramble::between(&self.cube, &self.states[self.done]) }
+    }
+
+    /// Returns the move expected next, or
+    /// None once the scramble is done.
+    pub fn expected(&self) -> Option<Move> {
+        self.moves.get(self.done).copied()
+    }
+
+    /// Returns the number of moves of
+    /// the scramble made correctly.
+    pub fn position(&self) -> usize {
+        self.done
+    }
+
+    /// Returns the moves of the
+    /// scramble left to make.
+    pub fn remaining(&self) -> &[Move] {
+        &self.moves[self.done..]
+    }
+
+    /// Returns the cube with
+    /// every move made.
+    pub fn cube(&self) -> &SqOne {
+        &self.cube
+    }
+
+    /// Returns true if every move of the
+    /// scramble has been made and the cube
+    /// is in the scrambled state.
+    pub fn is_done(&self) -> bool {
+        self.done == self.moves.len() && self.cube == self.states[self.done]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_moves() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (6, 0)] };
+        let mut checker = follow(&scramble).unwrap();
+        assert_eq!(checker.push(Move::Twist(1, 0)), Feedback::Correct);
+        assert_eq!(checker.push(Move::Slash), Feedback::Correct);
+
+        // The twist is split in two, so the
+        // cube is off track until both
+        // layers are turned.
+        assert!(matches!(checker.push(Move::Twist(-3, 0)), Feedback::OffTrack { .. }));
+        assert_eq!(checker.push(Move::Twist(0, 2)), Feedback::Correct);
+        assert_eq!(checker.position(), 3);
+
+        // A wrong twist is fixed by the
+        // moves given with the feedback.
+        let fix = match checker.push(Move::Twist(1, 0)) {
+            Feedback::OffTrack { fix } => fix,
+            feedback => panic!("{:?}", feedback),
+        };
+        assert_eq!(checker.push(Move::Slash), Feedback::WrongMove { expected: Some(Move::Slash) });
+        let mut last = Feedback::Correct;
+        for next in fix.moves() {
+            last = checker.push(next);
+        }
+        assert_eq!(last, Feedback::Correct);
+
+        assert_eq!(checker.push(Move::Slash), Feedback::Correct);
+        assert_eq!(checker.push(Move::Twist(6, 0)), Feedback::Correct);
+        assert!(checker.is_done());
+        assert!(checker.remaining().is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+//! Module for reading and writing
+//! scrambles in the notation used by
+//! csTimer.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::cube::twist_amount;
+use crate::notation::ScrambleFormat;
+use crate::Scramble;
+
+/// Errors returned when a scramble
+/// cannot be read.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ParseError {
+
+    /// The text holds no turns
+    /// or slashes.
+    Empty,
+
+    /// A turn is not written as (x,y)
+    /// with whole numbers from -6 to 6.
+    InvalidTurn(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "scramble is empty"),
+            ParseError::InvalidTurn(turn) => write!(f, "invalid turn {:?}", turn),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Returns the scramble written the
+/// way csTimer writes it, with a space
+/// on each side of every slash, as in
+/// (1,0) / (-3,2).
+pub fn format(scramble: &Scramble) -> String {
+    scramble.format_with(&ScrambleFormat::cstimer())
+}
+
+/// Returns the scrambles with one on
+/// each line, which csTimer accepts
+/// as a list of scrambles to import.
+pub fn format_list(scrambles: &[Scramble]) -> String {
+    scrambles.iter().map(format).collect::<Vec<String>>().join("\n")
+}
+
+/// Reads a scramble in csTimer notation.
+/// Spacing is ignored, and a slash with
+/// no turn before or after it stands
+/// for a turn of (0,0), so scrambles
+/// may start or end with a slash.
+pub fn parse(text: &str) -> Result<Scramble, ParseError> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if text.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    let turns = text.split('/').map(parse_turn).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Scramble { turns })
+}
+
+/// Reads a list of scrambles with one on
+/// each line, skipping blank lines and
+/// the numbering csTimer adds, as in
+/// "1. (1,0) / (-3,2)".
+pub fn parse_list(text: &str) -> Result<Vec<Scramble>, ParseError> {
+    text.lines()
+        .map(|line| {
+            let line = line.trim();
+            match line.split_once(". ") {
+                Some((number, rest)) if number.chars().all(|c| c.is_ascii_digit()) => rest,
+                _ => line,
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .map(parse)
+        .collect()
+}
+
+/// Reads a single turn with its spaces
+/// removed. An empty turn is (0,0).
+fn parse_turn(turn: &str) -> Result<(i8, i8), ParseError> {
+    if turn.is_empty() {
+        return Ok((0, 0));
+    }
+
+    turn.strip_prefix('(')
+        .and_then(|turn| turn.strip_suffix(')'))
+        .and_then(parse_pair)
+        .ok_or_else(|| ParseError::InvalidTurn(turn.to_string()))
+}
+
+/// Reads the amounts of a turn written
+/// as x,y without parentheses or spaces.
+pub(crate) fn parse_pair(pair: &str) -> Option<(i8, i8)> {
+    let (top, bottom) = pair.split_once(',')?;
+
+    // A half turn may be written as
+    // either 6 or -6, and is kept as 6.
+    let amount = |amount: &str| amount.parse::<i8>().ok().and_then(twist_amount);
+
+    Some((amount(top)?, amount(bottom)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use crate::cube::SqOne;
+
+    #[test]
+    fn parse_cstimer_scramble() {
+        let scramble = parse("(1,0) / (-3,2)/(0, -6) /").unwrap();
+        assert_eq!(scramble.turns, vec![(1, 0), (-3, 2), (0, 6), (0, 0)]);
+        assert_eq!(parse("(-6,0)"), parse("(6,0)"));
+        assert_eq!(parse(" "), Err(ParseError::Empty));
+        assert_eq!(parse("(1,0) / (7,0)"), Err(ParseError::InvalidTurn("(7,0)".to_string())));
+        assert_eq!(parse("1,0"), Err(ParseError::InvalidTurn("1,0".to_string())));
+    }
+
+    // The expected slots are worked out by
+    // hand from the notation, not taken
+    // from csTimer: a positive turn is
+    // clockwise seen from that face, and a
+    // slash swaps slots 6 to 11 of each
+    // layer.
+    #[test]
+    fn notation_state() {
+        let mut cube = SqOne::new();
+        cube.apply(&parse("(1,0) / (-3,2) / (0,-3) / (3,-2) /").unwrap()).unwrap();
+        let top = [3, 3, 13, 8, 1, 1, 9, 2, 2, 10, 5, 5];
+        let bottom = [4, 4, 6, 6, 14, 12, 0, 0, 11, 7, 7, 15];
+        assert_eq!(cube.slots(), (top, bottom, false));
+    }
+
+    // Writing a scramble and reading it
+    // back gives the same cube.
+    #[test]
+    fn round_trip_scrambles() {
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble();
+        let text = format_list(&[scramble.clone(), Scramble { turns: vec![(0, 0)] }]);
+        let parsed = parse_list(&format!("1. {}\n\n", text)).unwrap();
+        assert_eq!(parsed[0], scramble);
+        assert_eq!(parsed[0].verify(&cube), Ok(()));
+        assert_eq!(parsed.len(), 2);
+    }
+}
@@ -2,11 +2,124 @@
 //! with the orientation of a square 1
 //! cube in order to produce a scramble.
 
-use crate::Scramble;
+#[cfg(any(feature = "solver", feature = "render"))]
+use alloc::format;
+use alloc::boxed::Box;
+use alloc::string::String;
+#[cfg(feature = "solver")]
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::error::Error;
+use core::fmt;
+use core::hash::{Hash, Hasher};
 
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "solver")]
+use rand::SeedableRng;
+
+#[cfg(feature = "solver")]
+use crate::export;
+use crate::fen::{self, FenError};
+use crate::memo::{self, Lettering, Memo};
+use crate::permutation::Permutation;
+use crate::policy::{MovePolicy, UniformLayers};
+use crate::recovery::{self, Diff};
+#[cfg(feature = "render")]
+use crate::render;
+use crate::shape;
+use crate::symmetry::{self, Symmetry};
+#[cfg(feature = "solver")]
+use crate::shape::{Shape, ShapeState};
+#[cfg(feature = "solver")]
+use crate::solver::{self, Position};
+#[cfg(feature = "solver")]
+use crate::{Metric, OptionsError, ScrambleOptions, SuffixPolicy, FILTER_TRIES, WCA_MIN_SLASHES};
+use crate::{Move, Scramble};
+
+/// Errors returned when a move
+/// cannot be made on the cube.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SqOneError {
+
+    /// A corner crosses the slash
+    /// line, so the cube cannot
+    /// be slashed.
+    SlashBlocked,
+
+    /// A twist amount is outside
+    /// the range -6 to 6.
+    TwistOutOfRange(i8),
+}
+
+impl fmt::Display for SqOneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SqOneError::SlashBlocked => write!(f, "a corner is blocking the slash"),
+            SqOneError::TwistOutOfRange(amount) => {
+                write!(f, "twist of {} is outside the range -6 to 6", amount)
+            },
+        }
+    }
+}
+
+impl Error for SqOneError {}
+
+/// Broken invariants of the internal
+/// state of a cube, which no sequence
+/// of moves can produce.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum InvariantError {
+
+    /// The layer does not have
+    /// twelve half slots. Layers are
+    /// held in fixed arrays, so this is
+    /// no longer returned.
+    LayerLength(Layer),
+
+    /// The offset of the layer is
+    /// outside the range -5 to 6.
+    OffsetOutOfRange(Layer, i8),
+
+    /// The corner starting at this slot
+    /// of the layer has no second half,
+    /// or the slot is a second half with
+    /// no corner before it.
+    SplitCorner(Layer, usize),
+
+    /// The piece is not on the cube.
+    MissingPiece(PieceId),
+
+    /// The piece is on the cube
+    /// more than once.
+    DuplicatePiece(PieceId),
+}
+
+impl fmt::Display for InvariantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvariantError::LayerLength(layer) => write!(f, "{:?} layer does not have 12 slots", layer),
+            InvariantError::OffsetOutOfRange(layer, offset) => {
+                write!(f, "{:?} layer offset {} is outside the range -5 to 6", layer, offset)
+            },
+            InvariantError::SplitCorner(layer, slot) => write!(f, "corner split at slot {} of {:?} layer", slot, layer),
+            InvariantError::MissingPiece(piece) => write!(f, "piece {} is missing", piece.index()),
+            InvariantError::DuplicatePiece(piece) => write!(f, "piece {} is repeated", piece.index()),
+        }
+    }
+}
+
+impl Error for InvariantError {}
+
+/// Color of a sticker on a solved
+/// cube in the western scheme. The
+/// color scheme in use decides how
+/// each is drawn.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 #[repr(u8)]
-enum Color {
+pub enum Color {
     White = 0,
     Yellow,
     Blue = 3,
@@ -15,10 +128,83 @@ enum Color {
     Orange,
 }
 
+/// Colors drawn for each face of the
+/// cube, as SVG colors. The scheme only
+/// changes how the cube is drawn, so
+/// scrambles are the same in any scheme.
+/// Cubes hold a &'static scheme, so
+/// cloning one never copies the colors.
+/// A custom scheme is made a static
+/// with from_colors.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ColorScheme {
+    pub up: &'static str,
+    pub down: &'static str,
+    pub front: &'static str,
+    pub right: &'static str,
+    pub back: &'static str,
+    pub left: &'static str,
+}
+
+/// Scheme returned by
+/// ColorScheme::western.
+static WESTERN: ColorScheme = ColorScheme::from_colors(["#ffffff", "#ffd500", "#009b48", "#b90000", "#0045ad", "#ff5900"]);
+
+/// Scheme returned by
+/// ColorScheme::japanese.
+static JAPANESE: ColorScheme = ColorScheme::from_colors(["#ffffff", "#0045ad", "#009b48", "#b90000", "#ffd500", "#ff5900"]);
+
+impl ColorScheme {
+
+    /// Returns the western scheme, with
+    /// white opposite yellow, green
+    /// opposite blue and red opposite
+    /// orange.
+    pub fn western() -> &'static ColorScheme {
+        &WESTERN
+    }
+
+    /// Returns the Japanese scheme, with
+    /// white opposite blue, green opposite
+    /// yellow and red opposite orange.
+    pub fn japanese() -> &'static ColorScheme {
+        &JAPANESE
+    }
+
+    /// Creates a scheme from the colors
+    /// of the up, down, front, right, back
+    /// and left faces.
+    pub const fn from_colors(colors: [&'static str; 6]) -> ColorScheme {
+        let [up, down, front, right, back, left] = colors;
+        ColorScheme { up, down, front, right, back, left }
+    }
+
+    /// Returns the color drawn for the
+    /// face a piece color belongs to.
+    #[cfg(feature = "render")]
+    fn color(&self, color: Color) -> &'static str {
+        match color {
+            Color::White => self.up,
+            Color::Yellow => self.down,
+            Color::Green => self.front,
+            Color::Orange => self.left,
+            Color::Blue => self.back,
+            Color::Red => self.right,
+        }
+    }
+}
+
+impl Default for ColorScheme {
+    fn default() -> ColorScheme {
+        WESTERN
+    }
+}
+
 /// Returns true if the combination 
 /// of colors is possible on the
 /// cube. (Opposite colors, ex. yellow
 /// and white, cannot be on the same piece)
+#[allow(dead_code)]
 fn possible(c1: u8, c2: u8) -> bool {
     c1.abs_diff(c2) != 1
 }
@@ -33,6 +219,7 @@ impl EdgeColor {
     /// Returns true if the edge created
     /// contains a possible combination
     /// of colors
+    #[allow(dead_code)]
     pub fn possible(&self) -> bool {
         possible(self.colors.0 as u8, self.colors.1 as u8)
     }
@@ -48,6 +235,7 @@ impl CornerColor {
     /// Function returns true if the
     /// combination of colors is possible
     /// for the corner
+    #[allow(dead_code)]
     pub fn possible(&self) -> bool {
         possible(self.colors.0 as u8, self.colors.1 as u8)
             && possible(self.colors.1 as u8, self.colors.2 as u8)
@@ -62,6 +250,78 @@ enum Piece {
     Corner(CornerColor),
 }
 
+impl Piece {
+
+    /// Returns the index of the piece
+    /// on a solved cube. Corners are
+    /// numbered 0-7 and edges 8-15,
+    /// with the top layer pieces first.
+    fn index(&self) -> u8 {
+        match self {
+            Piece::Corner(c) => layer_index(c.colors.2) * 4 + color_index(c.colors.1),
+            Piece::Edge(e) => 8 + layer_index(e.colors.1) * 4 + color_index(e.colors.0),
+        }
+    }
+}
+
+/// Stable identity of a piece, given by
+/// its index on a solved cube. Corners
+/// are numbered 0-7 and edges 8-15,
+/// with the top layer pieces first.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct PieceId(u8);
+
+impl PieceId {
+
+    /// Returns the piece with the given
+    /// index, or None if the index is
+    /// not below 16.
+    pub fn new(index: u8) -> Option<PieceId> {
+        (index < 16).then_some(PieceId(index))
+    }
+
+    /// Returns the index of the piece.
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns true if the piece
+    /// is a corner.
+    pub fn is_corner(&self) -> bool {
+        self.0 < 8
+    }
+
+    /// Returns every piece in order
+    /// of index.
+    pub fn all() -> impl Iterator<Item = PieceId> {
+        (0..16).map(PieceId)
+    }
+}
+
+/// Top or bottom layer of the cube.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Layer {
+    Top,
+    Bottom,
+}
+
+impl Layer {
+
+    /// Returns the degrees a twist of the
+    /// layer by the number of slots turns
+    /// its pieces, in the angles of
+    /// PieceView. The bottom layer turns
+    /// the other way, as seen from above.
+    /// A fraction of the amount gives the
+    /// layer part way through the twist.
+    pub fn twist_degrees(self, amount: f64) -> f64 {
+        match self {
+            Layer::Top => 30.0 * amount,
+            Layer::Bottom => -30.0 * amount,
+        }
+    }
+}
+
 const COLOR_ORDER: [Color; 4] = [
     Color::Green,
     Color::Orange,
@@ -69,13 +329,201 @@ const COLOR_ORDER: [Color; 4] = [
     Color::Red,
 ];
 
-#[derive(Debug)]
+/// Returns the position of the side
+/// color in the COLOR_ORDER array.
+fn color_index(color: Color) -> u8 {
+    COLOR_ORDER.iter().position(|&c| c == color).unwrap() as u8
+}
+
+/// Returns 0 for a piece belonging
+/// to the top (white) layer and 1
+/// for the bottom (yellow) layer.
+fn layer_index(color: Color) -> u8 {
+    (color == Color::Yellow) as u8
+}
+
+/// Kinds of piece in the top and
+/// bottom layers.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum PieceKind {
+    Edge,
+    Corner,
+}
+
+/// Pieces covering the slots of a
+/// layer, from SqOne::slot_occupancy.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Occupancy {
+
+    /// Kind of piece covering each
+    /// physical slot. A corner covers
+    /// two slots.
+    pub slots: [PieceKind; 12],
+
+    /// Number of corners and edges
+    /// in the layer.
+    pub corners: usize,
+    pub edges: usize,
+}
+
+/// Read only view of a piece in
+/// a layer of the cube.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct PieceView {
+    pub id: PieceId,
+    pub kind: PieceKind,
+
+    /// Color facing up or down, then
+    /// the side colors in slot order.
+    pub colors: Vec<Color>,
+
+    /// Angle in degrees from the start
+    /// of slot 0 to the start of the
+    /// piece, 30 for each slot.
+    pub angle: u16,
+
+    /// Degrees the piece covers, 30 for
+    /// an edge and 60 for a corner.
+    pub width: u16,
+}
+
+impl PieceView {
+
+    /// Returns the angle in degrees of
+    /// the middle of the piece.
+    pub fn center(&self) -> f64 {
+        (self.angle as f64 + self.width as f64 / 2.0) % 360.0
+    }
+
+    /// Returns the angle of the start of
+    /// the piece once its layer is turned
+    /// by the degrees, from 0 up to 360.
+    pub fn rotated(&self, degrees: f64) -> f64 {
+        let angle = (self.angle as f64 + degrees) % 360.0;
+        if angle < 0.0 { angle + 360.0 } else { angle }
+    }
+}
+
+/// Pieces of each layer split by the
+/// color facing up or down, from
+/// SqOne::pieces_by_layer_color. White
+/// pieces start in the top layer and
+/// yellow pieces in the bottom layer.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LayerColors {
+
+    /// Pieces of the top layer with white
+    /// or yellow facing up, in slot order.
+    pub top_white: Vec<PieceId>,
+    pub top_yellow: Vec<PieceId>,
+
+    /// Pieces of the bottom layer with
+    /// white or yellow facing down, in
+    /// slot order as seen from above.
+    pub bottom_white: Vec<PieceId>,
+    pub bottom_yellow: Vec<PieceId>,
+}
+
+impl LayerColors {
+
+    /// Returns the pieces of the kind
+    /// that are out of their own layer,
+    /// yellow on top then white on the
+    /// bottom.
+    pub fn misoriented(&self, kind: PieceKind) -> Vec<PieceId> {
+        self.top_yellow
+            .iter()
+            .chain(&self.bottom_white)
+            .copied()
+            .filter(|piece| piece.is_corner() == (kind == PieceKind::Corner))
+            .collect()
+    }
+
+    /// Returns true if every corner is
+    /// in its own layer.
+    pub fn corners_oriented(&self) -> bool {
+        self.misoriented(PieceKind::Corner).is_empty()
+    }
+
+    /// Returns true if every edge is
+    /// in its own layer.
+    pub fn edges_oriented(&self) -> bool {
+        self.misoriented(PieceKind::Edge).is_empty()
+    }
+}
+
+/// Sticker colors of a piece in a
+/// layer, used for drawing the cube.
+#[cfg(feature = "render")]
+pub(crate) struct Sticker<'a> {
+    pub(crate) id: PieceId,
+
+    /// First physical slot the
+    /// piece covers.
+    pub(crate) slot: usize,
+
+    pub(crate) corner: bool,
+
+    /// Color facing up or down.
+    pub(crate) face: &'a str,
+
+    /// Side colors in slot order.
+    pub(crate) sides: Vec<&'a str>,
+}
+
+#[derive(Clone, Debug)]
 pub struct SqOne {
-    top: Vec<Option<Piece>>,
+    top: [Option<Piece>; 12],
     top_offset: i8,
-    bottom: Vec<Option<Piece>>,
+    bottom: [Option<Piece>; 12],
     bottom_offset: i8,
     middle: bool,
+
+    // Moves made so far and moves that
+    // were undone, if history is kept.
+    history: Option<Vec<Move>>,
+    undone: Vec<Move>,
+
+    scheme: &'static ColorScheme,
+    observers: Observers,
+}
+
+/// Move made on a cube, sent to the
+/// observers given to SqOne::subscribe.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct MoveEvent {
+    pub action: Move,
+
+    /// Offsets of the top and bottom
+    /// layers before and after the move.
+    /// A slash leaves them unchanged.
+    pub before: (i8, i8),
+    pub after: (i8, i8),
+
+    /// Whether the middle layer is
+    /// flipped after the move.
+    pub middle: bool,
+}
+
+/// Function called with each move.
+pub type Observer = Box<dyn FnMut(&MoveEvent) + Send + Sync>;
+
+/// Observers of a cube. Clones start
+/// with none, so copies made to search
+/// or check moves send no events.
+#[derive(Default)]
+struct Observers(Vec<Observer>);
+
+impl Clone for Observers {
+    fn clone(&self) -> Observers {
+        Observers::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} observers", self.0.len())
+    }
 }
 
 /// Returns the value of a % n
@@ -85,11 +533,84 @@ pub struct SqOne {
 ///
 /// Ex:     -1 % 4 == -1, abs_mod(-1, 4) == 3
 fn abs_mod(a: i8, n: i8) -> u8 {
-    let mut a = a;
-    while a < 0 {
-        a += n;
+    a.rem_euclid(n) as u8
+}
+
+/// Returns a twist amount from -6 to 6
+/// in the range -5 to 6, writing a half
+/// turn of -6 as 6, or None if it is
+/// more than a half turn.
+pub(crate) fn twist_amount(amount: i8) -> Option<i8> {
+    match amount {
+        -6 => Some(6),
+        -5..=6 => Some(amount),
+        _ => None,
+    }
+}
+
+impl Default for SqOne {
+    fn default() -> SqOne {
+        SqOne::new()
+    }
+}
+
+// Cubes are compared by the piece in
+// each slot and the middle layer, so
+// cubes twisted into the same state are
+// equal whatever their history or color
+// scheme.
+impl PartialEq for SqOne {
+    fn eq(&self, other: &SqOne) -> bool {
+        self.slots() == other.slots()
+    }
+}
+
+impl Eq for SqOne {}
+
+impl Hash for SqOne {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.slots().hash(state);
+    }
+}
+
+impl PartialOrd for SqOne {
+    fn partial_cmp(&self, other: &SqOne) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SqOne {
+    fn cmp(&self, other: &SqOne) -> Ordering {
+        self.slots().cmp(&other.slots())
+    }
+}
+
+/// Most slashes made from a solved
+/// cube to build an arbitrary cube.
+#[cfg(any(test, feature = "arbitrary"))]
+const ARBITRARY_SLASHES: u8 = 40;
+
+// Only legal states are built, by making
+// twists and slashes the input picks from
+// a solved cube, so fuzzers and property
+// tests never see a cube no moves reach.
+#[cfg(any(test, feature = "arbitrary"))]
+impl<'a> arbitrary::Arbitrary<'a> for SqOne {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<SqOne> {
+        let mut cube = SqOne::new();
+        for _ in 0..u.int_in_range(0..=ARBITRARY_SLASHES)? {
+            let (top, bottom) = *u.choose(&cube.slash_turns())?;
+            cube.twist(top, bottom);
+            cube.flip();
+        }
+        cube.twist(u.int_in_range(-5..=6)?, u.int_in_range(-5..=6)?);
+
+        Ok(cube)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, None)
     }
-    (a % n) as u8
 }
 
 impl SqOne {
@@ -112,6 +633,10 @@ impl SqOne {
             bottom,
             bottom_offset: 0,
             middle: false,
+            history: None,
+            undone: vec![],
+            scheme: ColorScheme::western(),
+            observers: Observers::default(),
         }
     }
 
@@ -121,7 +646,7 @@ impl SqOne {
     /// is_top_layer is set, the top color
     /// will be white. Otherwise, it will
     /// be yellow.
-    fn gen_layer(is_top_layer: bool) -> Vec<Option<Piece>> {
+    fn gen_layer(is_top_layer: bool) -> [Option<Piece>; 12] {
         
         // Set the top color of
         // the layer
@@ -131,7 +656,7 @@ impl SqOne {
             Color::Yellow
         };
 
-        let mut layer = Vec::with_capacity(12);
+        let mut layer = [None; 12];
         for i in 0usize..4 {
 
             // Create the i-th corner
@@ -158,14 +683,372 @@ impl SqOne {
 
             // Add the corner
             // and edge
-            layer.push(Some(corner));
-            layer.push(None);
-            layer.push(Some(edge));
+            layer[i * 3] = Some(corner);
+            layer[i * 3 + 2] = Some(edge);
         }
 
         layer
     }
 
+    /// Returns the index of the piece
+    /// in each physical slot of the
+    /// top and bottom layers, followed
+    /// by the middle layer state. Corners
+    /// fill two consecutive slots.
+    pub(crate) fn slots(&self) -> ([u8; 12], [u8; 12], bool) {
+        (
+            SqOne::layer_slots(&self.top[..], self.top_offset),
+            SqOne::layer_slots(&self.bottom[..], self.bottom_offset),
+            self.middle,
+        )
+    }
+
+    /// Creates a cube with the piece of
+    /// each index in the given slots,
+    /// the reverse of slots.
+    pub(crate) fn from_slots(top: &[u8; 12], bottom: &[u8; 12], middle: bool) -> SqOne {
+        let solved = SqOne::new();
+        let piece = |index: u8| {
+            solved.top
+                .iter()
+                .chain(solved.bottom.iter())
+                .flatten()
+                .find(|piece| piece.index() == index)
+                .copied()
+        };
+
+        // Only the first slot of each
+        // piece holds it, the second
+        // slot of a corner is None.
+        let layer = |slots: &[u8; 12]| {
+            core::array::from_fn(|i| if slots[i] == slots[(i + 11) % 12] { None } else { piece(slots[i]) })
+        };
+
+        SqOne {
+            top: layer(top),
+            top_offset: 0,
+            bottom: layer(bottom),
+            bottom_offset: 0,
+            middle,
+            history: None,
+            undone: vec![],
+            scheme: ColorScheme::western(),
+            observers: Observers::default(),
+        }
+    }
+
+    /// Returns Twizzle URL query parameters
+    /// that show the cube, as the inverse of
+    /// a solution set up from a solved cube.
+    /// This runs the solver.
+    #[cfg(feature = "solver")]
+    pub fn to_twizzle_params(&self) -> String {
+        let setup = solver::solve(self).inverse();
+        format!("puzzle=square1&setup-alg={}", export::encode_component(&setup.to_string()))
+    }
+
+    /// Returns the cube as an HTML figure
+    /// drawn with render::svg, captioned
+    /// with its state string, for showing
+    /// the cube in notebooks.
+    #[cfg(feature = "render")]
+    pub fn to_html(&self) -> String {
+        format!(
+            "<figure class=\"sq1\">{}<figcaption><code>{}</code></figcaption></figure>",
+            render::svg(self),
+            fen::to_fen(self),
+        )
+    }
+
+    /// Prints the cube as HTML in the
+    /// form evcxr reads, so the cube is
+    /// drawn when it ends a cell of an
+    /// evcxr Jupyter notebook.
+    #[cfg(feature = "evcxr")]
+    pub fn evcxr_display(&self) {
+        println!("EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT", self.to_html());
+    }
+
+    /// Returns the pieces of the top
+    /// layer in slot order.
+    pub fn top(&self) -> impl Iterator<Item = PieceView> {
+        self.layer_view(Layer::Top).into_iter()
+    }
+
+    /// Returns the pieces of the bottom
+    /// layer in slot order, as seen
+    /// from above.
+    pub fn bottom(&self) -> impl Iterator<Item = PieceView> {
+        self.layer_view(Layer::Bottom).into_iter()
+    }
+
+    /// Returns the pieces of the layer
+    /// in slot order.
+    fn layer_view(&self, layer: Layer) -> Vec<PieceView> {
+        let (pieces, offset) = match layer {
+            Layer::Top => (&self.top, self.top_offset),
+            Layer::Bottom => (&self.bottom, self.bottom_offset),
+        };
+
+        let mut views = vec![];
+        for slot in 0..12 {
+            let piece = match pieces[abs_mod(slot as i8 - offset, 12) as usize] {
+                Some(piece) => piece,
+                None => continue,
+            };
+            let (kind, width, colors) = match piece {
+                Piece::Corner(c) => {
+
+                    // A corner turned over into the
+                    // other layer has its sides in
+                    // the opposite slot order.
+                    let home = if layer_index(c.colors.2) == 0 { Layer::Top } else { Layer::Bottom };
+                    let colors = if home == layer {
+                        vec![c.colors.2, c.colors.0, c.colors.1]
+                    } else {
+                        vec![c.colors.2, c.colors.1, c.colors.0]
+                    };
+                    (PieceKind::Corner, 60, colors)
+                },
+                Piece::Edge(e) => (PieceKind::Edge, 30, vec![e.colors.1, e.colors.0]),
+            };
+            views.push(PieceView { id: PieceId(piece.index()), kind, colors, angle: slot as u16 * 30, width });
+        }
+
+        views
+    }
+
+    /// Returns the stickers of each piece
+    /// in the layer, in slot order.
+    #[cfg(feature = "render")]
+    pub(crate) fn stickers(&self, layer: Layer) -> Vec<Sticker<'_>> {
+        self.layer_view(layer)
+            .into_iter()
+            .map(|view| Sticker {
+                id: view.id,
+                slot: view.angle as usize / 30,
+                corner: view.kind == PieceKind::Corner,
+                face: self.scheme.color(view.colors[0]),
+                sides: view.colors[1..].iter().map(|&color| self.scheme.color(color)).collect(),
+            })
+            .collect()
+    }
+
+    /// Returns the colors of the left and
+    /// right halves of the front of the
+    /// middle layer.
+    #[cfg(feature = "render")]
+    pub(crate) fn middle_stickers(&self) -> (&str, &str) {
+        let front = self.scheme.color(COLOR_ORDER[0]);
+        if self.middle {
+            (front, self.scheme.color(COLOR_ORDER[2]))
+        } else {
+            (front, front)
+        }
+    }
+
+    /// Returns which kind of piece covers
+    /// each physical slot of the layer,
+    /// with the pieces counted.
+    pub fn slot_occupancy(&self, layer: Layer) -> Occupancy {
+        let (top, bottom, _) = self.slots();
+        let slots = if layer == Layer::Top { top } else { bottom };
+        let slots = slots.map(|piece| if piece < 8 { PieceKind::Corner } else { PieceKind::Edge });
+        let covered = slots.iter().filter(|&&kind| kind == PieceKind::Corner).count();
+
+        Occupancy { slots, corners: covered / 2, edges: 12 - covered }
+    }
+
+    /// Returns the number of corners
+    /// in the top layer.
+    pub fn corners_on_top(&self) -> usize {
+        self.slot_occupancy(Layer::Top).corners
+    }
+
+    /// Returns the number of edges
+    /// in the top layer.
+    pub fn edges_on_top(&self) -> usize {
+        self.slot_occupancy(Layer::Top).edges
+    }
+
+    /// Returns true if the middle layer
+    /// is turned a half turn from solved.
+    pub fn middle_flipped(&self) -> bool {
+        self.middle
+    }
+
+    /// Returns the pieces of each layer
+    /// split by whether white or yellow
+    /// faces up or down, the way people
+    /// judge orienting the corners and
+    /// edges.
+    pub fn pieces_by_layer_color(&self) -> LayerColors {
+        let split = |layer: Layer| -> (Vec<PieceId>, Vec<PieceId>) {
+            let (white, yellow): (Vec<PieceView>, Vec<PieceView>) = self.layer_view(layer)
+                .into_iter()
+                .partition(|view| view.colors[0] == Color::White);
+            (white.iter().map(|view| view.id).collect(), yellow.iter().map(|view| view.id).collect())
+        };
+        let (top_white, top_yellow) = split(Layer::Top);
+        let (bottom_white, bottom_yellow) = split(Layer::Bottom);
+
+        LayerColors { top_white, top_yellow, bottom_white, bottom_yellow }
+    }
+
+    /// Returns how the cube differs from
+    /// the other, as recovery::diff gives
+    /// with this cube as the actual one.
+    pub fn diff(&self, other: &SqOne) -> Diff {
+        recovery::diff(self, other)
+    }
+
+    /// Returns the piece covering the
+    /// physical slot of the layer.
+    ///
+    /// Panics if the slot is not
+    /// below 12.
+    pub fn piece_at(&self, layer: Layer, slot: usize) -> PieceId {
+        let (top, bottom, _) = self.slots();
+        match layer {
+            Layer::Top => PieceId(top[slot]),
+            Layer::Bottom => PieceId(bottom[slot]),
+        }
+    }
+
+    /// Returns the layer and the first
+    /// physical slot covered by the piece.
+    /// A corner across slots 11 and 0
+    /// starts at slot 11.
+    pub fn position_of(&self, piece: PieceId) -> (Layer, usize) {
+        let (top, bottom, _) = self.slots();
+        for (layer, slots) in [(Layer::Top, top), (Layer::Bottom, bottom)] {
+            for i in 0..12 {
+                if slots[i] == piece.0 && slots[(i + 11) % 12] != piece.0 {
+                    return (layer, i);
+                }
+            }
+        }
+
+        unreachable!()
+    }
+
+    /// Returns the permutation of the
+    /// corners, with the positions read
+    /// from slot 0 of the top layer and
+    /// then the bottom layer.
+    pub fn corner_permutation(&self) -> Permutation {
+        Permutation::new(self.read_pieces().into_iter().filter(|&p| p < 8).collect())
+    }
+
+    /// Returns the blind solving memo of
+    /// the cube with the default lettering,
+    /// or None if the cube is not in cube
+    /// shape lined up like a solved cube.
+    pub fn memo(&self) -> Option<Memo> {
+        memo::memo(self, &Lettering::default())
+    }
+
+    /// Returns the state as a short string
+    /// that can be typed and shared, read
+    /// back by from_fen.
+    pub fn to_fen(&self) -> String {
+        fen::to_fen(self)
+    }
+
+    /// Reads a state string written by
+    /// to_fen.
+    pub fn from_fen(text: &str) -> Result<SqOne, FenError> {
+        fen::from_fen(text)
+    }
+
+    /// Returns the number of moves needed
+    /// to solve the cube in the metric,
+    /// which is optimal for cubes close to
+    /// solved and the length of the
+    /// two-phase solution otherwise.
+    #[cfg(feature = "solver")]
+    pub fn distance_to_solved(&self, metric: Metric) -> usize {
+        solver::distance(self, metric)
+    }
+
+    /// Returns the canonical representative
+    /// of the cube under turning it upside
+    /// down, mirroring it and turning its
+    /// layers, so equivalent cubes give
+    /// the same representative.
+    pub fn canonicalize(&self) -> SqOne {
+        symmetry::canonicalize(self).0
+    }
+
+    /// Returns the symmetry that maps the
+    /// cube to its canonical representative.
+    pub fn symmetry_class(&self) -> Symmetry {
+        symmetry::canonicalize(self).1
+    }
+
+    /// Returns the permutation of the
+    /// edges, with the positions read
+    /// from slot 0 of the top layer and
+    /// then the bottom layer.
+    pub fn edge_permutation(&self) -> Permutation {
+        Permutation::new(self.read_pieces().into_iter().filter(|&p| p >= 8).map(|p| p - 8).collect())
+    }
+
+    /// Returns the index of each piece in
+    /// the order they are read, starting
+    /// from slot 0 of each layer.
+    fn read_pieces(&self) -> Vec<u8> {
+        let (top, bottom, _) = self.slots();
+        let mut pieces = vec![];
+        for layer in [top, bottom] {
+            for i in 0..12 {
+                if layer[i] != layer[(i + 11) % 12] {
+                    pieces.push(layer[i]);
+                }
+            }
+        }
+
+        pieces
+    }
+
+    /// Returns the piece index in each
+    /// physical slot of a single layer.
+    fn layer_slots(layer: &[Option<Piece>], offset: i8) -> [u8; 12] {
+        let mut slots = [0; 12];
+        for (i, slot) in slots.iter_mut().enumerate() {
+            let j = abs_mod(i as i8 - offset, 12);
+
+            // A None slot is the second
+            // half of the corner before it.
+            let piece = match layer[j as usize] {
+                Some(piece) => piece,
+                None => layer[abs_mod(j as i8 - 1, 12) as usize].unwrap(),
+            };
+            *slot = piece.index();
+        }
+
+        slots
+    }
+
+    /// Applies the turns of a scramble
+    /// to the cube, slashing between
+    /// each pair of turns. If a slash
+    /// is blocked by a corner, returns
+    /// the index of the turn before it.
+    pub(crate) fn apply(&mut self, scramble: &Scramble) -> Result<(), usize> {
+        for (i, &(top, bottom)) in scramble.turns.iter().enumerate() {
+            if i > 0 {
+                if !self.can_flip() {
+                    return Err(i - 1);
+                }
+                self.flip();
+            }
+            self.twist(top, bottom);
+        }
+
+        Ok(())
+    }
+
     /// Returns true if the layer
     /// slice specified can be
     /// flipped.
@@ -174,120 +1057,328 @@ impl SqOne {
         // If the front or back piece is
         // partway through a corner piece,
         // then the layer cannot be flipped.
-        if let Some(Piece::Corner(_)) = layer[abs_mod(5 - offset, 12) as usize] {
-            return false;
-        } else if let Some(Piece::Corner(_)) = layer[abs_mod(11 - offset, 12) as usize] {
-            return false;
-        }
-
-        true
+        let front = abs_mod(5 - offset, 12) as usize;
+        let back = if front >= 6 { front - 6 } else { front + 6 };
+        !matches!(layer[front], Some(Piece::Corner(_))) && !matches!(layer[back], Some(Piece::Corner(_)))
     }
 
     /// Returns true if the current
     /// configuration of the top and
     /// bottom layer is able to
     /// be flipped.
-    fn can_flip(&self) -> bool {
+    pub(crate) fn can_flip(&self) -> bool {
         SqOne::can_flip_layer(&self.top[..], self.top_offset)
             && SqOne::can_flip_layer(&self.bottom[..], self.bottom_offset)
     }
 
-    /// When provided a layer of the
-    /// Square One, it will consume the
-    /// later half of the layer and
-    /// return the properly reversed
-    /// vector.
-    fn get_reverse(layer: &Vec<Option<Piece>>, offset: i8) -> Vec<Option<Piece>> {
-
-        let mut layer = layer.clone();
-
-        // Initialize a new vector
-        // to store the reverse of
-        // half of the provided layer.
-        // Iter will be used to iterate
-        // across the layer, and end
-        // is the index after all 
-        // pieces that should be reversed.
-        let mut reverse: Vec<Option<Piece>> = vec![];
-        let mut iter = abs_mod(11 - offset, 12);
-        let end = abs_mod(5 - offset, 12);
-
-        loop {
+    /// Returns the twist amounts of the
+    /// layer, from -5 to 6, after which it
+    /// lines up for a slash. Amounts are
+    /// in the standard notation, as given
+    /// to try_twist for the layer.
+    pub fn legal_twists(&self, layer: Layer) -> Vec<i8> {
+        let mask = self.legal_twist_mask(layer);
+        (-5..=6).filter(|amount| mask & 1 << (amount + 5) != 0).collect()
+    }
 
-            // If the end is reached,
-            // return the reversed vector
-            if iter == end {
-                return reverse;
-            }
+    /// Returns legal_twists as a mask,
+    /// with bit amount + 5 set for each
+    /// amount that lines the layer up.
+    pub fn legal_twist_mask(&self, layer: Layer) -> u16 {
+        let mut mask = 0;
+        for amount in -5..=6 {
+            let aligned = match layer {
+                Layer::Top => SqOne::can_flip_layer(&self.top[..], self.top_offset + amount),
+                Layer::Bottom => SqOne::can_flip_layer(&self.bottom[..], self.bottom_offset - amount),
+            };
+            mask |= (aligned as u16) << (amount + 5);
+        }
 
-            match &layer[iter as usize] {
+        mask
+    }
 
-                // If the value is Some, just
-                // add that value to the vector
-                Some(_) => reverse.push(layer[iter as usize].take()),
+    /// Returns the pieces in the slots
+    /// of the layer being slashed, read
+    /// from the last slot back to the
+    /// first. A corner read backwards is
+    /// put before its empty half, so the
+    /// half can be placed into the other
+    /// layer as it is.
+    fn reversed_half(layer: &[Option<Piece>], offset: i8) -> [Option<Piece>; 6] {
+        let last = abs_mod(11 - offset, 12) as usize;
+        let slot = |k: usize| layer[if k > last { last + 12 - k } else { last - k }];
+        let mut half = [None; 6];
+        let mut k = 0;
+        while k < 6 {
+            match slot(k) {
+                Some(piece) => {
+                    half[k] = Some(piece);
+                    k += 1;
+                },
 
-                // If it is None, then this
-                // is in the middle of a corner
-                // piece. This and the next need
-                // to be added in reverse order.
+                // The empty half of a corner
+                // comes before the corner.
                 None => {
-                    reverse.push(layer[abs_mod(iter as i8 - 1, 12) as usize].take());
-                    reverse.push(None);
-                    iter = abs_mod(iter as i8 - 1, 12);
+                    half[k] = slot(k + 1);
+                    k += 2;
                 },
             }
-
-            // Decrement the index mod
-            // the size of the layer
-            iter = abs_mod(iter as i8 - 1, 12);
         }
+
+        half
     }
 
     /// Flips the top and bottom
     /// layer of the cube with
     /// the alignment and piece/color
     /// orientation.
-    fn flip(&mut self) {
+    pub(crate) fn flip(&mut self) {
         if !self.can_flip() {
             return;
         }
 
         // Get the reverse of half
         // of the bottom and top layers.
-        let mut top_reverse = SqOne::get_reverse(&mut self.top, self.top_offset);
-        let mut bottom_reverse = SqOne::get_reverse(&mut self.bottom, self.bottom_offset);
-        let mut index = 0;
+        let top_reverse = SqOne::reversed_half(&self.top, self.top_offset);
+        let bottom_reverse = SqOne::reversed_half(&self.bottom, self.bottom_offset);
 
-        // Assign the flipped half
-        // into the opposite layer.
-        for i in 6..12 {
-            self.top[abs_mod(i - self.top_offset, 12) as usize] = bottom_reverse[index].take();
-            self.bottom[abs_mod(i - self.bottom_offset, 12) as usize] = top_reverse[index].take();
-            index += 1;
+        // Assign the flipped half into
+        // the opposite layer, starting
+        // from the slot after the slash.
+        let top_start = abs_mod(6 - self.top_offset, 12) as usize;
+        let bottom_start = abs_mod(6 - self.bottom_offset, 12) as usize;
+        for k in 0..6 {
+            let wrap = |i: usize| if i >= 12 { i - 12 } else { i };
+            self.top[wrap(top_start + k)] = bottom_reverse[k];
+            self.bottom[wrap(bottom_start + k)] = top_reverse[k];
         }
 
         self.middle = !self.middle;
+        self.notify(Move::Slash, (self.top_offset, self.bottom_offset));
     }
 
-    /// Twists the cube by the provided
-    /// offset. This follows the standard
-    /// Square One notation.
-    fn twist(&mut self, top_offset: i8, bottom_offset: i8) {
-        let top_sum = self.top_offset + top_offset;
+    /// Slashes the cube, or returns an
+    /// error if a corner crosses the
+    /// slash line.
+    pub fn try_slash(&mut self) -> Result<(), SqOneError> {
+        if !self.can_flip() {
+            return Err(SqOneError::SlashBlocked);
+        }
+        self.flip();
+        self.record(Move::Slash);
+
+        Ok(())
+    }
+
+    /// Twists the top and bottom layers
+    /// using the standard Square One
+    /// notation, or returns an error if
+    /// either amount is outside -6 to 6.
+    /// A half turn of -6 is recorded as 6.
+    pub fn try_twist(&mut self, top: i8, bottom: i8) -> Result<(), SqOneError> {
+        let amount = |amount| twist_amount(amount).ok_or(SqOneError::TwistOutOfRange(amount));
+        let (top, bottom) = (amount(top)?, amount(bottom)?);
+        self.twist(top, bottom);
+        if (top, bottom) != (0, 0) {
+            self.record(Move::Twist(top, bottom));
+        }
+
+        Ok(())
+    }
+
+    /// Applies a single move, or returns
+    /// an error if it cannot be made.
+    pub fn apply_move(&mut self, next: Move) -> Result<(), SqOneError> {
+        match next {
+            Move::Twist(top, bottom) => self.try_twist(top, bottom),
+            Move::Slash => self.try_slash(),
+        }
+    }
+
+    /// Produces a solved Square One that
+    /// keeps a history of the moves made
+    /// with try_twist, try_slash and
+    /// apply_move, so they can be undone.
+    pub fn with_history() -> SqOne {
+        SqOne {
+            history: Some(vec![]),
+            ..SqOne::new()
+        }
+    }
+
+    /// Produces a solved Square One
+    /// drawn in the color scheme.
+    pub fn with_scheme(scheme: &'static ColorScheme) -> SqOne {
+        SqOne { scheme, ..SqOne::new() }
+    }
+
+    /// Returns the cube in the shapes,
+    /// reached from solved in as few
+    /// slashes as possible. The same
+    /// shapes always give the same cube.
+    /// Returns None if the shapes cannot
+    /// be reached.
+    #[cfg(feature = "solver")]
+    pub fn with_shape(state: ShapeState) -> Option<SqOne> {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut turns = shape::path_to(Position::solved(), state, &mut rng)?;
+        turns.push((0, 0));
+
+        let mut cube = SqOne::new();
+        cube.apply(&Scramble { turns }).unwrap();
+        Some(cube)
+    }
+
+    /// Returns the cube with a star on
+    /// top, leaving the other two corners
+    /// next to each other on the bottom.
+    #[cfg(feature = "solver")]
+    pub fn star() -> SqOne {
+        SqOne::with_shape(ShapeState { top: Shape::Star, bottom: Shape::TwoCorners { gap: 0 } }).unwrap()
+    }
+
+    /// Returns the cube with a scallop
+    /// on top and a kite on the bottom.
+    #[cfg(feature = "solver")]
+    pub fn scallop_kite() -> SqOne {
+        SqOne::with_shape(ShapeState { top: Shape::Scallop, bottom: Shape::Kite }).unwrap()
+    }
+
+    /// Returns the cube with a kite on
+    /// each layer, the shape most cube
+    /// shape methods pass through.
+    #[cfg(feature = "solver")]
+    pub fn kite_kite() -> SqOne {
+        SqOne::with_shape(ShapeState { top: Shape::Kite, bottom: Shape::Kite }).unwrap()
+    }
+
+    /// Returns the cube with a barrel
+    /// on each layer.
+    #[cfg(feature = "solver")]
+    pub fn barrel_barrel() -> SqOne {
+        SqOne::with_shape(ShapeState { top: Shape::Barrel, bottom: Shape::Barrel }).unwrap()
+    }
+
+    /// Returns the solved cube with two
+    /// edges of the top layer swapped, the
+    /// simplest cube shape state that
+    /// needs a parity algorithm.
+    pub fn cube_shape_with_parity() -> SqOne {
+        let mut cube = SqOne::new();
+        let edges: Vec<usize> = (0..12).filter(|&slot| matches!(cube.top[slot], Some(Piece::Edge(_)))).collect();
+        cube.top.swap(edges[0], edges[1]);
+        cube
+    }
+
+    /// Returns the color scheme the
+    /// cube is drawn in.
+    pub fn scheme(&self) -> &'static ColorScheme {
+        self.scheme
+    }
+
+    /// Changes the color scheme the cube
+    /// is drawn in, leaving its pieces
+    /// where they are.
+    pub fn set_scheme(&mut self, scheme: &'static ColorScheme) {
+        self.scheme = scheme;
+    }
+
+    /// Returns the moves in the history,
+    /// which is empty if history is not
+    /// kept.
+    pub fn history(&self) -> &[Move] {
+        self.history.as_deref().unwrap_or(&[])
+    }
+
+    /// Adds a move to the history and
+    /// forgets the undone moves.
+    fn record(&mut self, next: Move) {
+        if let Some(history) = &mut self.history {
+            history.push(next);
+            self.undone.clear();
+        }
+    }
+
+    /// Undoes the last move in the
+    /// history and returns it.
+    pub fn undo(&mut self) -> Option<Move> {
+        let last = self.history.as_mut()?.pop()?;
+        match last {
+            Move::Twist(top, bottom) => self.twist(-top, -bottom),
+            Move::Slash => self.flip(),
+        }
+        self.undone.push(last);
+
+        Some(last)
+    }
+
+    /// Makes the last undone move again
+    /// and returns it.
+    pub fn redo(&mut self) -> Option<Move> {
+        let next = self.undone.pop()?;
+        match next {
+            Move::Twist(top, bottom) => self.twist(top, bottom),
+            Move::Slash => self.flip(),
+        }
+        self.history.as_mut()?.push(next);
+
+        Some(next)
+    }
+
+    /// Twists the cube by the provided
+    /// offset. This follows the standard
+    /// Square One notation.
+    pub(crate) fn twist(&mut self, top_offset: i8, bottom_offset: i8) {
+        let before = (self.top_offset, self.bottom_offset);
+        let top_sum = self.top_offset + top_offset;
         let bot_sum = self.bottom_offset - bottom_offset;
         self.top_offset = abs_mod(top_sum + 5, 12) as i8 - 5;
         self.bottom_offset = abs_mod(bot_sum + 5, 12) as i8 - 5;
+        self.notify(Move::Twist(top_offset, bottom_offset), before);
+    }
+
+    /// Calls each observer with the move
+    /// just made from the offsets before.
+    fn notify(&mut self, action: Move, before: (i8, i8)) {
+        if self.observers.0.is_empty() {
+            return;
+        }
+
+        let event = MoveEvent {
+            action,
+            before,
+            after: (self.top_offset, self.bottom_offset),
+            middle: self.middle,
+        };
+        for observer in &mut self.observers.0 {
+            observer(&event);
+        }
+    }
+
+    /// Calls the observer after each twist
+    /// and slash made on this cube, such as
+    /// by try_twist, apply_move, undo or
+    /// scramble_rng, so a drawing can be
+    /// animated. Clones of the cube do not
+    /// keep the observers, and states set
+    /// without moves, as by random state
+    /// scrambles, send no events.
+    pub fn subscribe(&mut self, observer: Observer) {
+        self.observers.0.push(observer);
+    }
+
+    /// Removes every observer.
+    pub fn unsubscribe_all(&mut self) {
+        self.observers.0.clear();
     }
 
     /// Generates a random offset value
     /// for the given layer.
-    fn rand_layer_offset(layer: &[Option<Piece>], offset: i8) -> i8 {
-        use rand::{thread_rng, Rng};
+    fn rand_layer_offset<R: Rng>(layer: &[Option<Piece>], offset: i8, rng: &mut R) -> i8 {
 
         // Loop through until an offset
         // that would allow this layer
         // to flip is found.
-        let mut rng = rand::thread_rng();
         let r = loop {
             let rnum = rng.gen_range(-5..6 + 1);
             if SqOne::can_flip_layer(layer, rnum + offset) {
@@ -302,40 +1393,580 @@ impl SqOne {
         }
     }
 
+    /// Checks that the state could be
+    /// reached by moves, returning the
+    /// first broken invariant found.
+    pub fn invariants(&self) -> Result<(), InvariantError> {
+        self.validate().map_err(|errors| errors[0])
+    }
+
+    /// Checks that the state could be
+    /// reached by moves: each layer fills
+    /// twelve half slots, every corner
+    /// fills two neighbouring half slots
+    /// and every piece is on the cube
+    /// once. Every broken invariant is
+    /// returned, so states built from
+    /// outside input can be reported in
+    /// full.
+    pub fn validate(&self) -> Result<(), Vec<InvariantError>> {
+        let mut errors = vec![];
+        let mut counts = [0; 16];
+        for (layer, pieces, offset) in [
+            (Layer::Top, &self.top, self.top_offset),
+            (Layer::Bottom, &self.bottom, self.bottom_offset),
+        ] {
+            if !(-5..=6).contains(&offset) {
+                errors.push(InvariantError::OffsetOutOfRange(layer, offset));
+            }
+            // Only the first half of a corner
+            // holds it, so a corner must be
+            // followed by an empty half and an
+            // empty half must follow a corner.
+            for i in 0..12 {
+                let slot = abs_mod(i as i8 + offset, 12) as usize;
+                let split = match pieces[i] {
+                    Some(piece) => {
+                        counts[piece.index() as usize] += 1;
+                        matches!(piece, Piece::Corner(_)) && pieces[(i + 1) % 12].is_some()
+                    },
+                    None => !matches!(pieces[(i + 11) % 12], Some(Piece::Corner(_))),
+                };
+                if split {
+                    errors.push(InvariantError::SplitCorner(layer, slot));
+                }
+            }
+        }
+
+        for piece in PieceId::all() {
+            match counts[piece.index() as usize] {
+                0 => errors.push(InvariantError::MissingPiece(piece)),
+                1 => {},
+                _ => errors.push(InvariantError::DuplicatePiece(piece)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns true if every piece is in
+    /// its solved slot and the middle
+    /// layer is solved.
+    pub fn is_solved(&self) -> bool {
+        self.slots() == SqOne::new().slots()
+    }
+
+    /// Returns true if the pieces are
+    /// an odd permutation of a solved
+    /// cube, so a parity algorithm is
+    /// needed to solve it. Returns None
+    /// if the cube is not in cube shape.
+    pub fn has_parity(&self) -> Option<bool> {
+        if !shape::classify(self).is_cube() {
+            return None;
+        }
+
+        // Read the corners and edges of
+        // both layers starting from a corner.
+        // Turning a layer cycles four corners
+        // and four edges, so the combined
+        // parity does not depend on the twist.
+        let (top, bottom, _) = self.slots();
+        let mut corners = vec![];
+        let mut edges = vec![];
+        for layer in [top, bottom] {
+            let start = (0..12)
+                .find(|&i| layer[i] < 8 && layer[i] != layer[(i + 11) % 12])
+                .unwrap();
+            for i in (start..start + 12).map(|i| i % 12) {
+                if layer[i] == layer[(i + 11) % 12] {
+                    continue;
+                }
+                if layer[i] < 8 {
+                    corners.push(layer[i]);
+                } else {
+                    edges.push(layer[i]);
+                }
+            }
+        }
+
+        let mut odd = false;
+        for pieces in [corners, edges] {
+            for i in 0..pieces.len() {
+                for j in i + 1..pieces.len() {
+                    if pieces[i] > pieces[j] {
+                        odd = !odd;
+                    }
+                }
+            }
+        }
+
+        Some(odd)
+    }
+
+    /// Scrambles the cube like scramble,
+    /// generating new scrambles until the
+    /// result meets the options, with the
+    /// generator chosen by the options.
+    #[cfg(feature = "solver")]
+    pub fn scramble_with(&mut self, options: &ScrambleOptions) -> Scramble {
+        self.scramble_with_rng(options, &mut options.build_rng())
+    }
+
+    /// Scrambles the cube like scramble_with,
+    /// drawing random numbers from rng so a
+    /// seeded rng gives the same scramble.
+    #[cfg(feature = "solver")]
+    pub fn scramble_with_rng<R: Rng>(&mut self, options: &ScrambleOptions, rng: &mut R) -> Scramble {
+        self.scramble_tries(options, rng, true).expect("the last scramble is kept")
+    }
+
+    /// Scrambles the cube like scramble_with,
+    /// but returns an error rather than
+    /// keeping a scramble that misses the
+    /// options once FILTER_TRIES have
+    /// been rejected.
+    #[cfg(feature = "solver")]
+    pub fn try_scramble_with(&mut self, options: &ScrambleOptions) -> Result<Scramble, OptionsError> {
+        self.try_scramble_with_rng(options, &mut options.build_rng())
+    }
+
+    /// Scrambles the cube like
+    /// try_scramble_with, drawing random
+    /// numbers from rng.
+    #[cfg(feature = "solver")]
+    pub fn try_scramble_with_rng<R: Rng>(&mut self, options: &ScrambleOptions, rng: &mut R) -> Result<Scramble, OptionsError> {
+        self.scramble_tries(options, rng, false)
+    }
+
+    /// Generates scrambles until one meets
+    /// the options. Every rejection counts
+    /// toward FILTER_TRIES, after which the
+    /// next scramble is kept if keep is
+    /// set and is an error otherwise.
+    #[cfg(feature = "solver")]
+    fn scramble_tries<R: Rng>(&mut self, options: &ScrambleOptions, rng: &mut R, keep: bool) -> Result<Scramble, OptionsError> {
+        trace_span!("scramble");
+        let middle = options.wanted_middle();
+        let slash_suffix = options.suffix == SuffixPolicy::Slash;
+        let mut rejected = 0;
+        loop {
+            let sampled = options.random_state || options.layer.is_some() || options.cube_shape || options.difficulty.is_some();
+            let (mut cube, mut scramble) = if sampled {
+                let mut cube = match (options.layer, options.difficulty) {
+                    (Some(layer), _) => SqOne::random_layer_state(layer, options.pieces, rng),
+                    (None, _) if options.cube_shape => SqOne::random_cube_shape_state(rng),
+                    (None, Some((_, most))) if most < WCA_MIN_SLASHES => SqOne::random_walk_state(most, rng),
+                    (None, _) => SqOne::random_state(rng),
+                };
+                cube.scheme = self.scheme;
+                if options.parity.is_some() && options.layer.is_none() && !options.cube_shape {
+                    cube.return_to_cube_shape(rng);
+                }
+
+                // Flipping the middle layer alone
+                // would leave the states cube shape
+                // moves can reach.
+                if let Some(middle) = middle.filter(|_| !options.cube_shape) {
+                    cube.middle = middle;
+                }
+
+                // Solve the cube one slash back
+                // so the scramble can end with
+                // that slash, skipping states
+                // no slash can reach.
+                let scramble = if slash_suffix {
+                    if !cube.can_flip() {
+                        rejected += 1;
+                        if rejected > FILTER_TRIES && !keep {
+                            return Err(OptionsError::Exhausted);
+                        }
+                        continue;
+                    }
+                    let mut before = cube.clone();
+                    before.flip();
+                    let mut scramble = solver::solve(&before).inverse();
+                    scramble.turns.push((0, 0));
+                    scramble
+                } else {
+                    solver::solve(&cube).inverse()
+                };
+                (cube, scramble)
+            } else {
+                let mut cube = self.clone();
+                let mut scramble = cube.scramble_rng(rng);
+
+                // Merge the first twist back to
+                // cube shape into the last turn
+                // of the scramble.
+                if options.parity.is_some() {
+                    let mut turns = cube.return_to_cube_shape(rng).into_iter();
+                    let (top, bottom) = turns.next().unwrap();
+                    let last = scramble.turns.last_mut().unwrap();
+                    *last = (
+                        abs_mod(last.0 + top + 5, 12) as i8 - 5,
+                        abs_mod(last.1 + bottom + 5, 12) as i8 - 5,
+                    );
+                    scramble.turns.extend(turns);
+                }
+
+                // A final slash flips the middle
+                // layer again after the fix.
+                if middle.is_some_and(|middle| middle != (cube.middle != slash_suffix)) {
+                    let mut target = cube.clone();
+                    target.middle = !cube.middle;
+                    scramble = scramble.join(Scramble::between(&cube, &target));
+                    cube = target;
+                }
+                if slash_suffix && scramble.turns.last() != Some(&(0, 0)) {
+                    if !cube.can_flip() {
+                        rejected += 1;
+                        if rejected > FILTER_TRIES && !keep {
+                            return Err(OptionsError::Exhausted);
+                        }
+                        continue;
+                    }
+                    cube.flip();
+                    scramble.turns.push((0, 0));
+                }
+                (cube, scramble)
+            };
+
+            // Twisting a uniform state by a
+            // random amount keeps it uniform.
+            if options.suffix == SuffixPolicy::Twist && scramble.turns.len() > 1 && scramble.turns.last() == Some(&(0, 0)) {
+                let mut turn = (0, 0);
+                while turn == (0, 0) {
+                    turn = (rng.gen_range(-5..=6), rng.gen_range(-5..=6));
+                }
+                cube.twist(turn.0, turn.1);
+                *scramble.turns.last_mut().unwrap() = turn;
+            }
+
+            // The cheap checks come first, so
+            // the solver is only asked about
+            // cubes that pass them.
+            let missed = middle.is_some_and(|middle| middle != cube.middle)
+                || options.parity.is_some() && !options.cube_shape && cube.has_parity() != options.parity
+                || options.min_slashes > 0 && !solver::needs_slashes(&cube, options.min_slashes)
+                || options.difficulty.is_some_and(|(least, most)| !solver::slashes_within(&cube, least, most))
+                || options.filter.is_some_and(|filter| !(filter.0)(&cube, &scramble));
+            if missed {
+                rejected += 1;
+                if rejected <= FILTER_TRIES {
+                    continue;
+                }
+                if !keep {
+                    return Err(OptionsError::Exhausted);
+                }
+            }
+
+            cube.observers = core::mem::take(&mut self.observers);
+            *self = cube;
+            return Ok(scramble);
+        }
+    }
+
+    /// Returns the cube to cube shape in
+    /// as few slashes as possible and then
+    /// twists it at random, returning the
+    /// turns made. The first turn comes
+    /// before any slash.
+    #[cfg(feature = "solver")]
+    fn return_to_cube_shape<R: Rng>(&mut self, rng: &mut R) -> Vec<(i8, i8)> {
+        let cube_shape = ShapeState { top: Shape::Square, bottom: Shape::Square };
+        let mut turns = shape::path_to(Position::from(&*self), cube_shape, rng).unwrap();
+        for &(top, bottom) in &turns {
+            self.twist(top, bottom);
+            self.flip();
+        }
+
+        let turn = (rng.gen_range(-5..=6), rng.gen_range(-5..=6));
+        self.twist(turn.0, turn.1);
+        turns.push(turn);
+
+        turns
+    }
+
+    /// Returns a cube in cube shape with
+    /// the pieces of one layer shuffled
+    /// around it, only those of kind if
+    /// given, and the rest solved.
+    #[cfg(feature = "solver")]
+    pub(crate) fn random_layer_state<R: Rng>(layer: Layer, kind: Option<PieceKind>, rng: &mut R) -> SqOne {
+        let (mut top, mut bottom, _) = SqOne::new().slots();
+        let slots = match layer {
+            Layer::Top => &mut top,
+            Layer::Bottom => &mut bottom,
+        };
+
+        // Corners cover the first two slots
+        // of every three and edges the last.
+        let mut corners: Vec<u8> = (0..4).map(|i| slots[i * 3]).collect();
+        let mut edges: Vec<u8> = (0..4).map(|i| slots[i * 3 + 2]).collect();
+        if kind != Some(PieceKind::Edge) {
+            corners.shuffle(rng);
+        }
+        if kind != Some(PieceKind::Corner) {
+            edges.shuffle(rng);
+        }
+        for i in 0..4 {
+            slots[i * 3] = corners[i];
+            slots[i * 3 + 1] = corners[i];
+            slots[i * 3 + 2] = edges[i];
+        }
+
+        SqOne::from_slots(&top, &bottom, false)
+    }
+
+    /// Returns the cube reached from solved
+    /// by the number of slashes, each after
+    /// a random twist that allows it, and a
+    /// final random twist. The cube needs
+    /// at most that many slashes.
+    #[cfg(feature = "solver")]
+    pub(crate) fn random_walk_state<R: Rng>(slashes: usize, rng: &mut R) -> SqOne {
+        let mut cube = SqOne::new();
+        for _ in 0..slashes {
+            loop {
+                let turn = (rng.gen_range(-5..=6), rng.gen_range(-5..=6));
+                cube.twist(turn.0, turn.1);
+                if cube.can_flip() {
+                    break;
+                }
+                cube.twist(-turn.0, -turn.1);
+            }
+            cube.flip();
+        }
+        cube.twist(rng.gen_range(-5..=6), rng.gen_range(-5..=6));
+
+        cube
+    }
+
+    /// Returns a cube picked uniformly
+    /// from the states in cube shape that
+    /// cube shape moves can reach, with
+    /// corners and edges shuffled between
+    /// both layers. The solver solves
+    /// these with cube shape moves only.
+    #[cfg(feature = "solver")]
+    pub(crate) fn random_cube_shape_state<R: Rng>(rng: &mut R) -> SqOne {
+        let (top, bottom, _) = SqOne::new().slots();
+        let mut corners: Vec<u8> = (0..8).map(|i| if i < 4 { top[i * 3] } else { bottom[(i - 4) * 3] }).collect();
+        let mut edges: Vec<u8> = (0..8).map(|i| if i < 4 { top[i * 3 + 2] } else { bottom[(i - 4) * 3 + 2] }).collect();
+
+        // Cube shape moves never change
+        // parity, so half of the states
+        // can only be solved by leaving
+        // cube shape.
+        loop {
+            corners.shuffle(rng);
+            edges.shuffle(rng);
+            let layer = |start: usize| {
+                let mut slots = [0; 12];
+                for i in 0..4 {
+                    slots[i * 3] = corners[start + i];
+                    slots[i * 3 + 1] = corners[start + i];
+                    slots[i * 3 + 2] = edges[start + i];
+                }
+                slots
+            };
+
+            let cube = SqOne::from_slots(&layer(0), &layer(4), rng.gen());
+            if cube.has_parity() == Some(false) {
+                return cube;
+            }
+        }
+    }
+
+    /// Returns the cube turned upside
+    /// down (z2), twisted so that a solved
+    /// cube stays solved.
+    pub fn rotate_z2(&self) -> SqOne {
+        self.reflect(true, false)
+    }
+
+    /// Returns the cube mirrored left to
+    /// right through the slash line,
+    /// twisted so that a solved cube
+    /// stays solved.
+    pub fn mirror(&self) -> SqOne {
+        self.reflect(false, true)
+    }
+
+    /// Applies a reflection, then undoes
+    /// the twist it makes to a solved cube.
+    fn reflect(&self, flipped: bool, mirrored: bool) -> SqOne {
+        let (top, bottom) = symmetry::REFLECTION_TWIST;
+        Symmetry { flipped, mirrored, relabel: (0, 0), twist: (-top, -bottom) }.apply(self)
+    }
+
+    /// Returns a cube picked uniformly
+    /// from every legal state, covering
+    /// the shape, the pieces and the
+    /// middle layer, without making any
+    /// moves.
+    pub fn random_state<R: Rng>(rng: &mut R) -> SqOne {
+        let solved = SqOne::new();
+        let pieces: Vec<Piece> = solved.top.iter().chain(solved.bottom.iter()).flatten().copied().collect();
+
+        loop {
+
+            // Deal the shuffled pieces into the
+            // top layer until it is full, and
+            // start each layer at a random slot.
+            let mut order = pieces.clone();
+            order.shuffle(rng);
+            let mut width = 0;
+            let split = order
+                .iter()
+                .position(|piece| {
+                    width += if let Piece::Corner(_) = piece { 2 } else { 1 };
+                    width >= 12
+                })
+                .unwrap();
+            if width != 12 {
+                continue;
+            }
+            let (top, bottom) = order.split_at(split + 1);
+
+            // Each state can be dealt from any
+            // of its pieces in each layer, so
+            // keep layers with fewer pieces more
+            // often to make every state equally
+            // likely. A star and a layer of ten
+            // pieces are the least common.
+            if rng.gen_range(0..top.len() * bottom.len()) >= 60 {
+                continue;
+            }
+
+            let layer = |pieces: &[Piece]| {
+                let mut layer = [None; 12];
+                let mut slot = 0;
+                for &piece in pieces {
+                    layer[slot] = Some(piece);
+                    slot += if let Piece::Corner(_) = piece { 2 } else { 1 };
+                }
+                layer
+            };
+
+            return SqOne {
+                top: layer(top),
+                top_offset: rng.gen_range(-5..=6),
+                bottom: layer(bottom),
+                bottom_offset: rng.gen_range(-5..=6),
+                middle: rng.gen(),
+                history: None,
+                undone: vec![],
+                scheme: ColorScheme::western(),
+                observers: Observers::default(),
+            };
+        }
+    }
+
+    /// Scrambles the cube into the given
+    /// shape, or a random shape other than
+    /// a cube if none is given, using as
+    /// few slashes as possible. The colors
+    /// of the pieces are left to chance,
+    /// for practicing cubeshape alone.
+    ///
+    /// Panics if the shape cannot be
+    /// reached.
+    #[cfg(feature = "solver")]
+    pub fn scramble_shape_only(&mut self, shape: Option<ShapeState>) -> Scramble {
+        let mut rng = rand::thread_rng();
+        let target = shape.unwrap_or_else(|| {
+            let shapes: Vec<ShapeState> = shape::all_shapes()
+                .into_iter()
+                .map(|(state, _)| state)
+                .filter(|state| !state.is_cube())
+                .collect();
+            let state = shapes[rng.gen_range(0..shapes.len())];
+            if rng.gen() { state.flipped() } else { state }
+        });
+
+        // Finish with a random twist so the
+        // shape is not left lined up for
+        // the next slash.
+        let mut turns = shape::path_to(Position::from(&*self), target, &mut rng)
+            .expect("shape is not reachable");
+        turns.push((rng.gen_range(-5..=6), rng.gen_range(-5..=6)));
+
+        let scramble = Scramble { turns };
+        self.apply(&scramble).unwrap();
+
+        scramble
+    }
+
     /// Scrambles the cube using NUM_FLIPS
     /// twists/flips, and then returns the
     /// list of twists to get the scramble.
+    #[cfg(feature = "std")]
     pub fn scramble(&mut self) -> Scramble {
+        self.scramble_rng(&mut rand::thread_rng())
+    }
+
+    /// Returns a random turn after which
+    /// the cube can be slashed. The top
+    /// and bottom cannot both be 0.
+    pub(crate) fn random_turn<R: Rng>(&self, rng: &mut R) -> (i8, i8) {
+        let top_layer_offset = Self::rand_layer_offset(&self.top[..], self.top_offset, rng);
+        let mut bot_layer_offset = Self::rand_layer_offset(&self.bottom[..], self.bottom_offset, rng);
+        if top_layer_offset == 0 {
+            while bot_layer_offset == 0 {
+                bot_layer_offset = Self::rand_layer_offset(&self.bottom[..], self.bottom_offset, rng);
+            }
+        }
+
+        (top_layer_offset, abs_mod(5 - bot_layer_offset, 12) as i8 - 5)
+    }
+
+    /// Returns every turn, with amounts
+    /// from -5 to 6, after which the cube
+    /// can be slashed.
+    pub(crate) fn slash_turns(&self) -> Vec<(i8, i8)> {
+        let bottoms = self.legal_twists(Layer::Bottom);
+        self.legal_twists(Layer::Top)
+            .into_iter()
+            .flat_map(|top| bottoms.iter().map(move |&bottom| (top, bottom)))
+            .collect()
+    }
+
+    /// Scrambles the cube like scramble,
+    /// drawing random numbers from rng so a
+    /// seeded rng gives the same scramble.
+    /// This is the scrambler available
+    /// without the std feature.
+    pub fn scramble_rng<R: Rng>(&mut self, rng: &mut R) -> Scramble {
+        self.scramble_with_policy(&mut UniformLayers, rng)
+    }
+
+    /// Scrambles the cube like scramble_rng,
+    /// with the policy picking each turn.
+    pub fn scramble_with_policy<P: MovePolicy, R: Rng>(&mut self, policy: &mut P, rng: &mut R) -> Scramble {
         const NUM_FLIPS: u32 = 20;
         let mut twists = vec![];
 
         // Generate NUM_FLIPS random
         // twists to scramble the cube.
-        for _ in 0..NUM_FLIPS {
-
-            // Get a random top and 
-            // bottom layer offset to
-            // turn the cube by. Both
-            // the top and bottom cannot
-            // be equal to 0.
-            let top_layer_offset = Self::rand_layer_offset(&self.top[..], self.top_offset);
-            let mut bot_layer_offset = Self::rand_layer_offset(&self.bottom[..], self.bottom_offset);
-            if top_layer_offset == 0 {
-                while bot_layer_offset == 0 {
-                    bot_layer_offset = Self::rand_layer_offset(&self.bottom[..], self.bottom_offset);
-                }
-            }
-
-            // Twist the cube by the random
-            // top and bottom offsets, and
-            // flip the cube for the next
-            // random offset.
-            self.twist(top_layer_offset, -bot_layer_offset);
-            self.flip();
+        for i in 0..NUM_FLIPS {
 
-            // Add the twist offset values
-            // to the list to return.
-            twists.push((top_layer_offset, -bot_layer_offset));
+            // Twist the cube by a random
+            // turn, and flip the cube for
+            // the next random turn. The
+            // scramble does not end with
+            // a slash.
+            let turn = policy.turn(self, rng);
+            self.twist(turn.0, turn.1);
+            if i + 1 < NUM_FLIPS {
+                self.flip();
+            }
+            twists.push(turn);
         }
 
         Scramble { turns: twists }
@@ -345,6 +1976,10 @@ impl SqOne {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    #[cfg(feature = "solver")]
+    use crate::MAX_SLASHES;
 
     // Creates a new Square One
     // and asserts that the cube
@@ -354,7 +1989,7 @@ mod tests {
         let test_cube = SqOne::new();
         assert_eq!(test_cube.top_offset, 0);
         assert_eq!(test_cube.bottom_offset, 0);
-        assert_eq!(test_cube.middle, false);
+        assert!(!test_cube.middle);
     }
 
     #[test]
@@ -363,6 +1998,29 @@ mod tests {
         test_cube.flip();
     }
 
+    #[test]
+    fn try_slash_blocked() {
+        let mut test_cube = SqOne::new();
+        test_cube.try_twist(1, 0).unwrap();
+        assert_eq!(test_cube.try_slash(), Ok(()));
+        test_cube.try_twist(1, 0).unwrap();
+        assert_eq!(test_cube.try_slash(), Err(SqOneError::SlashBlocked));
+        assert!(test_cube.middle);
+    }
+
+    #[test]
+    fn try_twist_out_of_range() {
+        let mut test_cube = SqOne::new();
+        assert_eq!(test_cube.try_twist(7, 0), Err(SqOneError::TwistOutOfRange(7)));
+        assert_eq!(test_cube.try_twist(0, -7), Err(SqOneError::TwistOutOfRange(-7)));
+        assert_eq!(test_cube.top_offset, 0);
+        assert_eq!(test_cube.bottom_offset, 0);
+
+        let mut half_turn = SqOne::with_history();
+        half_turn.try_twist(0, -6).unwrap();
+        assert_eq!(half_turn.history(), [Move::Twist(0, 6)]);
+    }
+
     #[test]
     fn twist_cube() {
         let mut test_cube = SqOne::new();
@@ -378,4 +2036,503 @@ mod tests {
         let scramble = test_cube.scramble();
         println!("{}", scramble);
     }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_shape_only() {
+        let mut test_cube = SqOne::new();
+        let scramble = test_cube.scramble_shape_only(None);
+        assert!(!shape::classify(&test_cube).is_cube());
+        assert_eq!(scramble.verify(&test_cube), Ok(()));
+
+        let star = shape::all_shapes()
+            .into_iter()
+            .map(|(state, _)| state)
+            .find(|state| state.top == shape::Shape::Star || state.bottom == shape::Shape::Star)
+            .unwrap();
+        let mut test_cube = SqOne::new();
+        test_cube.scramble_shape_only(Some(star));
+        assert_eq!(shape::classify(&test_cube), star);
+    }
+
+    #[test]
+    fn solved_cube_parity() {
+        let mut test_cube = SqOne::new();
+        assert_eq!(test_cube.has_parity(), Some(false));
+        for _ in 0..12 {
+            test_cube.twist(1, 1);
+            assert_eq!(test_cube.has_parity(), Some(false));
+        }
+        test_cube.try_slash().unwrap();
+        assert_eq!(test_cube.has_parity(), None);
+    }
+
+    #[test]
+    fn observe_moves() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut cube = SqOne::new();
+        let sink = Arc::clone(&events);
+        cube.subscribe(Box::new(move |event| sink.lock().unwrap().push(*event)));
+        cube.try_twist(1, 0).unwrap();
+        cube.try_slash().unwrap();
+
+        // Clones do not send events.
+        cube.clone().try_twist(3, 3).unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![
+            MoveEvent { action: Move::Twist(1, 0), before: (0, 0), after: (1, 0), middle: false },
+            MoveEvent { action: Move::Slash, before: (1, 0), after: (1, 0), middle: true },
+        ]);
+
+        cube.unsubscribe_all();
+        cube.try_twist(1, 0).unwrap();
+        assert_eq!(events.lock().unwrap().len(), 2);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn count_pieces() {
+        let cube = SqOne::new();
+        assert_eq!((cube.corners_on_top(), cube.edges_on_top()), (4, 4));
+        let occupancy = cube.slot_occupancy(Layer::Bottom);
+        assert_eq!((occupancy.corners, occupancy.edges), (4, 4));
+        assert_eq!(occupancy.slots.iter().filter(|&&kind| kind == PieceKind::Edge).count(), 4);
+
+        let star = SqOne::star();
+        assert_eq!((star.corners_on_top(), star.edges_on_top()), (6, 0));
+        assert_eq!(star.slot_occupancy(Layer::Bottom).edges, 8);
+    }
+
+    #[test]
+    fn list_legal_twists() {
+        let cube = SqOne::new();
+        assert_eq!(cube.legal_twists(Layer::Top), vec![-5, -3, -2, 0, 1, 3, 4, 6]);
+        assert_eq!(cube.legal_twist_mask(Layer::Top), 0b1011_0110_1101);
+        for layer in [Layer::Top, Layer::Bottom] {
+            for amount in -5..=6 {
+                let mut twisted = cube.clone();
+                match layer {
+                    Layer::Top => twisted.twist(amount, 0),
+                    Layer::Bottom => twisted.twist(0, amount),
+                }
+                assert_eq!(twisted.can_flip(), cube.legal_twists(layer).contains(&amount));
+            }
+        }
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn cube_as_html() {
+        let html = SqOne::new().to_html();
+        assert!(html.starts_with("<figure class=\"sq1\"><svg "));
+        assert!(html.ends_with("<figcaption><code>b6db6d 08192a3b4c5d6e7f -</code></figcaption></figure>"));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn named_states() {
+        let parity = SqOne::cube_shape_with_parity();
+        assert_eq!(parity.has_parity(), Some(true));
+        assert!(parity.invariants().is_ok());
+
+        let state = ShapeState { top: Shape::Scallop, bottom: Shape::Kite };
+        assert_eq!(shape::classify(&SqOne::scallop_kite()), state);
+        assert_eq!(SqOne::with_shape(state).unwrap().slots(), SqOne::scallop_kite().slots());
+        assert_eq!(shape::classify(&SqOne::star()).top, Shape::Star);
+        assert_eq!(shape::classify(&SqOne::kite_kite()), ShapeState { top: Shape::Kite, bottom: Shape::Kite });
+        assert_eq!(shape::classify(&SqOne::barrel_barrel()), ShapeState { top: Shape::Barrel, bottom: Shape::Barrel });
+        assert_eq!(SqOne::with_shape(ShapeState { top: Shape::Star, bottom: Shape::Star }), None);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_with_parity() {
+        for parity in [false, true] {
+            let mut test_cube = SqOne::new();
+            let options = ScrambleOptions::new().require_parity(parity);
+            let scramble = test_cube.scramble_with(&options);
+            assert_eq!(test_cube.has_parity(), Some(parity));
+            assert_eq!(scramble.verify(&test_cube), Ok(()));
+        }
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_with_wca_options() {
+        let mut test_cube = SqOne::new();
+        let scramble = test_cube.scramble_with(&ScrambleOptions::wca());
+        assert_eq!(scramble.verify(&test_cube), Ok(()));
+        assert!(solver::needs_slashes(&test_cube, crate::WCA_MIN_SLASHES));
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut test_cube = SqOne::with_history();
+        test_cube.try_twist(1, 0).unwrap();
+        test_cube.try_slash().unwrap();
+        let slashed = test_cube.slots();
+        assert_eq!(test_cube.history(), &[Move::Twist(1, 0), Move::Slash]);
+
+        assert_eq!(test_cube.undo(), Some(Move::Slash));
+        assert_eq!(test_cube.undo(), Some(Move::Twist(1, 0)));
+        assert_eq!(test_cube.undo(), None);
+        assert_eq!(test_cube.slots(), SqOne::new().slots());
+
+        assert_eq!(test_cube.redo(), Some(Move::Twist(1, 0)));
+        assert_eq!(test_cube.redo(), Some(Move::Slash));
+        assert_eq!(test_cube.slots(), slashed);
+
+        // A new move forgets the moves
+        // that were undone.
+        test_cube.undo();
+        test_cube.try_twist(0, 1).unwrap();
+        assert_eq!(test_cube.redo(), None);
+        assert!(SqOne::new().history().is_empty());
+    }
+
+    #[test]
+    fn find_pieces() {
+        let mut test_cube = SqOne::new();
+        let corner = PieceId::new(0).unwrap();
+        assert_eq!(test_cube.piece_at(Layer::Top, 1), corner);
+        assert_eq!(test_cube.position_of(corner), (Layer::Top, 0));
+
+        test_cube.twist(-1, 0);
+        assert_eq!(test_cube.position_of(corner), (Layer::Top, 11));
+        for piece in PieceId::all() {
+            let (layer, slot) = test_cube.position_of(piece);
+            assert_eq!(test_cube.piece_at(layer, slot), piece);
+        }
+        assert_eq!(PieceId::new(16), None);
+    }
+
+    #[test]
+    fn permutations_of_pieces() {
+        let mut test_cube = SqOne::new();
+        assert!(test_cube.corner_permutation().is_identity());
+        assert!(test_cube.edge_permutation().is_identity());
+
+        // Turning the top layer a quarter
+        // cycles its four corners and edges.
+        test_cube.twist(3, 0);
+        assert_eq!(test_cube.corner_permutation().cycles(), vec![vec![0, 3, 2, 1]]);
+        assert_eq!(test_cube.edge_permutation().cycles(), vec![vec![0, 3, 2, 1]]);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn twizzle_params() {
+        let mut test_cube = SqOne::new();
+        test_cube.try_twist(1, 0).unwrap();
+        assert_eq!(test_cube.to_twizzle_params(), "puzzle=square1&setup-alg=(1%2C%200)");
+    }
+
+    #[test]
+    fn seeded_scramble_rng() {
+        let mut first = SqOne::new();
+        let mut second = SqOne::new();
+        let scramble = first.scramble_rng(&mut StdRng::seed_from_u64(9));
+        assert_eq!(second.scramble_rng(&mut StdRng::seed_from_u64(9)), scramble);
+        assert!(scramble.verify(&first).is_ok());
+    }
+
+    #[cfg(all(feature = "solver", feature = "render"))]
+    #[test]
+    fn color_schemes() {
+        let mut test_cube = SqOne::with_scheme(ColorScheme::japanese());
+        assert_eq!(test_cube.scheme().down, ColorScheme::japanese().down);
+        assert_ne!(crate::render::svg(&test_cube), crate::render::svg(&SqOne::new()));
+
+        // The scheme is kept through a
+        // scramble but does not change it.
+        let options = ScrambleOptions::new().random_state(true);
+        let scramble = test_cube.scramble_with_rng(&options, &mut StdRng::seed_from_u64(3));
+        assert_eq!(test_cube.scheme(), ColorScheme::japanese());
+        assert_eq!(SqOne::new().scramble_with_rng(&options, &mut StdRng::seed_from_u64(3)), scramble);
+
+        static GRAY: ColorScheme = ColorScheme::from_colors(["#fff", "#888", "#aaa", "#bbb", "#ccc", "#ddd"]);
+        test_cube.set_scheme(&GRAY);
+        assert!(crate::render::svg(&test_cube).contains("#888"));
+    }
+
+    #[test]
+    fn compare_states() {
+        use std::collections::{BTreeSet, HashSet};
+
+        // Twisting there and back gives an
+        // equal cube with a longer history.
+        let mut test_cube = SqOne::with_history();
+        test_cube.try_twist(1, 0).unwrap();
+        test_cube.try_twist(-1, 0).unwrap();
+        assert_eq!(test_cube, SqOne::new());
+        assert_eq!(HashSet::from([test_cube.clone(), SqOne::new()]).len(), 1);
+
+        test_cube.try_slash().unwrap();
+        assert_ne!(test_cube, SqOne::new());
+        let sorted = BTreeSet::from([test_cube.clone(), SqOne::new()]);
+        assert_eq!(sorted.len(), 2);
+        assert_eq!(SqOne::with_history().cmp(&SqOne::new()), Ordering::Equal);
+    }
+
+    // Random moves, including blocked
+    // slashes, from arbitrary cubes always
+    // leave a legal state that undoing the
+    // moves returns to the start.
+    #[test]
+    fn random_moves_keep_invariants() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut bytes = [0; 128];
+        for _ in 0..50 {
+            rng.fill(&mut bytes[..]);
+            let start = SqOne::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            assert_eq!(start.invariants(), Ok(()));
+
+            let mut test_cube = SqOne { history: Some(vec![]), ..start.clone() };
+            for _ in 0..30 {
+                let next = if rng.gen() {
+                    Move::Slash
+                } else {
+                    Move::Twist(rng.gen_range(-5..=6), rng.gen_range(-5..=6))
+                };
+                let _ = test_cube.apply_move(next);
+                assert_eq!(test_cube.invariants(), Ok(()));
+            }
+            while test_cube.undo().is_some() {}
+            assert_eq!(test_cube, start);
+        }
+    }
+
+    #[test]
+    fn broken_invariants() {
+        let mut test_cube = SqOne::new();
+        test_cube.top[1] = test_cube.top[2];
+        assert_eq!(test_cube.invariants(), Err(InvariantError::SplitCorner(Layer::Top, 0)));
+
+        let mut test_cube = SqOne::new();
+        test_cube.bottom_offset = 9;
+        assert_eq!(test_cube.invariants(), Err(InvariantError::OffsetOutOfRange(Layer::Bottom, 9)));
+
+        let mut test_cube = SqOne::new();
+        test_cube.bottom[2] = test_cube.top[2];
+        assert_eq!(test_cube.invariants(), Err(InvariantError::DuplicatePiece(PieceId::new(8).unwrap())));
+    }
+
+    #[test]
+    fn validate_reports_every_error() {
+        let mut test_cube = SqOne::new();
+        test_cube.bottom[2] = test_cube.top[2];
+        test_cube.top_offset = -7;
+        assert_eq!(test_cube.validate(), Err(vec![
+            InvariantError::OffsetOutOfRange(Layer::Top, -7),
+            InvariantError::DuplicatePiece(PieceId::new(8).unwrap()),
+            InvariantError::MissingPiece(PieceId::new(12).unwrap()),
+        ]));
+        assert!(SqOne::new().validate().is_ok());
+    }
+
+    #[test]
+    fn sample_random_states() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let states: Vec<SqOne> = (0..200).map(|_| SqOne::random_state(&mut rng)).collect();
+        assert!(states.iter().all(|state| state.validate().is_ok()));
+        assert!(states.iter().any(|state| state.middle) && states.iter().any(|state| !state.middle));
+
+        // Fewer than one state in a
+        // thousand is in cube shape, unlike
+        // the end of a random move scramble.
+        let cubes = states.iter().filter(|state| shape::classify(state).is_cube()).count();
+        assert!(cubes < 5);
+    }
+
+    #[test]
+    fn view_layers() {
+        let cube = SqOne::new();
+        let top: Vec<PieceView> = cube.top().collect();
+        assert_eq!(top.len(), 8);
+        assert!(top.iter().all(|piece| piece.colors[0] == Color::White));
+        assert_eq!(top.iter().map(|piece| piece.colors.len()).sum::<usize>(), 4 * 3 + 4 * 2);
+        assert_eq!(top[1].angle - top[0].angle, top[0].width);
+        assert_eq!(top.iter().map(|piece| piece.width).sum::<u16>(), 360);
+        assert!(cube.bottom().all(|piece| piece.colors[0] == Color::Yellow));
+        assert!(cube.top().all(|piece| piece.id.index() % 8 < 4));
+
+        // Turning a view by the degrees of a
+        // twist puts it where the twist does.
+        let mut twisted = cube.clone();
+        twisted.try_twist(1, -2).unwrap();
+        for (layer, amount, before, after) in [
+            (Layer::Top, 1.0, cube.top().collect::<Vec<_>>(), twisted.top().collect::<Vec<_>>()),
+            (Layer::Bottom, -2.0, cube.bottom().collect(), twisted.bottom().collect()),
+        ] {
+            for piece in &before {
+                let moved = after.iter().find(|view| view.id == piece.id).unwrap();
+                assert_eq!(piece.rotated(layer.twist_degrees(amount)), moved.angle as f64);
+            }
+        }
+    }
+
+    #[test]
+    fn pieces_by_layer_color() {
+        let mut cube = SqOne::new();
+        let colors = cube.pieces_by_layer_color();
+        assert_eq!((colors.top_white.len(), colors.bottom_yellow.len()), (8, 8));
+        assert!(colors.corners_oriented() && colors.edges_oriented());
+
+        // A slash swaps the right halves,
+        // taking as many pieces out of each
+        // layer as it brings in.
+        cube.apply(&Scramble { turns: vec![(1, 0), (0, 0)] }).unwrap();
+        let colors = cube.pieces_by_layer_color();
+        assert_eq!(colors.top_yellow.len(), colors.bottom_white.len());
+        assert!(!colors.corners_oriented() && !colors.edges_oriented());
+        assert!(colors.misoriented(PieceKind::Corner).iter().all(|piece| piece.is_corner()));
+        let counted = colors.top_white.len() + colors.top_yellow.len() + colors.bottom_white.len() + colors.bottom_yellow.len();
+        assert_eq!(counted, 16);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_middle_layer() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for options in [ScrambleOptions::new(), ScrambleOptions::new().require_parity(true)] {
+            for flipped in [false, true] {
+                let mut cube = SqOne::new();
+                let scramble = cube.scramble_with_rng(&options.middle_flipped(flipped), &mut rng);
+                assert_eq!(cube.middle_flipped(), flipped);
+                assert_eq!(scramble.verify(&cube), Ok(()));
+            }
+        }
+        let mut cube = SqOne::new();
+        cube.scramble_with_rng(&ScrambleOptions::new().random_state(true).middle_flipped(true), &mut rng);
+        assert!(cube.middle_flipped());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_one_layer() {
+        let mut rng = StdRng::seed_from_u64(592);
+        let (solved_top, solved_bottom, _) = SqOne::new().slots();
+        for kind in [None, Some(PieceKind::Corner), Some(PieceKind::Edge)] {
+            let mut options = ScrambleOptions::new().only_layer(Layer::Bottom);
+            if let Some(kind) = kind {
+                options = options.only_pieces(kind);
+            }
+            let mut cube = SqOne::new();
+            let scramble = cube.scramble_with_rng(&options, &mut rng);
+            assert_eq!(scramble.verify(&cube), Ok(()));
+
+            let (top, bottom, middle) = cube.slots();
+            assert_eq!((top, middle), (solved_top, false));
+            for slot in 0..12 {
+                let moved = bottom[slot] != solved_bottom[slot];
+                assert!(!moved || kind != Some(if slot % 3 == 2 { PieceKind::Corner } else { PieceKind::Edge }));
+            }
+        }
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_in_cube_shape() {
+        let mut rng = StdRng::seed_from_u64(593);
+        let cube_shape = ShapeState { top: Shape::Square, bottom: Shape::Square };
+        for parity in [false, true] {
+            let mut cube = SqOne::new();
+            let options = ScrambleOptions::new().cube_shape(true).require_parity(parity);
+            let scramble = cube.scramble_with_rng(&options, &mut rng);
+            assert_eq!(cube.has_parity(), Some(false));
+
+            let mut replayed = SqOne::new();
+            for next in scramble.moves() {
+                replayed.apply_move(next).unwrap();
+                if next == Move::Slash {
+                    assert_eq!(shape::classify(&replayed), cube_shape);
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_to_difficulty() {
+        let mut rng = StdRng::seed_from_u64(621);
+        for (least, most) in [(2, 4), (11, 13)] {
+            let mut cube = SqOne::new();
+            let options = ScrambleOptions::new().target_difficulty(least..=most).unwrap();
+            let scramble = cube.scramble_with_rng(&options, &mut rng);
+            assert_eq!(scramble.verify(&cube), Ok(()));
+            assert!(solver::needs_slashes(&cube, least));
+            assert!(most == MAX_SLASHES || !solver::needs_slashes(&cube, most + 1));
+        }
+
+        let mut cube = SqOne::new();
+        cube.scramble_with_rng(&ScrambleOptions::new().target_difficulty(..2).unwrap(), &mut rng);
+        assert!(!solver::needs_slashes(&cube, 2));
+
+        // Ranges no cube is in are refused
+        // when they are set.
+        let options = ScrambleOptions::new();
+        assert_eq!(options.target_difficulty(14..).err(), Some(OptionsError::Unreachable(14)));
+        assert_eq!(options.target_difficulty(5..5).err(), Some(OptionsError::EmptyRange));
+        assert_eq!(options.min_slashes(14).err(), Some(OptionsError::Unreachable(14)));
+        let hard = options.min_slashes(WCA_MIN_SLASHES).unwrap();
+        assert_eq!(hard.target_difficulty(..=8).err(), Some(OptionsError::Conflict));
+        let easy = options.target_difficulty(..=8).unwrap();
+        assert_eq!(easy.min_slashes(WCA_MIN_SLASHES).err(), Some(OptionsError::Conflict));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_with_filter() {
+        let mut rng = StdRng::seed_from_u64(626);
+        let options = ScrambleOptions::new().filter(|cube, scramble| cube.corners_on_top() == 5 && scramble.len() % 2 == 0);
+        for _ in 0..3 {
+            let mut cube = SqOne::new();
+            let scramble = cube.scramble_with_rng(&options, &mut rng);
+            assert_eq!((cube.corners_on_top(), scramble.len() % 2), (5, 0));
+        }
+
+        // A filter nothing passes still
+        // gives a scramble in the end, or
+        // an error when asked for one.
+        let mut cube = SqOne::new();
+        let options = ScrambleOptions::new().filter(|_, _| false);
+        let scramble = cube.scramble_with_rng(&options, &mut rng);
+        assert_eq!(scramble.verify(&cube), Ok(()));
+        assert_eq!(SqOne::new().try_scramble_with_rng(&options, &mut rng), Err(OptionsError::Exhausted));
+        assert!(SqOne::new().try_scramble_with_rng(&ScrambleOptions::new(), &mut rng).is_ok());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_suffixes() {
+        let mut rng = StdRng::seed_from_u64(590);
+        for random_state in [false, true] {
+            for parity in [None, Some(true)] {
+                let mut options = ScrambleOptions::new().random_state(random_state);
+                if let Some(parity) = parity {
+                    options = options.require_parity(parity);
+                }
+                for suffix in [SuffixPolicy::Twist, SuffixPolicy::Slash, SuffixPolicy::MiddleFixed] {
+                    let mut cube = SqOne::new();
+                    let scramble = cube.scramble_with_rng(&options.suffix(suffix), &mut rng);
+                    assert_eq!(scramble.verify(&cube), Ok(()));
+                    let last = *scramble.turns.last().unwrap();
+                    match suffix {
+                        SuffixPolicy::Twist => assert_ne!(last, (0, 0)),
+                        SuffixPolicy::Slash => assert_eq!(last, (0, 0)),
+                        _ => assert!(!cube.middle_flipped()),
+                    }
+                }
+            }
+        }
+
+        let mut cube = SqOne::new();
+        let options = ScrambleOptions::new().suffix(SuffixPolicy::Slash).middle_flipped(true);
+        let scramble = cube.scramble_with_rng(&options, &mut rng);
+        assert!(cube.middle_flipped());
+        assert!(SuffixPolicy::Slash.format().format(&scramble).ends_with(" /"));
+    }
 }
@@ -0,0 +1,155 @@
+//! Module for writing datasets of cube
+//! states labelled with the number of
+//! moves needed to solve them, so models
+//! predicting the distance can be trained
+//! without reimplementing the puzzle.
+//!
+//! The binary format starts with the
+//! bytes SQ1D, the format version and
+//! the metric, 0 for twists and 1 for
+//! slashes. Each sample then takes
+//! RECORD_LEN bytes: the piece index in
+//! each slot of the top and then bottom
+//! layer, two slots a byte with the high
+//! nibble first, and a last byte holding
+//! the distance, with the high bit set
+//! if the middle layer is flipped.
+
+use std::io::{self, Write};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cube::SqOne;
+use crate::{fen, map_batch, solver, Metric};
+
+/// Version of the binary format
+/// written by Dataset::write_binary.
+pub const DATASET_VERSION: u8 = 1;
+
+/// Bytes of each sample in the
+/// binary format.
+pub const RECORD_LEN: usize = 13;
+
+/// Cube state with its distance
+/// from solved.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Sample {
+    slots: ([u8; 12], [u8; 12], bool),
+
+    /// Fewest moves that solve the
+    /// state, from solver::solve_optimal.
+    pub distance: u8,
+}
+
+impl Sample {
+
+    /// Returns the cube in the state.
+    pub fn cube(&self) -> SqOne {
+        let (top, bottom, middle) = &self.slots;
+        SqOne::from_slots(top, bottom, *middle)
+    }
+
+    /// Returns the state string of the
+    /// cube from fen::to_fen.
+    pub fn fen(&self) -> String {
+        fen::to_fen(&self.cube())
+    }
+}
+
+/// Samples labelled in one metric.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Dataset {
+    pub metric: Metric,
+    pub samples: Vec<Sample>,
+}
+
+impl Dataset {
+
+    /// Generates count samples, seeded by
+    /// seed so the same seed always gives
+    /// the same dataset. Each state is made
+    /// by a random walk of up to max_slashes
+    /// slashes from solved and labelled by
+    /// the optimal solver, which grows slow
+    /// past a handful of slashes. With the
+    /// parallel feature, the samples are
+    /// split across every core.
+    pub fn generate(count: usize, metric: Metric, max_slashes: usize, seed: u64) -> Dataset {
+        let mut master = StdRng::seed_from_u64(seed);
+        let seeds: Vec<u64> = (0..count).map(|_| master.gen()).collect();
+
+        Dataset { metric, samples: map_batch(&seeds, |&seed| label_one(seed, metric, max_slashes)) }
+    }
+
+    /// Writes the samples as CSV with a
+    /// header, one line per sample holding
+    /// the state string and distance.
+    pub fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "state,distance")?;
+        for sample in &self.samples {
+            writeln!(out, "{},{}", sample.fen(), sample.distance)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the samples in the binary
+    /// format described at the top of
+    /// the module.
+    pub fn write_binary<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(b"SQ1D")?;
+        out.write_all(&[DATASET_VERSION, (self.metric == Metric::Slash) as u8])?;
+        for sample in &self.samples {
+            let (top, bottom, middle) = &sample.slots;
+            let mut record = [0; RECORD_LEN];
+            for (i, pair) in top.chunks(2).chain(bottom.chunks(2)).enumerate() {
+                record[i] = pair[0] << 4 | pair[1];
+            }
+            record[RECORD_LEN - 1] = (*middle as u8) << 7 | sample.distance;
+            out.write_all(&record)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Walks to and labels a state with an
+/// rng seeded by seed.
+fn label_one(seed: u64, metric: Metric, max_slashes: usize) -> Sample {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let cube = SqOne::random_walk_state(rng.gen_range(0..=max_slashes), &mut rng);
+    let distance = solver::solve_optimal(&cube, metric).len_in(metric);
+
+    Sample { slots: cube.slots(), distance: distance as u8 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_dataset() {
+        let dataset = Dataset::generate(4, Metric::Slash, 3, 627);
+        assert_eq!(dataset, Dataset::generate(4, Metric::Slash, 3, 627));
+        assert!(dataset.samples.iter().all(|sample| sample.distance <= 3));
+
+        let mut csv = vec![];
+        dataset.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        let (state, distance) = lines[1].split_once(',').unwrap();
+        assert_eq!(fen::from_fen(state).unwrap(), dataset.samples[0].cube());
+        assert_eq!(distance, dataset.samples[0].distance.to_string());
+
+        let mut binary = vec![];
+        dataset.write_binary(&mut binary).unwrap();
+        assert_eq!(binary.len(), 6 + 4 * RECORD_LEN);
+        assert_eq!(&binary[..6], b"SQ1D\x01\x01");
+        let (top, _, middle) = dataset.samples[0].slots;
+        assert_eq!(binary[6] >> 4, top[0]);
+        assert_eq!(binary[6] & 0xf, top[1]);
+        assert_eq!(binary[6 + RECORD_LEN - 1], (middle as u8) << 7 | dataset.samples[0].distance);
+    }
+}
@@ -0,0 +1,333 @@
+//! Module for one error type covering
+//! the errors of the crate, holding
+//! where in the input each happened so
+//! frontends can point at the problem
+//! rather than only name it. With the
+//! diagnostics feature, Error implements
+//! miette::Diagnostic with its code, help
+//! and the range at fault labelled.
+
+#[cfg(feature = "diagnostics")]
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use crate::cstimer::ParseError;
+use crate::cube::{InvariantError, SqOne};
+use crate::fen::{self, FenError};
+use crate::notation::Dialect;
+use crate::{IllegalAt, Scramble, ScrambleError, VerifyError};
+
+/// Errors of the crate, each with the
+/// byte range of the input at fault
+/// when it is known.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum Error {
+
+    /// Scramble text that cannot be
+    /// read, with the range of the turn
+    /// that is not valid.
+    Parse { error: ParseError, span: Option<Range<usize>> },
+
+    /// A turn moves a layer by more
+    /// than a half turn.
+    TurnOutOfRange { turn: usize, span: Option<Range<usize>> },
+
+    /// A slash blocked by a corner, after
+    /// the turn at this index, with its
+    /// index in Scramble::moves when known
+    /// and the range of the slash.
+    IllegalSlash { turn: usize, position: Option<usize>, span: Option<Range<usize>> },
+
+    /// A scramble that can be applied
+    /// but gives a different cube, with
+    /// the index in Scramble::moves of the
+    /// first move that differs when known.
+    Mismatch { position: Option<usize> },
+
+    /// A cube state no moves can reach,
+    /// naming the layer and slot or the
+    /// piece at fault.
+    InvalidState(InvariantError),
+
+    /// A state string that cannot be
+    /// read, with the range of the field
+    /// that is not valid.
+    Fen { error: FenError, span: Option<Range<usize>> },
+}
+
+impl Error {
+
+    /// Returns the byte range of the
+    /// input at fault, if known.
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Error::Parse { span, .. }
+            | Error::TurnOutOfRange { span, .. }
+            | Error::IllegalSlash { span, .. }
+            | Error::Fen { span, .. } => span.clone(),
+            Error::Mismatch { .. } | Error::InvalidState(_) => None,
+        }
+    }
+
+    /// Returns a short code naming the
+    /// kind of error, as in sq1::parse.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Parse { .. } => "sq1::parse",
+            Error::TurnOutOfRange { .. } => "sq1::turn_out_of_range",
+            Error::IllegalSlash { .. } => "sq1::illegal_slash",
+            Error::Mismatch { .. } => "sq1::mismatch",
+            Error::InvalidState(_) => "sq1::invalid_state",
+            Error::Fen { .. } => "sq1::fen",
+        }
+    }
+
+    /// Returns advice on fixing the
+    /// input, if there is any.
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            Error::Parse { error: ParseError::Empty, .. } => Some("write at least one turn or slash"),
+            Error::Parse { .. } => Some("write each turn as (top,bottom) with amounts from -6 to 6"),
+            Error::TurnOutOfRange { .. } => Some("turn each layer by at most 6 either way"),
+            Error::IllegalSlash { .. } => Some("check the turn before the slash lines up the corners"),
+            Error::Mismatch { .. } => None,
+            Error::InvalidState(_) => Some("every corner must fill two neighbouring slots and every piece appear once"),
+            Error::Fen { .. } => Some("write the state as SqOne::to_fen does: shape, pieces and flags"),
+        }
+    }
+
+    /// Returns the message without
+    /// the position.
+    fn message(&self) -> String {
+        match self {
+            Error::Parse { error, .. } => error.to_string(),
+            Error::TurnOutOfRange { turn, .. } => format!("turn {} is out of range", turn),
+            Error::IllegalSlash { turn, position: Some(position), .. } => IllegalAt { turn: *turn, position: *position }.to_string(),
+            Error::IllegalSlash { turn, position: None, .. } => format!("slash after turn {} is blocked by a corner", turn),
+            Error::Mismatch { position: Some(position) } => format!("scramble gives a different cube from move {}", position),
+            Error::Mismatch { position: None } => "scramble gives a different cube".to_string(),
+            Error::InvalidState(error) => format!("invalid state: {}", error),
+            Error::Fen { error, .. } => error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())?;
+        match self.span() {
+            Some(span) => write!(f, " at byte {}", span.start),
+            None => Ok(()),
+        }
+    }
+}
+
+// The input is not kept, so it is given
+// to the report with with_source_code.
+#[cfg(feature = "diagnostics")]
+impl miette::Diagnostic for Error {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Some(Box::new(Error::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        Error::help(self).map(|help| Box::new(help) as Box<dyn fmt::Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let label = miette::LabeledSpan::new_with_span(Some(self.message()), self.span()?);
+        Some(Box::new(core::iter::once(label)))
+    }
+}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Parse { error, .. } => Some(error),
+            Error::InvalidState(error) => Some(error),
+            Error::Fen { error, .. } => Some(error),
+            Error::TurnOutOfRange { .. } | Error::IllegalSlash { .. } | Error::Mismatch { .. } => None,
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Error {
+        Error::Parse { error, span: None }
+    }
+}
+
+impl From<IllegalAt> for Error {
+    fn from(at: IllegalAt) -> Error {
+        Error::IllegalSlash { turn: at.turn, position: Some(at.position), span: None }
+    }
+}
+
+impl From<ScrambleError> for Error {
+    fn from(error: ScrambleError) -> Error {
+        match error {
+            ScrambleError::Empty => Error::Parse { error: ParseError::Empty, span: None },
+            ScrambleError::TurnOutOfRange(turn) => Error::TurnOutOfRange { turn, span: None },
+            ScrambleError::IllegalSlash(turn) => Error::IllegalSlash { turn, position: None, span: None },
+        }
+    }
+}
+
+impl From<VerifyError> for Error {
+    fn from(error: VerifyError) -> Error {
+        match error {
+            VerifyError::IllegalSlash(turn) => ScrambleError::IllegalSlash(turn).into(),
+            VerifyError::Mismatch(position) => Error::Mismatch { position },
+        }
+    }
+}
+
+impl From<InvariantError> for Error {
+    fn from(error: InvariantError) -> Error {
+        Error::InvalidState(error)
+    }
+}
+
+impl From<FenError> for Error {
+    fn from(error: FenError) -> Error {
+        Error::Fen { error, span: None }
+    }
+}
+
+/// Reads a scramble in the dialect and
+/// checks every slash can be made from
+/// a solved cube, returning an error
+/// with the range of the turn or slash
+/// at fault.
+pub fn read_scramble(text: &str, dialect: Dialect) -> Result<Scramble, Error> {
+    let (turns, slashes) = segments(text, dialect);
+    let scramble = dialect.parse(text).map_err(|error| {
+        let span = match &error {
+            ParseError::Empty => Some(0..text.len()),
+            ParseError::InvalidTurn(turn) => turns.iter().find(|span| clean(&text[(*span).clone()], dialect) == *turn).cloned(),
+        };
+        Error::Parse { error, span }
+    })?;
+
+    if let Some(turn) = scramble.turns.iter().position(|&(top, bottom)| top.abs() > 6 || bottom.abs() > 6) {
+        return Err(Error::TurnOutOfRange { turn, span: turns.get(turn).cloned() });
+    }
+    scramble
+        .is_legal_from(&SqOne::new())
+        .map_err(|at| Error::IllegalSlash { turn: at.turn, position: Some(at.position), span: slashes.get(at.turn).cloned() })?;
+
+    Ok(scramble)
+}
+
+/// Reads a state string, returning an
+/// error with the range of the field
+/// at fault.
+pub fn read_fen(text: &str) -> Result<SqOne, Error> {
+    fen::from_fen(text).map_err(|error| {
+        let fields: Vec<Range<usize>> = text
+            .split_whitespace()
+            .map(|field| {
+                let start = field.as_ptr() as usize - text.as_ptr() as usize;
+                start..start + field.len()
+            })
+            .collect();
+        let field = match error {
+            FenError::FieldCount(_) => None,
+            FenError::InvalidShape => Some(0),
+            FenError::InvalidPieces => Some(1),
+            FenError::InvalidFlags => Some(2),
+        };
+        let span = match field {
+            Some(field) => fields.get(field).cloned(),
+            None => Some(0..text.len()),
+        };
+        Error::Fen { error, span }
+    })
+}
+
+/// Returns the text of a turn as the
+/// parser of the dialect reports it.
+fn clean(turn: &str, dialect: Dialect) -> String {
+    match dialect {
+        Dialect::Spoken => turn.to_lowercase().replace([',', '.'], " ").trim().to_string(),
+        _ => turn.chars().filter(|c| !c.is_whitespace()).collect(),
+    }
+}
+
+/// Returns the range of each turn, with
+/// spaces around it left out, and of
+/// each slash in the text as read in
+/// the dialect.
+fn segments(text: &str, dialect: Dialect) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let token = match dialect {
+        Dialect::Abf => "f",
+        Dialect::Spoken => "slash",
+        _ => "/",
+    };
+    let slashes: Vec<Range<usize>> = text
+        .to_ascii_lowercase()
+        .match_indices(token)
+        .map(|(start, _)| start..start + token.len())
+        .collect();
+
+    let bounds = core::iter::once(0).chain(slashes.iter().map(|slash| slash.end));
+    let ends = slashes.iter().map(|slash| slash.start).chain(core::iter::once(text.len()));
+    let turns = bounds
+        .zip(ends)
+        .map(|(start, end)| {
+            let turn = &text[start..end];
+            let trimmed = turn.trim_start();
+            let start = start + turn.len() - trimmed.len();
+            start..start + trimmed.trim_end().len()
+        })
+        .collect();
+
+    (turns, slashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_at_input() {
+        let text = "(1,0) / (7,0) / (0,3)";
+        let error = read_scramble(text, Dialect::Standard).unwrap_err();
+        assert_eq!(error.span(), Some(8..13));
+        assert_eq!(error.to_string(), "invalid turn \"(7,0)\" at byte 8");
+
+        let error = read_scramble("(0,0) / (0,0) / (2,0) / (0,3)", Dialect::Standard).unwrap_err();
+        assert_eq!(error, Error::IllegalSlash { turn: 2, position: Some(3), span: Some(22..23) });
+        assert_eq!(error.to_string(), "slash after turn 2 (move 3) is blocked by a corner at byte 22");
+        assert_eq!(read_scramble("A1 F B2 F", Dialect::Abf).map(|scramble| scramble.turns), Ok(vec![(1, 0), (0, 2), (0, 0)]));
+
+        let fen = fen::to_fen(&SqOne::new()).replacen('-', "x", 1);
+        let error = read_fen(&fen).unwrap_err();
+        assert_eq!(error, Error::Fen { error: FenError::InvalidFlags, span: Some(fen.rfind('x').unwrap()..fen.len()) });
+        assert_eq!(Error::from(VerifyError::Mismatch(Some(4))).span(), None);
+        assert_eq!(Error::from(VerifyError::Mismatch(Some(4))).to_string(), "scramble gives a different cube from move 4");
+        assert_eq!(Error::from(VerifyError::Mismatch(None)).to_string(), "scramble gives a different cube");
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn report_with_source() {
+        use miette::{Diagnostic, NarratableReportHandler, Report};
+
+        let text = "(1,0) /\n(7,0)";
+        let error = read_scramble(text, Dialect::Standard).unwrap_err();
+        assert_eq!(Diagnostic::code(&error).unwrap().to_string(), "sq1::parse");
+        let label = error.labels().unwrap().next().unwrap();
+        assert_eq!((label.offset(), label.len()), (8, 5));
+
+        let report = Report::new(error).with_source_code(text);
+        let mut rendered = String::new();
+        NarratableReportHandler::new().render_report(&mut rendered, report.as_ref()).unwrap();
+        assert!(rendered.contains("(7,0)"));
+        assert!(rendered.contains("write each turn as (top,bottom) with amounts from -6 to 6"));
+    }
+}
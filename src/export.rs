@@ -0,0 +1,284 @@
+//! Module for writing scrambles in the
+//! formats used by WCA competition
+//! software, and reading them back
+//! to audit scramble sets.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::cstimer::{self, ParseError};
+use crate::cube::SqOne;
+use crate::notation::ScrambleFormat;
+use crate::json::{self, Json};
+use crate::{Scramble, ScrambleGroup};
+
+/// Event id of the Square One in
+/// WCA competition files.
+pub const EVENT_ID: &str = "sq1";
+
+/// Returns the scramble written the
+/// way TNoodle writes it, with no space
+/// inside each turn, as in (1,0)/ (-3,2).
+pub fn wca_string(scramble: &Scramble) -> String {
+    scramble.format_with(&ScrambleFormat::wca())
+}
+
+/// Returns the scrambles of each round
+/// as a WCIF event in JSON, with one
+/// scramble set per group.
+pub fn wcif_event(rounds: &[Vec<ScrambleGroup>]) -> String {
+    let rounds: Vec<String> = rounds
+        .iter()
+        .enumerate()
+        .map(|(i, groups)| {
+            let sets: Vec<String> = groups
+                .iter()
+                .enumerate()
+                .map(|(j, group)| {
+                    format!(
+                        "{{\"id\":{},\"scrambles\":{},\"extraScrambles\":{}}}",
+                        j + 1,
+                        json_strings(&group.scrambles),
+                        json_strings(&group.extras),
+                    )
+                })
+                .collect();
+            format!("{{\"id\":\"{}-r{}\",\"scrambleSets\":[{}]}}", EVENT_ID, i + 1, sets.join(","))
+        })
+        .collect();
+
+    format!("{{\"id\":\"{}\",\"rounds\":[{}]}}", EVENT_ID, rounds.join(","))
+}
+
+/// Errors returned when scrambles
+/// cannot be read from WCIF.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum WcifError {
+
+    /// The text is not valid JSON. Holds
+    /// the byte offset of the error.
+    InvalidJson(usize),
+
+    /// The text holds no Square One
+    /// event.
+    MissingEvent,
+
+    /// A round or scramble set is missing
+    /// the field or the field has the
+    /// wrong type.
+    InvalidField(&'static str),
+
+    /// A scramble cannot be read.
+    InvalidScramble(ParseError),
+}
+
+impl fmt::Display for WcifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WcifError::InvalidJson(offset) => write!(f, "invalid JSON at byte {}", offset),
+            WcifError::MissingEvent => write!(f, "no {} event", EVENT_ID),
+            WcifError::InvalidField(field) => write!(f, "missing or invalid field {:?}", field),
+            WcifError::InvalidScramble(error) => write!(f, "invalid scramble: {}", error),
+        }
+    }
+}
+
+impl Error for WcifError {}
+
+/// Reads the scrambles of each round
+/// of the Square One event, from either
+/// a whole competition in WCIF or a
+/// single event as written by
+/// wcif_event. Other fields and events
+/// are ignored.
+pub fn parse_wcif(text: &str) -> Result<Vec<Vec<ScrambleGroup>>, WcifError> {
+    let value = json::parse_json(text).map_err(WcifError::InvalidJson)?;
+    let is_event = |event: &&Json| matches!(event.field("id"), Some(Json::String(id)) if id == EVENT_ID);
+    let event = match value.field("events") {
+        Some(Json::Array(events)) => events.iter().find(is_event),
+        _ => Some(&value).filter(is_event),
+    };
+    let rounds = match event.ok_or(WcifError::MissingEvent)?.field("rounds") {
+        Some(Json::Array(rounds)) => rounds,
+        _ => return Err(WcifError::InvalidField("rounds")),
+    };
+
+    rounds
+        .iter()
+        .map(|round| match round.field("scrambleSets") {
+            Some(Json::Array(sets)) => sets
+                .iter()
+                .map(|set| {
+                    Ok(ScrambleGroup {
+                        scrambles: read_scrambles(set, "scrambles")?,
+                        extras: read_scrambles(set, "extraScrambles")?,
+                    })
+                })
+                .collect(),
+            _ => Err(WcifError::InvalidField("scrambleSets")),
+        })
+        .collect()
+}
+
+/// Reads a field of a scramble set
+/// holding an array of scrambles.
+fn read_scrambles(set: &Json, name: &'static str) -> Result<Vec<Scramble>, WcifError> {
+    match set.field(name) {
+        Some(Json::Array(scrambles)) => scrambles
+            .iter()
+            .map(|scramble| match scramble {
+                Json::String(text) => cstimer::parse(text).map_err(WcifError::InvalidScramble),
+                _ => Err(WcifError::InvalidField(name)),
+            })
+            .collect(),
+        _ => Err(WcifError::InvalidField(name)),
+    }
+}
+
+/// Place of a scramble in the rounds
+/// read from WCIF, counting from 0.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct ScramblePlace {
+    pub round: usize,
+    pub group: usize,
+
+    /// Whether the scramble is
+    /// one of the extras.
+    pub extra: bool,
+    pub index: usize,
+}
+
+/// Problems found by audit.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum AuditIssue {
+
+    /// The slash after the turn at the
+    /// index is blocked by a corner.
+    IllegalSlash(ScramblePlace, usize),
+
+    /// The second scramble gives the
+    /// same state as the first, so
+    /// competitors could get the
+    /// same scramble twice.
+    Duplicate(ScramblePlace, ScramblePlace),
+}
+
+/// Checks every scramble of the rounds
+/// can be applied from solved and that
+/// no two give the same state, returning
+/// each problem found in order.
+pub fn audit(rounds: &[Vec<ScrambleGroup>]) -> Vec<AuditIssue> {
+    let mut issues = Vec::new();
+    let mut seen = BTreeMap::new();
+    for (round, groups) in rounds.iter().enumerate() {
+        for (group, scrambles) in groups.iter().enumerate() {
+            let numbered = scrambles.scrambles.iter().enumerate().map(|(index, scramble)| (false, index, scramble));
+            let extras = scrambles.extras.iter().enumerate().map(|(index, scramble)| (true, index, scramble));
+            for (extra, index, scramble) in numbered.chain(extras) {
+                let place = ScramblePlace { round, group, extra, index };
+                let mut cube = SqOne::new();
+                if let Err(slash) = cube.apply(scramble) {
+                    issues.push(AuditIssue::IllegalSlash(place, slash));
+                    continue;
+                }
+                if let Some(&first) = seen.get(&cube.slots()) {
+                    issues.push(AuditIssue::Duplicate(first, place));
+                } else {
+                    seen.insert(cube.slots(), place);
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Returns the text percent-encoded
+/// for use as a URL query value, leaving
+/// the characters JavaScript's
+/// encodeURIComponent leaves.
+pub(crate) fn encode_component(text: &str) -> String {
+    let mut encoded = String::new();
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// Returns the scrambles as a JSON
+/// array of strings. Scrambles only
+/// hold digits and punctuation, so
+/// nothing needs escaping.
+pub(crate) fn json_strings(scrambles: &[Scramble]) -> String {
+    let strings: Vec<String> = scrambles
+        .iter()
+        .map(|scramble| format!("\"{}\"", wca_string(scramble)))
+        .collect();
+
+    format!("[{}]", strings.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn write_wca_string() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (0, 6)] };
+        assert_eq!(wca_string(&scramble), "(1,0)/ (-3,2)/ (0,6)");
+    }
+
+    #[test]
+    fn encode_url_component() {
+        assert_eq!(encode_component("(1, -3) / (0,6)"), "(1%2C%20-3)%20%2F%20(0%2C6)");
+    }
+
+    #[test]
+    fn write_wcif_event() {
+        let group = ScrambleGroup {
+            scrambles: vec![Scramble { turns: vec![(1, 0), (0, -1)] }],
+            extras: vec![Scramble { turns: vec![(0, 1)] }],
+        };
+        assert_eq!(
+            wcif_event(&[vec![group]]),
+            "{\"id\":\"sq1\",\"rounds\":[{\"id\":\"sq1-r1\",\"scrambleSets\":[\
+            {\"id\":1,\"scrambles\":[\"(1,0)/ (0,-1)\"],\"extraScrambles\":[\"(0,1)\"]}]}]}",
+        );
+    }
+
+    #[test]
+    fn read_wcif() {
+        let groups = vec![
+            ScrambleGroup {
+                scrambles: vec![Scramble { turns: vec![(1, 0), (0, -1)] }, Scramble { turns: vec![(0, 3)] }],
+                extras: vec![Scramble { turns: vec![(0, 1)] }],
+            },
+            ScrambleGroup { scrambles: vec![Scramble { turns: vec![(1, 0), (0, -1)] }], extras: vec![] },
+        ];
+        let event = wcif_event(core::slice::from_ref(&groups));
+        assert_eq!(parse_wcif(&event), Ok(vec![groups.clone()]));
+
+        let competition = format!("{{\"formatVersion\":\"1.0\",\"events\":[{{\"id\":\"333\",\"rounds\":[]}},{}]}}", event);
+        assert_eq!(parse_wcif(&competition), Ok(vec![groups.clone()]));
+        assert_eq!(parse_wcif("{\"events\":[]}"), Err(WcifError::MissingEvent));
+        assert_eq!(parse_wcif("{\"id\":\"sq1\"}"), Err(WcifError::InvalidField("rounds")));
+
+        // The second group repeats the
+        // first scramble of the first.
+        let place = |group, extra, index| ScramblePlace { round: 0, group, extra, index };
+        assert_eq!(audit(&[groups]), vec![AuditIssue::Duplicate(place(0, false, 0), place(1, false, 0))]);
+
+        let blocked = ScrambleGroup { scrambles: vec![Scramble { turns: vec![(2, 0), (0, 0)] }], extras: vec![] };
+        assert_eq!(audit(&[vec![blocked]]), vec![AuditIssue::IllegalSlash(place(0, false, 0), 0)]);
+    }
+}
@@ -0,0 +1,176 @@
+//! Module for writing the state of a
+//! cube as a short string that can be
+//! typed and shared in chat, like FEN
+//! for chess positions, and reading
+//! it back exactly.
+//!
+//! The string has three fields split by
+//! spaces, as in b6db6d 08192a3b4c5d6e7f -
+//! for a solved cube:
+//!
+//! - The shape, as three hex digits for
+//!   each of the top and bottom layers,
+//!   with bit i set when a piece starts
+//!   at slot i.
+//! - The pieces, as the hex index of the
+//!   piece at each start, top layer
+//!   first.
+//! - The flags, m if the middle layer is
+//!   flipped and - otherwise.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::cube::SqOne;
+
+/// Errors returned when a state
+/// string cannot be read.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FenError {
+
+    /// The string does not have
+    /// exactly three fields.
+    FieldCount(usize),
+
+    /// The shape is not six hex digits
+    /// marking where pieces start.
+    InvalidShape,
+
+    /// The pieces are not sixteen hex
+    /// digits, each piece once, with
+    /// corners and edges fitting
+    /// the shape.
+    InvalidPieces,
+
+    /// The flags are not m or -.
+    InvalidFlags,
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "expected 3 fields but found {}", count),
+            FenError::InvalidShape => write!(f, "invalid shape"),
+            FenError::InvalidPieces => write!(f, "invalid pieces"),
+            FenError::InvalidFlags => write!(f, "invalid flags"),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+/// Returns the state string of the cube.
+pub fn to_fen(cube: &SqOne) -> String {
+    let (top, bottom, middle) = cube.slots();
+    let hex = |digit: u32| char::from_digit(digit, 16).unwrap();
+
+    let mut shape = String::new();
+    let mut pieces = String::new();
+    for slots in [top, bottom] {
+        let mut mask = 0;
+        for i in 0..12 {
+            if slots[i] != slots[(i + 11) % 12] {
+                mask |= 1 << i;
+                pieces.push(hex(slots[i] as u32));
+            }
+        }
+        for shift in [8, 4, 0] {
+            shape.push(hex((mask >> shift) & 0xf));
+        }
+    }
+
+    let flags = if middle { "m" } else { "-" };
+    let mut fen = shape;
+    fen.push(' ');
+    fen.push_str(&pieces);
+    fen.push(' ');
+    fen.push_str(flags);
+
+    fen
+}
+
+/// Reads a state string, ignoring the
+/// case of hex digits and extra spaces.
+pub fn from_fen(text: &str) -> Result<SqOne, FenError> {
+    let fields: Vec<&str> = text.split_whitespace().collect();
+    let [shape, pieces, flags] = fields[..] else {
+        return Err(FenError::FieldCount(fields.len()));
+    };
+
+    if shape.len() != 6 || !shape.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(FenError::InvalidShape);
+    }
+    let top_mask = u16::from_str_radix(&shape[..3], 16).map_err(|_| FenError::InvalidShape)?;
+    let bottom_mask = u16::from_str_radix(&shape[3..], 16).map_err(|_| FenError::InvalidShape)?;
+
+    let mut pieces = pieces.chars().map(|c| c.to_digit(16).map(|digit| digit as u8));
+    let mut seen = [false; 16];
+    let mut layer = |mask: u16| -> Result<[u8; 12], FenError> {
+        let starts: Vec<usize> = (0..12).filter(|&i| mask & (1 << i) != 0).collect();
+        if starts.is_empty() {
+            return Err(FenError::InvalidShape);
+        }
+
+        // Each piece fills the slots up to
+        // the next start, which must be one
+        // for an edge and two for a corner.
+        let mut slots = [0; 12];
+        for (n, &start) in starts.iter().enumerate() {
+            let width = (starts[(n + 1) % starts.len()] + 12 - start - 1) % 12 + 1;
+            let piece = pieces.next().flatten().ok_or(FenError::InvalidPieces)?;
+            if seen[piece as usize] || width != if piece < 8 { 2 } else { 1 } {
+                return Err(FenError::InvalidPieces);
+            }
+            seen[piece as usize] = true;
+            for i in 0..width {
+                slots[(start + i) % 12] = piece;
+            }
+        }
+
+        Ok(slots)
+    };
+    let top = layer(top_mask)?;
+    let bottom = layer(bottom_mask)?;
+    if pieces.next().is_some() || seen.contains(&false) {
+        return Err(FenError::InvalidPieces);
+    }
+
+    let middle = match flags {
+        "m" | "M" => true,
+        "-" => false,
+        _ => return Err(FenError::InvalidFlags),
+    };
+
+    Ok(SqOne::from_slots(&top, &bottom, middle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn fen_round_trip() {
+        let solved = SqOne::new();
+        assert_eq!(to_fen(&solved), "b6db6d 08192a3b4c5d6e7f -");
+        assert_eq!(from_fen(&to_fen(&solved)), Ok(solved));
+
+        let mut cube = SqOne::new();
+        cube.try_twist(1, 0).unwrap();
+        cube.try_slash().unwrap();
+        cube.try_twist(-3, 2).unwrap();
+        let fen = to_fen(&cube);
+        assert!(fen.ends_with(" m"));
+        let parsed = from_fen(&fen.to_uppercase()).unwrap();
+        assert_eq!(parsed, cube);
+        assert_eq!(parsed.invariants(), Ok(()));
+
+        assert_eq!(from_fen("b6db6d"), Err(FenError::FieldCount(1)));
+        assert_eq!(from_fen("b6db6 0 -"), Err(FenError::InvalidShape));
+        let wrong = fen[..7].to_string() + "x" + &fen[8..];
+        assert_eq!(from_fen(&wrong), Err(FenError::InvalidPieces));
+        assert_eq!(from_fen(&fen.replace(" m", " x")), Err(FenError::InvalidFlags));
+    }
+}
@@ -0,0 +1,191 @@
+//! Module for fingerprinting scrambles,
+//! so organizers can publish the
+//! fingerprints before a competition and
+//! later show the scrambles used match.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::cube::SqOne;
+use crate::{Scramble, VerifyError};
+
+/// Number of hex digits in a
+/// fingerprint, the first 128 bits of
+/// the hash. Fingerprints were 64 bits
+/// before, and those no longer match.
+pub const FINGERPRINT_LEN: usize = 32;
+
+/// Version written at the start of the
+/// bytes that are hashed, so a change to
+/// the layout gives new fingerprints
+/// rather than clashing ones.
+const VERSION: u8 = 1;
+
+/// Returns the bytes hashed for the
+/// scramble. Each turn is written as
+/// its two amounts from -5 to 6, so a
+/// half turn written as 6 or -6 gives
+/// the same bytes, followed by the slots
+/// of the cube it produces.
+fn canonical(scramble: &Scramble, cube: &SqOne) -> Vec<u8> {
+    let mut bytes = vec![VERSION];
+    bytes.extend((scramble.len() as u32).to_be_bytes());
+    for &(top, bottom) in scramble {
+        for amount in [top, bottom] {
+            bytes.push(((amount + 5).rem_euclid(12) - 5) as u8);
+        }
+    }
+
+    let (top, bottom, middle) = cube.slots();
+    bytes.extend(top);
+    bytes.extend(bottom);
+    bytes.push(middle as u8);
+
+    bytes
+}
+
+/// Returns the fingerprint of the
+/// scramble and the cube it produces from
+/// solved, as the first FINGERPRINT_LEN
+/// hex digits of a SHA-256 hash.
+pub fn fingerprint(scramble: &Scramble) -> Result<String, VerifyError> {
+    let mut cube = SqOne::new();
+    cube.apply(scramble).map_err(VerifyError::IllegalSlash)?;
+
+    let mut hex = String::new();
+    for byte in &sha256(&canonical(scramble, &cube))[..FINGERPRINT_LEN / 2] {
+        write!(hex, "{:02x}", byte).unwrap();
+    }
+
+    Ok(hex)
+}
+
+/// Returns true if the fingerprint,
+/// in either case, is the fingerprint
+/// of the scramble.
+pub fn verify(scramble: &Scramble, fingerprint: &str) -> bool {
+    self::fingerprint(scramble).is_ok_and(|expected| expected.eq_ignore_ascii_case(fingerprint.trim()))
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// Returns the SHA-256 hash of the
+/// bytes, as in FIPS 180-4. It is
+/// written out here so fingerprints
+/// build without std or any hashing
+/// crate.
+pub(crate) fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+        0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    // Pad with a one bit, zeros and the
+    // length in bits to a multiple of
+    // 64 bytes.
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((bytes.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes(block[i * 4..i * 4 + 4].try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+
+    let mut hash = [0; 32];
+    for (i, value) in state.iter().enumerate() {
+        hash[i * 4..i * 4 + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| alloc::format!("{:02x}", byte)).collect()
+    }
+
+    // The first five are the examples of
+    // NIST for SHA-256, and the others
+    // fall either side of the length
+    // that needs a second padding block.
+    #[test]
+    fn hash_sha256() {
+        let cases: [(&[u8], &str); 8] = [
+            (b"", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+            (b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            (
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                "cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+            ),
+            (&[b'a'; 1_000_000], "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"),
+            (&[b'a'; 55], "9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318"),
+            (&[b'a'; 56], "b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"),
+            (&[b'a'; 64], "ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"),
+        ];
+        for (message, hash) in cases {
+            assert_eq!(hex(&sha256(message)), hash);
+        }
+    }
+
+    #[test]
+    fn fingerprint_scrambles() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 6), (2, 2)] };
+        let print = fingerprint(&scramble).unwrap();
+        assert_eq!(print, "49caf7d27e391573f0490a2c930c74b1");
+        assert!(verify(&Scramble { turns: vec![(1, 0), (-3, -6), (2, 2)] }, &print.to_uppercase()));
+        assert!(!verify(&Scramble { turns: vec![(1, 0), (-3, 6), (2, 1)] }, &print));
+
+        let illegal = Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] };
+        assert_eq!(fingerprint(&illegal), Err(VerifyError::IllegalSlash(2)));
+    }
+}
@@ -0,0 +1,121 @@
+//! Module for grading solutions sent in
+//! for a scramble, as in fewest moves
+//! challenges, where the moves have to
+//! solve the cube and are scored by how
+//! close they come to the best solution.
+
+use crate::cube::SqOne;
+use crate::{solver, Metric, Scramble, VerifyError};
+
+/// Result of grading a solution,
+/// from solution.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Grade {
+
+    /// The solution leaves the cube
+    /// solved.
+    pub solved: bool,
+
+    /// Turn of the solution after which
+    /// its slash is blocked, in which case
+    /// the moves after it are not made.
+    pub illegal_slash: Option<usize>,
+
+    /// Length of the solution in the
+    /// twist and slash metrics.
+    pub twists: usize,
+    pub slashes: usize,
+
+    /// Fewest moves known to solve the
+    /// scrambled cube in each metric. These
+    /// are from solver::distance, or the
+    /// solution itself if it solves the
+    /// cube in fewer.
+    pub best_twists: usize,
+    pub best_slashes: usize,
+}
+
+impl Grade {
+
+    /// Returns the length of the
+    /// solution in the metric.
+    pub fn len_in(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Twist => self.twists,
+            Metric::Slash => self.slashes,
+        }
+    }
+
+    /// Returns the fewest moves known to
+    /// solve the cube in the metric.
+    pub fn best_in(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Twist => self.best_twists,
+            Metric::Slash => self.best_slashes,
+        }
+    }
+
+    /// Returns the number of moves over
+    /// the best in the metric, or None if
+    /// the solution does not solve the cube.
+    pub fn excess(&self, metric: Metric) -> Option<usize> {
+        self.solved.then(|| self.len_in(metric) - self.best_in(metric))
+    }
+
+    /// Returns true if the solution solves
+    /// the cube in as few moves as the best
+    /// known in the metric.
+    pub fn is_best(&self, metric: Metric) -> bool {
+        self.excess(metric) == Some(0)
+    }
+}
+
+/// Grades the moves as a solution to
+/// the scramble applied to a solved
+/// cube, or returns an error if a slash
+/// of the scramble itself is blocked.
+pub fn solution(scramble: &Scramble, moves: &Scramble) -> Result<Grade, VerifyError> {
+    let mut cube = SqOne::new();
+    cube.apply(scramble).map_err(VerifyError::IllegalSlash)?;
+    let best_twists = solver::distance(&cube, Metric::Twist);
+    let best_slashes = solver::distance(&cube, Metric::Slash);
+
+    let illegal_slash = cube.apply(moves).err();
+    let solved = illegal_slash.is_none() && cube.is_solved();
+    let (twists, slashes) = (moves.len_in(Metric::Twist), moves.len_in(Metric::Slash));
+
+    Ok(Grade {
+        solved,
+        illegal_slash,
+        twists,
+        slashes,
+        best_twists: if solved { best_twists.min(twists) } else { best_twists },
+        best_slashes: if solved { best_slashes.min(slashes) } else { best_slashes },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grade_solutions() {
+        let scramble = Scramble { turns: vec![(1, 0), (-1, 0)] };
+        let grade = solution(&scramble, &scramble).unwrap();
+        assert!(grade.solved);
+        assert_eq!((grade.slashes, grade.best_slashes), (1, 1));
+        assert!(grade.is_best(Metric::Slash));
+
+        let long = Scramble { turns: vec![(1, 0), (-1, 0), (0, 0), (0, 0)] };
+        let grade = solution(&scramble, &long).unwrap();
+        assert_eq!(grade.excess(Metric::Slash), Some(2));
+
+        let blocked = Scramble { turns: vec![(1, 0), (-1, 0), (2, 0), (0, 0)] };
+        let grade = solution(&scramble, &blocked).unwrap();
+        assert_eq!(grade.illegal_slash, Some(2));
+        assert!(!grade.solved);
+        assert_eq!(grade.excess(Metric::Twist), None);
+
+        assert_eq!(solution(&blocked, &scramble), Err(VerifyError::IllegalSlash(2)));
+    }
+}
@@ -0,0 +1,204 @@
+//! Module for planning the scramble sets
+//! of a competition round from the number
+//! of competitors, groups and attempts,
+//! so each group gets its scrambles,
+//! extras, letter and seed in one go.
+
+use crate::export::{self, json_strings};
+use crate::{splitmix, BatchError, Scramble, ScrambleGroup, ScrambleOptions};
+
+/// Attempts of each competitor in
+/// an average of 5 round.
+pub const AVERAGE_ATTEMPTS: usize = 5;
+
+/// Shape of a round to be scrambled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct RoundSpec {
+    competitors: usize,
+    groups: usize,
+    attempts: usize,
+    extras: usize,
+}
+
+impl RoundSpec {
+
+    /// Creates a round of the competitors
+    /// split into the number of groups, at
+    /// least one, each making attempts.
+    /// Rounds of AVERAGE_ATTEMPTS get two
+    /// extras a group and shorter rounds
+    /// get one, as TNoodle does.
+    pub fn new(competitors: usize, groups: usize, attempts: usize) -> RoundSpec {
+        RoundSpec {
+            competitors,
+            groups: groups.max(1),
+            attempts,
+            extras: if attempts >= AVERAGE_ATTEMPTS { 2 } else { 1 },
+        }
+    }
+
+    /// Creates a round with as few groups
+    /// as keep each to at most max_size
+    /// competitors.
+    pub fn with_group_size(competitors: usize, max_size: usize, attempts: usize) -> RoundSpec {
+        RoundSpec::new(competitors, competitors.div_ceil(max_size.max(1)), attempts)
+    }
+
+    /// Sets the number of extra
+    /// scrambles of each group.
+    pub fn extras(mut self, extras: usize) -> RoundSpec {
+        self.extras = extras;
+        self
+    }
+
+    /// Returns the number of groups.
+    pub fn groups(&self) -> usize {
+        self.groups
+    }
+
+    /// Returns the number of competitors
+    /// in each group, split as evenly as
+    /// possible with earlier groups taking
+    /// any left over.
+    pub fn group_sizes(&self) -> Vec<usize> {
+        let (size, rest) = (self.competitors / self.groups, self.competitors % self.groups);
+        (0..self.groups).map(|i| size + (i < rest) as usize).collect()
+    }
+}
+
+/// Scrambles of one group with its
+/// letter, seed and size.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Group {
+    pub label: String,
+
+    /// Seed the scrambles were generated
+    /// from, so the group can be made again
+    /// on its own if its set is lost.
+    pub seed: u64,
+    pub competitors: usize,
+    pub set: ScrambleGroup,
+}
+
+/// Groups of a round in order.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Round {
+    pub groups: Vec<Group>,
+}
+
+/// Returns the letter of the group at
+/// the index, A to Z and then AA, AB
+/// and so on.
+pub fn label(index: usize) -> String {
+    let mut label = vec![];
+    let mut rest = index + 1;
+    while rest > 0 {
+        rest -= 1;
+        label.push(b'A' + (rest % 26) as u8);
+        rest /= 26;
+    }
+    label.reverse();
+
+    String::from_utf8(label).unwrap()
+}
+
+/// Returns the seed of the group at the
+/// index of a round planned with seed.
+pub fn group_seed(seed: u64, index: usize) -> u64 {
+    splitmix(seed.wrapping_add(index as u64))
+}
+
+/// Generates the scrambles of one group
+/// of the round from its seed, the same
+/// ones plan gives it.
+pub fn generate_group(spec: &RoundSpec, options: &ScrambleOptions, seed: u64) -> Result<ScrambleGroup, BatchError> {
+    let mut batch = Scramble::generate_batch_seeded(spec.attempts + spec.extras, options, seed)?;
+    let extras = batch.split_off(spec.attempts);
+
+    Ok(ScrambleGroup { scrambles: batch, extras })
+}
+
+/// Plans the round, generating the
+/// scrambles of each group from its
+/// own seed drawn from seed.
+pub fn plan(spec: &RoundSpec, options: &ScrambleOptions, seed: u64) -> Result<Round, BatchError> {
+    let groups = spec.group_sizes()
+        .into_iter()
+        .enumerate()
+        .map(|(i, competitors)| {
+            let seed = group_seed(seed, i);
+            Ok(Group { label: label(i), seed, competitors, set: generate_group(spec, options, seed)? })
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Round { groups })
+}
+
+impl Round {
+
+    /// Returns the scramble sets of
+    /// the groups in order.
+    pub fn sets(&self) -> Vec<ScrambleGroup> {
+        self.groups.iter().map(|group| group.set.clone()).collect()
+    }
+
+    /// Returns the round as JSON, with
+    /// the letter, seed and size of each
+    /// group beside its scrambles.
+    pub fn to_json(&self) -> String {
+        let groups: Vec<String> = self.groups
+            .iter()
+            .map(|group| {
+                format!(
+                    "{{\"label\":\"{}\",\"seed\":{},\"competitors\":{},\"scrambles\":{},\"extraScrambles\":{}}}",
+                    group.label,
+                    group.seed,
+                    group.competitors,
+                    json_strings(&group.set.scrambles),
+                    json_strings(&group.set.extras),
+                )
+            })
+            .collect();
+
+        format!("{{\"groups\":[{}]}}", groups.join(","))
+    }
+}
+
+/// Returns the rounds as a WCIF event,
+/// as export::wcif_event writes it.
+pub fn wcif_event(rounds: &[Round]) -> String {
+    let rounds: Vec<Vec<ScrambleGroup>> = rounds.iter().map(Round::sets).collect();
+    export::wcif_event(&rounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_labels() {
+        let labels: Vec<String> = [0, 1, 25, 26, 27, 701, 702].into_iter().map(label).collect();
+        assert_eq!(labels, ["A", "B", "Z", "AA", "AB", "ZZ", "AAA"]);
+    }
+
+    #[test]
+    fn plan_round() {
+        let spec = RoundSpec::with_group_size(40, 16, AVERAGE_ATTEMPTS);
+        assert_eq!(spec.groups(), 3);
+        assert_eq!(spec.group_sizes(), [14, 13, 13]);
+
+        let options = ScrambleOptions::new();
+        let round = plan(&spec, &options, 639).unwrap();
+        assert_eq!(Ok(round.clone()), plan(&spec, &options, 639));
+        assert_eq!(round.groups[2].label, "C");
+        assert!(round.groups.iter().all(|group| group.set.scrambles.len() == 5 && group.set.extras.len() == 2));
+        assert_eq!(Ok(round.groups[1].set.clone()), generate_group(&spec, &options, round.groups[1].seed));
+
+        let mean = plan(&RoundSpec::new(3, 0, 3), &options, 639).unwrap();
+        assert_eq!((mean.groups.len(), mean.groups[0].set.extras.len()), (1, 1));
+
+        let event = wcif_event(core::slice::from_ref(&round));
+        assert_eq!(export::parse_wcif(&event), Ok(vec![round.sets()]));
+        assert!(round.to_json().starts_with("{\"groups\":[{\"label\":\"A\","));
+    }
+}
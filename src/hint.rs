@@ -0,0 +1,65 @@
+//! Module for hints in simulators,
+//! suggesting the next few moves of
+//! a human style solution.
+
+use crate::algs::Alg;
+use crate::analysis::Phase;
+use crate::cube::SqOne;
+use crate::human;
+use crate::Move;
+
+/// Suggested move with the phase
+/// of the solution it belongs to.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Hint {
+    pub action: Move,
+    pub phase: Phase,
+
+    /// Algorithm the move is part of,
+    /// if any.
+    pub alg: Option<&'static Alg>,
+}
+
+/// Returns the first n moves of a human
+/// style solution of the cube, phase by
+/// phase as in human::solve. Returns
+/// fewer when the solution is shorter,
+/// and none when the cube is solved.
+pub fn next_moves(cube: &SqOne, n: usize) -> Vec<Hint> {
+    human::solve(cube).steps.iter()
+        .flat_map(|step| {
+            step.moves.moves().into_iter()
+                .map(|action| Hint { action, phase: step.phase, alg: step.alg })
+        })
+        .take(n)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn hint_moves() {
+        assert_eq!(next_moves(&SqOne::new(), 3), vec![]);
+
+        let mut rng = StdRng::seed_from_u64(597);
+        let mut cube = SqOne::new();
+        cube.scramble_rng(&mut rng);
+        let hints = next_moves(&cube, 4);
+        assert_eq!(hints.len(), 4);
+        assert_eq!(hints[0].phase, Phase::CubeShape);
+
+        // Following every hint solves the cube.
+        let hints = next_moves(&cube, usize::MAX);
+        for hint in &hints {
+            match hint.action {
+                Move::Twist(top, bottom) => cube.try_twist(top, bottom).unwrap(),
+                Move::Slash => cube.try_slash().unwrap(),
+            }
+        }
+        assert!(cube.is_solved());
+    }
+}
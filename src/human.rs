@@ -0,0 +1,150 @@
+//! Module for solving a Square One the
+//! way people do, one phase at a time
+//! with an algorithm from the database
+//! for each, so learners get a solution
+//! they can follow.
+
+use crate::algs::{self, Alg, AlgCase};
+use crate::analysis::Phase;
+use crate::cube::SqOne;
+use crate::solver;
+use crate::Scramble;
+
+/// Moves of one phase of a
+/// human style solution.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Step {
+    pub phase: Phase,
+
+    /// Algorithm the moves use, or None
+    /// when no algorithm fits and the
+    /// two-phase solver finishes the cube,
+    /// as for a flipped middle layer left
+    /// at the end.
+    pub alg: Option<&'static Alg>,
+
+    /// Moves of the step, starting with
+    /// the layer turn lining the cube up
+    /// for the algorithm.
+    pub moves: Scramble,
+
+    /// Whether the algorithm is done
+    /// with the cube held upside down.
+    pub upside_down: bool,
+}
+
+/// Solution made of phase steps,
+/// from solve.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct HumanSolution {
+    pub steps: Vec<Step>,
+}
+
+impl HumanSolution {
+
+    /// Returns the moves of every
+    /// step joined together.
+    pub fn scramble(&self) -> Scramble {
+        self.steps.iter().fold(Scramble { turns: vec![] }, |joined, step| joined.join(step.moves.clone()))
+    }
+}
+
+/// Returns the number of the first
+/// phase the cube has not finished,
+/// counting a cube solved apart from
+/// its middle layer as finished.
+fn progress(cube: &SqOne) -> usize {
+    match AlgCase::recognize(cube) {
+        Some(case) => Phase::all().iter().position(|&phase| phase == case.phase()).unwrap(),
+        None => Phase::all().len(),
+    }
+}
+
+/// Solves the cube phase by phase: cube
+/// shape, corner orientation, edge
+/// orientation, corner permutation and
+/// edge permutation with parity. Each
+/// step turns the layers and applies
+/// the algorithm for the case, which
+/// may finish later phases too.
+pub fn solve(cube: &SqOne) -> HumanSolution {
+    let mut cube = cube.clone();
+    let mut steps = vec![];
+    while let Some(case) = AlgCase::recognize(&cube) {
+        match next_step(&cube, case) {
+            Some(step) => {
+                cube.apply(&step.moves).unwrap();
+                steps.push(step);
+            },
+
+            // Without a fitting algorithm the
+            // solver finishes the cube.
+            None => {
+                let moves = solver::solve(&cube);
+                cube.apply(&moves).unwrap();
+                steps.push(Step { phase: case.phase(), alg: None, moves, upside_down: false });
+            },
+        }
+    }
+
+    if !cube.is_solved() {
+        let moves = solver::solve(&cube);
+        steps.push(Step { phase: Phase::EdgePermutation, alg: None, moves, upside_down: false });
+    }
+
+    HumanSolution { steps }
+}
+
+/// Returns the step for the case of
+/// the cube, trying every layer turn
+/// before the algorithm and, for shape
+/// algorithms, holding the cube upside
+/// down.
+fn next_step(cube: &SqOne, case: AlgCase) -> Option<Step> {
+    let before = progress(cube);
+    for top in -5..=6 {
+        for bottom in -5..=6 {
+            let mut turned = cube.clone();
+            turned.try_twist(top, bottom).unwrap();
+            let Some(alg) = algs::lookup(&turned) else {
+                continue;
+            };
+
+            let upside_down = [false, true];
+            let tries = if case.phase() == Phase::CubeShape { &upside_down[..] } else { &upside_down[..1] };
+            for &upside_down in tries {
+                let moves = if upside_down { alg.scramble().rotated() } else { alg.scramble() };
+                let moves = Scramble { turns: vec![(top, bottom)] }.join(moves);
+                let mut after = cube.clone();
+                if after.apply(&moves).is_ok() && progress(&after) > before {
+                    return Some(Step { phase: case.phase(), alg: Some(alg), moves, upside_down });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn solve_in_phases() {
+        let mut rng = StdRng::seed_from_u64(596);
+        for _ in 0..5 {
+            let mut cube = SqOne::new();
+            cube.scramble_rng(&mut rng);
+            let solution = solve(&cube);
+            let phases: Vec<Phase> = solution.steps.iter().map(|step| step.phase).collect();
+            assert!(phases.windows(2).all(|pair| pair[0] <= pair[1]));
+            assert!(solution.steps.iter().filter(|step| step.alg.is_none()).count() <= 1);
+
+            cube.apply(&solution.scramble()).unwrap();
+            assert!(cube.is_solved());
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Module for helpers that fit the
+//! crate into other services and
+//! devices.
+
+#[cfg(feature = "discord")]
+pub mod discord;
+pub mod smart_cube;
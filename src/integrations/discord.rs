@@ -0,0 +1,59 @@
+//! Module for posting scrambles from
+//! Discord bots, as a message with the
+//! scramble in a code block and the
+//! scrambled state as an attached image.
+
+use crate::cube::SqOne;
+use crate::render::Theme;
+use crate::{export, png, Scramble, VerifyError};
+
+/// Name the image is attached under.
+pub const IMAGE_NAME: &str = "scramble.png";
+
+/// Message for a Discord bot to send,
+/// with the image attached as a file
+/// named IMAGE_NAME.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Message {
+
+    /// Text of the message, with the
+    /// scramble in a code block so it is
+    /// shown in a monospaced font.
+    pub content: String,
+
+    /// Scrambled state as PNG bytes.
+    pub image: Vec<u8>,
+}
+
+/// Returns the scramble as a message,
+/// with the state drawn in the theme.
+/// Gives an error if a slash of the
+/// scramble is blocked.
+pub fn message(scramble: &Scramble, theme: &Theme) -> Result<Message, VerifyError> {
+    let mut cube = SqOne::new();
+    cube.apply(scramble).map_err(VerifyError::IllegalSlash)?;
+
+    Ok(Message { content: code_block(scramble), image: png::png(&cube, theme) })
+}
+
+/// Returns the scramble in
+/// a code block.
+pub fn code_block(scramble: &Scramble) -> String {
+    format!("```\n{}\n```", export::wca_string(scramble))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discord_message() {
+        let scramble = Scramble { turns: vec![(1, 0), (0, 3), (0, 0)] };
+        let message = message(&scramble, &Theme::new()).unwrap();
+        assert_eq!(message.content, format!("```\n{}\n```", export::wca_string(&scramble)));
+        assert!(message.image.starts_with(b"\x89PNG"));
+
+        let blocked = Scramble { turns: vec![(2, 0), (0, 0)] };
+        assert_eq!(super::message(&blocked, &Theme::new()).map(|_| ()), Err(VerifyError::IllegalSlash(0)));
+    }
+}
@@ -0,0 +1,157 @@
+//! Module for following the moves of a
+//! smart cube that reports each turn it
+//! senses, so timers can tell when the
+//! physical cube has been scrambled.
+
+use crate::cube::{Layer, SqOne, SqOneError};
+use crate::{Scramble, VerifyError};
+
+/// Move sensed by a smart cube.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PhysicalMove {
+
+    /// Turn of one layer by the amount in
+    /// twelfths of a turn, in the same
+    /// direction as the standard notation
+    /// for that layer. Devices that send
+    /// each step of a turn can send 1 or
+    /// -1 at a time.
+    Turn { layer: Layer, amount: i8 },
+
+    Slash,
+}
+
+/// Source of the moves of a smart cube,
+/// implemented over the Bluetooth or
+/// other link of a device.
+pub trait SmartCube {
+
+    /// Returns the next move sensed, or
+    /// None once the device disconnects.
+    fn next_move(&mut self) -> Option<PhysicalMove>;
+}
+
+impl<I: Iterator<Item = PhysicalMove>> SmartCube for I {
+    fn next_move(&mut self) -> Option<PhysicalMove> {
+        self.next()
+    }
+}
+
+/// Result of a move given to a
+/// ScrambleTracker.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TrackStatus {
+
+    /// The cube does not yet
+    /// match the scramble.
+    Scrambling,
+
+    /// The cube matches the
+    /// scrambled state.
+    Scrambled,
+}
+
+/// Follows the moves of a smart cube
+/// from solved, checking them against
+/// the state a scramble gives.
+#[derive(Clone, Debug)]
+pub struct ScrambleTracker {
+    cube: SqOne,
+    target: SqOne,
+}
+
+impl ScrambleTracker {
+
+    /// Creates a tracker for a solved
+    /// cube about to be scrambled. Gives
+    /// an error if a slash of the scramble
+    /// is blocked.
+    pub fn new(scramble: &Scramble) -> Result<ScrambleTracker, VerifyError> {
+        let mut target = SqOne::new();
+        target.apply(scramble).map_err(VerifyError::IllegalSlash)?;
+
+        Ok(ScrambleTracker { cube: SqOne::new(), target })
+    }
+
+    /// Returns the cube as the
+    /// device has been moved.
+    pub fn cube(&self) -> &SqOne {
+        &self.cube
+    }
+
+    /// Returns true if the cube
+    /// matches the scramble.
+    pub fn is_scrambled(&self) -> bool {
+        self.cube == self.target
+    }
+
+    /// Makes the move on the cube. Gives
+    /// an error for a slash the cube could
+    /// not make, which means the tracker
+    /// has lost track of the device.
+    pub fn push(&mut self, physical: PhysicalMove) -> Result<TrackStatus, SqOneError> {
+        match physical {
+            PhysicalMove::Turn { layer, amount } => {
+                let amount = (amount + 5).rem_euclid(12) - 5;
+                match layer {
+                    Layer::Top => self.cube.try_twist(amount, 0)?,
+                    Layer::Bottom => self.cube.try_twist(0, amount)?,
+                }
+            },
+            PhysicalMove::Slash => self.cube.try_slash()?,
+        }
+
+        Ok(if self.is_scrambled() { TrackStatus::Scrambled } else { TrackStatus::Scrambling })
+    }
+
+    /// Makes the moves of the device until
+    /// the cube matches the scramble or the
+    /// device disconnects, returning
+    /// whether the cube was scrambled.
+    pub fn follow(&mut self, device: &mut impl SmartCube) -> Result<bool, SqOneError> {
+        while !self.is_scrambled() {
+            match device.next_move() {
+                Some(physical) => {
+                    self.push(physical)?;
+                },
+                None => return Ok(false),
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Starts over from a solved cube,
+    /// as after the device is resynced.
+    pub fn reset(&mut self) {
+        self.cube = SqOne::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn track_smart_cube() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (0, 0)] };
+        let mut tracker = ScrambleTracker::new(&scramble).unwrap();
+        let top = |amount| PhysicalMove::Turn { layer: Layer::Top, amount };
+        let bottom = |amount| PhysicalMove::Turn { layer: Layer::Bottom, amount };
+        assert_eq!(tracker.push(top(1)), Ok(TrackStatus::Scrambling));
+
+        // The device sends the bottom
+        // turn one step at a time.
+        let mut moves: Vec<PhysicalMove> = vec![PhysicalMove::Slash, top(-3), bottom(1), bottom(1)];
+        moves.extend([PhysicalMove::Slash, top(2)]);
+        assert_eq!(tracker.follow(&mut moves.into_iter()), Ok(true));
+        assert!(tracker.is_scrambled());
+
+        tracker.reset();
+        assert_eq!(tracker.push(top(2)), Ok(TrackStatus::Scrambling));
+        assert_eq!(tracker.push(PhysicalMove::Slash), Err(SqOneError::SlashBlocked));
+        assert_eq!(tracker.follow(&mut core::iter::empty()), Ok(false));
+    }
+}
@@ -0,0 +1,322 @@
+//! Module for reading and writing the
+//! JSON of sessions, WCIF files and
+//! audit logs, which is read from
+//! files and requests that may not be
+//! well formed.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::CharIndices;
+
+/// Returns the text as a JSON string,
+/// escaping quotes, backslashes and
+/// control characters.
+pub(crate) fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+
+    json
+}
+
+/// JSON value read by Parser. Numbers
+/// are kept as text, since only whole
+/// numbers are read from them.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+
+    /// Returns the field of an object.
+    pub(crate) fn field(&self, name: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().find(|(key, _)| key == name).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// Most arrays and objects a value may
+/// be nested in. The files read hold a
+/// few levels, and the limit keeps text
+/// such as [[[[... from overflowing the
+/// stack of the reader.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// Reads the text as JSON, or returns
+/// the byte offset of the error.
+/// Values nested deeper than MAX_DEPTH
+/// are an error at their opening byte.
+pub(crate) fn parse_json(text: &str) -> Result<Json, usize> {
+    Parser { text, pos: 0, depth: 0 }.document()
+}
+
+/// Reader for the JSON of sessions,
+/// WCIF files and audit logs, following
+/// RFC 8259.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+
+    /// Reads a value that fills the
+    /// whole text.
+    fn document(mut self) -> Result<Json, usize> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.pos != self.text.len() {
+            return Err(self.error());
+        }
+
+        Ok(value)
+    }
+
+    fn error(&self) -> usize {
+        self.pos
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    /// Skips whitespace and then the
+    /// byte, or returns an error if the
+    /// next byte is different.
+    fn expect(&mut self, byte: u8) -> Result<(), usize> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error());
+        }
+        self.pos += 1;
+
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, usize> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        for (word, value) in [("null", Json::Null), ("true", Json::Bool(true)), ("false", Json::Bool(false))] {
+            if rest.starts_with(word) {
+                self.pos += word.len();
+                return Ok(value);
+            }
+        }
+
+        match self.peek() {
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'[') => {
+                let values = self.list(b']', |parser| parser.value())?;
+                Ok(Json::Array(values))
+            },
+            Some(b'{') => {
+                let fields = self.list(b'}', |parser| {
+                    parser.skip_whitespace();
+                    let key = parser.string()?;
+                    parser.expect(b':')?;
+                    Ok((key, parser.value()?))
+                })?;
+                Ok(Json::Object(fields))
+            },
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                Ok(Json::Number(self.text[start..self.pos].to_string()))
+            },
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Reads comma separated items up
+    /// to the closing byte, starting at
+    /// the opening byte, one level deeper
+    /// than the list.
+    fn list<T>(
+        &mut self,
+        close: u8,
+        mut item: impl FnMut(&mut Self) -> Result<T, usize>,
+    ) -> Result<Vec<T>, usize> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error());
+        }
+        self.depth += 1;
+        self.pos += 1;
+
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            self.depth -= 1;
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(byte) if byte == close => {
+                    self.pos += 1;
+                    self.depth -= 1;
+                    return Ok(items);
+                },
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// Reads a string, starting at
+    /// its opening quote.
+    fn string(&mut self) -> Result<String, usize> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error());
+        }
+        self.pos += 1;
+
+        let mut string = String::new();
+        let mut chars = self.text[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(string);
+                },
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => match code_unit(&mut chars) {
+                            // A high surrogate joins the
+                            // low one escaped after it
+                            // into one character.
+                            Some(high @ 0xd800..=0xdbff) => {
+                                let mut after = chars.clone();
+                                let low = match (after.next(), after.next()) {
+                                    (Some((_, '\\')), Some((_, 'u'))) => code_unit(&mut after),
+                                    _ => None,
+                                };
+                                match low {
+                                    Some(low @ 0xdc00..=0xdfff) => {
+                                        chars = after;
+                                        char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)).unwrap()
+                                    },
+                                    _ => char::REPLACEMENT_CHARACTER,
+                                }
+                            },
+
+                            // A surrogate on its own is
+                            // not a character.
+                            Some(unit) => char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER),
+                            None => {
+                                self.pos += i;
+                                return Err(self.error());
+                            },
+                        },
+                        _ => {
+                            self.pos += i;
+                            return Err(self.error());
+                        },
+                    };
+                    string.push(escaped);
+                },
+                c => string.push(c),
+            }
+        }
+        self.pos = self.text.len();
+
+        Err(self.error())
+    }
+}
+
+/// Reads the four hex digits of a \u
+/// escape as a UTF-16 code unit.
+fn code_unit(chars: &mut CharIndices<'_>) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+    if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn parse_values() {
+        let text = r#" {"a": [1, -2.5e3, "x\"\n"], "b": {"c": null, "d": [true, false]}} "#;
+        let value = parse_json(text).unwrap();
+        assert_eq!(
+            value.field("a"),
+            Some(&Json::Array(vec![
+                Json::Number("1".to_string()),
+                Json::Number("-2.5e3".to_string()),
+                Json::String("x\"\n".to_string()),
+            ])),
+        );
+        assert_eq!(value.field("b").and_then(|b| b.field("c")), Some(&Json::Null));
+        assert_eq!(parse_json(&json_string("tab\there \u{1}")), Ok(Json::String("tab\there \u{1}".to_string())));
+        assert_eq!(parse_json("[1,]"), Err(3));
+        assert_eq!(parse_json("[1] 2"), Err(4));
+    }
+
+    // Nesting past the limit is an error
+    // rather than a stack overflow.
+    #[test]
+    fn limit_depth() {
+        let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse_json(&nested(MAX_DEPTH)).is_ok());
+        assert_eq!(parse_json(&nested(MAX_DEPTH + 1)), Err(MAX_DEPTH));
+        assert_eq!(parse_json(&"[{\"a\":".repeat(100_000)), Err(6 * (MAX_DEPTH / 2)));
+        assert!(parse_json(&format!("[{},{}]", nested(MAX_DEPTH - 1), nested(MAX_DEPTH - 1))).is_ok());
+    }
+
+    // Characters outside the basic plane
+    // are escaped as surrogate pairs.
+    #[test]
+    fn decode_surrogates() {
+        let string = |text: &str| parse_json(text).map(|value| match value {
+            Json::String(string) => string,
+            _ => panic!("not a string"),
+        });
+        assert_eq!(string(r#""\ud83d\ude00 \u00e9""#), Ok("\u{1f600} \u{e9}".to_string()));
+        assert_eq!(string(r#""\ud83d""#), Ok("\u{fffd}".to_string()));
+        assert_eq!(string(r#""\ud83dx\ude00""#), Ok("\u{fffd}x\u{fffd}".to_string()));
+        assert_eq!(string(r#""\ud83d\n""#), Ok("\u{fffd}\n".to_string()));
+        assert_eq!(string(r#""\u12""#), Err(1));
+        assert_eq!(string(r#""\u+123""#), Err(1));
+    }
+}
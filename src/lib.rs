@@ -1,22 +1,1727 @@
 //! Main component for creating and
 //! interacting with Square-One
 //! cubes and scrambling them.
+//!
+//! Without the std feature the crate is
+//! no_std with alloc, keeping the cube
+//! model, moves, notation and seeded
+//! random move scrambles. The solver
+//! and render features, on by default,
+//! add the solver with everything built
+//! on it and drawing the cube, so a
+//! scramble only build can leave out
+//! the solver tables. The serde feature,
+//! which also builds without std, makes
+//! scrambles and moves serializable.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// Enters a tracing span with the name
+/// and fields until the end of the
+/// enclosing block, or does nothing
+/// without the tracing feature.
+#[cfg(feature = "solver")]
+macro_rules! trace_span {
+    ($name:literal $(, $($field:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name $(, $($field)*)?).entered();
+    };
+}
+
+/// Sends a tracing event with the name
+/// and a formatted message, or does
+/// nothing without the tracing feature.
+#[cfg(feature = "solver")]
+macro_rules! trace_event {
+    ($name:literal, $($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::info!(name: $name, $($arg)*);
+    };
+}
+
+#[cfg(feature = "solver")]
+pub mod algs;
+#[cfg(feature = "solver")]
+pub mod analysis;
+#[cfg(feature = "solver")]
+pub mod audit;
+pub mod cases;
+#[cfg(feature = "solver")]
+pub mod check;
+pub mod cstimer;
 pub mod cube;
+#[cfg(feature = "solver")]
+pub mod dataset;
+pub mod error;
+pub mod export;
+pub mod fen;
+pub mod fingerprint;
+#[cfg(feature = "solver")]
+pub mod grade;
+#[cfg(feature = "solver")]
+pub mod groups;
+#[cfg(feature = "solver")]
+pub mod hint;
+#[cfg(feature = "solver")]
+pub mod human;
+pub mod integrations;
+mod json;
+pub mod memo;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod notation;
+pub mod permutation;
+#[cfg(feature = "image")]
+pub mod png;
+pub mod policy;
+#[cfg(feature = "solver")]
+pub mod pool;
+#[cfg(feature = "std")]
+pub mod progress;
+pub mod puzzle;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "solver")]
+pub mod reconstruction;
+#[cfg(feature = "solver")]
+pub mod recent;
+pub mod recovery;
+#[cfg(feature = "solver")]
+pub mod relay;
+#[cfg(feature = "std")]
+pub mod rng;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "solver")]
+pub mod selftest;
+pub mod session;
+pub mod shape;
+#[cfg(feature = "sheets")]
+pub mod sheet;
+#[cfg(feature = "solver")]
+pub mod solver;
+#[cfg(feature = "solver")]
+pub mod square2;
+pub mod super_square;
+pub mod symmetry;
+pub mod testkit;
+#[cfg(feature = "std")]
+pub mod timer;
+#[cfg(feature = "tracing")]
+pub mod trace;
+#[cfg(feature = "solver")]
+pub mod trainer;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "solver")]
+pub mod walk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+use core::ops::{Bound, Index, RangeBounds};
+#[cfg(feature = "solver")]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "solver")]
+use std::collections::HashSet;
+
+use rand_chacha::ChaCha20Rng;
+
+/// The rand crate and its traits, so
+/// code passing its own generators to
+/// the crate uses the same version.
+pub use rand::{self, Rng, RngCore, SeedableRng};
+/// ChaCha generators, whose output is
+/// the same on every platform.
+pub use rand_chacha;
+
+#[cfg(feature = "solver")]
+use analysis::Grade;
+use cube::{Layer, PieceKind, SqOne};
+#[cfg(feature = "std")]
+use rng::RngChoice;
+#[cfg(feature = "solver")]
+use rng::ChosenRng;
+use notation::{ScrambleFormat, TrailingSlash};
+
+/// Ways of counting the length of
+/// a Square One move sequence.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Metric {
+
+    /// Each twist of the layers, (x, y)
+    /// with either amount non-zero, and
+    /// each slash count as one move.
+    Twist,
 
-use std::fmt;
+    /// Only slashes are counted.
+    Slash,
+}
+
+/// Single move of a Square One.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+
+    /// Twist of the top and bottom
+    /// layers in the standard notation.
+    Twist(i8, i8),
 
+    /// Slash of the right half.
+    Slash,
+}
+
+/// Sequence of turns of the top and
+/// bottom layers, with a slash between
+/// each pair of turns.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Vec<(i8, i8)>", into = "Vec<(i8, i8)>"))]
 pub struct Scramble {
     turns: Vec<(i8, i8)>,
 }
 
+/// Ways a generated scramble ends,
+/// which differ between communities.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SuffixPolicy {
+
+    /// The scramble ends however the
+    /// generator finished it.
+    #[default]
+    AsGenerated,
+
+    /// The scramble always ends with
+    /// a twist after the last slash,
+    /// never with a slash.
+    Twist,
+
+    /// The scramble always ends with a
+    /// slash, written as a bare slash by
+    /// the format from format.
+    Slash,
+
+    /// The scramble ends with the middle
+    /// layer solved, adding moves that
+    /// only turn the middle layer when
+    /// needed.
+    MiddleFixed,
+}
+
+impl SuffixPolicy {
+
+    /// Returns how a slash at the end
+    /// is written under this policy.
+    pub fn trailing_slash(&self) -> TrailingSlash {
+        match self {
+            SuffixPolicy::Slash => TrailingSlash::Bare,
+            _ => TrailingSlash::Turn,
+        }
+    }
+
+    /// Returns the Display layout with
+    /// the ending written for this policy.
+    pub fn format(&self) -> ScrambleFormat {
+        ScrambleFormat::new().trailing_slash(self.trailing_slash())
+    }
+}
+
+/// Options for generating a scramble
+/// with SqOne::scramble_with.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct ScrambleOptions {
+    parity: Option<bool>,
+    middle: Option<bool>,
+    random_state: bool,
+    min_slashes: usize,
+    suffix: SuffixPolicy,
+    layer: Option<Layer>,
+    pieces: Option<PieceKind>,
+    cube_shape: bool,
+    difficulty: Option<(usize, usize)>,
+    filter: Option<Filter>,
+    #[cfg(feature = "std")]
+    rng: RngChoice,
+}
+
+/// Fewest slashes a random state
+/// scramble must need to be solved
+/// under the WCA regulations.
+pub const WCA_MIN_SLASHES: usize = 11;
+
+/// Most slashes any state needs to
+/// be solved, the God's number of the
+/// puzzle in the slash metric.
+pub const MAX_SLASHES: usize = 13;
+
+/// Predicate of ScrambleOptions::filter.
+/// Options are compared by the address
+/// of their filter.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Filter(pub(crate) fn(&SqOne, &Scramble) -> bool);
+
+impl PartialEq for Filter {
+    fn eq(&self, other: &Filter) -> bool {
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
+
+impl Eq for Filter {}
+
+/// Scrambles rejected for missing any of
+/// the ScrambleOptions, such as parity,
+/// difficulty or the filter, before
+/// SqOne::scramble_with keeps the next
+/// one whether it meets them or not and
+/// SqOne::try_scramble_with returns an
+/// error. Also the repeated scrambles in
+/// a row before a batch of distinct ones
+/// gives up.
+pub const FILTER_TRIES: usize = 1000;
+
+/// Version of the way daily scrambles
+/// are made from a date. The scramble
+/// for a date only changes when this
+/// version changes.
+pub const DAILY_VERSION: u32 = 3;
+
+/// Returns the 64 bit FNV-1a hash of
+/// the bytes, which unlike the hashers
+/// in std is the same on every platform
+/// and version.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash
+}
+
+/// Returns the SplitMix64 mix of x,
+/// which spreads consecutive counters
+/// into unrelated seeds.
+#[cfg(feature = "solver")]
+pub(crate) fn splitmix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Maps each item with f, returning the
+/// results in the order of the items.
+/// With the parallel feature the items
+/// are shared across every core by rayon.
+#[cfg(feature = "solver")]
+pub(crate) fn map_batch<T: Sync, U: Send>(items: &[T], f: impl Fn(&T) -> U + Sync + Send) -> Vec<U> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+impl ScrambleOptions {
+
+    /// Returns options that place no
+    /// requirements on the scramble.
+    pub fn new() -> ScrambleOptions {
+        ScrambleOptions::default()
+    }
+
+    /// Requires the scrambled cube to
+    /// have parity or not. Parity is only
+    /// defined in cube shape, so the
+    /// scramble finishes in cube shape.
+    pub fn require_parity(mut self, parity: bool) -> ScrambleOptions {
+        self.parity = Some(parity);
+        self
+    }
+
+    /// Requires the middle layer of the
+    /// scrambled cube to be flipped or not.
+    /// A random state is picked with the
+    /// middle layer already set, and random
+    /// moves are followed by final moves
+    /// that only turn the middle layer.
+    pub fn middle_flipped(mut self, flipped: bool) -> ScrambleOptions {
+        self.middle = Some(flipped);
+        self
+    }
+
+    /// Picks a random state uniformly
+    /// and uses the inverse of its solution
+    /// as the scramble, instead of making
+    /// random moves. The scramble always
+    /// starts from a solved cube.
+    pub fn random_state(mut self, random_state: bool) -> ScrambleOptions {
+        self.random_state = random_state;
+        self
+    }
+
+    /// Requires every solution of the
+    /// scrambled cube to have at least this
+    /// many slashes, as proven by
+    /// solver::needs_slashes. The first
+    /// scramble generates the tables of the
+    /// optimal solver, and cubes close to
+    /// the limit take seconds to prove.
+    /// Returns an error if no cube needs
+    /// that many, or the target_difficulty
+    /// range ends below it.
+    pub fn min_slashes(mut self, slashes: usize) -> Result<ScrambleOptions, OptionsError> {
+        if slashes > MAX_SLASHES {
+            return Err(OptionsError::Unreachable(slashes));
+        }
+        if self.difficulty.is_some_and(|(_, most)| most < slashes) {
+            return Err(OptionsError::Conflict);
+        }
+        self.min_slashes = slashes;
+        Ok(self)
+    }
+
+    /// Scrambles only the pieces of one
+    /// layer, keeping the other layer
+    /// solved and the cube in cube shape.
+    /// The state is picked uniformly from
+    /// those cubes and the inverse of its
+    /// solution is the scramble.
+    pub fn only_layer(mut self, layer: Layer) -> ScrambleOptions {
+        self.layer = Some(layer);
+        self
+    }
+
+    /// Moves only pieces of this kind
+    /// when used with only_layer, as in
+    /// corner or edge permutation drills.
+    pub fn only_pieces(mut self, kind: PieceKind) -> ScrambleOptions {
+        self.pieces = Some(kind);
+        self
+    }
+
+    /// Keeps both layers in cube shape
+    /// after every slash of the scramble,
+    /// as PBL and permutation trainers
+    /// need. The state is picked uniformly
+    /// from cubes in cube shape and solved
+    /// with cube shape moves only. These
+    /// moves never change parity, so the
+    /// cube never has parity and
+    /// require_parity is ignored.
+    pub fn cube_shape(mut self, cube_shape: bool) -> ScrambleOptions {
+        self.cube_shape = cube_shape;
+        self
+    }
+
+    /// Requires the fewest slashes that
+    /// solve the scrambled cube to be in
+    /// the range, as proven by
+    /// solver::slashes_within, for easy
+    /// scrambles for beginners or hard ones
+    /// for practice. States are sampled as
+    /// with random_state, but when the
+    /// range ends below WCA_MIN_SLASHES,
+    /// which random states almost never
+    /// are, they are made by a random walk
+    /// of that many slashes from solved
+    /// instead, so are not picked
+    /// uniformly. Returns an error if no
+    /// cube is in the range, or it ends
+    /// below min_slashes.
+    pub fn target_difficulty(mut self, slashes: impl RangeBounds<usize>) -> Result<ScrambleOptions, OptionsError> {
+        let least = match slashes.start_bound() {
+            Bound::Included(&least) => least,
+            Bound::Excluded(&least) => least + 1,
+            Bound::Unbounded => 0,
+        };
+        let most = match slashes.end_bound() {
+            Bound::Included(&most) => most,
+            Bound::Excluded(&most) => most.saturating_sub(1),
+            Bound::Unbounded => usize::MAX,
+        };
+        if least > MAX_SLASHES {
+            return Err(OptionsError::Unreachable(least));
+        }
+        if least > most {
+            return Err(OptionsError::EmptyRange);
+        }
+        if most < self.min_slashes {
+            return Err(OptionsError::Conflict);
+        }
+        self.difficulty = Some((least, most));
+        Ok(self)
+    }
+
+    /// Requires the filter to accept the
+    /// scrambled cube and its scramble,
+    /// for requirements the other options
+    /// do not cover, such as no solved
+    /// layer. Scrambles are generated
+    /// again when it rejects them, up to
+    /// FILTER_TRIES rejections in all, so a
+    /// filter nothing passes cannot hang.
+    pub fn filter(mut self, filter: fn(&SqOne, &Scramble) -> bool) -> ScrambleOptions {
+        self.filter = Some(Filter(filter));
+        self
+    }
+
+    /// Sets how the scramble ends. A
+    /// slash at the end flips the middle
+    /// layer, which middle_flipped
+    /// takes into account.
+    pub fn suffix(mut self, suffix: SuffixPolicy) -> ScrambleOptions {
+        self.suffix = suffix;
+        self
+    }
+
+    /// Sets the random number generator
+    /// used by SqOne::scramble_with. With a
+    /// seeded generator each call gives the
+    /// same scramble.
+    #[cfg(feature = "std")]
+    pub fn rng(mut self, rng: RngChoice) -> ScrambleOptions {
+        self.rng = rng;
+        self
+    }
+
+    /// Returns a new generator of the
+    /// chosen kind.
+    #[cfg(feature = "solver")]
+    pub(crate) fn build_rng(&self) -> ChosenRng {
+        self.rng.build()
+    }
+
+    /// Returns the middle layer the
+    /// scramble must leave, if any.
+    #[cfg(feature = "solver")]
+    pub(crate) fn wanted_middle(&self) -> Option<bool> {
+        match self.suffix {
+            SuffixPolicy::MiddleFixed => self.middle.or(Some(false)),
+            _ => self.middle,
+        }
+    }
+
+    /// Returns the options for random
+    /// state scrambles that need at least
+    /// WCA_MIN_SLASHES slashes.
+    pub fn wca() -> ScrambleOptions {
+        ScrambleOptions {
+            min_slashes: WCA_MIN_SLASHES,
+            ..ScrambleOptions::new().random_state(true)
+        }
+    }
+}
+
+/// Scrambles for one group of a
+/// competition round, with extras to
+/// use if a scramble is misapplied.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ScrambleGroup {
+    pub scrambles: Vec<Scramble>,
+    pub extras: Vec<Scramble>,
+}
+
+/// Endless iterator of scrambles from
+/// a solved cube, owning its rng so a
+/// seed always gives the same scrambles.
+#[cfg(feature = "solver")]
+pub struct ScrambleStream {
+    options: ScrambleOptions,
+    rng: ChaCha20Rng,
+    cube: SqOne,
+}
+
+#[cfg(feature = "solver")]
+impl ScrambleStream {
+
+    /// Creates a stream of scrambles
+    /// with the options, seeded by seed.
+    pub fn new(options: ScrambleOptions, seed: u64) -> ScrambleStream {
+        ScrambleStream {
+            options,
+            rng: ChaCha20Rng::seed_from_u64(seed),
+            cube: SqOne::new(),
+        }
+    }
+
+    /// Returns the cube produced by the
+    /// last scramble.
+    pub fn cube(&self) -> &SqOne {
+        &self.cube
+    }
+}
+
+#[cfg(feature = "solver")]
+impl Iterator for ScrambleStream {
+    type Item = Scramble;
+
+    fn next(&mut self) -> Option<Scramble> {
+        self.cube = SqOne::new();
+        Some(self.cube.scramble_with_rng(&self.options, &mut self.rng))
+    }
+}
+
+/// Scramble source shared between
+/// threads without a lock. Each call
+/// takes the next seed from an atomic
+/// counter and scrambles with its own
+/// rng, so a seed still always gives
+/// the same scrambles when called from
+/// one thread.
+#[cfg(feature = "solver")]
+pub struct ScrambleGenerator {
+    options: ScrambleOptions,
+    seed: u64,
+    counter: AtomicU64,
+}
+
+#[cfg(feature = "solver")]
+impl ScrambleGenerator {
+
+    /// Creates a generator of scrambles
+    /// with the options, seeded by seed.
+    pub fn new(options: ScrambleOptions, seed: u64) -> ScrambleGenerator {
+        ScrambleGenerator {
+            options,
+            seed,
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a generator seeded once
+    /// from the thread rng.
+    pub fn from_entropy(options: ScrambleOptions) -> ScrambleGenerator {
+        ScrambleGenerator::new(options, rand::thread_rng().gen())
+    }
+
+    /// Returns the options every
+    /// scramble is generated with.
+    pub fn options(&self) -> &ScrambleOptions {
+        &self.options
+    }
+
+    /// Generates the next scramble
+    /// from a solved cube.
+    pub fn generate(&self) -> Scramble {
+        self.generate_with_cube().0
+    }
+
+    /// Generates the next scramble and
+    /// returns it with the cube it makes.
+    pub fn generate_with_cube(&self) -> (Scramble, SqOne) {
+        let index = self.counter.fetch_add(1, Ordering::Relaxed);
+        let mut rng = ChaCha20Rng::seed_from_u64(splitmix(self.seed.wrapping_add(index)));
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble_with_rng(&self.options, &mut rng);
+        (scramble, cube)
+    }
+
+    /// Generates the next count
+    /// scrambles.
+    pub fn generate_batch(&self, count: usize) -> Vec<Scramble> {
+        (0..count).map(|_| self.generate()).collect()
+    }
+}
+
+/// Iterator over the cube after each
+/// move of a scramble, created by
+/// Scramble::replay.
+pub struct Replay {
+    moves: vec::IntoIter<Move>,
+    cube: SqOne,
+}
+
+impl Iterator for Replay {
+    type Item = (Move, SqOne);
+
+    fn next(&mut self) -> Option<(Move, SqOne)> {
+        let next = self.moves.next()?;
+
+        // Stop early if a slash is blocked
+        // rather than skipping over it.
+        if self.cube.apply_move(next).is_err() {
+            self.moves = vec![].into_iter();
+            return None;
+        }
+
+        Some((next, self.cube.clone()))
+    }
+}
+
+/// How closely two scrambles match,
+/// from Scramble::equivalence.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Equivalence {
+
+    /// The turns are the same.
+    Identical,
+
+    /// The turns are the same once
+    /// both are simplified.
+    Simplified,
+
+    /// The turns differ, but both
+    /// produce the same cube.
+    SameState,
+
+    /// The cubes differ, or either
+    /// scramble has a blocked slash.
+    Different,
+}
+
+/// Reasons a scramble does not
+/// produce the expected cube.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum VerifyError {
+
+    /// The slash after the turn at
+    /// this index is blocked by a
+    /// corner.
+    IllegalSlash(usize),
+
+    /// Every move could be applied,
+    /// but the resulting cube is
+    /// different. Holds the index in
+    /// Scramble::moves of the first move
+    /// after which the replay differs from
+    /// the history of the expected cube,
+    /// or None if it keeps no history.
+    Mismatch(Option<usize>),
+}
+
+/// Reasons a list of turns cannot
+/// be made into a scramble.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ScrambleError {
+
+    /// The list holds no turns.
+    Empty,
+
+    /// The turn at this index moves a
+    /// layer by more than a half turn.
+    TurnOutOfRange(usize),
+
+    /// The slash after the turn at
+    /// this index is blocked by a
+    /// corner when the turns are applied
+    /// to a solved cube.
+    IllegalSlash(usize),
+}
+
+impl fmt::Display for ScrambleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScrambleError::Empty => write!(f, "scramble has no turns"),
+            ScrambleError::TurnOutOfRange(i) => write!(f, "turn {} is out of range", i),
+            ScrambleError::IllegalSlash(i) => write!(f, "slash after turn {} is blocked", i),
+        }
+    }
+}
+
+impl Error for ScrambleError {}
+
+/// Error of a batch of distinct
+/// scrambles that could not be filled,
+/// as when the options allow fewer cubes
+/// than the batch asks for.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct BatchError {
+    pub requested: usize,
+    pub found: usize,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "found only {} of {} distinct scrambles", self.found, self.requested)
+    }
+}
+
+impl Error for BatchError {}
+
+/// Reasons no scramble can be made
+/// with a set of ScrambleOptions.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum OptionsError {
+
+    /// FILTER_TRIES scrambles were
+    /// rejected without one meeting
+    /// the options.
+    Exhausted,
+
+    /// No cube needs this many slashes
+    /// or more, as it is above
+    /// MAX_SLASHES.
+    Unreachable(usize),
+
+    /// The difficulty range is empty.
+    EmptyRange,
+
+    /// The difficulty range ends below
+    /// the minimum slashes.
+    Conflict,
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::Exhausted => write!(f, "no scramble met the options after {} rejections", FILTER_TRIES),
+            OptionsError::Unreachable(slashes) => write!(f, "no cube needs {} slashes, the most is {}", slashes, MAX_SLASHES),
+            OptionsError::EmptyRange => write!(f, "difficulty range is empty"),
+            OptionsError::Conflict => write!(f, "difficulty range ends below the minimum slashes"),
+        }
+    }
+}
+
+impl Error for OptionsError {}
+
+/// First slash of a scramble that is
+/// blocked by a corner.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct IllegalAt {
+
+    /// Index of the turn before
+    /// the slash.
+    pub turn: usize,
+
+    /// Index of the slash in the
+    /// list from Scramble::moves.
+    pub position: usize,
+}
+
+impl fmt::Display for IllegalAt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "slash after turn {} (move {}) is blocked by a corner", self.turn, self.position)
+    }
+}
+
+impl Error for IllegalAt {}
+
+impl Scramble {
+
+    /// Creates a scramble from turns
+    /// with a slash between each, checking
+    /// that every amount is from -6 to 6
+    /// and that every slash can be made
+    /// from a solved cube. A half turn of
+    /// -6 is kept as 6.
+    pub fn new(turns: Vec<(i8, i8)>) -> Result<Scramble, ScrambleError> {
+        if turns.is_empty() {
+            return Err(ScrambleError::Empty);
+        }
+        let turns = turns
+            .into_iter()
+            .enumerate()
+            .map(|(i, turn)| normalize_turn(turn).ok_or(ScrambleError::TurnOutOfRange(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let scramble = Scramble { turns };
+        SqOne::new().apply(&scramble).map_err(ScrambleError::IllegalSlash)?;
+
+        Ok(scramble)
+    }
+
+    /// Generates count scrambles from a
+    /// solved cube with the options, where
+    /// no two scrambles produce the same
+    /// cube.
+    #[cfg(feature = "solver")]
+    pub fn generate_batch(count: usize, options: &ScrambleOptions) -> Result<Vec<Scramble>, BatchError> {
+        Scramble::generate_batch_seeded(count, options, rand::thread_rng().gen())
+    }
+
+    /// Generates a batch like generate_batch,
+    /// giving each scramble its own rng
+    /// seeded from the master seed, so the
+    /// same seed always gives the same batch.
+    /// With the parallel feature, the batch
+    /// is split across every core. Gives up
+    /// after FILTER_TRIES scrambles in a row
+    /// produce cubes already in the batch,
+    /// so options allowing too few cubes
+    /// cannot hang.
+    #[cfg(feature = "solver")]
+    pub fn generate_batch_seeded(count: usize, options: &ScrambleOptions, seed: u64) -> Result<Vec<Scramble>, BatchError> {
+        let mut master = ChaCha20Rng::seed_from_u64(seed);
+        let mut seen = HashSet::new();
+        let mut batch = Vec::with_capacity(count);
+        let mut repeats = 0;
+        while batch.len() < count {
+            let seeds: Vec<u64> = (batch.len()..count).map(|_| master.gen()).collect();
+            for (cube, scramble) in map_batch(&seeds, |&seed| generate_one(seed, options)) {
+                if seen.insert(cube) {
+                    batch.push(scramble);
+                    repeats = 0;
+                } else {
+                    repeats += 1;
+                }
+            }
+            if repeats >= FILTER_TRIES {
+                return Err(BatchError { requested: count, found: batch.len() });
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Generates groups of scrambles for a
+    /// competition round, each with the
+    /// given number of scrambles and extras.
+    /// No two scrambles in any group produce
+    /// the same cube.
+    #[cfg(feature = "solver")]
+    pub fn generate_groups(
+        groups: usize,
+        scrambles: usize,
+        extras: usize,
+        options: &ScrambleOptions,
+    ) -> Result<Vec<ScrambleGroup>, BatchError> {
+        let mut batch = Scramble::generate_batch(groups * (scrambles + extras), options)?.into_iter();
+        Ok((0..groups)
+            .map(|_| ScrambleGroup {
+                scrambles: batch.by_ref().take(scrambles).collect(),
+                extras: batch.by_ref().take(extras).collect(),
+            })
+            .collect())
+    }
+
+    /// Returns the daily scramble for the
+    /// date, so everyone scrambling on the
+    /// same day gets the same scramble. The
+    /// date should be written as YYYY-MM-DD.
+    /// The seed is a hash of the date and
+    /// DAILY_VERSION, seeding ChaCha20,
+    /// which unlike StdRng gives the same
+    /// numbers on every platform and
+    /// version of rand.
+    pub fn daily(date: &str) -> Scramble {
+        let key = format!("sq1-daily-v{}:{}", DAILY_VERSION, date.trim());
+        let mut rng = ChaCha20Rng::seed_from_u64(fnv1a(key.as_bytes()));
+        SqOne::new().scramble_rng(&mut rng)
+    }
+
+    /// Returns a scramble turning the cube
+    /// from into the cube to, made of a
+    /// solution of from followed by the
+    /// inverse of a solution of to. The
+    /// result is not the shortest, but
+    /// moves that cancel where the two
+    /// meet are removed.
+    #[cfg(feature = "solver")]
+    pub fn between(from: &SqOne, to: &SqOne) -> Scramble {
+        solver::solve(from).join(solver::solve(to).inverse())
+    }
+
+    /// Returns the scramble followed by
+    /// the other, merging the last turn
+    /// with the first turn of the other.
+    #[cfg(feature = "solver")]
+    pub(crate) fn join(self, other: Scramble) -> Scramble {
+        if self.is_empty() {
+            return other;
+        } else if other.is_empty() {
+            return self;
+        }
+
+        let mut turns = self.turns;
+        let mut rest = other.turns.into_iter();
+        let add = |a: i8, b: i8| (a + b + 5).rem_euclid(12) - 5;
+
+        // A (0, 0) twist between two slashes
+        // leaves them cancelling each other.
+        let (top, bottom) = rest.next().unwrap();
+        let last = turns.pop().unwrap();
+        let mut joined = (add(last.0, top), add(last.1, bottom));
+        while joined == (0, 0) && !turns.is_empty() {
+            match rest.next() {
+                Some((top, bottom)) => {
+                    let last = turns.pop().unwrap();
+                    joined = (add(last.0, top), add(last.1, bottom));
+                },
+                None => break,
+            }
+        }
+        turns.push(joined);
+        turns.extend(rest);
+
+        Scramble { turns }
+    }
+
+    /// Returns the scramble with every
+    /// amount from -5 to 6, and with each
+    /// pair of slashes around a (0, 0)
+    /// turn removed, merging the turns on
+    /// either side. The result produces
+    /// the same cube.
+    pub fn simplified(&self) -> Scramble {
+        let add = |a: i8, b: i8| (a + b + 5).rem_euclid(12) - 5;
+        let mut turns: Vec<(i8, i8)> = Vec::with_capacity(self.turns.len());
+        for &(top, bottom) in &self.turns {
+            if turns.len() > 1 && turns.last() == Some(&(0, 0)) {
+                turns.pop();
+                let last = turns.last_mut().unwrap();
+                *last = (add(last.0, top), add(last.1, bottom));
+            } else {
+                turns.push((add(top, 0), add(bottom, 0)));
+            }
+        }
+
+        Scramble { turns }
+    }
+
+    /// Returns the scramble in the form of
+    /// official scrambles, so scrambles from
+    /// any source compare and print the
+    /// same: simplified, then ending with
+    /// the middle layer solved, adding moves
+    /// that only turn the middle layer when
+    /// the scramble leaves it flipped.
+    /// Returns an error if a slash of the
+    /// scramble is blocked.
+    #[cfg(feature = "solver")]
+    pub fn normalize(&self) -> Result<Scramble, VerifyError> {
+        let simplified = self.simplified();
+        let mut cube = SqOne::new();
+        cube.apply(&simplified).map_err(VerifyError::IllegalSlash)?;
+        if !cube.middle_flipped() {
+            return Ok(simplified);
+        }
+
+        let (top, bottom, middle) = cube.slots();
+        let fix = Scramble::between(&cube, &SqOne::from_slots(&top, &bottom, !middle));
+        Ok(simplified.join(fix).simplified())
+    }
+
+    /// Returns the scramble mirrored left
+    /// to right, which produces the mirror
+    /// of its cube as SqOne::mirror gives.
+    pub fn mirrored(&self) -> Scramble {
+        self.reflected(|(top, bottom)| (-top, -bottom))
+    }
+
+    /// Returns the scramble for the cube
+    /// held upside down, which produces
+    /// the cube SqOne::rotate_z2 gives.
+    pub fn rotated(&self) -> Scramble {
+        self.reflected(|(top, bottom)| (bottom, top))
+    }
+
+    /// Maps each turn, then adds the twist
+    /// a reflection makes to a solved cube
+    /// before the turns and undoes it
+    /// after them.
+    fn reflected(&self, map: fn((i8, i8)) -> (i8, i8)) -> Scramble {
+        let add = |a: i8, b: i8| (a + b + 5).rem_euclid(12) - 5;
+        let (top, bottom) = symmetry::REFLECTION_TWIST;
+        let mut turns: Vec<(i8, i8)> = self.turns
+            .iter()
+            .map(|&turn| map(turn))
+            .map(|(top, bottom)| (add(top, 0), add(bottom, 0)))
+            .collect();
+
+        // A reflected slash swaps the other
+        // halves of the layers, which is a
+        // slash between two half turns. The
+        // half turns between slashes cancel.
+        let half = if turns.len() > 1 { 6 } else { 0 };
+        if let Some(first) = turns.first_mut() {
+            *first = (add(first.0, top + half), add(first.1, bottom + half));
+        }
+        if let Some(last) = turns.last_mut() {
+            *last = (add(last.0, half - top), add(last.1, half - bottom));
+        }
+
+        Scramble { turns }
+    }
+
+    /// Returns whether both scrambles
+    /// produce the same cube from a
+    /// solved cube.
+    pub fn equivalent(&self, other: &Scramble) -> bool {
+        self.equivalence(other) != Equivalence::Different
+    }
+
+    /// Returns how closely the scramble
+    /// matches the other, checking the
+    /// turns before applying both.
+    pub fn equivalence(&self, other: &Scramble) -> Equivalence {
+        if self == other {
+            return Equivalence::Identical;
+        }
+        if self.simplified() == other.simplified() {
+            return Equivalence::Simplified;
+        }
+
+        let (mut cube, mut other_cube) = (SqOne::new(), SqOne::new());
+        if cube.apply(self).is_ok() && other_cube.apply(other).is_ok() && cube.slots() == other_cube.slots() {
+            Equivalence::SameState
+        } else {
+            Equivalence::Different
+        }
+    }
+
+    /// Returns an iterator over the
+    /// turns, with a slash between each.
+    pub fn iter(&self) -> core::slice::Iter<'_, (i8, i8)> {
+        self.turns.iter()
+    }
+
+    /// Returns the number of turns,
+    /// which is one more than the number
+    /// of slashes.
+    pub fn len(&self) -> usize {
+        self.turns.len()
+    }
+
+    /// Returns true if the scramble
+    /// has no turns.
+    pub fn is_empty(&self) -> bool {
+        self.turns.is_empty()
+    }
+
+    /// Adds a turn to the end of the
+    /// scramble, after a slash unless
+    /// the scramble is empty. The turn is
+    /// checked as in new, and the scramble
+    /// is left as it was on an error.
+    pub fn push(&mut self, turn: (i8, i8)) -> Result<(), ScrambleError> {
+        let index = self.turns.len();
+        let turn = normalize_turn(turn).ok_or(ScrambleError::TurnOutOfRange(index))?;
+        if index > 0 {
+            let mut cube = SqOne::new();
+            cube.apply(self).map_err(ScrambleError::IllegalSlash)?;
+            if !cube.can_flip() {
+                return Err(ScrambleError::IllegalSlash(index - 1));
+            }
+        }
+        self.turns.push(turn);
+
+        Ok(())
+    }
+
+    /// Keeps only the first len turns
+    /// and the slashes between them.
+    pub fn truncate(&mut self, len: usize) {
+        self.turns.truncate(len);
+    }
+
+    /// Returns the scramble written
+    /// in the layout.
+    pub fn format_with(&self, format: &ScrambleFormat) -> String {
+        format.format(self)
+    }
+
+    /// Writes the scramble in the layout
+    /// used by Display straight into out.
+    pub fn write_to<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        ScrambleFormat::new().write_to(self, out)
+    }
+
+    /// Returns a short hash of the
+    /// scramble and the cube it produces,
+    /// which stays the same across versions
+    /// of the crate.
+    pub fn fingerprint(&self) -> Result<String, VerifyError> {
+        fingerprint::fingerprint(self)
+    }
+
+    /// Returns the scramble with a turn
+    /// and its slash on each line, followed
+    /// by the shapes of the layers after
+    /// the slash, as in (3,0)/ # Kite-Scallop.
+    /// The last line shows the final shape.
+    pub fn annotated(&self) -> Result<String, VerifyError> {
+        let mut cube = SqOne::new();
+        let mut lines = vec![];
+        for (i, &(top, bottom)) in self.turns.iter().enumerate() {
+
+            // Every turn but the last is
+            // followed by its slash.
+            let (turns, slash) = if i + 1 < self.turns.len() {
+                (vec![(top, bottom), (0, 0)], "/")
+            } else {
+                (vec![(top, bottom)], "")
+            };
+            cube.apply(&Scramble { turns }).map_err(|_| VerifyError::IllegalSlash(i))?;
+            lines.push(format!("({},{}){} # {}", top, bottom, slash, shape::classify(&cube)));
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Returns the number of moves in
+    /// the scramble counted in the metric.
+    pub fn len_in(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Twist => self.moves().len(),
+            Metric::Slash => self.turns.len().saturating_sub(1),
+        }
+    }
+
+    /// Returns the moves of the scramble,
+    /// leaving out twists of (0, 0).
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        for (i, &(top, bottom)) in self.turns.iter().enumerate() {
+            if i > 0 {
+                moves.push(Move::Slash);
+            }
+            if (top, bottom) != (0, 0) {
+                moves.push(Move::Twist(top, bottom));
+            }
+        }
+
+        moves
+    }
+
+    /// Returns an iterator over each move
+    /// of the scramble applied to the start
+    /// cube, with the cube after the move.
+    /// The iterator ends early if a slash
+    /// is blocked.
+    pub fn replay(&self, start: &SqOne) -> Replay {
+        Replay {
+            moves: self.moves().into_iter(),
+            cube: start.clone(),
+        }
+    }
+
+    /// Returns the difficulty measures of
+    /// the cube the scramble produces from
+    /// a solved cube.
+    #[cfg(feature = "solver")]
+    pub fn stats(&self) -> Result<Grade, VerifyError> {
+        let mut cube = SqOne::new();
+        cube.apply(self).map_err(VerifyError::IllegalSlash)?;
+
+        Ok(analysis::grade(&cube))
+    }
+
+    /// Returns a link to the scramble on
+    /// Twizzle, the successor of
+    /// alg.cubing.net that supports the
+    /// Square One, which animates the
+    /// scramble from a solved cube.
+    pub fn to_alg_url(&self) -> String {
+        format!(
+            "https://alpha.twizzle.net/edit/?puzzle=square1&alg={}",
+            export::encode_component(&self.to_string()),
+        )
+    }
+
+    /// Returns the scramble that undoes
+    /// this one, with the turns reversed
+    /// and each twist inverted.
+    pub fn inverse(&self) -> Scramble {
+        let turns = self.turns
+            .iter()
+            .rev()
+            .map(|&(top, bottom)| ((5 - top).rem_euclid(12) - 5, (5 - bottom).rem_euclid(12) - 5))
+            .collect();
+
+        Scramble { turns }
+    }
+
+    /// Checks that every slash of the
+    /// scramble can be made when starting
+    /// from the cube, returning where the
+    /// first blocked slash is if not.
+    pub fn is_legal_from(&self, cube: &SqOne) -> Result<(), IllegalAt> {
+        let turn = match cube.clone().apply(self) {
+            Ok(()) => return Ok(()),
+            Err(turn) => turn,
+        };
+
+        // Each turn up to the slash is a
+        // move unless it is (0, 0), and
+        // each earlier slash is a move.
+        let twists = self.turns[..=turn].iter().filter(|&&turn| turn != (0, 0)).count();
+
+        Err(IllegalAt { turn, position: twists + turn })
+    }
+
+    /// Applies the scramble to a solved
+    /// cube and checks that it produces
+    /// the expected cube.
+    pub fn verify(&self, expected: &SqOne) -> Result<(), VerifyError> {
+        let mut cube = SqOne::new();
+        cube.apply(self).map_err(VerifyError::IllegalSlash)?;
+        if cube.slots() == expected.slots() {
+            return Ok(());
+        }
+
+        // The expected cube is replayed from
+        // solved through its history beside
+        // the scramble, up to the first move
+        // after which the two differ. Without
+        // a history the move is not known.
+        let history = expected.history();
+        if history.is_empty() {
+            return Err(VerifyError::Mismatch(None));
+        }
+        let mut reference = SqOne::new();
+        let diverged = self
+            .replay(&SqOne::new())
+            .zip(history)
+            .position(|((_, cube), &next)| reference.apply_move(next).is_err() || cube.slots() != reference.slots())
+            .unwrap_or_else(|| self.moves().len().min(history.len()));
+
+        Err(VerifyError::Mismatch(Some(diverged)))
+    }
+
+    /// Returns a scramble giving the same
+    /// cube with as few slashes as the
+    /// solver finds, stopping once it has
+    /// target slashes or fewer. The search
+    /// tries the two-phase solver, then
+    /// other two-phase solutions, then an
+    /// optimal search for SHORTEN_LIMIT.
+    /// Returns this scramble if none is
+    /// shorter, or an error if a slash
+    /// of it is blocked.
+    #[cfg(feature = "solver")]
+    pub fn shorten(&self, target: usize) -> Result<Scramble, VerifyError> {
+        let mut cube = SqOne::new();
+        cube.apply(self).map_err(VerifyError::IllegalSlash)?;
+
+        let mut best = self.clone();
+        let mut keep = |candidate: Scramble| {
+            if candidate.len_in(Metric::Slash) < best.len_in(Metric::Slash) {
+                best = candidate;
+            }
+            best.len_in(Metric::Slash) <= target
+        };
+        let done = keep(solver::solve(&cube).inverse())
+            || solver::solve_n(&cube, SHORTEN_CANDIDATES).into_iter().any(|solution| keep(solution.inverse()));
+        if !done {
+            let cancel = progress::CancelToken::with_timeout(SHORTEN_LIMIT);
+            if let Some(solution) = solver::solve_until(&cube, Metric::Slash, &cancel) {
+                keep(solution.inverse());
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+/// Two-phase solutions Scramble::shorten
+/// compares.
+#[cfg(feature = "solver")]
+const SHORTEN_CANDIDATES: usize = 8;
+
+/// Longest time Scramble::shorten
+/// spends on an optimal search.
+#[cfg(feature = "solver")]
+pub const SHORTEN_LIMIT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Slots of a scrambled cube with the
+/// scramble that produces it.
+#[cfg(feature = "solver")]
+type Generated = (([u8; 12], [u8; 12], bool), Scramble);
+
+/// Generates a scramble from a solved
+/// cube with an rng seeded by seed.
+#[cfg(feature = "solver")]
+fn generate_one(seed: u64, options: &ScrambleOptions) -> Generated {
+    let mut cube = SqOne::new();
+    let scramble = cube.scramble_with_rng(options, &mut ChaCha20Rng::seed_from_u64(seed));
+
+    (cube.slots(), scramble)
+}
+
+// An empty scramble is written as
+// nothing rather than panicking.
 impl fmt::Display for Scramble {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut output = String::new();
-        for i in 0..self.turns.len()-1 {
-            output = [output, format!("{:?} / ", self.turns[i])].concat();
+        self.write_to(f)
+    }
+}
+
+impl TryFrom<Vec<(i8, i8)>> for Scramble {
+    type Error = ScrambleError;
+
+    fn try_from(turns: Vec<(i8, i8)>) -> Result<Scramble, ScrambleError> {
+        Scramble::new(turns)
+    }
+}
+
+impl From<Scramble> for Vec<(i8, i8)> {
+    fn from(scramble: Scramble) -> Vec<(i8, i8)> {
+        scramble.turns
+    }
+}
+
+/// Returns the turn with both amounts
+/// from -5 to 6, or None if either is
+/// more than a half turn.
+fn normalize_turn((top, bottom): (i8, i8)) -> Option<(i8, i8)> {
+    Some((cube::twist_amount(top)?, cube::twist_amount(bottom)?))
+}
+
+impl Index<usize> for Scramble {
+    type Output = (i8, i8);
+
+    fn index(&self, index: usize) -> &(i8, i8) {
+        &self.turns[index]
+    }
+}
+
+impl IntoIterator for Scramble {
+    type Item = (i8, i8);
+    type IntoIter = vec::IntoIter<(i8, i8)>;
+
+    fn into_iter(self) -> vec::IntoIter<(i8, i8)> {
+        self.turns.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Scramble {
+    type Item = &'a (i8, i8);
+    type IntoIter = core::slice::Iter<'a, (i8, i8)>;
+
+    fn into_iter(self) -> core::slice::Iter<'a, (i8, i8)> {
+        self.turns.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn reflect_scrambles() {
+        let mut rng = StdRng::seed_from_u64(594);
+        let scramble = Scramble { turns: vec![(1, 0), (2, -3), (3, 3), (-1, 2), (0, 0)] };
+        for scramble in [scramble.clone(), SqOne::new().scramble_rng(&mut rng), Scramble { turns: vec![(2, 1)] }] {
+            let mut cube = SqOne::new();
+            cube.apply(&scramble).unwrap();
+            for (transformed, expected) in [(scramble.mirrored(), cube.mirror()), (scramble.rotated(), cube.rotate_z2())] {
+                let mut reflected = SqOne::new();
+                reflected.apply(&transformed).unwrap();
+                assert_eq!(reflected.slots(), expected.slots());
+            }
         }
-        output = [output, format!("{:?}", self.turns[self.turns.len()-1])].concat();
-        write!(f, "{}", output.to_string())
+
+        assert_eq!(scramble.mirrored().mirrored(), scramble);
+        assert_eq!(scramble.rotated().rotated(), scramble);
+        assert_eq!(SqOne::new().mirror().slots(), SqOne::new().slots());
+        assert_eq!(SqOne::new().rotate_z2().slots(), SqOne::new().slots());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_scrambles() {
+        let scramble = Scramble::new(vec![(1, 0), (-3, 2), (0, 3)]).unwrap();
+        let json = serde_json::to_string(&scramble).unwrap();
+        assert_eq!(json, "[[1,0],[-3,2],[0,3]]");
+        assert_eq!(serde_json::from_str::<Scramble>(&json).unwrap(), scramble);
+        assert_eq!(serde_json::from_str::<Move>("\"Slash\"").unwrap(), Move::Slash);
+
+        // Blocked slashes are rejected
+        // like in Scramble::new.
+        assert!(serde_json::from_str::<Scramble>("[[2,0],[0,0]]").is_err());
+        assert!(serde_json::from_str::<Scramble>("[[7,0]]").is_err());
     }
-}
\ No newline at end of file
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn compare_scrambles() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (6, 0)] };
+        assert_eq!(scramble.equivalence(&scramble.clone()), Equivalence::Identical);
+
+        let longer = Scramble { turns: vec![(1, 0), (-1, 2), (0, 0), (-2, 0), (-6, 0)] };
+        assert_eq!(longer.simplified(), scramble);
+        assert_eq!(longer.equivalence(&scramble), Equivalence::Simplified);
+
+        let twisted = Scramble { turns: vec![(1, 3), (-3, 2), (6, 0)] };
+        let other = Scramble::between(&SqOne::new(), &{
+            let mut cube = SqOne::new();
+            cube.apply(&twisted).unwrap();
+            cube
+        });
+        assert_eq!(twisted.equivalence(&other), Equivalence::SameState);
+        assert!(twisted.equivalent(&other));
+        assert!(!scramble.equivalent(&twisted));
+        assert!(!scramble.equivalent(&Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] }));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn normalize_scramble() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (6, 0)] };
+        let longer = Scramble { turns: vec![(13, 0), (-1, 2), (0, 0), (-2, 0), (-6, 0)] };
+        assert_eq!(longer.normalize(), Ok(scramble));
+
+        // One slash leaves the middle layer
+        // flipped until the fix at the end.
+        let flipped = Scramble { turns: vec![(1, 0), (0, -1)] };
+        let normalized = flipped.normalize().unwrap();
+        let (mut before, mut after) = (SqOne::new(), SqOne::new());
+        before.apply(&flipped).unwrap();
+        after.apply(&normalized).unwrap();
+        assert!(!after.middle_flipped());
+        assert_eq!((after.slots().0, after.slots().1), (before.slots().0, before.slots().1));
+        assert!(normalized.iter().skip(1).rev().skip(1).all(|&turn| turn != (0, 0)));
+        assert!(normalized.iter().all(|&(top, bottom)| (-5..=6).contains(&top) && (-5..=6).contains(&bottom)));
+
+        assert_eq!(Scramble { turns: vec![(2, 0), (0, 0)] }.normalize(), Err(VerifyError::IllegalSlash(0)));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn shorten_scramble() {
+        let mut rng = StdRng::seed_from_u64(606);
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble_rng(&mut rng);
+        let shorter = scramble.shorten(15).unwrap();
+        assert!(shorter.len() < scramble.len());
+        assert_eq!(shorter.verify(&cube), Ok(()));
+
+        // Nothing is shorter than a
+        // scramble with one slash.
+        let short = Scramble { turns: vec![(1, 0), (0, 3)] };
+        assert_eq!(short.shorten(0), Ok(short.clone()));
+        assert_eq!(Scramble { turns: vec![] }.shorten(0), Ok(Scramble { turns: vec![] }));
+        assert_eq!(Scramble { turns: vec![(2, 0), (0, 0)] }.shorten(0), Err(VerifyError::IllegalSlash(0)));
+    }
+
+    #[test]
+    fn check_legal_from() {
+        let scramble = Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] };
+        assert_eq!(scramble.is_legal_from(&SqOne::new()), Err(IllegalAt { turn: 2, position: 4 }));
+        assert_eq!(scramble.moves()[4], Move::Slash);
+
+        let mut cube = SqOne::new();
+        cube.try_twist(1, 0).unwrap();
+        let scramble = Scramble { turns: vec![(0, 0), (0, 0)] };
+        assert_eq!(scramble.is_legal_from(&cube), Ok(()));
+        let scramble = Scramble { turns: vec![(0, 0), (0, 0), (1, 0), (0, 0)] };
+        assert_eq!(scramble.is_legal_from(&cube), Err(IllegalAt { turn: 2, position: 3 }));
+    }
+
+    #[test]
+    fn verify_scramble() {
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble();
+        assert_eq!(scramble.verify(&cube), Ok(()));
+        assert_eq!(scramble.verify(&SqOne::new()), Err(VerifyError::Mismatch(None)));
+
+        // A wrong turn is found by the history
+        // of the expected cube.
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (0, 3), (2, -1)] };
+        let mut cube = SqOne::with_history();
+        for next in scramble.moves() {
+            cube.apply_move(next).unwrap();
+        }
+        let wrong = Scramble { turns: vec![(1, 0), (-3, 2), (0, -3), (2, -1)] };
+        assert_eq!(wrong.verify(&cube), Err(VerifyError::Mismatch(Some(4))));
+        let short = Scramble { turns: vec![(1, 0), (-3, 2)] };
+        assert_eq!(short.verify(&cube), Err(VerifyError::Mismatch(Some(3))));
+    }
+
+    #[test]
+    fn inverse_scramble() {
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble();
+        cube.apply(&scramble.inverse()).unwrap();
+        assert_eq!(cube.slots(), SqOne::new().slots());
+        assert_eq!(Scramble { turns: vec![(6, -5)] }.inverse().turns, vec![(6, 5)]);
+    }
+
+    #[test]
+    fn replay_scramble() {
+        let scramble = Scramble { turns: vec![(1, 0), (0, 0), (0, -1)] };
+        let moves: Vec<Move> = scramble.replay(&SqOne::new()).map(|(m, _)| m).collect();
+        assert_eq!(moves, vec![Move::Twist(1, 0), Move::Slash, Move::Slash, Move::Twist(0, -1)]);
+
+        let mut cube = SqOne::new();
+        cube.apply(&scramble).unwrap();
+        let (_, last) = scramble.replay(&SqOne::new()).last().unwrap();
+        assert_eq!(last.slots(), cube.slots());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn generate_scramble_groups() {
+        let groups = Scramble::generate_groups(2, 5, 2, &ScrambleOptions::new()).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert!(groups.iter().all(|group| group.scrambles.len() == 5 && group.extras.len() == 2));
+
+        let all: HashSet<&Scramble> = groups
+            .iter()
+            .flat_map(|group| group.scrambles.iter().chain(group.extras.iter()))
+            .collect();
+        assert_eq!(all.len(), 14);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn generate_seeded_batch() {
+        let options = ScrambleOptions::new().random_state(true);
+        let batch = Scramble::generate_batch_seeded(3, &options, 7).unwrap();
+        assert_eq!(Ok(batch.clone()), Scramble::generate_batch_seeded(3, &options, 7));
+        assert_ne!(Ok(batch), Scramble::generate_batch_seeded(3, &options, 8));
+
+        // A batch larger than the 144 twists
+        // of a solved cube, the only cubes
+        // needing no slashes, gives up.
+        let twists = ScrambleOptions::new().target_difficulty(..=0).unwrap();
+        assert_eq!(Scramble::generate_batch_seeded(200, &twists, 7), Err(BatchError { requested: 200, found: 144 }));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn share_generator() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<ScrambleGenerator>();
+
+        let generator = ScrambleGenerator::new(ScrambleOptions::new(), 9);
+        let batch = generator.generate_batch(4);
+        assert_eq!(batch, ScrambleGenerator::new(ScrambleOptions::new(), 9).generate_batch(4));
+        let (scramble, cube) = generator.generate_with_cube();
+        assert_eq!(scramble.verify(&cube), Ok(()));
+        assert!(!batch.contains(&scramble));
+
+        let threaded: Vec<Scramble> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| generator.generate())).collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+        assert_eq!(threaded.len(), 4);
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn stream_scrambles() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let mut stream = ScrambleStream::new(ScrambleOptions::new(), 3);
+        assert_send(&stream);
+        let scramble = stream.next().unwrap();
+        assert_eq!(scramble.verify(stream.cube()), Ok(()));
+
+        let first: Vec<Scramble> = ScrambleStream::new(ScrambleOptions::new(), 3).take(2).collect();
+        assert_eq!(first[0], scramble);
+        assert_ne!(first[0], first[1]);
+    }
+
+    #[test]
+    fn scramble_alg_url() {
+        let scramble = Scramble { turns: vec![(1, 0), (0, -1)] };
+        assert_eq!(
+            scramble.to_alg_url(),
+            "https://alpha.twizzle.net/edit/?puzzle=square1&alg=(1%2C%200)%20%2F%20(0%2C%20-1)",
+        );
+    }
+
+    // Two top twists of 1 leave a
+    // corner across the slash line.
+    #[test]
+    fn count_moves_in_metrics() {
+        let scramble = Scramble { turns: vec![(0, 0), (3, 0), (0, 0), (1, -1)] };
+        assert_eq!(scramble.len_in(Metric::Slash), 3);
+        assert_eq!(scramble.len_in(Metric::Twist), 5);
+        assert_eq!(Scramble { turns: vec![(0, 0)] }.len_in(Metric::Twist), 0);
+
+        let mut empty = scramble.clone();
+        empty.truncate(0);
+        assert_eq!((empty.len_in(Metric::Slash), empty.len_in(Metric::Twist)), (0, 0));
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn scramble_between_states() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let from = SqOne::random_state(&mut rng);
+        let to = SqOne::random_state(&mut rng);
+        let mut cube = from.clone();
+        cube.apply(&Scramble::between(&from, &to)).unwrap();
+        assert_eq!(cube, to);
+
+        // The solutions of a cube and its
+        // own inverse cancel completely.
+        assert_eq!(Scramble::between(&from, &from).turns, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn annotate_shapes() {
+        let scramble = Scramble { turns: vec![(1, 0), (2, 0), (0, 0)] };
+        assert_eq!(scramble.annotated().unwrap(), "(1,0)/ # Square-Square\n(2,0)/ # Kite-Kite\n(0,0) # Kite-Kite");
+        let scramble = Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] };
+        assert_eq!(scramble.annotated(), Err(VerifyError::IllegalSlash(2)));
+    }
+
+    // Applying a scramble a turn at a
+    // time gives the same cube.
+    #[test]
+    fn edit_scramble_turns() {
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble();
+        let mut partial = Scramble { turns: vec![] };
+        assert!(partial.is_empty());
+        for &turn in &scramble {
+            partial.push(turn).unwrap();
+        }
+        assert_eq!(partial.verify(&cube), Ok(()));
+        assert_eq!(partial[1], scramble.iter().copied().nth(1).unwrap());
+
+        partial.truncate(2);
+        assert_eq!(partial.len(), 2);
+        assert_eq!(partial.into_iter().collect::<Vec<_>>(), scramble.turns[..2]);
+
+        let mut blocked = Scramble::new(vec![(1, 0), (0, 0), (1, 0)]).unwrap();
+        assert_eq!(blocked.push((0, 7)), Err(ScrambleError::TurnOutOfRange(3)));
+        assert_eq!(blocked.push((0, 0)), Err(ScrambleError::IllegalSlash(2)));
+        assert_eq!(blocked.len(), 3);
+        let mut half_turn = Scramble::new(vec![(0, 0)]).unwrap();
+        half_turn.push((-6, 0)).unwrap();
+        assert_eq!(half_turn[1], (6, 0));
+    }
+
+    #[test]
+    fn create_checked_scramble() {
+        assert_eq!(Scramble::new(vec![(1, 0), (-3, 6)]).unwrap().to_string(), "(1, 0) / (-3, 6)");
+        assert_eq!(Scramble::new(vec![]), Err(ScrambleError::Empty));
+        assert_eq!(Scramble::new(vec![(-6, 0)]), Scramble::new(vec![(6, 0)]));
+        assert_eq!(Scramble::try_from(vec![(1, 0), (7, 0)]), Err(ScrambleError::TurnOutOfRange(1)));
+        let turns = vec![(1, 0), (0, 0), (1, 0), (0, 0)];
+        assert_eq!(Scramble::try_from(turns), Err(ScrambleError::IllegalSlash(2)));
+        assert_eq!(Scramble { turns: vec![] }.to_string(), "");
+    }
+
+    // The daily scramble for a date must
+    // only change with DAILY_VERSION.
+    #[test]
+    fn daily_scramble() {
+        assert_eq!(Scramble::daily("2024-06-01"), Scramble::daily(" 2024-06-01\n"));
+        assert_ne!(Scramble::daily("2024-06-01"), Scramble::daily("2024-06-02"));
+        assert_eq!(
+            Scramble::daily("2024-06-01").to_string(),
+            "(-5, 0) / (6, 2) / (3, 0) / (-4, 0) / (1, 6) / (-3, -4) / (6, 4) / (0, -1) / (6, 0) / (2, 6) / \
+            (2, -2) / (-4, 4) / (3, 2) / (6, 6) / (-5, 5) / (6, -5) / (1, 6) / (5, 5) / (-3, 1) / (6, 6)",
+        );
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn verify_illegal_slash() {
+        let scramble = Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] };
+        assert_eq!(scramble.verify(&SqOne::new()), Err(VerifyError::IllegalSlash(2)));
+    }
+}
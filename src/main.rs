@@ -1,8 +1,32 @@
-
 use sq1_scrambler::cube::SqOne;
 
 fn main() {
+    #[cfg(feature = "tui")]
+    if std::env::args().any(|arg| arg == "--tui") {
+        if let Err(error) = sq1_scrambler::tui::run() {
+            eprintln!("{}", error);
+        }
+        return;
+    }
+
+    #[cfg(feature = "solver")]
+    if let Some(path) = std::env::args().skip_while(|arg| arg != "--write-tables").nth(1) {
+        let written = std::fs::File::create(&path)
+            .and_then(|file| sq1_scrambler::solver::write_tables(std::io::BufWriter::new(file)));
+        if let Err(error) = written {
+            eprintln!("{}", error);
+        }
+        return;
+    }
+
     let mut cube = SqOne::new();
     let scramble = cube.scramble();
-    println!("{}", scramble);
+    if std::env::args().any(|arg| arg == "--shapes") {
+        match scramble.annotated() {
+            Ok(annotated) => println!("{}", annotated),
+            Err(error) => eprintln!("{:?}", error),
+        }
+    } else {
+        println!("{}", scramble);
+    }
 }
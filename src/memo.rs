@@ -0,0 +1,140 @@
+//! Module for writing the memo of a
+//! cube for blind solving, as the letters
+//! of the positions each piece is swapped
+//! into from a buffer.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::cube::SqOne;
+use crate::permutation::Permutation;
+use crate::shape;
+
+/// Letters given to each corner and
+/// edge position, numbered like the
+/// pieces of a solved cube: the top
+/// layer clockwise from slot 0 and then
+/// the bottom layer. The first position
+/// of each kind is the buffer.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Lettering {
+    pub corners: [char; 8],
+    pub edges: [char; 8],
+}
+
+impl Default for Lettering {
+
+    /// Letters A to H for both the
+    /// corners and the edges, like the
+    /// Speffz scheme letters each kind
+    /// of piece on its own.
+    fn default() -> Lettering {
+        let letters = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H'];
+        Lettering { corners: letters, edges: letters }
+    }
+}
+
+/// Letters to memorize for the
+/// corners and the edges of a cube.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Memo {
+    pub corners: String,
+    pub edges: String,
+}
+
+impl Memo {
+
+    /// Returns true if the corners and
+    /// edges together need an odd number
+    /// of swaps, which takes a parity
+    /// algorithm to solve.
+    pub fn has_parity(&self) -> bool {
+        (self.corners.chars().count() + self.edges.chars().count()) % 2 == 1
+    }
+}
+
+/// Returns the memo of the cube with
+/// the lettering, or None if the cube
+/// is not in cube shape with both layers
+/// lined up like a solved cube. The
+/// middle layer is left out.
+pub fn memo(cube: &SqOne, lettering: &Lettering) -> Option<Memo> {
+    let (top, bottom, _) = cube.slots();
+    let aligned = |layer: [u8; 12]| layer[0] < 8 && layer[0] == layer[1];
+    if !shape::classify(cube).is_cube() || !aligned(top) || !aligned(bottom) {
+        return None;
+    }
+
+    Some(Memo {
+        corners: trace(&cube.corner_permutation(), &lettering.corners),
+        edges: trace(&cube.edge_permutation(), &lettering.edges),
+    })
+}
+
+/// Returns the letters of the targets
+/// of each swap with the buffer in
+/// position 0 that solves the pieces.
+/// When the buffer holds its own piece,
+/// a new cycle starts from the first
+/// unsolved position.
+fn trace(permutation: &Permutation, letters: &[char; 8]) -> String {
+    let mut pieces: Vec<u8> = permutation.as_slice().to_vec();
+    let mut memo = String::new();
+    loop {
+        let target = match pieces[0] {
+            0 => match (1..8).find(|&i| pieces[i] as usize != i) {
+                Some(position) => position,
+                None => break,
+            },
+            piece => piece as usize,
+        };
+        memo.push(letters[target]);
+        pieces.swap(0, target);
+    }
+
+    memo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solved_memo_is_empty() {
+        let memo = memo(&SqOne::new(), &Lettering::default()).unwrap();
+        assert_eq!(memo, Memo { corners: String::new(), edges: String::new() });
+        assert!(!memo.has_parity());
+    }
+
+    // Turning the top layer a quarter
+    // cycles the four top corners and
+    // edges through the buffer.
+    #[test]
+    fn memo_of_quarter_turn() {
+        let mut cube = SqOne::new();
+        cube.try_twist(3, 0).unwrap();
+        let memo = cube.memo().unwrap();
+        assert_eq!(memo.corners, "DCB");
+        assert_eq!(memo.edges, "DCB");
+        assert!(!memo.has_parity());
+    }
+
+    #[test]
+    fn memo_needs_aligned_cube_shape() {
+        let mut cube = SqOne::new();
+        cube.try_twist(1, 0).unwrap();
+        assert_eq!(cube.memo(), None);
+        cube.try_twist(-1, 0).unwrap();
+        cube.try_slash().unwrap();
+        assert_eq!(cube.memo(), None);
+    }
+
+    // A cycle starting away from the
+    // buffer is broken into and closed
+    // again with the same letter.
+    #[test]
+    fn trace_cycle_break() {
+        let permutation = Permutation::new(vec![0, 2, 1, 3, 4, 5, 6, 7]);
+        assert_eq!(trace(&permutation, &Lettering::default().corners), "BCB");
+    }
+}
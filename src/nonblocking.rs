@@ -0,0 +1,134 @@
+//! Module running the expensive parts
+//! of the crate on Tokio's blocking
+//! thread pool, so the executor is never
+//! stalled by them.
+//!
+//! Each function returns a Task, a
+//! future resolving to the result once
+//! the blocking thread has finished the
+//! work. It must be called from within a
+//! Tokio runtime. Blocking work keeps
+//! running when its task is dropped, so
+//! the long running functions take a
+//! CancelToken to stop them.
+
+use std::future::Future;
+use std::panic;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::task::JoinHandle;
+
+use crate::cube::SqOne;
+use crate::progress::{CancelToken, Cancelled};
+use crate::solver;
+use crate::{BatchError, Metric, Scramble, ScrambleOptions};
+
+/// Future resolving to the result of
+/// work running on the blocking pool.
+/// A panic in the work is resumed when
+/// the task is polled.
+pub struct Task<T> {
+    handle: JoinHandle<T>,
+}
+
+impl<T> Future for Task<T> {
+    type Output = T;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        match Pin::new(&mut self.handle).poll(cx) {
+            Poll::Ready(Ok(value)) => Poll::Ready(value),
+            Poll::Ready(Err(error)) => panic::resume_unwind(error.into_panic()),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Runs f on the blocking pool and
+/// returns a task resolving to its
+/// result.
+pub fn spawn_blocking<T, F>(f: F) -> Task<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    Task { handle: tokio::task::spawn_blocking(f) }
+}
+
+/// Generates a scramble from a solved
+/// cube with the options, which for
+/// random state scrambles means a solve.
+pub fn generate(options: ScrambleOptions) -> Task<Scramble> {
+    spawn_blocking(move || SqOne::new().scramble_with(&options))
+}
+
+/// Generates a batch of count distinct
+/// scrambles, like
+/// Scramble::generate_batch.
+pub fn generate_batch(count: usize, options: ScrambleOptions) -> Task<Result<Vec<Scramble>, BatchError>> {
+    spawn_blocking(move || Scramble::generate_batch(count, &options))
+}
+
+/// Solves the cube with the two-phase
+/// solver.
+pub fn solve(cube: SqOne) -> Task<Scramble> {
+    spawn_blocking(move || solver::solve(&cube))
+}
+
+/// Solves the cube optimally in the
+/// metric, stopping early if the token
+/// is cancelled.
+pub fn solve_optimal(cube: SqOne, metric: Metric, cancel: CancelToken) -> Task<Result<Scramble, Cancelled>> {
+    spawn_blocking(move || solver::solve_optimal_with(&cube, metric, &mut (), &cancel))
+}
+
+/// Finds the best solution in the metric
+/// found before the token is cancelled,
+/// like solver::solve_until.
+pub fn solve_until(cube: SqOne, metric: Metric, cancel: CancelToken) -> Task<Option<Scramble>> {
+    spawn_blocking(move || solver::solve_until(&cube, metric, &cancel))
+}
+
+/// Generates the solver tables, so later
+/// solves start straight away, stopping
+/// early if the token is cancelled.
+pub fn prepare(cancel: CancelToken) -> Task<Result<(), Cancelled>> {
+    spawn_blocking(move || solver::prepare_with(&mut (), &cancel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the future to completion on
+    /// a new Tokio runtime.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+    }
+
+    #[test]
+    fn run_on_pool() {
+        block_on(async {
+            prepare(CancelToken::new()).await.unwrap();
+            let scramble = generate(ScrambleOptions::new().random_state(true)).await;
+            let mut cube = SqOne::new();
+            cube.apply(&scramble).unwrap();
+            let solution = solve(cube.clone()).await;
+            cube.apply(&solution).unwrap();
+            assert!(cube.is_solved());
+            assert_eq!(generate_batch(3, ScrambleOptions::new()).await.unwrap().len(), 3);
+        });
+
+        let result = panic::catch_unwind(|| block_on(async { spawn_blocking(|| panic!("job failed")).await }));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn cancel_optimal_solve() {
+        let mut cube = SqOne::new();
+        cube.apply(&Scramble::daily("2024-06-01")).unwrap();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(block_on(async { solve_optimal(cube, Metric::Slash, cancel).await }), Err(Cancelled));
+    }
+}
@@ -0,0 +1,691 @@
+//! Module for writing scrambles in
+//! the layouts wanted by different
+//! timers and scoresheets.
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::cstimer::{self, ParseError};
+use crate::cube::twist_amount;
+use crate::Scramble;
+
+/// Styles of scramble notation seen in
+/// the wild, each read by Dialect::parse
+/// and written by Dialect::format.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Dialect {
+
+    /// Turns inside parentheses,
+    /// as in (1,0) / (3,-3) /.
+    Standard,
+
+    /// Turns without parentheses,
+    /// as in 1,0 / 3,-3 /.
+    Bare,
+
+    /// Each layer turned on its own, as
+    /// in u1 / u3 d-3 /, leaving out
+    /// layers that are not turned.
+    Layers,
+
+    /// Top and bottom turns written as A
+    /// and B, with each slash written as
+    /// F for the flip of the right half
+    /// and middle layer, as in
+    /// A1 F A3 B-3 F.
+    Abf,
+
+    /// Turns as words to be read aloud,
+    /// as in one, zero, slash. minus
+    /// three, three, slash.
+    Spoken,
+}
+
+/// Words read aloud for the amounts
+/// of a turn from 0 to 6.
+const NUMBER_WORDS: [&str; 7] = ["zero", "one", "two", "three", "four", "five", "six"];
+
+/// Pause after each slash of the SSML
+/// from ssml, in milliseconds.
+pub const SSML_PAUSE_MS: u32 = 400;
+
+impl Dialect {
+
+    /// Returns the letters of the top and
+    /// bottom layers and of the slash, or
+    /// None if turns are written as pairs.
+    fn letters(self) -> Option<(char, char, char)> {
+        match self {
+            Dialect::Standard | Dialect::Bare | Dialect::Spoken => None,
+            Dialect::Layers => Some(('u', 'd', '/')),
+            Dialect::Abf => Some(('A', 'B', 'F')),
+        }
+    }
+
+    /// Returns the layout that writes
+    /// scrambles in this dialect.
+    pub fn format(self) -> ScrambleFormat {
+        let format = ScrambleFormat { dialect: self, ..ScrambleFormat::new() };
+        match self {
+            Dialect::Standard => format,
+            Dialect::Bare => format.parentheses(false).spaces(false).trailing_slash(TrailingSlash::Bare),
+            Dialect::Layers => format,
+            Dialect::Abf => format.separator(" F "),
+            Dialect::Spoken => format.separator(", slash. ").trailing_slash(TrailingSlash::Bare),
+        }
+    }
+
+    /// Reads a scramble in this dialect.
+    /// Spacing is ignored, letters may be
+    /// either case, and a slash with no
+    /// turn before or after it stands for
+    /// a turn of (0,0).
+    pub fn parse(self, text: &str) -> Result<Scramble, ParseError> {
+        let (top, bottom, slash) = match self.letters() {
+            Some(letters) => letters,
+            None if self == Dialect::Standard => return cstimer::parse(text),
+            None if self == Dialect::Spoken => return parse_spoken(text),
+            None => return parse_pairs(text),
+        };
+
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if text.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let turns = text
+            .split(|c: char| c.eq_ignore_ascii_case(&slash))
+            .map(|turn| parse_layers(turn, top, bottom))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Scramble { turns })
+    }
+}
+
+/// Ways of writing a slash at the
+/// end of a scramble, which is stored
+/// as a last turn of (0,0).
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TrailingSlash {
+
+    /// The last turn is written out,
+    /// as in (1,0) / (0,0).
+    Turn,
+
+    /// Only the slash is written,
+    /// as in (1,0) /.
+    Bare,
+}
+
+/// Widest line of a scramble printed
+/// on a scorecard, in characters.
+pub const SCORECARD_WIDTH: usize = 40;
+
+/// Layout used by Scramble::format_with
+/// to write a scramble.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ScrambleFormat {
+    separator: Cow<'static, str>,
+    parentheses: bool,
+    spaces: bool,
+    trailing_slash: TrailingSlash,
+    width: Option<usize>,
+    dialect: Dialect,
+    comments: bool,
+    numbered: bool,
+}
+
+impl Default for ScrambleFormat {
+    fn default() -> ScrambleFormat {
+        ScrambleFormat {
+            separator: Cow::Borrowed(" / "),
+            parentheses: true,
+            spaces: true,
+            trailing_slash: TrailingSlash::Turn,
+            width: None,
+            dialect: Dialect::Standard,
+            comments: true,
+            numbered: false,
+        }
+    }
+}
+
+impl ScrambleFormat {
+
+    /// Returns the layout used by
+    /// Display, as in (1, 0) / (-3, 2).
+    pub fn new() -> ScrambleFormat {
+        ScrambleFormat::default()
+    }
+
+    /// Returns the layout TNoodle uses
+    /// for WCA scrambles, as in
+    /// (1,0)/ (-3,2).
+    pub fn wca() -> ScrambleFormat {
+        ScrambleFormat::new().separator("/ ").spaces(false)
+    }
+
+    /// Returns the layout of scorecards,
+    /// the layout TNoodle uses broken
+    /// into lines of SCORECARD_WIDTH.
+    pub fn scorecard() -> ScrambleFormat {
+        ScrambleFormat::wca().wrap(SCORECARD_WIDTH)
+    }
+
+    /// Returns the layout csTimer uses,
+    /// as in (1,0) / (-3,2).
+    pub fn cstimer() -> ScrambleFormat {
+        ScrambleFormat::new().spaces(false)
+    }
+
+    /// Sets the text written for each
+    /// slash between turns.
+    pub fn separator(mut self, separator: &str) -> ScrambleFormat {
+        self.separator = Cow::Owned(separator.to_string());
+        self
+    }
+
+    /// Writes each turn inside
+    /// parentheses or without them.
+    pub fn parentheses(mut self, parentheses: bool) -> ScrambleFormat {
+        self.parentheses = parentheses;
+        self
+    }
+
+    /// Writes a space after the comma
+    /// of each turn or not.
+    pub fn spaces(mut self, spaces: bool) -> ScrambleFormat {
+        self.spaces = spaces;
+        self
+    }
+
+    /// Sets how a slash at the end of
+    /// the scramble is written.
+    pub fn trailing_slash(mut self, trailing_slash: TrailingSlash) -> ScrambleFormat {
+        self.trailing_slash = trailing_slash;
+        self
+    }
+
+    /// Breaks the scramble into lines
+    /// after a slash so that no line is
+    /// longer than width, unless a single
+    /// turn is longer.
+    pub fn wrap(mut self, width: usize) -> ScrambleFormat {
+        self.width = Some(width);
+        self
+    }
+
+    /// Writes the number of each turn
+    /// before it, as in 1. (1,0)/ 2. (-3,2),
+    /// counting from 1.
+    pub fn numbered(mut self, numbered: bool) -> ScrambleFormat {
+        self.numbered = numbered;
+        self
+    }
+
+    /// Writes the comments of an
+    /// AnnotatedScramble or strips them.
+    pub fn comments(mut self, comments: bool) -> ScrambleFormat {
+        self.comments = comments;
+        self
+    }
+
+    /// Returns the scramble written
+    /// in this layout.
+    pub fn format(&self, scramble: &Scramble) -> String {
+        let mut text = String::new();
+        self.write_to(scramble, &mut text).unwrap();
+        text
+    }
+
+    /// Writes the scramble in this layout
+    /// straight into out, without building
+    /// any strings along the way.
+    pub fn write_to<W: fmt::Write>(&self, scramble: &Scramble, out: &mut W) -> fmt::Result {
+        self.write_turns(&scramble.turns, 0, out)
+    }
+
+    /// Writes the turns of a scramble
+    /// starting at the turn of index first,
+    /// where turns is the rest of the
+    /// scramble.
+    fn write_turns<W: fmt::Write>(&self, turns: &[(i8, i8)], first: usize, out: &mut W) -> fmt::Result {
+        let slash = self.separator.trim_end();
+        let gap = &self.separator[slash.len()..];
+        let mut line = 0;
+        for (i, &(top, bottom)) in turns.iter().enumerate() {
+            let last = i + 1 == turns.len();
+            let bare = last && first + i > 0 && (top, bottom) == (0, 0) && self.trailing_slash == TrailingSlash::Bare;
+            let mut len = if bare { 0 } else { self.turn_len(top, bottom) };
+
+            // A turn written as nothing is
+            // followed by the slash without
+            // the spaces before it, and is
+            // not numbered.
+            let slash = if len == 0 { slash.trim_start() } else { slash };
+            let number = (self.numbered && len > 0).then_some(first + i + 1);
+            if let Some(number) = number {
+                len += number.ilog10() as usize + 3;
+            }
+            if !last {
+                len += slash.len();
+            }
+
+            // Start a new line when the turn
+            // and its slash would not fit, and
+            // only write the spaces after a
+            // slash once more follows them.
+            if self.width.is_some_and(|width| line > 0 && len > 0 && line + gap.len() + len > width) {
+                out.write_char('\n')?;
+                line = 0;
+            } else if line > 0 && len > 0 {
+                out.write_str(gap)?;
+                line += gap.len();
+            }
+            if let Some(number) = number {
+                write!(out, "{}. ", number)?;
+            }
+            if !bare {
+                self.write_turn(top, bottom, out)?;
+            }
+            if !last {
+                out.write_str(slash)?;
+            }
+            line += len;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a single turn
+    /// in this layout.
+    fn write_turn<W: fmt::Write>(&self, top: i8, bottom: i8, out: &mut W) -> fmt::Result {
+        if self.dialect == Dialect::Spoken {
+            write_spoken(top, out)?;
+            out.write_str(", ")?;
+            return write_spoken(bottom, out);
+        }
+        if let Some((top_letter, bottom_letter, _)) = self.dialect.letters() {
+            if top != 0 {
+                write!(out, "{}{}", top_letter, top)?;
+            }
+            if top != 0 && bottom != 0 {
+                out.write_char(' ')?;
+            }
+            if bottom != 0 {
+                write!(out, "{}{}", bottom_letter, bottom)?;
+            }
+            return Ok(());
+        }
+
+        let space = if self.spaces { " " } else { "" };
+        if self.parentheses {
+            write!(out, "({},{}{})", top, space, bottom)
+        } else {
+            write!(out, "{},{}{}", top, space, bottom)
+        }
+    }
+
+    /// Returns the length of a single
+    /// turn written in this layout.
+    fn turn_len(&self, top: i8, bottom: i8) -> usize {
+        let amount_len = |amount: i8| (amount < 0) as usize + if amount.unsigned_abs() >= 10 { 2 } else { 1 };
+        if self.dialect == Dialect::Spoken {
+            let word_len = |amount: i8| match NUMBER_WORDS.get(amount.unsigned_abs() as usize) {
+                Some(word) => 6 * (amount < 0) as usize + word.len(),
+                None => amount_len(amount),
+            };
+            return word_len(top) + 2 + word_len(bottom);
+        }
+        if self.dialect.letters().is_some() {
+            let layer_len = |amount: i8| if amount == 0 { 0 } else { 1 + amount_len(amount) };
+            return layer_len(top) + layer_len(bottom) + (top != 0 && bottom != 0) as usize;
+        }
+
+        amount_len(top) + amount_len(bottom) + 1 + self.spaces as usize + 2 * self.parentheses as usize
+    }
+}
+
+/// Scramble with an optional comment on
+/// each turn, as in (3,0)/ // setup,
+/// which are kept when it is read and
+/// can be written back out.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct AnnotatedScramble {
+    pub scramble: Scramble,
+
+    /// Comment on each turn and the slash
+    /// after it, in the order of the turns.
+    pub comments: Vec<Option<String>>,
+}
+
+impl AnnotatedScramble {
+
+    /// Returns the scramble
+    /// without comments.
+    pub fn new(scramble: Scramble) -> AnnotatedScramble {
+        let comments = vec![None; scramble.turns.len()];
+        AnnotatedScramble { scramble, comments }
+    }
+
+    /// Reads a scramble in the dialect
+    /// with comments. Anything after // on
+    /// a line is a comment on the last
+    /// turn before it, and a comment on the
+    /// same turn as another is joined
+    /// to it. Comments on turns written as
+    /// nothing, as (0,0) in the letter
+    /// dialects, are read on the turn
+    /// before.
+    pub fn parse(text: &str, dialect: Dialect) -> Result<AnnotatedScramble, ParseError> {
+        let is_slash = |c: char| c.eq_ignore_ascii_case(&dialect.letters().map_or('/', |(_, _, slash)| slash));
+        let mut moves = String::new();
+        let mut notes = vec![];
+        for line in text.lines() {
+            let (line, comment) = match line.split_once("//") {
+                Some((line, comment)) => (line, comment.trim()),
+                None => (line, ""),
+            };
+            moves.push_str(line);
+            moves.push(' ');
+
+            // A comment after a slash is on
+            // the turn before the slash.
+            if !comment.is_empty() {
+                let slashes = moves.matches(is_slash).count();
+                let after = moves.rsplit(is_slash).next().unwrap_or("");
+                let turn = if after.trim().is_empty() { slashes.saturating_sub(1) } else { slashes };
+                notes.push((turn, comment.to_string()));
+            }
+        }
+
+        let mut annotated = AnnotatedScramble::new(dialect.parse(&moves)?);
+        let last = annotated.comments.len() - 1;
+        for (turn, comment) in notes {
+            match &mut annotated.comments[turn.min(last)] {
+                Some(joined) => {
+                    joined.push_str("; ");
+                    joined.push_str(&comment);
+                },
+                empty => *empty = Some(comment),
+            }
+        }
+
+        Ok(annotated)
+    }
+
+    /// Returns the comment on the
+    /// turn, if it has one.
+    pub fn comment(&self, turn: usize) -> Option<&str> {
+        self.comments.get(turn)?.as_deref()
+    }
+
+    /// Sets the comment on the turn,
+    /// or removes it if None.
+    pub fn set_comment(&mut self, turn: usize, comment: Option<&str>) {
+        self.comments.resize(self.scramble.turns.len(), None);
+        self.comments[turn] = comment.map(ToString::to_string);
+    }
+
+    /// Returns the scramble written in the
+    /// layout, with each comment ending
+    /// the line of its turn unless the
+    /// layout strips comments.
+    pub fn format_with(&self, format: &ScrambleFormat) -> String {
+        let mut text = String::new();
+        self.write_to(format, &mut text).unwrap();
+        text
+    }
+
+    /// Writes the scramble like
+    /// format_with straight into out.
+    pub fn write_to<W: fmt::Write>(&self, format: &ScrambleFormat, out: &mut W) -> fmt::Result {
+        let turns = &self.scramble.turns;
+        let mut start = 0;
+        for end in 0..turns.len() {
+            let comment = self.comment(end).filter(|_| format.comments);
+            let last = end + 1 == turns.len();
+            if comment.is_none() && !last {
+                continue;
+            }
+
+            // The turns up to each comment
+            // are written as a scramble of
+            // their own, so they wrap as the
+            // layout wraps a scramble.
+            if start > 0 {
+                out.write_char('\n')?;
+            }
+            format.write_turns(&turns[start..=end], start, out)?;
+            if !last {
+                out.write_str(format.separator.trim_end())?;
+            }
+            if let Some(comment) = comment {
+                write!(out, " // {}", comment)?;
+            }
+            start = end + 1;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for AnnotatedScramble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(&ScrambleFormat::new(), f)
+    }
+}
+
+/// Reads a scramble written as pairs
+/// without parentheses, as in 1,0 / 3,-3.
+fn parse_pairs(text: &str) -> Result<Scramble, ParseError> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if text.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    let turns = text
+        .split('/')
+        .map(|turn| match turn {
+            "" => Ok((0, 0)),
+            _ => cstimer::parse_pair(turn).ok_or_else(|| ParseError::InvalidTurn(turn.to_string())),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Scramble { turns })
+}
+
+/// Returns the scramble read aloud as
+/// SSML, with a pause of SSML_PAUSE_MS
+/// after each slash, for timers that
+/// call out scrambles through a speech
+/// engine.
+pub fn ssml(scramble: &Scramble) -> String {
+    let separator = format!(", slash. <break time=\"{}ms\"/> ", SSML_PAUSE_MS);
+    let text = Dialect::Spoken.format().separator(&separator).format(scramble);
+
+    format!("<speak>{}</speak>", text)
+}
+
+/// Writes the amount as words, as in
+/// minus three, or as digits if there
+/// is no word for it.
+fn write_spoken<W: fmt::Write>(amount: i8, out: &mut W) -> fmt::Result {
+    match NUMBER_WORDS.get(amount.unsigned_abs() as usize) {
+        Some(word) if amount < 0 => write!(out, "minus {}", word),
+        Some(word) => out.write_str(word),
+        None => write!(out, "{}", amount),
+    }
+}
+
+/// Reads a scramble written as words,
+/// as in one, zero, slash. Case and
+/// punctuation are ignored.
+fn parse_spoken(text: &str) -> Result<Scramble, ParseError> {
+    let text = text.to_lowercase().replace([',', '.'], " ");
+    if text.trim().is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    let turns = text
+        .split("slash")
+        .map(|turn| {
+            let invalid = || ParseError::InvalidTurn(turn.trim().to_string());
+            let mut words = turn.split_whitespace();
+            let mut amount = || -> Result<Option<i8>, ParseError> {
+                let (sign, word) = match words.next() {
+                    Some("minus") => (-1, words.next().ok_or_else(invalid)?),
+                    Some(word) => (1, word),
+                    None => return Ok(None),
+                };
+                let value = NUMBER_WORDS.iter().position(|&number| number == word).ok_or_else(invalid)?;
+                Ok(twist_amount(sign * value as i8))
+            };
+            let turn = match (amount()?, amount()?) {
+                (Some(top), Some(bottom)) => (top, bottom),
+                (None, None) => (0, 0),
+                _ => return Err(invalid()),
+            };
+
+            match words.next() {
+                Some(_) => Err(invalid()),
+                None => Ok(turn),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Scramble { turns })
+}
+
+/// Reads the turns of the layers between
+/// two slashes with spaces removed, as in
+/// u3d-3. Each layer is turned at most
+/// once, and an amount followed by a
+/// prime is turned the other way.
+fn parse_layers(turn: &str, top: char, bottom: char) -> Result<(i8, i8), ParseError> {
+    let invalid = || ParseError::InvalidTurn(turn.to_string());
+    let mut amounts = (None, None);
+    let mut rest = turn;
+    while let Some(letter) = rest.chars().next() {
+        let layer = if letter.eq_ignore_ascii_case(&top) {
+            &mut amounts.0
+        } else if letter.eq_ignore_ascii_case(&bottom) {
+            &mut amounts.1
+        } else {
+            return Err(invalid());
+        };
+
+        rest = &rest[letter.len_utf8()..];
+        let end = rest.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let (amount, prime) = match rest[..end].strip_suffix('\'') {
+            Some(amount) => (amount, true),
+            None => (&rest[..end], false),
+        };
+        let amount = match amount.parse::<i8>() {
+            Ok(amount) if (-6..=6).contains(&amount) && layer.is_none() => amount,
+            _ => return Err(invalid()),
+        };
+        *layer = twist_amount(if prime { -amount } else { amount });
+        rest = &rest[end..];
+    }
+
+    Ok((amounts.0.unwrap_or(0), amounts.1.unwrap_or(0)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_layouts() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (0, 0)] };
+        assert_eq!(ScrambleFormat::new().format(&scramble), scramble.to_string());
+        assert_eq!(ScrambleFormat::wca().format(&scramble), "(1,0)/ (-3,2)/ (0,0)");
+        let format = ScrambleFormat::cstimer().parentheses(false).trailing_slash(TrailingSlash::Bare);
+        assert_eq!(format.format(&scramble), "1,0 / -3,2 /");
+        assert_eq!(ScrambleFormat::new().format(&Scramble { turns: vec![] }), "");
+    }
+
+    #[test]
+    fn wrap_lines() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (0, 6), (4, -5)] };
+        let format = ScrambleFormat::wca().wrap(14);
+        assert_eq!(format.format(&scramble), "(1,0)/ (-3,2)/\n(0,6)/ (4,-5)");
+        assert_eq!(ScrambleFormat::wca().wrap(1).format(&scramble).lines().count(), 4);
+
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 12), (0, 0)] };
+        let format = ScrambleFormat::new().trailing_slash(TrailingSlash::Bare).wrap(16);
+        let mut text = String::from("> ");
+        format.write_to(&scramble, &mut text).unwrap();
+        assert_eq!(text, "> (1, 0) /\n(-3, 12) /");
+    }
+
+    #[test]
+    fn number_turns() {
+        let scramble = Scramble { turns: vec![(1, 0), (-3, 2), (0, 6), (4, -5), (0, 0)] };
+        let format = ScrambleFormat::wca().numbered(true).trailing_slash(TrailingSlash::Bare);
+        assert_eq!(format.format(&scramble), "1. (1,0)/ 2. (-3,2)/ 3. (0,6)/ 4. (4,-5)/");
+        assert_eq!(format.clone().wrap(20).format(&scramble), "1. (1,0)/ 2. (-3,2)/\n3. (0,6)/ 4. (4,-5)/");
+
+        let long = Scramble { turns: vec![(1, 0); 12] };
+        let text = ScrambleFormat::scorecard().numbered(true).format(&long);
+        assert!(text.lines().all(|line| line.len() <= SCORECARD_WIDTH));
+        assert!(text.ends_with("12. (1,0)"));
+
+        // Numbers carry on across the
+        // lines split by comments.
+        let mut annotated = AnnotatedScramble::new(scramble);
+        annotated.set_comment(1, Some("pair"));
+        assert_eq!(annotated.format_with(&format), "1. (1,0)/ 2. (-3,2)/ // pair\n3. (0,6)/ 4. (4,-5)/");
+    }
+
+    #[test]
+    fn dialects() {
+        let scramble = Scramble { turns: vec![(0, 0), (1, 0), (3, -3), (0, 0)] };
+        assert_eq!(Dialect::Standard.format().format(&scramble), "(0, 0) / (1, 0) / (3, -3) / (0, 0)");
+        assert_eq!(Dialect::Bare.format().format(&scramble), "0,0 / 1,0 / 3,-3 /");
+        assert_eq!(Dialect::Layers.format().format(&scramble), "/ u1 / u3 d-3 /");
+        assert_eq!(Dialect::Abf.format().format(&scramble), "F A1 F A3 B-3 F");
+
+        assert_eq!(Dialect::Spoken.format().format(&scramble), "zero, zero, slash. one, zero, slash. three, minus three, slash.");
+
+        for dialect in [Dialect::Standard, Dialect::Bare, Dialect::Layers, Dialect::Abf, Dialect::Spoken] {
+            let text = dialect.format().format(&scramble);
+            assert_eq!(dialect.parse(&text), Ok(scramble.clone()));
+        }
+        assert_eq!(Dialect::Layers.parse("U3' D2 /").unwrap().turns, vec![(-3, 2), (0, 0)]);
+        assert_eq!(Dialect::Abf.parse("a1fb-2").unwrap().turns, vec![(1, 0), (0, -2)]);
+        assert_eq!(Dialect::Layers.parse("u1 u2"), Err(ParseError::InvalidTurn("u1u2".to_string())));
+        assert_eq!(Dialect::Bare.parse("1,0 / (3,3)"), Err(ParseError::InvalidTurn("(3,3)".to_string())));
+        assert_eq!(Dialect::Spoken.parse("One, Minus Two. Slash").unwrap().turns, vec![(1, -2), (0, 0)]);
+        assert_eq!(Dialect::Spoken.parse("one slash"), Err(ParseError::InvalidTurn("one".to_string())));
+
+        let text = ssml(&scramble);
+        assert!(text.starts_with("<speak>zero, zero, slash. <break time=\"400ms\"/> one, zero"));
+        assert!(text.ends_with("minus three, slash. <break time=\"400ms\"/></speak>"));
+    }
+
+    #[test]
+    fn comments_round_trip() {
+        let text = "(3,0)/ // setup\n(1,0)/ (-3,2) // pair up\n// then slash\n/";
+        let annotated = AnnotatedScramble::parse(text, Dialect::Standard).unwrap();
+        assert_eq!(annotated.scramble.turns, vec![(3, 0), (1, 0), (-3, 2), (0, 0)]);
+        assert_eq!(annotated.comment(0), Some("setup"));
+        assert_eq!(annotated.comment(2), Some("pair up; then slash"));
+
+        let format = ScrambleFormat::wca();
+        let written = annotated.format_with(&format);
+        assert_eq!(written, "(3,0)/ // setup\n(1,0)/ (-3,2)/ // pair up; then slash\n(0,0)");
+        assert_eq!(AnnotatedScramble::parse(&written, Dialect::Standard), Ok(annotated.clone()));
+        assert_eq!(annotated.format_with(&format.comments(false)), "(3,0)/ (1,0)/ (-3,2)/ (0,0)");
+
+        let mut layers = AnnotatedScramble::parse("u1 d2 / // first\nu3", Dialect::Layers).unwrap();
+        layers.set_comment(1, Some("last"));
+        let written = layers.format_with(&Dialect::Layers.format());
+        assert_eq!(AnnotatedScramble::parse(&written, Dialect::Layers), Ok(layers));
+    }
+}
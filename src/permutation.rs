@@ -0,0 +1,98 @@
+//! Module for describing how the pieces
+//! of a cube are permuted compared to
+//! a solved cube.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Permutation of a kind of piece, where
+/// each position holds the number of the
+/// piece there, counting from 0 in the
+/// order of a solved cube.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Permutation(Vec<u8>);
+
+impl Permutation {
+
+    /// Creates the permutation from the
+    /// number of the piece at each position.
+    ///
+    /// Panics if the pieces are not each
+    /// number below the length once.
+    pub fn new(pieces: Vec<u8>) -> Permutation {
+        let mut seen = vec![false; pieces.len()];
+        for &piece in &pieces {
+            assert!(!core::mem::replace(&mut seen[piece as usize], true), "piece {} repeated", piece);
+        }
+
+        Permutation(pieces)
+    }
+
+    /// Returns the number of the piece
+    /// at each position.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns true if every piece is
+    /// in its solved position.
+    pub fn is_identity(&self) -> bool {
+        self.0.iter().enumerate().all(|(i, &piece)| i == piece as usize)
+    }
+
+    /// Returns the cycles of the
+    /// permutation, leaving out pieces
+    /// in their solved position. Each
+    /// cycle starts from its smallest
+    /// position and lists the positions
+    /// in the order the pieces move.
+    pub fn cycles(&self) -> Vec<Vec<u8>> {
+        let mut visited = vec![false; self.0.len()];
+        let mut cycles = vec![];
+        for start in 0..self.0.len() {
+            if visited[start] || self.0[start] as usize == start {
+                continue;
+            }
+
+            // The piece at a position moves
+            // to the position it is solved
+            // in, so follow the pieces.
+            let mut cycle = vec![];
+            let mut position = start;
+            while !visited[position] {
+                visited[position] = true;
+                cycle.push(position as u8);
+                position = self.0[position] as usize;
+            }
+            cycles.push(cycle);
+        }
+
+        cycles
+    }
+
+    /// Returns true if the permutation
+    /// is made of an even number of swaps.
+    pub fn is_even(&self) -> bool {
+        self.cycles().iter().map(|cycle| cycle.len() - 1).sum::<usize>() % 2 == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_cycles() {
+        let perm = Permutation::new(vec![1, 2, 0, 3, 5, 4]);
+        assert_eq!(perm.cycles(), vec![vec![0, 1, 2], vec![4, 5]]);
+        assert!(!perm.is_even());
+        assert!(!perm.is_identity());
+        assert!(Permutation::new(vec![0, 1, 2]).is_identity());
+    }
+
+    #[test]
+    #[should_panic]
+    fn reject_repeated_piece() {
+        Permutation::new(vec![0, 0, 1]);
+    }
+}
@@ -0,0 +1,89 @@
+//! Module for drawing a Square One as
+//! a PNG image, for places that cannot
+//! show SVG such as chat bots. The
+//! drawing is the same as the SVG one
+//! from the render module, rasterized
+//! with tiny-skia and encoded by the
+//! png crate.
+
+use tiny_skia::{Color, FillRule, LineJoin, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+use crate::cube::SqOne;
+use crate::render::{self, Theme};
+
+/// Returns the cube drawn as a PNG image
+/// in the theme. The width of the theme
+/// sets the resolution, defaulting to
+/// one pixel for each SVG unit.
+pub fn png(cube: &SqOne, theme: &Theme) -> Vec<u8> {
+    let drawing = render::draw(cube, theme);
+    let width = (drawing.width * drawing.scale).round().max(1.0) as u32;
+    let height = (drawing.height * drawing.scale).round().max(1.0) as u32;
+    let mut pixmap = Pixmap::new(width, height).expect("the image is at least a pixel");
+    if let Some(background) = &drawing.background {
+        pixmap.fill(parse_color(background));
+    }
+
+    let transform = Transform::from_scale(drawing.scale as f32, drawing.scale as f32);
+    for polygon in &drawing.polygons {
+        let mut path = PathBuilder::new();
+        for (i, &(x, y)) in polygon.points.iter().enumerate() {
+            match i {
+                0 => path.move_to(x as f32, y as f32),
+                _ => path.line_to(x as f32, y as f32),
+            }
+        }
+        path.close();
+        let Some(path) = path.finish() else {
+            continue;
+        };
+
+        let mut paint = Paint { anti_alias: true, ..Paint::default() };
+        paint.set_color(parse_color(&polygon.fill));
+        pixmap.fill_path(&path, &paint, FillRule::Winding, transform, None);
+        paint.set_color(parse_color(&polygon.stroke));
+        let stroke = Stroke { width: polygon.stroke_width as f32, line_join: LineJoin::Round, ..Stroke::default() };
+        pixmap.stroke_path(&path, &paint, &stroke, transform, None);
+    }
+
+    pixmap.encode_png().expect("an image in memory can be encoded")
+}
+
+/// Returns a color written as #rgb,
+/// #rrggbb or #rrggbbaa, or black for
+/// any other color.
+fn parse_color(color: &str) -> Color {
+    let hex = color.strip_prefix('#').unwrap_or("");
+    let digits: Option<Vec<u8>> = hex.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect();
+    let channels: Vec<u8> = match (digits, hex.len()) {
+        (Some(digits), 3) => digits.iter().map(|d| d * 17).collect(),
+        (Some(digits), 6 | 8) => digits.chunks(2).map(|pair| pair[0] * 16 + pair[1]).collect(),
+        _ => vec![0, 0, 0],
+    };
+
+    let channel = |i: usize| channels.get(i).copied().unwrap_or(255);
+    Color::from_rgba8(channel(0), channel(1), channel(2), channel(3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_png() {
+        assert_eq!(parse_color("#ff5900"), Color::from_rgba8(255, 0x59, 0, 255));
+        assert_eq!(parse_color("#fff"), Color::WHITE);
+        assert_eq!(parse_color("red"), Color::BLACK);
+
+        let png = png(&SqOne::new(), &Theme::new().width(135.0).background("#fff"));
+        let image = Pixmap::decode_png(&png).unwrap();
+
+        // The drawing is half the size
+        // it has as an SVG, and compressed
+        // well below its raw pixels.
+        assert_eq!((image.width(), image.height()), (135, 80));
+        assert!(png.len() < image.data().len() / 2);
+        let corner = image.pixel(0, 0).unwrap();
+        assert_eq!((corner.red(), corner.green(), corner.blue(), corner.alpha()), (255, 255, 255, 255));
+    }
+}
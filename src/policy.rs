@@ -0,0 +1,140 @@
+//! Module for the ways a random move
+//! scrambler picks each turn, so other
+//! distributions can be plugged in and
+//! compared with analysis::sample_policy.
+
+use alloc::vec::Vec;
+
+use rand::Rng;
+
+use crate::cube::SqOne;
+
+/// Way of picking the turn before
+/// each slash of a random move
+/// scramble.
+pub trait MovePolicy {
+
+    /// Returns a turn of the top and
+    /// bottom layers after which the cube
+    /// can be slashed. Amounts are from
+    /// -6 to 6.
+    fn turn<R: Rng>(&mut self, cube: &SqOne, rng: &mut R) -> (i8, i8);
+}
+
+/// Policy of SqOne::scramble_rng. Each
+/// layer is turned by an amount drawn
+/// evenly from those that line it up
+/// for a slash, drawing the bottom again
+/// while both amounts are 0.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct UniformLayers;
+
+impl MovePolicy for UniformLayers {
+    fn turn<R: Rng>(&mut self, cube: &SqOne, rng: &mut R) -> (i8, i8) {
+        cube.random_turn(rng)
+    }
+}
+
+/// Draws evenly from every turn after
+/// which the cube can be slashed, so
+/// layers with more ways to line up do
+/// not bias the other layer.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct UniformTurns {
+
+    /// Whether (0, 0) may be drawn,
+    /// giving two slashes in a row.
+    pub allow_zero: bool,
+}
+
+impl MovePolicy for UniformTurns {
+    fn turn<R: Rng>(&mut self, cube: &SqOne, rng: &mut R) -> (i8, i8) {
+        let turns = allowed(cube, self.allow_zero);
+        turns[rng.gen_range(0..turns.len())]
+    }
+}
+
+/// Draws from the turns after which the
+/// cube can be slashed with the chance
+/// of each turn set by its weight. A
+/// turn of weight 0 is only drawn when
+/// every turn has weight 0.
+#[derive(Copy, Clone, Debug)]
+pub struct Weighted {
+    pub weight: fn(i8, i8) -> u32,
+    pub allow_zero: bool,
+}
+
+impl Weighted {
+
+    /// Weights each turn by the total
+    /// amount turned, favoring large
+    /// twists of both layers.
+    pub fn large_twists() -> Weighted {
+        Weighted {
+            weight: |top, bottom| top.unsigned_abs() as u32 + bottom.unsigned_abs() as u32,
+            allow_zero: false,
+        }
+    }
+}
+
+impl MovePolicy for Weighted {
+    fn turn<R: Rng>(&mut self, cube: &SqOne, rng: &mut R) -> (i8, i8) {
+        let turns = allowed(cube, self.allow_zero);
+        let total: u32 = turns.iter().map(|&(top, bottom)| (self.weight)(top, bottom)).sum();
+        if total == 0 {
+            return turns[rng.gen_range(0..turns.len())];
+        }
+
+        let mut pick = rng.gen_range(0..total);
+        for &(top, bottom) in &turns {
+            let weight = (self.weight)(top, bottom);
+            if pick < weight {
+                return (top, bottom);
+            }
+            pick -= weight;
+        }
+        unreachable!()
+    }
+}
+
+/// Returns the turns after which the
+/// cube can be slashed, leaving out
+/// (0, 0) unless allowed or it is the
+/// only turn.
+fn allowed(cube: &SqOne, allow_zero: bool) -> Vec<(i8, i8)> {
+    let turns = cube.slash_turns();
+    if allow_zero || turns.len() == 1 {
+        return turns;
+    }
+
+    turns.into_iter().filter(|&turn| turn != (0, 0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn scramble_with_policies() {
+        let mut rng = StdRng::seed_from_u64(604);
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble_with_policy(&mut UniformTurns::default(), &mut rng);
+        assert_eq!(scramble.len(), 20);
+        assert!(!scramble.turns.contains(&(0, 0)));
+
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble_with_policy(&mut Weighted::large_twists(), &mut rng);
+        assert_eq!(SqOne::new().apply(&scramble), Ok(()));
+
+        // The default policy gives the
+        // scrambles scramble_rng gives.
+        let (mut a, mut b) = (SqOne::new(), SqOne::new());
+        assert_eq!(
+            a.scramble_rng(&mut StdRng::seed_from_u64(1)),
+            b.scramble_with_policy(&mut UniformLayers, &mut StdRng::seed_from_u64(1)),
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Module for handing out scrambles that
+//! never repeat, keeping the fingerprints
+//! of the scrambles given out in a file
+//! so practice servers remember them
+//! across restarts.
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cube::SqOne;
+use crate::fingerprint::{fingerprint, FINGERPRINT_LEN};
+use crate::{Scramble, ScrambleOptions, FILTER_TRIES};
+
+/// Reasons a pool cannot issue
+/// a scramble.
+#[derive(Debug)]
+pub enum PoolError {
+
+    /// The file could not be written.
+    Io(io::Error),
+
+    /// FILTER_TRIES scrambles in a row
+    /// were already given out, as when
+    /// the options allow few cubes and
+    /// the pool holds most of them.
+    Exhausted,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Io(error) => write!(f, "{}", error),
+            PoolError::Exhausted => write!(f, "no new scramble found, reset the pool"),
+        }
+    }
+}
+
+impl Error for PoolError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PoolError::Io(error) => Some(error),
+            PoolError::Exhausted => None,
+        }
+    }
+}
+
+impl From<io::Error> for PoolError {
+    fn from(error: io::Error) -> PoolError {
+        PoolError::Io(error)
+    }
+}
+
+/// Scrambles given out so far, stored as
+/// one fingerprint per line in a file.
+/// Keeping a pool for each user means
+/// no user gets the same scramble twice
+/// until their pool is reset.
+pub struct ScramblePool {
+    path: PathBuf,
+    file: File,
+    options: ScrambleOptions,
+    issued: HashSet<String>,
+}
+
+impl ScramblePool {
+
+    /// Opens the pool stored at the path,
+    /// creating the file if it does not
+    /// exist. Lines that are not
+    /// fingerprints are skipped.
+    pub fn open(path: impl AsRef<Path>, options: ScrambleOptions) -> io::Result<ScramblePool> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+
+        let mut issued = HashSet::new();
+        for line in BufReader::new(&file).lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.len() == FINGERPRINT_LEN && line.chars().all(|c| c.is_ascii_hexdigit()) {
+                issued.insert(line.to_ascii_lowercase());
+            }
+        }
+
+        Ok(ScramblePool { path, file, options, issued })
+    }
+
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of scrambles
+    /// given out since the last reset.
+    pub fn len(&self) -> usize {
+        self.issued.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.issued.is_empty()
+    }
+
+    /// Returns true if the scramble
+    /// has been given out.
+    pub fn contains(&self, scramble: &Scramble) -> bool {
+        fingerprint(scramble).is_ok_and(|print| self.issued.contains(&print))
+    }
+
+    /// Generates a scramble that has not
+    /// been given out, and records it in
+    /// the file before returning it.
+    pub fn issue(&mut self) -> Result<Scramble, PoolError> {
+        for _ in 0..FILTER_TRIES {
+            let scramble = SqOne::new().scramble_with(&self.options);
+            let print = fingerprint(&scramble).expect("generated scrambles are legal");
+            if self.issued.contains(&print) {
+                continue;
+            }
+
+            writeln!(self.file, "{}", print)?;
+            self.file.flush()?;
+            self.issued.insert(print);
+
+            return Ok(scramble);
+        }
+
+        Err(PoolError::Exhausted)
+    }
+
+    /// Forgets every scramble given out,
+    /// emptying the file.
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.file.set_len(0)?;
+        self.issued.clear();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issue_from_pool() {
+        let path = std::env::temp_dir().join(format!("sq1-pool-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let mut pool = ScramblePool::open(&path, ScrambleOptions::new()).unwrap();
+        let first = pool.issue().unwrap();
+        let second = pool.issue().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(pool.len(), 2);
+
+        // The scrambles are remembered
+        // when the pool is opened again.
+        drop(pool);
+        let mut pool = ScramblePool::open(&path, ScrambleOptions::new()).unwrap();
+        assert!(pool.contains(&first) && pool.contains(&second));
+
+        pool.reset().unwrap();
+        assert!(pool.is_empty());
+        pool.issue().unwrap();
+        drop(pool);
+        assert_eq!(ScramblePool::open(&path, ScrambleOptions::new()).unwrap().len(), 1);
+
+        // Options allowing only the 144
+        // twists of a solved cube run out.
+        let twists = ScrambleOptions::new().target_difficulty(..=0).unwrap();
+        let mut pool = ScramblePool::open(&path, twists).unwrap();
+        pool.reset().unwrap();
+        let error = std::iter::repeat_with(|| pool.issue()).find_map(Result::err);
+        assert!(matches!(error, Some(PoolError::Exhausted)));
+        assert_eq!(pool.len(), 144);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+//! Module for observing and cancelling
+//! long running work, such as building
+//! the solver tables or searching for
+//! an optimal solution.
+
+#[cfg(feature = "solver")]
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Parts of the long running work
+/// that report progress.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Stage {
+
+    /// Search over the shapes
+    /// of the cube.
+    ShapeTable,
+
+    /// Moves of the permutation
+    /// coordinates in cube shape.
+    MoveTables,
+
+    /// Pruning table of the corner
+    /// permutations.
+    CornerTable,
+
+    /// Pruning table of the edge
+    /// permutations.
+    EdgeTable,
+
+    /// Pruning tables of the corners
+    /// and edges in every shape, built
+    /// for the optimal solver.
+    LayerTables,
+
+    /// Search for a solution, counting
+    /// the bound reached out of the
+    /// length of a known solution.
+    Search,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Stage::ShapeTable => "shape table",
+            Stage::MoveTables => "move tables",
+            Stage::CornerTable => "corner table",
+            Stage::EdgeTable => "edge table",
+            Stage::LayerTables => "layer tables",
+            Stage::Search => "search",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Receiver of progress reports,
+/// such as a progress bar.
+pub trait ProgressSink {
+
+    /// Called as work goes on with the
+    /// amount done out of total in the
+    /// stage. The last report of a stage
+    /// has done equal to total.
+    fn report(&mut self, stage: Stage, done: usize, total: usize);
+}
+
+/// Ignores every report.
+impl ProgressSink for () {
+    fn report(&mut self, _stage: Stage, _done: usize, _total: usize) {}
+}
+
+impl<F: FnMut(Stage, usize, usize)> ProgressSink for F {
+    fn report(&mut self, stage: Stage, done: usize, total: usize) {
+        self(stage, done, total)
+    }
+}
+
+/// Flag shared with running work that
+/// asks it to stop, either when it is
+/// cancelled or once its deadline has
+/// passed. Clones share the same flag,
+/// so one can be kept to cancel work
+/// given another.
+#[derive(Clone, Default, Debug)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+
+    /// Creates a token that has
+    /// not been cancelled.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Creates a token that is cancelled
+    /// once limit has passed from now.
+    pub fn with_timeout(limit: Duration) -> CancelToken {
+        CancelToken::new().deadline(Instant::now() + limit)
+    }
+
+    /// Returns this token, also cancelled
+    /// once the deadline has passed.
+    pub fn deadline(mut self, deadline: Instant) -> CancelToken {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Asks the work holding this
+    /// token to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the work
+    /// should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// Work stopped because its
+/// token was cancelled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// Calls to Progress::poll between
+/// each look at the token, since
+/// reading the clock for a deadline
+/// costs more than a search step.
+#[cfg(feature = "solver")]
+const POLL_INTERVAL: u32 = 256;
+
+/// Sink and token passed together
+/// through long running work.
+#[cfg(feature = "solver")]
+pub(crate) struct Progress<'a> {
+    sink: &'a mut dyn ProgressSink,
+    cancel: &'a CancelToken,
+    polls: Cell<u32>,
+}
+
+#[cfg(feature = "solver")]
+impl<'a> Progress<'a> {
+
+    /// Pairs a sink with a token. The
+    /// first poll looks at the token.
+    pub(crate) fn new(sink: &'a mut dyn ProgressSink, cancel: &'a CancelToken) -> Progress<'a> {
+        Progress { sink, cancel, polls: Cell::new(POLL_INTERVAL - 1) }
+    }
+
+    /// Reports progress, then returns
+    /// Cancelled if the work should stop.
+    pub(crate) fn report(&mut self, stage: Stage, done: usize, total: usize) -> Result<(), Cancelled> {
+        self.sink.report(stage, done, total);
+        self.check()
+    }
+
+    /// Returns Cancelled if the
+    /// work should stop.
+    pub(crate) fn check(&self) -> Result<(), Cancelled> {
+        if self.cancel.is_cancelled() {
+            return Err(Cancelled);
+        }
+
+        Ok(())
+    }
+
+    /// Checks the token every
+    /// POLL_INTERVAL calls, for use
+    /// inside a search.
+    pub(crate) fn poll(&self) -> Result<(), Cancelled> {
+        let polls = self.polls.get() + 1;
+        if polls < POLL_INTERVAL {
+            self.polls.set(polls);
+            return Ok(());
+        }
+
+        self.polls.set(0);
+        self.check()
+    }
+}
@@ -0,0 +1,200 @@
+//! Module for working with every
+//! puzzle in the crate through one
+//! interface, for timers that support
+//! more than one puzzle.
+
+use alloc::string::{String, ToString};
+#[cfg(feature = "solver")]
+use core::convert::Infallible;
+
+use rand::Rng;
+
+use crate::cstimer::{self, ParseError};
+use crate::cube::{SqOne, SqOneError};
+#[cfg(feature = "solver")]
+use crate::square2::Square2;
+use crate::super_square::{SuperMove, SuperScramble, SuperSqOne};
+use crate::{Move, Scramble};
+
+/// Puzzle that can be scrambled,
+/// moved and checked for being solved,
+/// with a text notation for scrambles.
+pub trait ScramblePuzzle: Sized {
+
+    /// Single move of the puzzle.
+    type Move;
+
+    /// Sequence of moves used to
+    /// scramble the puzzle.
+    type Scramble;
+
+    /// Error returned when a move
+    /// cannot be made.
+    type Error;
+
+    /// Produces a solved puzzle.
+    fn solved() -> Self;
+
+    /// Returns true if the puzzle
+    /// is solved.
+    fn is_solved(&self) -> bool;
+
+    /// Applies a single move, or returns
+    /// an error if it cannot be made.
+    fn apply_move(&mut self, next: Self::Move) -> Result<(), Self::Error>;
+
+    /// Applies every move of the
+    /// scramble in order.
+    fn apply_scramble(&mut self, scramble: &Self::Scramble) -> Result<(), Self::Error>;
+
+    /// Scrambles the puzzle with random
+    /// moves, returning the scramble.
+    fn random_scramble<R: Rng>(&mut self, rng: &mut R) -> Self::Scramble;
+
+    /// Reads a scramble in the notation
+    /// of the puzzle.
+    fn parse_scramble(text: &str) -> Result<Self::Scramble, ParseError>;
+
+    /// Writes a scramble in the notation
+    /// of the puzzle.
+    fn format_scramble(scramble: &Self::Scramble) -> String;
+}
+
+impl ScramblePuzzle for SqOne {
+    type Move = Move;
+    type Scramble = Scramble;
+    type Error = SqOneError;
+
+    fn solved() -> SqOne {
+        SqOne::new()
+    }
+
+    fn is_solved(&self) -> bool {
+        SqOne::is_solved(self)
+    }
+
+    fn apply_move(&mut self, next: Move) -> Result<(), SqOneError> {
+        SqOne::apply_move(self, next)
+    }
+
+    fn apply_scramble(&mut self, scramble: &Scramble) -> Result<(), SqOneError> {
+        self.apply(scramble).map_err(|_| SqOneError::SlashBlocked)
+    }
+
+    fn random_scramble<R: Rng>(&mut self, rng: &mut R) -> Scramble {
+        self.scramble_rng(rng)
+    }
+
+    fn parse_scramble(text: &str) -> Result<Scramble, ParseError> {
+        cstimer::parse(text)
+    }
+
+    fn format_scramble(scramble: &Scramble) -> String {
+        scramble.to_string()
+    }
+}
+
+impl ScramblePuzzle for SuperSqOne {
+    type Move = SuperMove;
+    type Scramble = SuperScramble;
+    type Error = SqOneError;
+
+    fn solved() -> SuperSqOne {
+        SuperSqOne::new()
+    }
+
+    fn is_solved(&self) -> bool {
+        SuperSqOne::is_solved(self)
+    }
+
+    fn apply_move(&mut self, next: SuperMove) -> Result<(), SqOneError> {
+        SuperSqOne::apply_move(self, next)
+    }
+
+    fn apply_scramble(&mut self, scramble: &SuperScramble) -> Result<(), SqOneError> {
+        self.apply(scramble)
+    }
+
+    fn random_scramble<R: Rng>(&mut self, rng: &mut R) -> SuperScramble {
+        self.scramble_rng(rng)
+    }
+
+    fn parse_scramble(text: &str) -> Result<SuperScramble, ParseError> {
+        SuperScramble::parse(text)
+    }
+
+    fn format_scramble(scramble: &SuperScramble) -> String {
+        scramble.to_string()
+    }
+}
+
+#[cfg(feature = "solver")]
+impl ScramblePuzzle for Square2 {
+    type Move = Move;
+    type Scramble = Scramble;
+    type Error = Infallible;
+
+    fn solved() -> Square2 {
+        Square2::new()
+    }
+
+    fn is_solved(&self) -> bool {
+        Square2::is_solved(self)
+    }
+
+    fn apply_move(&mut self, next: Move) -> Result<(), Infallible> {
+        Square2::apply_move(self, next);
+        Ok(())
+    }
+
+    fn apply_scramble(&mut self, scramble: &Scramble) -> Result<(), Infallible> {
+        self.apply(scramble);
+        Ok(())
+    }
+
+    fn random_scramble<R: Rng>(&mut self, rng: &mut R) -> Scramble {
+        self.scramble_rng(rng)
+    }
+
+    fn parse_scramble(text: &str) -> Result<Scramble, ParseError> {
+        cstimer::parse(text)
+    }
+
+    fn format_scramble(scramble: &Scramble) -> String {
+        scramble.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Debug;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Scrambles written in the notation
+    // of each puzzle and read back give
+    // the same puzzle.
+    fn round_trip<P: ScramblePuzzle + PartialEq + Debug>()
+    where
+        P::Error: Debug,
+    {
+        let mut puzzle = P::solved();
+        assert!(puzzle.is_solved());
+        let scramble = puzzle.random_scramble(&mut StdRng::seed_from_u64(11));
+        assert!(!puzzle.is_solved());
+
+        let mut applied = P::solved();
+        let text = P::format_scramble(&scramble);
+        applied.apply_scramble(&P::parse_scramble(&text).unwrap()).unwrap();
+        assert_eq!(applied, puzzle);
+    }
+
+    #[test]
+    fn scramble_every_puzzle() {
+        round_trip::<SqOne>();
+        round_trip::<SuperSqOne>();
+        #[cfg(feature = "solver")]
+        round_trip::<Square2>();
+    }
+}
@@ -0,0 +1,204 @@
+//! Module building the Python extension
+//! sq1_scrambler with PyO3. Build and
+//! install it with maturin, which reads
+//! pyproject.toml:
+//!
+//! ```text
+//! maturin develop --release
+//! ```
+//!
+//! States are written as strings like
+//! in the wasm module.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyType;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::cstimer;
+use crate::cube::SqOne;
+use crate::render;
+use crate::solver;
+use crate::wasm::{format_state, parse_state};
+use crate::{Scramble, ScrambleOptions};
+
+/// A scramble as a list of (top, bottom)
+/// turns with a slash between each.
+#[pyclass(name = "Scramble", module = "sq1_scrambler", eq, frozen)]
+#[derive(Clone, PartialEq)]
+pub struct PyScramble(Scramble);
+
+#[pymethods]
+impl PyScramble {
+
+    /// Reads a scramble in csTimer
+    /// notation.
+    #[new]
+    fn new(text: &str) -> PyResult<PyScramble> {
+        cstimer::parse(text).map(PyScramble).map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// Generates a scramble, where parity
+    /// is "even", "odd" or None and the
+    /// seed picks the scramble if given.
+    #[classmethod]
+    #[pyo3(signature = (wca = false, random_state = false, parity = None, min_slashes = None, seed = None))]
+    fn generate(
+        _cls: &Bound<'_, PyType>,
+        wca: bool,
+        random_state: bool,
+        parity: Option<&str>,
+        min_slashes: Option<usize>,
+        seed: Option<u64>,
+    ) -> PyResult<PyScramble> {
+        let mut options = if wca { ScrambleOptions::wca() } else { ScrambleOptions::new() };
+        if random_state {
+            options = options.random_state(true);
+        }
+        options = match parity {
+            Some("even") => options.require_parity(false),
+            Some("odd") => options.require_parity(true),
+            Some(other) => return Err(PyValueError::new_err(format!("unknown parity {}", other))),
+            None => options,
+        };
+        if let Some(slashes) = min_slashes {
+            options = options.min_slashes(slashes).map_err(|error| PyValueError::new_err(error.to_string()))?;
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed.unwrap_or_else(|| rand::thread_rng().gen()));
+        let scramble = SqOne::new().try_scramble_with_rng(&options, &mut rng);
+        scramble.map(PyScramble).map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// The turns of the scramble.
+    #[getter]
+    fn turns(&self) -> Vec<(i8, i8)> {
+        self.0.iter().copied().collect()
+    }
+
+    /// The number of slashes.
+    #[getter]
+    fn slashes(&self) -> usize {
+        self.0.len().saturating_sub(1)
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.len()
+    }
+
+    fn __str__(&self) -> String {
+        cstimer::format(&self.0)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Scramble('{}')", cstimer::format(&self.0))
+    }
+}
+
+/// A scramble given to SqOne.apply,
+/// either built or as csTimer text.
+#[derive(FromPyObject)]
+enum ScrambleArg {
+    Scramble(PyScramble),
+    Text(String),
+}
+
+/// A Square One in some state.
+#[pyclass(name = "SqOne", module = "sq1_scrambler", frozen)]
+#[derive(Clone)]
+pub struct PySqOne(SqOne);
+
+#[pymethods]
+impl PySqOne {
+
+    /// The state of a solved cube.
+    #[classattr]
+    const SOLVED: &'static str = "00811922a33b/44c55d66e77f/0";
+
+    /// Reads a state, or makes a solved
+    /// cube if none is given.
+    #[new]
+    #[pyo3(signature = (state = None))]
+    fn new(state: Option<&str>) -> PyResult<PySqOne> {
+        match state {
+            Some(state) => parse_state(state).map(PySqOne).map_err(PyValueError::new_err),
+            None => Ok(PySqOne(SqOne::new())),
+        }
+    }
+
+    /// The state written as a string.
+    #[getter]
+    fn state(&self) -> String {
+        format_state(&self.0)
+    }
+
+    /// Returns the cube after the
+    /// scramble.
+    fn apply(&self, scramble: ScrambleArg) -> PyResult<PySqOne> {
+        let scramble = match scramble {
+            ScrambleArg::Scramble(scramble) => scramble.0,
+            ScrambleArg::Text(text) => PyScramble::new(&text)?.0,
+        };
+        let mut cube = self.0.clone();
+        cube.apply(&scramble)
+            .map_err(|slash| PyValueError::new_err(format!("slash {} is blocked", slash + 1)))?;
+
+        Ok(PySqOne(cube))
+    }
+
+    /// Returns a scramble that
+    /// solves the cube.
+    fn solve(&self) -> PyScramble {
+        PyScramble(solver::solve(&self.0))
+    }
+
+    fn is_solved(&self) -> bool {
+        self.0.is_solved()
+    }
+
+    /// Returns the cube drawn as SVG.
+    fn svg(&self) -> String {
+        render::svg(&self.0)
+    }
+
+    fn _repr_svg_(&self) -> String {
+        self.svg()
+    }
+
+    fn __eq__(&self, other: &Bound<'_, PyAny>) -> bool {
+        other.extract::<PySqOne>().is_ok_and(|other| other.0 == self.0)
+    }
+
+    fn __hash__(&self) -> u64 {
+        crate::fnv1a(self.state().as_bytes())
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SqOne('{}')", self.state())
+    }
+}
+
+/// The sq1_scrambler Python module.
+#[pymodule]
+fn sq1_scrambler(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyScramble>()?;
+    module.add_class::<PySqOne>()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_scrambled_state() {
+        let scramble = PyScramble::new("(1,0) / (-3,2) / (0,3)").unwrap();
+        let cube = PySqOne::new(None).unwrap().apply(ScrambleArg::Scramble(scramble)).unwrap();
+        assert!(!cube.is_solved());
+        assert_eq!(PySqOne::new(Some(&cube.state())).unwrap().0, cube.0);
+        let solution = cube.solve();
+        assert!(cube.apply(ScrambleArg::Text(solution.__str__())).unwrap().is_solved());
+    }
+}
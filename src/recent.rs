@@ -0,0 +1,142 @@
+//! Module for keeping scrambles from
+//! repeating over a long practice
+//! session, by remembering the last
+//! scrambles handed out.
+
+use std::collections::VecDeque;
+
+use rand::Rng;
+
+use crate::cube::SqOne;
+use crate::fingerprint;
+use crate::{Scramble, ScrambleOptions, FILTER_TRIES};
+
+/// Remembers the fingerprints of the
+/// last scrambles generated or handed
+/// to it, and generates scrambles that
+/// are none of them. Scrambles count as
+/// the same when Scramble::normalize
+/// gives the same form, so a copy with
+/// moves that cancel still repeats.
+#[derive(Clone, Debug)]
+pub struct RecentFilter {
+    capacity: usize,
+    recent: VecDeque<String>,
+}
+
+impl RecentFilter {
+
+    /// Creates a filter remembering
+    /// the last capacity scrambles.
+    pub fn new(capacity: usize) -> RecentFilter {
+        RecentFilter { capacity, recent: VecDeque::with_capacity(capacity) }
+    }
+
+    /// Returns the number of scrambles
+    /// remembered.
+    pub fn len(&self) -> usize {
+        self.recent.len()
+    }
+
+    /// Returns true if no scramble
+    /// is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    /// Forgets every scramble.
+    pub fn clear(&mut self) {
+        self.recent.clear();
+    }
+
+    /// Returns true if the scramble is
+    /// one of the last remembered. A
+    /// scramble with a blocked slash
+    /// is never recent.
+    pub fn is_recent(&self, scramble: &Scramble) -> bool {
+        key(scramble).is_some_and(|key| self.recent.contains(&key))
+    }
+
+    /// Remembers the scramble, forgetting
+    /// the oldest once at capacity. A
+    /// scramble with a blocked slash is
+    /// not remembered.
+    pub fn remember(&mut self, scramble: &Scramble) {
+        let Some(key) = key(scramble) else {
+            return;
+        };
+        if self.capacity == 0 {
+            return;
+        }
+        if self.recent.len() == self.capacity {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(key);
+    }
+
+    /// Generates a scramble with the
+    /// options that is not recent and
+    /// remembers it.
+    pub fn generate(&mut self, options: &ScrambleOptions) -> Scramble {
+        self.generate_rng(options, &mut options.build_rng())
+    }
+
+    /// Generates a scramble like generate
+    /// drawing from rng. After FILTER_TRIES
+    /// recent scrambles in a row, as when
+    /// the options allow few scrambles, the
+    /// last is kept anyway.
+    pub fn generate_rng<R: Rng>(&mut self, options: &ScrambleOptions, rng: &mut R) -> Scramble {
+        let mut tries = 0;
+        loop {
+            let scramble = SqOne::new().scramble_with_rng(options, rng);
+            tries += 1;
+            if !self.is_recent(&scramble) || tries >= FILTER_TRIES {
+                self.remember(&scramble);
+                return scramble;
+            }
+        }
+    }
+}
+
+/// Returns the fingerprint of the
+/// normal form of the scramble, or None
+/// if a slash of it is blocked.
+fn key(scramble: &Scramble) -> Option<String> {
+    fingerprint::fingerprint(&scramble.normalize().ok()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn skip_recent() {
+        let mut filter = RecentFilter::new(2);
+        let first = Scramble { turns: vec![(1, 0), (-1, 0)] };
+        filter.remember(&first);
+        assert!(filter.is_recent(&first));
+
+        // Slashing twice more cancels out,
+        // so the scramble still repeats.
+        let padded = Scramble { turns: vec![(1, 0), (-1, 0), (0, 0), (0, 0)] };
+        assert!(filter.is_recent(&padded));
+        assert!(!filter.is_recent(&Scramble { turns: vec![(-3, 0), (0, 0)] }));
+
+        filter.remember(&Scramble { turns: vec![(0, 3), (0, 0)] });
+        filter.remember(&Scramble { turns: vec![(3, 0), (0, 0)] });
+        assert_eq!(filter.len(), 2);
+        assert!(!filter.is_recent(&first));
+
+        // The same seed draws the remembered
+        // scramble first, which is skipped.
+        let options = ScrambleOptions::new();
+        let repeat = SqOne::new().scramble_with_rng(&options, &mut StdRng::seed_from_u64(643));
+        filter.remember(&repeat);
+        let next = filter.generate_rng(&options, &mut StdRng::seed_from_u64(643));
+        assert_ne!(next, repeat);
+        assert!(filter.is_recent(&next) && filter.is_recent(&repeat));
+    }
+}
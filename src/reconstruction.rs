@@ -0,0 +1,245 @@
+//! Module for reading solve
+//! reconstructions and counting the
+//! moves spent on each phase of the
+//! solve.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::analysis::{self, Phase};
+use crate::cstimer::{self, ParseError};
+use crate::cube::SqOne;
+use crate::{Move, Scramble};
+
+/// Errors returned when a
+/// reconstruction cannot be read or
+/// its moves cannot be made.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ReconstructionError {
+
+    /// The text holds no scramble.
+    Empty,
+
+    /// The scramble cannot be read.
+    InvalidScramble(ParseError),
+
+    /// A move of the solution is not a
+    /// slash or a turn written as (x,y).
+    InvalidMove(String),
+
+    /// The slash after the turn at this
+    /// index of the scramble is blocked.
+    IllegalScramble(usize),
+
+    /// The move at this index of the
+    /// solution cannot be made.
+    IllegalMove(usize),
+}
+
+impl fmt::Display for ReconstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReconstructionError::Empty => write!(f, "reconstruction has no scramble"),
+            ReconstructionError::InvalidScramble(error) => write!(f, "invalid scramble: {}", error),
+            ReconstructionError::InvalidMove(text) => write!(f, "invalid move {:?}", text),
+            ReconstructionError::IllegalScramble(i) => write!(f, "slash after turn {} of the scramble is blocked", i),
+            ReconstructionError::IllegalMove(i) => write!(f, "move {} of the solution cannot be made", i),
+        }
+    }
+}
+
+impl Error for ReconstructionError {}
+
+/// Line of a solution, with the
+/// comment written after it.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Step {
+    pub moves: Vec<Move>,
+    pub comment: Option<String>,
+}
+
+/// Scramble and solution of a solve.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Reconstruction {
+    pub scramble: Scramble,
+    pub steps: Vec<Step>,
+}
+
+/// Moves spent on a phase.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct PhaseCount {
+    pub phase: Phase,
+
+    /// Twists and slashes, counted
+    /// as in Metric::Twist.
+    pub moves: usize,
+    pub slashes: usize,
+}
+
+/// Moves spent on each phase of a
+/// reconstructed solve.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Analysis {
+
+    /// Phases finished during the
+    /// solve, in order. Phases already
+    /// done by the scramble are left out.
+    pub phases: Vec<PhaseCount>,
+
+    /// Moves made after the last phase
+    /// finished, if the cube was not
+    /// solved by the end.
+    pub unfinished: usize,
+
+    /// Whether parity had to be fixed,
+    /// if edge orientation was reached.
+    pub parity: Option<bool>,
+
+    /// Whether the solution
+    /// solves the cube.
+    pub solved: bool,
+}
+
+impl Reconstruction {
+
+    /// Reads a reconstruction. The first
+    /// line is the scramble and each line
+    /// after it is a step of the solution.
+    /// Anything after // on a line is its
+    /// comment, and blank lines are skipped.
+    pub fn parse(text: &str) -> Result<Reconstruction, ReconstructionError> {
+        let mut lines = text.lines().filter_map(|line| {
+            let (moves, comment) = match line.split_once("//") {
+                Some((moves, comment)) => (moves.trim(), Some(comment.trim().to_string())),
+                None => (line.trim(), None),
+            };
+            (!moves.is_empty() || comment.is_some()).then_some((moves, comment))
+        });
+
+        let scramble = loop {
+            match lines.next() {
+                Some(("", _)) => continue,
+                Some((scramble, _)) => break cstimer::parse(scramble).map_err(ReconstructionError::InvalidScramble)?,
+                None => return Err(ReconstructionError::Empty),
+            }
+        };
+        let steps = lines
+            .map(|(moves, comment)| Ok(Step { moves: parse_moves(moves)?, comment }))
+            .collect::<Result<_, ReconstructionError>>()?;
+
+        Ok(Reconstruction { scramble, steps })
+    }
+
+    /// Returns every move of the
+    /// solution in order.
+    pub fn solution(&self) -> Vec<Move> {
+        self.steps.iter().flat_map(|step| step.moves.iter().copied()).collect()
+    }
+
+    /// Applies the scramble and solution
+    /// to a solved cube, counting the moves
+    /// spent on each phase.
+    pub fn analyze(&self) -> Result<Analysis, ReconstructionError> {
+        let mut cube = SqOne::new();
+        cube.apply(&self.scramble).map_err(ReconstructionError::IllegalScramble)?;
+        let solution = self.solution();
+        let marks = analysis::phases(&solution, &cube).map_err(ReconstructionError::IllegalMove)?;
+
+        let mut counts = vec![];
+        let mut start = 0;
+        for mark in &marks {
+            let moves = &solution[start..mark.index];
+            let slashes = moves.iter().filter(|&&next| next == Move::Slash).count();
+            counts.push(PhaseCount { phase: mark.phase, moves: moves.len(), slashes });
+            start = mark.index;
+        }
+
+        // Parity is read from the cube
+        // when edges are first separated.
+        let separated = marks.iter().find(|mark| mark.phase == Phase::EdgeOrientation).map(|mark| mark.index);
+        let mut parity = None;
+        for (i, &next) in solution.iter().enumerate() {
+            cube.apply_move(next).unwrap();
+            if Some(i + 1) == separated {
+                parity = cube.has_parity();
+            }
+        }
+
+        Ok(Analysis { phases: counts, unfinished: solution.len() - start, parity, solved: cube.is_solved() })
+    }
+}
+
+/// Reads the moves of a line, where
+/// each move is a slash or a turn
+/// written as (x,y). Turns of (0,0)
+/// are left out.
+fn parse_moves(text: &str) -> Result<Vec<Move>, ReconstructionError> {
+    let mut moves = vec![];
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('/') {
+            moves.push(Move::Slash);
+            rest = after;
+        } else {
+            let end = rest.find(')').map_or(rest.len(), |end| end + 1);
+            let turn = cstimer::parse(&rest[..end])
+                .ok()
+                .filter(|scramble| scramble.len() == 1)
+                .ok_or_else(|| ReconstructionError::InvalidMove(rest[..end].to_string()))?;
+
+            // A half turn may be written as
+            // either 6 or -6.
+            let half = |amount: i8| if amount == -6 { 6 } else { amount };
+            moves.extend(turn.moves().into_iter().map(|next| match next {
+                Move::Twist(top, bottom) => Move::Twist(half(top), half(bottom)),
+                next => next,
+            }));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(moves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reconstruction() {
+        let text = "(1,0) / (0,3)\n\n(0,-3)/ // undo\n(-1, 0) (0,0)\n// done\n";
+        let reconstruction = Reconstruction::parse(text).unwrap();
+        assert_eq!(reconstruction.scramble, Scramble { turns: vec![(1, 0), (0, 3)] });
+        assert_eq!(reconstruction.steps.len(), 3);
+        assert_eq!(reconstruction.steps[0].comment.as_deref(), Some("undo"));
+        assert_eq!(reconstruction.solution(), vec![Move::Twist(0, -3), Move::Slash, Move::Twist(-1, 0)]);
+
+        assert_eq!(Reconstruction::parse(" \n"), Err(ReconstructionError::Empty));
+        let invalid = Reconstruction::parse("(1,0)\n(1,0) x");
+        assert_eq!(invalid, Err(ReconstructionError::InvalidMove("x".to_string())));
+    }
+
+    // Solving a scramble with its inverse
+    // finishes every phase.
+    #[test]
+    fn analyze_phases() {
+        let scramble = SqOne::new().scramble();
+        let text = format!("{}\n{}", scramble, scramble.inverse());
+        let analysis = Reconstruction::parse(&text).unwrap().analyze().unwrap();
+        assert!(analysis.solved);
+        assert_eq!(analysis.unfinished, 0);
+        assert_eq!(analysis.phases.last().unwrap().phase, Phase::EdgePermutation);
+        let total: usize = analysis.phases.iter().map(|count| count.moves).sum();
+        assert_eq!(total, scramble.inverse().len_in(crate::Metric::Twist));
+
+        // A twisted bottom layer only
+        // needs the last phase.
+        let analysis = Reconstruction::parse("(0,3)\n(0,-6) (0,3)").unwrap().analyze().unwrap();
+        assert_eq!(analysis.phases, [PhaseCount { phase: Phase::EdgePermutation, moves: 2, slashes: 0 }]);
+        assert_eq!(analysis.parity, None);
+
+        let blocked = Reconstruction::parse("(1,0)\n/ (1,0) /").unwrap();
+        assert_eq!(blocked.analyze(), Err(ReconstructionError::IllegalMove(2)));
+    }
+}
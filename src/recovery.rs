@@ -0,0 +1,167 @@
+//! Module for recovering from a
+//! mis-scramble, by finding the pieces
+//! that differ from the intended cube
+//! and the moves that fix them.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::cube::{Layer, PieceId, SqOne};
+#[cfg(feature = "solver")]
+use crate::Scramble;
+
+/// A piece that is not where the
+/// intended cube has it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Misplaced {
+    pub piece: PieceId,
+
+    /// Layer and first slot of the
+    /// piece on the actual cube.
+    pub actual: (Layer, usize),
+
+    /// Layer and first slot of the
+    /// piece on the intended cube.
+    pub intended: (Layer, usize),
+}
+
+/// Differences between an actual
+/// and an intended cube.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Diff {
+    pub misplaced: Vec<Misplaced>,
+
+    /// Twist of each layer of the actual
+    /// cube, top then bottom, after which
+    /// it matches the intended layer, or
+    /// None if no twist does. A layer that
+    /// already matches has Some(0).
+    pub offsets: [Option<i8>; 2],
+
+    /// Whether the middle layer
+    /// differs.
+    pub middle: bool,
+}
+
+impl Diff {
+
+    /// Returns true if the cubes
+    /// are the same.
+    pub fn is_empty(&self) -> bool {
+        self.misplaced.is_empty() && !self.middle
+    }
+}
+
+impl fmt::Display for Misplaced {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.piece.is_corner() { "corner" } else { "edge" };
+        let layer = |layer: Layer| if layer == Layer::Top { "top" } else { "bottom" };
+        write!(
+            f,
+            "{} {} is in {} slot {} but should be in {} slot {}",
+            kind,
+            self.piece.index(),
+            layer(self.actual.0),
+            self.actual.1,
+            layer(self.intended.0),
+            self.intended.1,
+        )
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "the cubes are the same");
+        }
+        for misplaced in &self.misplaced {
+            writeln!(f, "{}", misplaced)?;
+        }
+        for (name, offset) in ["top", "bottom"].iter().zip(self.offsets) {
+            if let Some(offset) = offset.filter(|&offset| offset != 0) {
+                writeln!(f, "the {} layer matches after a twist of {}", name, offset)?;
+            }
+        }
+        if self.middle {
+            writeln!(f, "the middle layer is flipped")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the pieces of the actual
+/// cube that are not where the intended
+/// cube has them, the twists lining each
+/// layer up, and whether the middle
+/// layer differs.
+pub fn diff(actual: &SqOne, intended: &SqOne) -> Diff {
+    let misplaced = PieceId::all()
+        .map(|piece| Misplaced {
+            piece,
+            actual: actual.position_of(piece),
+            intended: intended.position_of(piece),
+        })
+        .filter(|misplaced| misplaced.actual != misplaced.intended)
+        .collect();
+
+    let (top, bottom, middle) = actual.slots();
+    let (intended_top, intended_bottom, intended_middle) = intended.slots();
+
+    // Twisting the top by t moves the
+    // piece in slot i - t to slot i, and
+    // the bottom the other way round.
+    let offset = |layer: [u8; 12], intended: [u8; 12], sign: i8| {
+        (-5..=6).find(|&twist: &i8| {
+            (0..12).all(|i: i8| layer[(i - sign * twist).rem_euclid(12) as usize] == intended[i as usize])
+        })
+    };
+
+    Diff {
+        misplaced,
+        offsets: [offset(top, intended_top, 1), offset(bottom, intended_bottom, -1)],
+        middle: middle != intended_middle,
+    }
+}
+
+/// Returns a scramble turning the
+/// actual cube into the intended cube.
+#[cfg(feature = "solver")]
+pub fn fix(actual: &SqOne, intended: &SqOne) -> Scramble {
+    Scramble::between(actual, intended)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_of_wrong_twist() {
+        let intended = SqOne::new();
+        let mut actual = SqOne::new();
+        actual.try_twist(3, 0).unwrap();
+
+        // A quarter turn of the top layer
+        // moves its eight pieces.
+        let diff = diff(&actual, &intended);
+        assert_eq!(diff.misplaced.len(), 8);
+        assert!(!diff.middle);
+        assert_eq!(diff.misplaced[0].to_string(), "corner 0 is in top slot 3 but should be in top slot 0");
+        assert_eq!(diff.offsets, [Some(-3), Some(0)]);
+        assert!(diff.to_string().ends_with("the top layer matches after a twist of -3\n"));
+        assert_eq!(actual.diff(&intended), diff);
+        assert!(super::diff(&intended, &intended).is_empty());
+    }
+
+    #[cfg(feature = "solver")]
+    #[test]
+    fn fix_mis_scramble() {
+        let mut intended = SqOne::new();
+        intended.apply(&Scramble { turns: vec![(1, 0), (2, -3), (3, 3)] }).unwrap();
+        let mut actual = SqOne::new();
+        actual.apply(&Scramble { turns: vec![(1, 0), (-1, -3), (3, 3)] }).unwrap();
+
+        actual.apply(&fix(&actual, &intended)).unwrap();
+        assert_eq!(actual, intended);
+    }
+}
@@ -0,0 +1,178 @@
+//! Module for relay events, where one
+//! competitor solves several cubes in a
+//! row, as in 2-5 Square One relay. Each
+//! cube gets its own scramble and label,
+//! and the set is checked as a whole.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::cube::SqOne;
+use crate::notation::ScrambleFormat;
+use crate::{BatchError, Scramble, ScrambleGroup, ScrambleOptions, VerifyError};
+
+/// Reasons the cubes of a relay are
+/// not the ones its scrambles give.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RelayError {
+
+    /// The number of cubes differs from
+    /// the number of scrambles.
+    WrongCount { expected: usize, found: usize },
+
+    /// The scramble of the cube at this
+    /// index does not produce it.
+    Cube(usize, VerifyError),
+}
+
+impl fmt::Display for RelayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayError::WrongCount { expected, found } => {
+                write!(f, "expected {} cubes but found {}", expected, found)
+            }
+            RelayError::Cube(i, VerifyError::IllegalSlash(turn)) => {
+                write!(f, "{}: slash after turn {} is blocked", label(*i), turn)
+            }
+            RelayError::Cube(i, VerifyError::Mismatch(Some(position))) => {
+                write!(f, "{}: scramble gives a different cube from move {}", label(*i), position)
+            }
+            RelayError::Cube(i, VerifyError::Mismatch(None)) => {
+                write!(f, "{}: scramble gives a different cube", label(*i))
+            }
+        }
+    }
+}
+
+impl Error for RelayError {}
+
+/// Scrambles for each cube of a relay,
+/// in the order the cubes are solved.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Relay {
+    scrambles: Vec<Scramble>,
+}
+
+/// Returns the label of the cube at
+/// the index, counting from Cube 1.
+pub fn label(index: usize) -> String {
+    format!("Cube {}", index + 1)
+}
+
+impl Relay {
+
+    /// Generates a relay of the number of
+    /// cubes with the options, where no two
+    /// cubes are scrambled the same way.
+    pub fn generate(cubes: usize, options: &ScrambleOptions) -> Result<Relay, BatchError> {
+        Ok(Relay { scrambles: Scramble::generate_batch(cubes, options)? })
+    }
+
+    /// Generates a relay like generate,
+    /// so the same seed always gives the
+    /// same scrambles.
+    pub fn generate_seeded(cubes: usize, options: &ScrambleOptions, seed: u64) -> Result<Relay, BatchError> {
+        Ok(Relay { scrambles: Scramble::generate_batch_seeded(cubes, options, seed)? })
+    }
+
+    /// Creates a relay of scrambles
+    /// made elsewhere, one per cube.
+    pub fn from_scrambles(scrambles: Vec<Scramble>) -> Relay {
+        Relay { scrambles }
+    }
+
+    /// Returns the scrambles in the
+    /// order the cubes are solved.
+    pub fn scrambles(&self) -> &[Scramble] {
+        &self.scrambles
+    }
+
+    /// Returns the number of cubes.
+    pub fn len(&self) -> usize {
+        self.scrambles.len()
+    }
+
+    /// Returns true if the relay
+    /// has no cubes.
+    pub fn is_empty(&self) -> bool {
+        self.scrambles.is_empty()
+    }
+
+    /// Returns each scramble with the
+    /// label of its cube.
+    pub fn labelled(&self) -> impl Iterator<Item = (String, &Scramble)> {
+        self.scrambles.iter().enumerate().map(|(i, scramble)| (label(i), scramble))
+    }
+
+    /// Returns the scrambles as one group,
+    /// so a relay can be laid out and
+    /// exported like any other round.
+    pub fn group(&self) -> ScrambleGroup {
+        ScrambleGroup { scrambles: self.scrambles.clone(), extras: vec![] }
+    }
+
+    /// Checks that each cube, in order, is
+    /// the one its scramble gives applied
+    /// to a solved cube, stopping at the
+    /// first that is not.
+    pub fn verify(&self, cubes: &[SqOne]) -> Result<(), RelayError> {
+        if cubes.len() != self.scrambles.len() {
+            return Err(RelayError::WrongCount { expected: self.scrambles.len(), found: cubes.len() });
+        }
+        for (i, (scramble, cube)) in self.scrambles.iter().zip(cubes).enumerate() {
+            scramble.verify(cube).map_err(|error| RelayError::Cube(i, error))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns a line for each cube with
+    /// its label and scramble, written
+    /// in the format.
+    pub fn format_with(&self, format: &ScrambleFormat) -> String {
+        self.labelled()
+            .map(|(label, scramble)| format!("{}: {}", label, scramble.format_with(format)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for Relay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format_with(&ScrambleFormat::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relay_round_trip() {
+        let relay = Relay::generate_seeded(5, &ScrambleOptions::new(), 622).unwrap();
+        assert_eq!(Ok(relay.clone()), Relay::generate_seeded(5, &ScrambleOptions::new(), 622));
+        assert_eq!(relay.len(), 5);
+        assert_eq!(relay.group().scrambles, relay.scrambles());
+
+        let mut cubes: Vec<SqOne> = relay.scrambles()
+            .iter()
+            .map(|scramble| {
+                let mut cube = SqOne::new();
+                cube.apply(scramble).unwrap();
+                cube
+            })
+            .collect();
+        assert_eq!(relay.verify(&cubes), Ok(()));
+
+        let text = relay.to_string();
+        assert_eq!(text.lines().count(), 5);
+        assert!(text.lines().nth(2).unwrap().starts_with("Cube 3: "));
+
+        cubes.swap(1, 3);
+        assert_eq!(relay.verify(&cubes), Err(RelayError::Cube(1, VerifyError::Mismatch(None))));
+        assert_eq!(
+            relay.verify(&cubes[..2]),
+            Err(RelayError::WrongCount { expected: 5, found: 2 }),
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Module for drawing a Square One as
+//! an SVG image, with the top layer seen
+//! from above on the left, the bottom
+//! layer seen from below on the right
+//! and the middle layer underneath.
+
+use std::f64::consts::PI;
+use std::fmt::Write;
+
+use crate::cube::{ColorScheme, Layer, PieceId, PieceKind, SqOne};
+
+/// Half the width of a layer,
+/// without its side stickers.
+const RADIUS: f64 = 50.0;
+
+/// Width of the side stickers
+/// around each layer.
+const BAND: f64 = 10.0;
+
+/// Space around and between
+/// the drawings.
+const MARGIN: f64 = 10.0;
+
+/// Angle where slot 0 starts, so the
+/// corners of a solved cube point at
+/// the corners of the drawing.
+const START: f64 = -75.0;
+
+/// Look of the drawing, and which pieces
+/// to emphasize, for svg_with.
+#[derive(Clone, Debug)]
+pub struct Theme {
+
+    /// Colors of the stickers, or None
+    /// for the scheme of the cube.
+    pub scheme: Option<&'static ColorScheme>,
+
+    /// Color of the lines
+    /// between stickers.
+    pub stroke: String,
+    pub stroke_width: f64,
+
+    /// Color filling the image behind
+    /// the cube, or None to leave
+    /// it transparent.
+    pub background: Option<String>,
+
+    /// Width of the image in pixels, or
+    /// None for the size it is drawn at.
+    /// The height keeps the proportions.
+    pub width: Option<f64>,
+
+    /// Pieces outlined in the
+    /// highlight color.
+    pub highlighted: Vec<PieceId>,
+    pub highlight: String,
+
+    /// Pieces drawn in color, or None
+    /// for every piece. The others and
+    /// the middle layer are drawn in
+    /// the gray color.
+    pub shown: Option<Vec<PieceId>>,
+    pub gray: String,
+}
+
+impl Theme {
+
+    /// Creates the default theme,
+    /// as used by svg.
+    pub fn new() -> Theme {
+        Theme::default()
+    }
+
+    /// Sets the colors of
+    /// the stickers.
+    pub fn scheme(mut self, scheme: &'static ColorScheme) -> Self {
+        self.scheme = Some(scheme);
+        self
+    }
+
+    /// Sets the color and width of
+    /// the lines between stickers.
+    pub fn stroke(mut self, color: &str, width: f64) -> Self {
+        self.stroke = color.to_string();
+        self.stroke_width = width;
+        self
+    }
+
+    /// Sets the color behind
+    /// the cube.
+    pub fn background(mut self, color: &str) -> Self {
+        self.background = Some(color.to_string());
+        self
+    }
+
+    /// Sets the width of the
+    /// image in pixels.
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Outlines the pieces in
+    /// the highlight color.
+    pub fn highlight(mut self, pieces: &[PieceId]) -> Self {
+        self.highlighted = pieces.to_vec();
+        self
+    }
+
+    /// Draws only the pieces in color,
+    /// graying out the rest.
+    pub fn only(mut self, pieces: &[PieceId]) -> Self {
+        self.shown = Some(pieces.to_vec());
+        self
+    }
+
+    /// Draws only the corners or only
+    /// the edges in color, as for a
+    /// trainer of corner permutation.
+    pub fn only_kind(self, kind: PieceKind) -> Self {
+        let pieces: Vec<PieceId> = PieceId::all().filter(|id| id.is_corner() == (kind == PieceKind::Corner)).collect();
+        self.only(&pieces)
+    }
+
+    /// Returns the fill color of the
+    /// sticker of a piece.
+    fn fill<'a>(&'a self, id: PieceId, color: &'a str) -> &'a str {
+        match &self.shown {
+            Some(shown) if !shown.contains(&id) => &self.gray,
+            _ => color,
+        }
+    }
+
+    /// Returns the color and width of
+    /// the outline of a piece.
+    fn outline(&self, id: PieceId) -> (&str, f64) {
+        if self.highlighted.contains(&id) {
+            (&self.highlight, 3.0 * self.stroke_width)
+        } else {
+            (&self.stroke, self.stroke_width)
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            scheme: None,
+            stroke: "#000".to_string(),
+            stroke_width: 1.0,
+            background: None,
+            width: None,
+            highlighted: vec![],
+            highlight: "#ff00ff".to_string(),
+            shown: None,
+            gray: "#808080".to_string(),
+        }
+    }
+}
+
+/// Returns the cube drawn as an
+/// SVG image.
+pub fn svg(cube: &SqOne) -> String {
+    svg_with(cube, &Theme::default())
+}
+
+/// Filled polygon of a drawing.
+pub(crate) struct Polygon {
+    pub(crate) points: Vec<(f64, f64)>,
+    pub(crate) fill: String,
+    pub(crate) stroke: String,
+    pub(crate) stroke_width: f64,
+}
+
+/// Cube drawn as polygons, which
+/// are written out as SVG or
+/// rasterized.
+pub(crate) struct Drawing {
+
+    /// Size of the drawing in the
+    /// units of the polygons.
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+
+    /// Pixels for each unit
+    /// of the drawing.
+    pub(crate) scale: f64,
+
+    pub(crate) background: Option<String>,
+    pub(crate) polygons: Vec<Polygon>,
+}
+
+/// Returns the cube drawn in the
+/// theme, in drawing order.
+pub(crate) fn draw(cube: &SqOne, theme: &Theme) -> Drawing {
+    let mut cube = cube.clone();
+    if let Some(scheme) = theme.scheme {
+        cube.set_scheme(scheme);
+    }
+
+    let size = 2.0 * (RADIUS + BAND);
+    let width = 3.0 * MARGIN + 2.0 * size;
+    let height = 3.0 * MARGIN + size + BAND;
+    let scale = theme.width.map_or(1.0, |pixels| pixels / width);
+
+    let mut polygons = vec![];
+    let center = MARGIN + size / 2.0;
+    draw_layer(&mut polygons, &cube, theme, Layer::Top, (center, center));
+    draw_layer(&mut polygons, &cube, theme, Layer::Bottom, (width - center, center));
+
+    // The middle layer is drawn as the
+    // front face, split at the slash.
+    let (left, right) = cube.middle_stickers();
+    let y = 2.0 * MARGIN + size;
+    let half = (width - 2.0 * MARGIN) / 2.0;
+    for (i, color) in [left, right].iter().enumerate() {
+        let color: &str = if theme.shown.is_some() { &theme.gray } else { color };
+        polygons.push(polygon(vec![
+            (MARGIN + half * i as f64, y),
+            (MARGIN + half * (i + 1) as f64, y),
+            (MARGIN + half * (i + 1) as f64, y + BAND),
+            (MARGIN + half * i as f64, y + BAND),
+        ], color, (&theme.stroke, theme.stroke_width)));
+    }
+
+    Drawing { width, height, scale, background: theme.background.clone(), polygons }
+}
+
+/// Returns the cube drawn as an SVG
+/// image in the theme.
+pub fn svg_with(cube: &SqOne, theme: &Theme) -> String {
+    let drawing = draw(cube, theme);
+    let (width, height) = (drawing.width, drawing.height);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        width * drawing.scale, height * drawing.scale, width, height,
+    );
+    if let Some(background) = &drawing.background {
+        write!(svg, "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>", width, height, background).unwrap();
+    }
+    for polygon in &drawing.polygons {
+        let points: Vec<String> = polygon.points.iter().map(|(x, y)| format!("{:.1},{:.1}", x, y)).collect();
+        write!(
+            svg,
+            "<polygon points=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>",
+            points.join(" "), polygon.fill, polygon.stroke, polygon.stroke_width,
+        ).unwrap();
+    }
+    svg.push_str("</svg>");
+
+    svg
+}
+
+/// Returns the point at the distance
+/// and angle in degrees from center.
+fn point(center: (f64, f64), distance: f64, degrees: f64) -> (f64, f64) {
+    let radians = degrees * PI / 180.0;
+    (center.0 + distance * radians.cos(), center.1 + distance * radians.sin())
+}
+
+/// Draws each piece of the layer with
+/// its side stickers around it. The
+/// bottom layer is drawn turning the
+/// other way, as seen from below.
+fn draw_layer(polygons: &mut Vec<Polygon>, cube: &SqOne, theme: &Theme, layer: Layer, center: (f64, f64)) {
+    let direction = if layer == Layer::Top { 1.0 } else { -1.0 };
+    let side = RADIUS / (15.0 * PI / 180.0).cos();
+
+    for sticker in cube.stickers(layer) {
+        let start = START + direction * 30.0 * sticker.slot as f64;
+        let angle = |slots: f64| start + direction * 30.0 * slots;
+
+        // A corner reaches the corner of
+        // the square, between its sides.
+        let outline: Vec<(f64, f64)> = if sticker.corner {
+            vec![(side, angle(0.0)), (RADIUS * 2f64.sqrt(), angle(1.0)), (side, angle(2.0))]
+        } else {
+            vec![(side, angle(0.0)), (side, angle(1.0))]
+        };
+
+        let stroke = theme.outline(sticker.id);
+        let mut face = vec![center];
+        face.extend(outline.iter().map(|&(d, a)| point(center, d, a)));
+        polygons.push(polygon(face, theme.fill(sticker.id, sticker.face), stroke));
+
+        // Each side sticker extends the
+        // outer edge of the piece outwards.
+        let scale = (RADIUS + BAND) / RADIUS;
+        for (i, color) in sticker.sides.iter().enumerate() {
+            let (d0, a0) = outline[i];
+            let (d1, a1) = outline[i + 1];
+            polygons.push(polygon(vec![
+                point(center, d0, a0),
+                point(center, d1, a1),
+                point(center, d1 * scale, a1),
+                point(center, d0 * scale, a0),
+            ], theme.fill(sticker.id, color), stroke));
+        }
+    }
+}
+
+/// Returns a filled polygon outlined
+/// with the stroke color and width.
+fn polygon(points: Vec<(f64, f64)>, color: &str, stroke: (&str, f64)) -> Polygon {
+    Polygon { points, fill: color.to_string(), stroke: stroke.0.to_string(), stroke_width: stroke.1 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draw_solved_cube() {
+        let svg = svg(&SqOne::new());
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+
+        // Each layer has 8 pieces with 12
+        // side stickers, and the middle
+        // layer has 2 halves.
+        assert_eq!(svg.matches("<polygon").count(), 2 * (8 + 12) + 2);
+    }
+
+    #[test]
+    fn draw_in_theme() {
+        let cube = SqOne::new();
+        let theme = Theme::new()
+            .stroke("#222", 2.0)
+            .background("#eee")
+            .width(500.0)
+            .highlight(&[PieceId::new(0).unwrap()])
+            .only_kind(PieceKind::Corner);
+        let svg = svg_with(&cube, &theme);
+        assert!(svg.contains("width=\"500\""));
+        assert!(svg.contains("<rect width="));
+
+        // The highlighted corner has its
+        // face and 2 side stickers outlined.
+        assert_eq!(svg.matches("stroke=\"#ff00ff\" stroke-width=\"6\"").count(), 3);
+
+        // The 8 edges with 1 side sticker
+        // each and the middle are grayed.
+        assert_eq!(svg.matches("fill=\"#808080\"").count(), 8 * 2 + 2);
+        assert_eq!(svg_with(&cube, &Theme::new()), super::svg(&cube));
+    }
+}
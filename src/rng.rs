@@ -0,0 +1,109 @@
+//! Module for choosing the random number
+//! generator behind SqOne::scramble_with,
+//! trading speed against scrambles that
+//! can be reproduced on any platform.
+
+use rand::rngs::{SmallRng, ThreadRng};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Random number generator used to
+/// make scrambles, set with
+/// ScrambleOptions::rng.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub enum RngChoice {
+
+    /// The thread local generator of
+    /// rand, seeded by the system.
+    #[default]
+    Thread,
+
+    /// A fast generator seeded with the
+    /// number, whose output may differ
+    /// across platforms and versions
+    /// of rand.
+    Small(u64),
+
+    /// ChaCha20 seeded with the number,
+    /// whose output is the same on every
+    /// platform, for scrambles that must
+    /// be reproduced elsewhere.
+    ChaCha(u64),
+}
+
+impl RngChoice {
+
+    /// Returns a new generator of this
+    /// choice. Seeded choices give the
+    /// same numbers each time.
+    pub fn build(self) -> ChosenRng {
+        match self {
+            RngChoice::Thread => ChosenRng::Thread(rand::thread_rng()),
+            RngChoice::Small(seed) => ChosenRng::Small(SmallRng::seed_from_u64(seed)),
+            RngChoice::ChaCha(seed) => ChosenRng::ChaCha(Box::new(ChaCha20Rng::seed_from_u64(seed))),
+        }
+    }
+}
+
+/// Generator built from an RngChoice.
+#[derive(Clone, Debug)]
+pub enum ChosenRng {
+    Thread(ThreadRng),
+    Small(SmallRng),
+    ChaCha(Box<ChaCha20Rng>),
+}
+
+impl RngCore for ChosenRng {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            ChosenRng::Thread(rng) => rng.next_u32(),
+            ChosenRng::Small(rng) => rng.next_u32(),
+            ChosenRng::ChaCha(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            ChosenRng::Thread(rng) => rng.next_u64(),
+            ChosenRng::Small(rng) => rng.next_u64(),
+            ChosenRng::ChaCha(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            ChosenRng::Thread(rng) => rng.fill_bytes(dest),
+            ChosenRng::Small(rng) => rng.fill_bytes(dest),
+            ChosenRng::ChaCha(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            ChosenRng::Thread(rng) => rng.try_fill_bytes(dest),
+            ChosenRng::Small(rng) => rng.try_fill_bytes(dest),
+            ChosenRng::ChaCha(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "solver"))]
+mod tests {
+    use super::*;
+    use crate::cube::SqOne;
+    use crate::ScrambleOptions;
+
+    #[test]
+    fn choose_rng() {
+        let options = ScrambleOptions::new().rng(RngChoice::ChaCha(619));
+        let scramble = SqOne::new().scramble_with(&options);
+        assert_eq!(SqOne::new().scramble_with(&options), scramble);
+
+        // ChaCha20 gives the same numbers
+        // on every platform.
+        assert_eq!(RngChoice::ChaCha(0).build().next_u32(), 0x81f5f7b2);
+        let small = ScrambleOptions::new().rng(RngChoice::Small(619));
+        assert_eq!(SqOne::new().scramble_with(&small), SqOne::new().scramble_with(&small));
+        assert_ne!(RngChoice::Thread.build().next_u64(), RngChoice::Thread.build().next_u64());
+    }
+}
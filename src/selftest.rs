@@ -0,0 +1,131 @@
+//! Module for a self test of the
+//! scrambler and solver that competition
+//! software can run and record, showing
+//! that random states are solved and
+//! that their scrambles give them.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::cube::SqOne;
+use crate::{solver, Metric, Scramble};
+
+/// Random state the round trip
+/// failed on.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Failure {
+
+    /// Index of the state among
+    /// those generated.
+    pub index: usize,
+
+    /// The state, written by to_fen.
+    pub state: String,
+    pub solution: Scramble,
+}
+
+/// Results of a round trip test.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Report {
+    pub seed: u64,
+    pub states: usize,
+    pub failures: Vec<Failure>,
+
+    /// Most and total slashes of
+    /// the solutions found.
+    pub max_slashes: usize,
+    pub total_slashes: usize,
+    pub elapsed: Duration,
+}
+
+impl Report {
+
+    /// Returns true if every
+    /// state passed.
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Returns the mean slashes of the
+    /// solutions, or 0 with no states.
+    pub fn mean_slashes(&self) -> f64 {
+        self.total_slashes as f64 / self.states.max(1) as f64
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} states passed with seed {} in {:.3?}",
+            self.states - self.failures.len(),
+            self.states,
+            self.seed,
+            self.elapsed,
+        )?;
+        writeln!(f, "solutions took {:.2} slashes on average and {} at most", self.mean_slashes(), self.max_slashes)?;
+        for failure in &self.failures {
+            writeln!(f, "state {} ({}) failed with {}", failure.index, failure.state, failure.solution)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Generates n random states from the
+/// seed and solves each one, checking
+/// that the solution solves the state
+/// and that its inverse, the scramble a
+/// random state scrambler gives, makes
+/// the state from a solved cube. The
+/// same seed always tests the same
+/// states.
+pub fn roundtrip(n: usize, seed: u64) -> Report {
+    let start = Instant::now();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut report = Report {
+        seed,
+        states: n,
+        failures: vec![],
+        max_slashes: 0,
+        total_slashes: 0,
+        elapsed: Duration::ZERO,
+    };
+
+    for index in 0..n {
+        let state = SqOne::random_state(&mut rng);
+        let solution = solver::solve(&state);
+
+        let mut solved = state.clone();
+        let solves = solved.apply(&solution).is_ok() && solved.is_solved();
+        let scrambles = solution.inverse().verify(&state).is_ok();
+        if !solves || !scrambles {
+            report.failures.push(Failure { index, state: state.to_fen(), solution: solution.clone() });
+        }
+
+        let slashes = if solution.is_empty() { 0 } else { solution.len_in(Metric::Slash) };
+        report.max_slashes = report.max_slashes.max(slashes);
+        report.total_slashes += slashes;
+    }
+    report.elapsed = start.elapsed();
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_states() {
+        let report = roundtrip(20, 617);
+        assert!(report.passed(), "{}", report);
+        assert_eq!(report.states, 20);
+        assert!(report.max_slashes > 0);
+        assert!(report.to_string().starts_with("20 of 20 states passed with seed 617"));
+        assert_eq!(roundtrip(5, 1).total_slashes, roundtrip(5, 1).total_slashes);
+    }
+}
@@ -0,0 +1,337 @@
+//! Module serving scrambles over HTTP
+//! for self-hosted timers. Each
+//! connection is handled on its own
+//! thread, up to MAX_CONNECTIONS at once.
+//!
+//! - `GET /scramble?count=N&seed=S`
+//!   returns `{"scrambles":[...]}` with
+//!   N scrambles (1 by default), the same
+//!   for the same seed.
+//! - `POST /solve` with a scramble in
+//!   csTimer notation as the body returns
+//!   `{"solution":"..."}`.
+//!
+//! Errors return `{"error":"..."}`.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use crate::cube::SqOne;
+use crate::error::read_scramble;
+use crate::export;
+use crate::notation::Dialect;
+use crate::solver;
+use crate::{Scramble, ScrambleOptions};
+
+/// Most scrambles returned by
+/// one request.
+pub const MAX_COUNT: usize = 1000;
+
+/// Most connections handled at once.
+/// Others are answered with 503.
+pub const MAX_CONNECTIONS: usize = 64;
+
+/// Largest request body read, which
+/// is far longer than any scramble.
+const MAX_BODY: usize = 64 * 1024;
+
+/// Largest request line and headers
+/// read, in bytes.
+const MAX_HEADERS: usize = 8 * 1024;
+
+/// Longest wait for a client to send
+/// more of its request, or to read
+/// the response.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Request read from a connection.
+struct Request {
+    method: String,
+    target: String,
+    body: String,
+}
+
+/// Status and message answering a
+/// request that cannot be read.
+type Rejection = (u16, &'static str);
+
+/// Body of a successful GET /scramble.
+#[derive(Serialize)]
+struct Scrambles {
+    scrambles: Vec<String>,
+}
+
+/// Body of a successful POST /solve.
+#[derive(Serialize)]
+struct Solution {
+    solution: String,
+}
+
+/// Body of an error response.
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+/// Serves requests on the address
+/// until the listener fails.
+pub fn serve<A: ToSocketAddrs>(addr: A) -> io::Result<()> {
+    run(TcpListener::bind(addr)?)
+}
+
+/// Serves requests from the listener,
+/// each connection on its own thread.
+fn run(listener: TcpListener) -> io::Result<()> {
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = stream?;
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            let _ = write_response(&mut stream, 503, &error("too many connections"));
+            continue;
+        }
+
+        // A client hanging up early only
+        // ends its own request.
+        let open = Arc::clone(&open);
+        thread::spawn(move || {
+            if let Err(error) = respond(stream) {
+                eprintln!("{}", error);
+            }
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads one request from the stream
+/// and writes the response.
+fn respond(mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    match read_request(&mut BufReader::new(&mut stream))? {
+        Ok(request) => {
+            let (status, json) = handle(&request.method, &request.target, &request.body);
+            write_response(&mut stream, status, &json)
+        },
+
+        // The rest of the request is read
+        // and dropped after the response,
+        // since closing with it unread
+        // resets the connection before the
+        // client sees the response.
+        Err((status, message)) => {
+            write_response(&mut stream, status, &error(message))?;
+            stream.shutdown(Shutdown::Write)?;
+            io::copy(&mut (&stream).take(MAX_BODY as u64), &mut io::sink()).map(|_| ())
+        },
+    }
+}
+
+/// Reads a request, or the rejection
+/// of a request that cannot be read.
+fn read_request<R: BufRead>(reader: &mut R) -> io::Result<Result<Request, Rejection>> {
+    let mut head = vec![];
+    let mut limit = reader.take(MAX_HEADERS as u64);
+    loop {
+        let mut line = String::new();
+        if limit.read_line(&mut line)? == 0 {
+            break;
+        }
+        if !line.ends_with('\n') {
+            if limit.limit() == 0 {
+                return Ok(Err((431, "request headers are too large")));
+            }
+            break;
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        head.push(line);
+    }
+    if head.is_empty() {
+        return Ok(Err((400, "empty request")));
+    }
+
+    let mut length = 0;
+    for header in &head[1..] {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                match value.trim().parse() {
+                    Ok(value) if value <= MAX_BODY => length = value,
+                    Ok(_) => return Ok(Err((413, "request body is too large"))),
+                    Err(_) => return Ok(Err((400, "invalid Content-Length"))),
+                }
+            }
+        }
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = head[0].split_whitespace();
+    Ok(Ok(Request {
+        method: parts.next().unwrap_or("").to_string(),
+        target: parts.next().unwrap_or("").to_string(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+/// Writes a response with the status
+/// and JSON body.
+fn write_response<W: Write>(out: &mut W, status: u16, json: &str) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Service Unavailable",
+    };
+    write!(
+        out,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        json.len(),
+        json,
+    )?;
+    out.flush()
+}
+
+/// Returns the status code and JSON
+/// body answering a request.
+pub fn handle(method: &str, target: &str, body: &str) -> (u16, String) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let result = match (method, path) {
+        ("GET", "/scramble") => scrambles(query),
+        ("POST", "/solve") => solve(body),
+        (_, "/scramble") | (_, "/solve") => return (405, error("method not allowed")),
+        _ => return (404, error("not found")),
+    };
+
+    match result {
+        Ok(json) => (200, json),
+        Err(message) => (400, error(&message)),
+    }
+}
+
+/// Returns a batch of scrambles for
+/// the query parameters.
+fn scrambles(query: &str) -> Result<String, String> {
+    let mut count = 1;
+    let mut seed = None;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match name {
+            "count" => count = value.parse().map_err(|_| format!("invalid count {}", value))?,
+            "seed" => seed = Some(value.parse().map_err(|_| format!("invalid seed {}", value))?),
+            _ => return Err(format!("unknown parameter {}", name)),
+        }
+    }
+    if count == 0 || count > MAX_COUNT {
+        return Err(format!("count must be from 1 to {}", MAX_COUNT));
+    }
+
+    let options = ScrambleOptions::new();
+    let batch = match seed {
+        Some(seed) => Scramble::generate_batch_seeded(count, &options, seed),
+        None => Scramble::generate_batch(count, &options),
+    };
+    let batch = batch.map_err(|error| error.to_string())?;
+
+    Ok(to_json(&Scrambles { scrambles: batch.iter().map(export::wca_string).collect() }))
+}
+
+/// Returns a solution of the cube
+/// the scramble in the body produces.
+fn solve(body: &str) -> Result<String, String> {
+    let scramble = read_scramble(body, Dialect::Standard).map_err(|error| error.to_string())?;
+    let mut cube = SqOne::new();
+    cube.apply(&scramble).expect("read_scramble checks every slash");
+    let solution = solver::solve(&cube);
+
+    Ok(to_json(&Solution { solution: export::wca_string(&solution) }))
+}
+
+/// Returns an error message as JSON.
+fn error(message: &str) -> String {
+    to_json(&ErrorBody { error: message })
+}
+
+/// Returns the body written as JSON.
+fn to_json<T: Serialize>(body: &T) -> String {
+    serde_json::to_string(body).expect("response bodies are plain structs of strings")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstimer;
+
+    #[test]
+    fn seeded_scrambles() {
+        let (status, json) = handle("GET", "/scramble?count=3&seed=5", "");
+        assert_eq!(status, 200);
+        assert!(json.starts_with("{\"scrambles\":[\""));
+        assert_eq!(json.matches("\",\"").count(), 2);
+        assert_eq!(handle("GET", "/scramble?seed=5&count=3", "").1, json);
+    }
+
+    #[test]
+    fn solve_scramble() {
+        let (status, json) = handle("POST", "/solve", "(1,0) / (-3,2) / (0,3)");
+        assert_eq!(status, 200);
+        let solution = json.trim_start_matches("{\"solution\":\"").trim_end_matches("\"}");
+        let mut cube = SqOne::new();
+        cube.apply(&cstimer::parse("(1,0) / (-3,2) / (0,3)").unwrap()).unwrap();
+        cube.apply(&cstimer::parse(solution).unwrap()).unwrap();
+        assert!(cube.is_solved());
+    }
+
+    #[test]
+    fn reject_bad_requests() {
+        assert_eq!(handle("GET", "/scramble?count=0", "").0, 400);
+        assert_eq!(handle("GET", "/scramble?count=x", "").0, 400);
+        assert_eq!(handle("POST", "/scramble", "").0, 405);
+        assert_eq!(handle("GET", "/", "").0, 404);
+        assert_eq!(handle("POST", "/solve", "(\"7,0)"), (400, "{\"error\":\"invalid turn \\\"(\\\\\\\"7,0)\\\" at byte 0\"}".to_string()));
+    }
+
+    /// Sends the request to a server on
+    /// the address and returns the
+    /// status line of the response.
+    fn send(addr: std::net::SocketAddr, request: &[u8]) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request).unwrap();
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).unwrap();
+        response.trim_end().to_string()
+    }
+
+    #[test]
+    fn serve_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || run(listener));
+
+        // A client that never finishes its
+        // request does not hold others up.
+        let _idle = TcpStream::connect(addr).unwrap();
+        let solve = b"POST /solve HTTP/1.1\r\nContent-Length: 5\r\n\r\n(1,0)";
+        assert_eq!(send(addr, solve), "HTTP/1.1 200 OK");
+
+        let huge = format!("GET /scramble HTTP/1.1\r\nX: {}\r\n\r\n", "a".repeat(MAX_HEADERS));
+        assert_eq!(send(addr, huge.as_bytes()), "HTTP/1.1 431 Request Header Fields Too Large");
+        let large = format!("POST /solve HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY + 1);
+        assert_eq!(send(addr, large.as_bytes()), "HTTP/1.1 413 Payload Too Large");
+        assert_eq!(send(addr, b"POST /solve HTTP/1.1\r\nContent-Length: -1\r\n\r\n"), "HTTP/1.1 400 Bad Request");
+    }
+}
@@ -0,0 +1,459 @@
+//! Module for keeping a session of
+//! timed solves, with the averages
+//! timers show and a JSON form for
+//! saving the session.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::error::Error;
+use core::fmt;
+
+use crate::cstimer::{self, ParseError};
+use crate::json::{json_string, parse_json, Json};
+use crate::Scramble;
+
+/// Version of the JSON form written by
+/// Session::to_json. It is raised on any
+/// change older readers cannot follow,
+/// and from_json keeps reading every
+/// earlier version.
+pub const SESSION_VERSION: u32 = 2;
+
+/// Penalty given to a solve.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub enum Penalty {
+    #[default]
+    None,
+
+    /// Two seconds are added
+    /// to the time.
+    PlusTwo,
+
+    /// The solve did not finish.
+    Dnf,
+}
+
+impl Penalty {
+
+    /// Returns the name of the penalty
+    /// in the JSON form of a session.
+    fn name(&self) -> &'static str {
+        match self {
+            Penalty::None => "none",
+            Penalty::PlusTwo => "+2",
+            Penalty::Dnf => "dnf",
+        }
+    }
+}
+
+/// Result of a solve or an average,
+/// ordered with every time before DNF.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Time {
+
+    /// Time in milliseconds.
+    Millis(u32),
+
+    /// Did not finish.
+    Dnf,
+}
+
+// Written the way timers show times,
+// as in 9.87, 1:02.35 or DNF.
+impl fmt::Display for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Time::Millis(ms) => {
+                let centis = (ms + 5) / 10;
+                let (minutes, seconds) = (centis / 6000, centis % 6000);
+                if minutes > 0 {
+                    write!(f, "{}:{:02}.{:02}", minutes, seconds / 100, seconds % 100)
+                } else {
+                    write!(f, "{}.{:02}", seconds / 100, seconds % 100)
+                }
+            },
+            Time::Dnf => write!(f, "DNF"),
+        }
+    }
+}
+
+/// Single timed solve.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Solve {
+    pub scramble: Scramble,
+
+    /// Time on the timer in
+    /// milliseconds, before any
+    /// penalty.
+    pub millis: u32,
+    pub penalty: Penalty,
+
+    /// Seconds since the Unix epoch
+    /// when the solve was done.
+    pub timestamp: u64,
+}
+
+impl Solve {
+
+    /// Returns the result of the solve
+    /// with its penalty applied.
+    pub fn time(&self) -> Time {
+        match self.penalty {
+            Penalty::None => Time::Millis(self.millis),
+            Penalty::PlusTwo => Time::Millis(self.millis.saturating_add(2000)),
+            Penalty::Dnf => Time::Dnf,
+        }
+    }
+}
+
+/// Errors returned when a session
+/// cannot be read from JSON.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum SessionError {
+
+    /// The text is not valid JSON. Holds
+    /// the byte offset of the error.
+    InvalidJson(usize),
+
+    /// A solve is missing the field or
+    /// the field has the wrong type.
+    InvalidField(&'static str),
+
+    /// The scramble of a solve
+    /// cannot be read.
+    InvalidScramble(ParseError),
+
+    /// The session was written in a
+    /// version of the format this crate
+    /// cannot read, such as one newer
+    /// than SESSION_VERSION.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::InvalidJson(offset) => write!(f, "invalid JSON at byte {}", offset),
+            SessionError::InvalidField(field) => write!(f, "missing or invalid field {:?}", field),
+            SessionError::InvalidScramble(error) => write!(f, "invalid scramble: {}", error),
+            SessionError::UnsupportedVersion(version) => {
+                write!(f, "unsupported session format version {}", version)
+            },
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+/// Solves of a session in the order
+/// they were done.
+#[derive(Clone, Eq, PartialEq, Default, Debug)]
+pub struct Session {
+    solves: Vec<Solve>,
+}
+
+impl Session {
+
+    /// Creates an empty session.
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Adds a solve to the end
+    /// of the session.
+    pub fn push(&mut self, solve: Solve) {
+        self.solves.push(solve);
+    }
+
+    /// Returns the solves in the
+    /// order they were done.
+    pub fn solves(&self) -> &[Solve] {
+        &self.solves
+    }
+
+    /// Returns the solves for changing,
+    /// such as to give a penalty.
+    pub fn solves_mut(&mut self) -> &mut [Solve] {
+        &mut self.solves
+    }
+
+    /// Returns the number of solves.
+    pub fn len(&self) -> usize {
+        self.solves.len()
+    }
+
+    /// Returns true if the session
+    /// has no solves.
+    pub fn is_empty(&self) -> bool {
+        self.solves.is_empty()
+    }
+
+    /// Returns the best single,
+    /// or None if there are no solves.
+    pub fn best(&self) -> Option<Time> {
+        self.solves.iter().map(Solve::time).min()
+    }
+
+    /// Returns the average of the last
+    /// count solves, or None if there are
+    /// fewer. The best and worst 5% of the
+    /// solves, rounded up, are left out,
+    /// so one each for an ao5 or ao12. The
+    /// average is a DNF if a DNF is
+    /// not left out.
+    pub fn average_of(&self, count: usize) -> Option<Time> {
+        if count == 0 || self.solves.len() < count {
+            return None;
+        }
+
+        let mut times: Vec<Time> = self.solves[self.solves.len() - count..].iter().map(Solve::time).collect();
+        times.sort();
+        let trim = (count * 5).div_ceil(100);
+        if count <= 2 * trim {
+            return None;
+        }
+
+        mean(&times[trim..count - trim])
+    }
+
+    /// Returns the average of the
+    /// last 5 solves.
+    pub fn ao5(&self) -> Option<Time> {
+        self.average_of(5)
+    }
+
+    /// Returns the average of the
+    /// last 12 solves.
+    pub fn ao12(&self) -> Option<Time> {
+        self.average_of(12)
+    }
+
+    /// Returns the mean of the last
+    /// count solves, or None if there are
+    /// fewer. Any DNF makes it a DNF.
+    pub fn mean_of(&self, count: usize) -> Option<Time> {
+        if count == 0 || self.solves.len() < count {
+            return None;
+        }
+        let times: Vec<Time> = self.solves[self.solves.len() - count..].iter().map(Solve::time).collect();
+
+        mean(&times)
+    }
+
+    /// Returns the mean of every
+    /// finished solve, or None if no
+    /// solve finished.
+    pub fn session_mean(&self) -> Option<Time> {
+        let times: Vec<Time> = self.solves.iter().map(Solve::time).filter(|&time| time != Time::Dnf).collect();
+        if times.is_empty() {
+            return None;
+        }
+
+        mean(&times)
+    }
+
+    /// Returns the session as JSON, with
+    /// the format version and each solve
+    /// holding its scramble, time in
+    /// milliseconds, penalty and
+    /// timestamp.
+    pub fn to_json(&self) -> String {
+        let solves: Vec<String> = self.solves
+            .iter()
+            .map(|solve| {
+                format!(
+                    "{{\"scramble\":{},\"time\":{},\"penalty\":\"{}\",\"timestamp\":{}}}",
+                    json_string(&solve.scramble.to_string()),
+                    solve.millis,
+                    solve.penalty.name(),
+                    solve.timestamp,
+                )
+            })
+            .collect();
+
+        format!("{{\"version\":{},\"solves\":[{}]}}", SESSION_VERSION, solves.join(","))
+    }
+
+    /// Reads a session written by to_json
+    /// in this or any earlier version of
+    /// the crate. Other fields are
+    /// ignored, so sessions may hold more
+    /// about each solve.
+    pub fn from_json(text: &str) -> Result<Session, SessionError> {
+        let value = parse_json(text).map_err(SessionError::InvalidJson)?;
+        match version(&value)? {
+            1..=SESSION_VERSION => (),
+            version => return Err(SessionError::UnsupportedVersion(version)),
+        }
+        let solves = match value.field("solves") {
+            Some(Json::Array(solves)) => solves,
+            _ => return Err(SessionError::InvalidField("solves")),
+        };
+
+        let mut session = Session::new();
+        for solve in solves {
+            let scramble = match solve.field("scramble") {
+                Some(Json::String(text)) => cstimer::parse(text).map_err(SessionError::InvalidScramble)?,
+                _ => return Err(SessionError::InvalidField("scramble")),
+            };
+            let penalty = match solve.field("penalty") {
+                None => Penalty::None,
+                Some(Json::String(name)) => [Penalty::None, Penalty::PlusTwo, Penalty::Dnf]
+                    .into_iter()
+                    .find(|penalty| penalty.name() == name)
+                    .ok_or(SessionError::InvalidField("penalty"))?,
+                Some(_) => return Err(SessionError::InvalidField("penalty")),
+            };
+            session.push(Solve {
+                scramble,
+                millis: solve.integer("time")?,
+                penalty,
+                timestamp: solve.integer("timestamp")?,
+            });
+        }
+
+        Ok(session)
+    }
+}
+
+/// Returns the format version of a
+/// session written by to_json.
+pub fn format_version(text: &str) -> Result<u32, SessionError> {
+    version(&parse_json(text).map_err(SessionError::InvalidJson)?)
+}
+
+/// Rewrites a session written in any
+/// earlier version of the format in the
+/// current one, so saved sessions can be
+/// upgraded once instead of on each
+/// load. Fields other than those of
+/// Solve are dropped.
+pub fn migrate(text: &str) -> Result<String, SessionError> {
+    Session::from_json(text).map(|session| session.to_json())
+}
+
+/// Returns the version field of a
+/// session. Sessions written before the
+/// field was added are version 1.
+fn version(value: &Json) -> Result<u32, SessionError> {
+    match value.field("version") {
+        None => Ok(1),
+        Some(_) => value.integer("version"),
+    }
+}
+
+/// Returns the mean of the times,
+/// rounded to the nearest millisecond,
+/// or a DNF if any time is a DNF.
+fn mean(times: &[Time]) -> Option<Time> {
+    let mut total: u64 = 0;
+    for time in times {
+        match time {
+            Time::Millis(ms) => total += *ms as u64,
+            Time::Dnf => return Some(Time::Dnf),
+        }
+    }
+    let count = times.len() as u64;
+
+    Some(Time::Millis(((total + count / 2) / count) as u32))
+}
+
+impl Json {
+
+    /// Returns the field of an object
+    /// as a whole number.
+    fn integer<T: core::str::FromStr>(&self, name: &'static str) -> Result<T, SessionError> {
+        match self.field(name) {
+            Some(Json::Number(number)) => number.parse().map_err(|_| SessionError::InvalidField(name)),
+            _ => Err(SessionError::InvalidField(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn session(times: &[Time]) -> Session {
+        let mut session = Session::new();
+        for (i, &time) in times.iter().enumerate() {
+            let (millis, penalty) = match time {
+                Time::Millis(ms) => (ms, Penalty::None),
+                Time::Dnf => (10000, Penalty::Dnf),
+            };
+            session.push(Solve {
+                scramble: Scramble { turns: vec![(1, 0), (-3, 2)] },
+                millis,
+                penalty,
+                timestamp: 1_700_000_000 + i as u64,
+            });
+        }
+
+        session
+    }
+
+    #[test]
+    fn compute_averages() {
+        let ms = Time::Millis;
+        let times = [ms(12000), ms(9000), Time::Dnf, ms(11000), ms(10000), ms(13000)];
+        let session = session(&times);
+        assert_eq!(session.best(), Some(ms(9000)));
+        assert_eq!(session.ao5(), Some(ms(11333)));
+        assert_eq!(session.ao12(), None);
+        assert_eq!(session.mean_of(3), Some(ms(11333)));
+        assert_eq!(session.mean_of(4), Some(Time::Dnf));
+        assert_eq!(session.session_mean(), Some(ms(11000)));
+
+        let session = self::session(&[ms(1000), Time::Dnf, Time::Dnf, ms(1000), ms(1000)]);
+        assert_eq!(session.ao5(), Some(Time::Dnf));
+        assert_eq!(ms(62349).to_string(), "1:02.35");
+        assert_eq!(ms(9870).to_string(), "9.87");
+
+        // A +2 on the longest time that can
+        // be stored stays at that time.
+        let mut session = self::session(&[ms(u32::MAX)]);
+        session.solves[0].penalty = Penalty::PlusTwo;
+        assert_eq!(session.solves()[0].time(), ms(u32::MAX));
+    }
+
+    // A session written as JSON and read
+    // back holds the same solves.
+    #[test]
+    fn round_trip_json() {
+        let mut session = session(&[Time::Millis(9871), Time::Dnf]);
+        session.solves[0].penalty = Penalty::PlusTwo;
+        let json = session.to_json();
+        assert_eq!(Session::from_json(&json), Ok(session));
+
+        let text = r#" { "solves" : [ { "scramble": "(1,0)/(0,3)", "time": 5, "timestamp": 7, "note": [null, true] } ] } "#;
+        let read = Session::from_json(text).unwrap();
+        assert_eq!(read.solves()[0].scramble, Scramble { turns: vec![(1, 0), (0, 3)] });
+        assert_eq!(read.solves()[0].penalty, Penalty::None);
+
+        assert_eq!(Session::from_json("{\"solves\":[}"), Err(SessionError::InvalidJson(11)));
+        assert_eq!(Session::from_json("{\"solves\":[{}]}"), Err(SessionError::InvalidField("scramble")));
+    }
+
+    // Sessions written before the format
+    // had a version still load and can be
+    // migrated, and newer ones are refused.
+    #[test]
+    fn migrate_versions() {
+        let old = r#"{"solves":[{"scramble":"(1,0)/(-3,2)","time":9871,"penalty":"+2","timestamp":1700000000}]}"#;
+        assert_eq!(format_version(old), Ok(1));
+        let session = Session::from_json(old).unwrap();
+        assert_eq!(session.solves()[0].time(), Time::Millis(11871));
+
+        let migrated = migrate(old).unwrap();
+        assert!(migrated.starts_with("{\"version\":2,"));
+        assert_eq!(format_version(&migrated), Ok(SESSION_VERSION));
+        assert_eq!(Session::from_json(&migrated), Ok(session));
+        assert_eq!(migrate(&migrated).as_deref(), Ok(migrated.as_str()));
+
+        let newer = migrated.replace("\"version\":2", "\"version\":3");
+        assert_eq!(Session::from_json(&newer), Err(SessionError::UnsupportedVersion(3)));
+        assert_eq!(format_version("{\"version\":\"2\"}"), Err(SessionError::InvalidField("version")));
+    }
+}
@@ -0,0 +1,402 @@
+//! Module for classifying the shapes
+//! of the top and bottom layers of a
+//! Square One into their common names.
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "solver")]
+use std::collections::HashMap;
+
+#[cfg(feature = "solver")]
+use rand::Rng;
+
+use crate::cube::SqOne;
+#[cfg(feature = "solver")]
+use crate::solver::Position;
+
+/// Shape of a single layer, ignoring
+/// how far the layer is twisted. Each
+/// shape is described by the number of
+/// edges between consecutive corners,
+/// reading clockwise.
+///
+/// Layers with three or two corners have
+/// no common names. They are described by
+/// the edges between their corners, and
+/// written as the sizes of their groups
+/// of edges, as in 4-2.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Shape {
+
+    /// Six corners and no edges.
+    Star,
+
+    /// Five corners and two edges
+    /// next to each other.
+    Pair,
+
+    /// Five corners and two edges with
+    /// a corner between them.
+    L,
+
+    /// Five corners and two edges with
+    /// two corners between them on one
+    /// side and three on the other.
+    Line,
+
+    /// Edges between corners 1-1-1-1.
+    Square,
+
+    /// Edges between corners 0-1-2-1.
+    Kite,
+
+    /// Edges between corners 0-2-0-2.
+    Barrel,
+
+    /// Edges between corners 0-0-2-2.
+    Shield,
+
+    /// Edges between corners 0-0-0-4.
+    Scallop,
+
+    /// Edges between corners 0-1-0-3.
+    Mushroom,
+
+    /// Edges between corners 0-0-1-3.
+    LeftFist,
+
+    /// Edges between corners 0-0-3-1.
+    RightFist,
+
+    /// Edges between corners 0-1-1-2.
+    LeftPawn,
+
+    /// Edges between corners 0-2-1-1.
+    RightPawn,
+
+    /// Three corners and six edges, with
+    /// the edges between the corners
+    /// starting from the smallest gap.
+    ThreeCorners { gaps: [u8; 3] },
+
+    /// Two corners and eight edges, with
+    /// gap edges between the corners on
+    /// the shorter side (0 to 4).
+    TwoCorners { gap: u8 },
+}
+
+/// Shapes of both layers of a cube.
+/// The bottom layer is classified as
+/// seen from below, so each layer keeps
+/// its name when the cube is turned
+/// upside down.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ShapeState {
+    pub top: Shape,
+    pub bottom: Shape,
+}
+
+impl ShapeState {
+
+    /// Returns the shapes seen after
+    /// turning the cube upside down.
+    pub fn flipped(&self) -> ShapeState {
+        ShapeState { top: self.bottom, bottom: self.top }
+    }
+
+    /// Returns true if both layers
+    /// are squares.
+    pub fn is_cube(&self) -> bool {
+        self.top == Shape::Square && self.bottom == Shape::Square
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shape::ThreeCorners { gaps } => {
+                let groups: Vec<String> = gaps.iter().filter(|&&edges| edges > 0).map(u8::to_string).collect();
+                f.write_str(&groups.join("-"))
+            },
+            Shape::TwoCorners { gap: 0 } => f.write_str("8"),
+            Shape::TwoCorners { gap } => write!(f, "{}-{}", 8 - gap, gap),
+            shape => write!(f, "{:?}", shape),
+        }
+    }
+}
+
+// Written as the top and bottom shapes
+// joined by a dash, as in Kite-Scallop.
+impl fmt::Display for ShapeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.top, self.bottom)
+    }
+}
+
+/// Returns the shapes of the top
+/// and bottom layers of the cube.
+pub fn classify(cube: &SqOne) -> ShapeState {
+    let (top, bottom, _) = cube.slots();
+    classify_slots(&top, &bottom)
+}
+
+/// Returns the shapes of the layers
+/// given the piece in each slot.
+pub(crate) fn classify_slots(top: &[u8; 12], bottom: &[u8; 12]) -> ShapeState {
+    let mut bottom = *bottom;
+    bottom.reverse();
+
+    ShapeState {
+        top: classify_layer(top),
+        bottom: classify_layer(&bottom),
+    }
+}
+
+/// Returns every reachable shape
+/// class with the number of slashes
+/// needed to return to cube shape,
+/// sorted by distance. A class and
+/// its upside down version are only
+/// listed once.
+#[cfg(feature = "solver")]
+pub fn all_shapes() -> Vec<(ShapeState, u8)> {
+    let mut shapes: Vec<(ShapeState, u8)> = explore(Position::solved())
+        .into_iter()
+        .filter(|(state, _)| *state <= state.flipped())
+        .map(|(state, (_, depth))| (state, depth))
+        .collect();
+    shapes.sort_by_key(|&(state, depth)| (depth, state));
+
+    shapes
+}
+
+/// Finds every shape reachable from
+/// the start, with a position of that
+/// shape and the number of slashes
+/// needed to reach it.
+#[cfg(feature = "solver")]
+pub(crate) fn explore(start: Position) -> HashMap<ShapeState, (Position, u8)> {
+    let mut found = HashMap::new();
+    let mut frontier = vec![start];
+    let mut depth = 0;
+
+    // Shapes ignore how far each layer
+    // is twisted, so every twist that
+    // allows a slash is tried from each
+    // newly found shape.
+    while !frontier.is_empty() {
+        let mut next_frontier = vec![];
+        for position in frontier {
+            let state = classify_slots(&position.top, &position.bottom);
+            if found.contains_key(&state) {
+                continue;
+            }
+            found.insert(state, (position, depth));
+            for top in 0..12 {
+                for bottom in 0..12 {
+                    let next = position.twist(top, bottom);
+                    if next.can_slash() {
+                        next_frontier.push(next.slash());
+                    }
+                }
+            }
+        }
+        frontier = next_frontier;
+        depth += 1;
+    }
+
+    found
+}
+
+/// Returns the turns of a shortest
+/// sequence of slashes from the start
+/// to the target shape, choosing at
+/// random between equally short turns,
+/// or None if the target is not
+/// reachable.
+#[cfg(feature = "solver")]
+pub(crate) fn path_to<R: Rng>(start: Position, target: ShapeState, rng: &mut R) -> Option<Vec<(i8, i8)>> {
+    let (goal, _) = *explore(Position::solved()).get(&target)?;
+    let distances = explore(goal);
+
+    let mut position = start;
+    let mut depth = distances[&classify_slots(&position.top, &position.bottom)].1;
+    let mut turns = vec![];
+    while depth > 0 {
+        let mut candidates = vec![];
+        for top in -5..=6 {
+            for bottom in -5..=6 {
+                let next = position.twist(top, bottom);
+                if !next.can_slash() {
+                    continue;
+                }
+                let next = next.slash();
+                let state = classify_slots(&next.top, &next.bottom);
+                if distances[&state].1 == depth - 1 {
+                    candidates.push(((top, bottom), next));
+                }
+            }
+        }
+        let (turn, next) = candidates[rng.gen_range(0..candidates.len())];
+        turns.push(turn);
+        position = next;
+        depth -= 1;
+    }
+
+    Some(turns)
+}
+
+/// Returns the shape of a layer given
+/// the piece in each slot, where pieces
+/// below 8 are corners.
+pub(crate) fn classify_layer(layer: &[u8; 12]) -> Shape {
+
+    // Count the edges after each
+    // corner, starting from the first
+    // corner in the layer.
+    let first = (0..12)
+        .find(|&i| layer[i] < 8 && layer[(i + 11) % 12] != layer[i])
+        .unwrap();
+    let mut gaps = vec![];
+    for i in 0..12 {
+        let slot = (first + i) % 12;
+        if layer[slot] == layer[(slot + 11) % 12] {
+            continue;
+        }
+        if layer[slot] < 8 {
+            gaps.push(0);
+        } else {
+            *gaps.last_mut().unwrap() += 1;
+        }
+    }
+
+    // Rotate the gaps to start from
+    // the smallest reading.
+    let gaps = (0..gaps.len())
+        .map(|i| [&gaps[i..], &gaps[..i]].concat())
+        .min()
+        .unwrap();
+
+    match gaps[..] {
+        [_, _, _, _, _, _] => Shape::Star,
+        [_, _, _, _, _] => {
+            let edges: Vec<usize> = (0..5).filter(|&i| gaps[i] > 0).collect();
+            match edges[..] {
+                [_] => Shape::Pair,
+                [a, b] if (b - a).min(5 - (b - a)) == 1 => Shape::L,
+                [_, _] => Shape::Line,
+                _ => unreachable!(),
+            }
+        },
+        [1, 1, 1, 1] => Shape::Square,
+        [0, 1, 2, 1] => Shape::Kite,
+        [0, 2, 0, 2] => Shape::Barrel,
+        [0, 0, 2, 2] => Shape::Shield,
+        [0, 0, 0, 4] => Shape::Scallop,
+        [0, 1, 0, 3] => Shape::Mushroom,
+        [0, 0, 1, 3] => Shape::LeftFist,
+        [0, 0, 3, 1] => Shape::RightFist,
+        [0, 1, 1, 2] => Shape::LeftPawn,
+        [0, 2, 1, 1] => Shape::RightPawn,
+        [a, b, c] => Shape::ThreeCorners { gaps: [a, b, c] },
+        [a, b] => Shape::TwoCorners { gap: a.min(b) },
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_solved_cube() {
+        let state = classify(&SqOne::new());
+        assert_eq!(state, ShapeState { top: Shape::Square, bottom: Shape::Square });
+    }
+
+    #[test]
+    fn classify_slashed_cube() {
+        let mut cube = SqOne::new();
+        cube.try_slash().unwrap();
+        let state = classify(&cube);
+        assert_eq!(state, ShapeState { top: Shape::Kite, bottom: Shape::Kite });
+    }
+
+    // Every pair of layers with eight
+    // corners between them is reachable.
+    #[cfg(feature = "solver")]
+    #[test]
+    fn enumerate_all_shapes() {
+        let shapes = all_shapes();
+        assert_eq!(shapes.len(), 90);
+        assert_eq!(shapes[0], (classify(&SqOne::new()), 0));
+        assert!(shapes.iter().skip(1).all(|&(state, depth)| !state.is_cube() && depth > 0));
+    }
+
+    #[test]
+    fn classify_mirrored_layers() {
+        let fist = [0, 0, 1, 1, 2, 2, 8, 3, 3, 9, 10, 11];
+        assert_eq!(classify_layer(&fist), Shape::LeftFist);
+
+        let mut mirrored = fist;
+        mirrored.reverse();
+        assert_eq!(classify_layer(&mirrored), Shape::RightFist);
+    }
+
+    #[test]
+    fn classify_uncommon_layers() {
+        let star = [0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5];
+        assert_eq!(classify_layer(&star), Shape::Star);
+
+        let five = [0, 0, 8, 1, 1, 2, 2, 9, 3, 3, 4, 4];
+        assert_eq!(classify_layer(&five), Shape::Line);
+        let five = [0, 0, 8, 1, 1, 9, 2, 2, 3, 3, 4, 4];
+        assert_eq!(classify_layer(&five), Shape::L);
+        let five = [0, 0, 8, 9, 1, 1, 2, 2, 3, 3, 4, 4];
+        assert_eq!(classify_layer(&five), Shape::Pair);
+
+        let three = [8, 0, 0, 9, 10, 1, 1, 11, 12, 13, 2, 2];
+        assert_eq!(classify_layer(&three), Shape::ThreeCorners { gaps: [1, 2, 3] });
+
+        let two = [8, 0, 0, 9, 10, 11, 1, 1, 12, 13, 14, 15];
+        assert_eq!(classify_layer(&two), Shape::TwoCorners { gap: 3 });
+    }
+
+    #[test]
+    fn write_shape_names() {
+        let state = ShapeState { top: Shape::Kite, bottom: Shape::Scallop };
+        assert_eq!(state.to_string(), "Kite-Scallop");
+        assert_eq!(Shape::ThreeCorners { gaps: [1, 2, 3] }.to_string(), "1-2-3");
+        assert_eq!(Shape::ThreeCorners { gaps: [0, 0, 6] }.to_string(), "6");
+        assert_eq!(Shape::TwoCorners { gap: 0 }.to_string(), "8");
+        assert_eq!(Shape::TwoCorners { gap: 3 }.to_string(), "5-3");
+    }
+
+    // Of the 29 layer shapes, only these
+    // have no common name.
+    #[cfg(feature = "solver")]
+    #[test]
+    fn list_unnamed_layers() {
+        let mut layers: Vec<Shape> = all_shapes().iter().flat_map(|(state, _)| [state.top, state.bottom]).collect();
+        layers.sort();
+        layers.dedup();
+        assert_eq!(layers.len(), 29);
+
+        let unnamed: Vec<Shape> = layers
+            .into_iter()
+            .filter(|shape| matches!(shape, Shape::ThreeCorners { .. } | Shape::TwoCorners { .. }))
+            .collect();
+        let three = |gaps| Shape::ThreeCorners { gaps };
+        let two = |gap| Shape::TwoCorners { gap };
+        assert_eq!(
+            unnamed,
+            [
+                three([0, 0, 6]), three([0, 1, 5]), three([0, 2, 4]), three([0, 3, 3]), three([0, 4, 2]),
+                three([0, 5, 1]), three([1, 1, 4]), three([1, 2, 3]), three([1, 3, 2]), three([2, 2, 2]),
+                two(0), two(1), two(2), two(3), two(4),
+            ],
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Module for laying out scrambles as
+//! printable HTML scramble sheets, with
+//! the scrambled state drawn next to
+//! each scramble. Printing the page
+//! from a browser gives a PDF.
+
+use std::fmt::Write;
+
+use crate::cube::SqOne;
+use crate::{export, render, ScrambleGroup, VerifyError};
+
+/// Styles of the sheet. Each group
+/// starts on a new page.
+const STYLE: &str = "\
+body{font-family:sans-serif;margin:0}\
+section{page-break-after:always;padding:1cm}\
+h1{font-size:18pt;margin:0 0 .5cm}\
+table{border-collapse:collapse;width:100%}\
+td{border:1px solid #000;padding:4px 8px;font-size:14pt}\
+td.number{width:1.5cm;text-align:center;font-weight:bold}\
+td.state{width:5cm}\
+td.state svg{width:5cm;height:auto}";
+
+/// Returns the text with the characters
+/// HTML treats specially escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Returns a printable HTML page with a
+/// section for each group, headed by the
+/// title and group letter. Each scramble
+/// is numbered and each extra is numbered
+/// E1, E2 and so on, with the state the
+/// scramble gives drawn beside it.
+pub fn html(title: &str, groups: &[ScrambleGroup]) -> Result<String, VerifyError> {
+    let title = escape(title);
+    let mut html = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title><style>{}</style></head><body>",
+        title, STYLE,
+    );
+
+    for (i, group) in groups.iter().enumerate() {
+        let letter = (b'A' + (i % 26) as u8) as char;
+        write!(html, "<section><h1>{} Group {}</h1><table>", title, letter).unwrap();
+
+        let numbered = group.scrambles
+            .iter()
+            .enumerate()
+            .map(|(j, scramble)| ((j + 1).to_string(), scramble))
+            .chain(group.extras.iter().enumerate().map(|(j, scramble)| (format!("E{}", j + 1), scramble)));
+        for (number, scramble) in numbered {
+            let mut cube = SqOne::new();
+            cube.apply(scramble).map_err(VerifyError::IllegalSlash)?;
+            write!(
+                html,
+                "<tr><td class=\"number\">{}</td><td>{}</td><td class=\"state\">{}</td></tr>",
+                number,
+                escape(&export::wca_string(scramble)),
+                render::svg(&cube),
+            )
+            .unwrap();
+        }
+        html.push_str("</table></section>");
+    }
+    html.push_str("</body></html>");
+
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Scramble, ScrambleOptions};
+
+    #[test]
+    fn write_scramble_sheet() {
+        let groups = Scramble::generate_groups(2, 5, 2, &ScrambleOptions::new()).unwrap();
+        let html = html("Square-1 <Round 1>", &groups).unwrap();
+        assert_eq!(html.matches("<section>").count(), 2);
+        assert_eq!(html.matches("<svg").count(), 14);
+        assert!(html.contains("<h1>Square-1 &lt;Round 1&gt; Group B</h1>"));
+        assert!(html.contains("<td class=\"number\">E2</td>"));
+
+        let illegal = Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] };
+        let group = ScrambleGroup { scrambles: vec![illegal], extras: vec![] };
+        assert_eq!(self::html("", &[group]), Err(VerifyError::IllegalSlash(2)));
+    }
+}
@@ -0,0 +1,2530 @@
+//! Module for finding move sequences
+//! that solve a Square One. Provides
+//! a fast two-phase solver and an
+//! optimal IDA* solver sharing the
+//! same pruning tables.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::cube::SqOne;
+use crate::fen;
+use crate::progress::{CancelToken, Cancelled, Progress, ProgressSink, Stage};
+use crate::json;
+use crate::{map_batch, Metric, Scramble, MAX_SLASHES};
+
+/// Returns the value of a twist
+/// amount in the standard notation
+/// range of -5 to 6.
+fn normalize(amount: i8) -> i8 {
+    (amount + 5).rem_euclid(12) - 5
+}
+
+/// Compact representation of a
+/// Square One used while searching.
+/// Each physical slot holds the index
+/// of the piece covering it, so corners
+/// fill two consecutive slots.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct Position {
+    pub(crate) top: [u8; 12],
+    pub(crate) bottom: [u8; 12],
+    pub(crate) middle: bool,
+}
+
+/// Order in which the pieces of a
+/// solved cube appear when reading
+/// each layer from slot 0.
+const SOLVED_SEQUENCE: [u8; 16] = [
+    0, 8, 1, 9, 2, 10, 3, 11,
+    4, 12, 5, 13, 6, 14, 7, 15,
+];
+
+impl Position {
+
+    /// Returns the position of a
+    /// solved cube.
+    pub(crate) fn solved() -> Position {
+        Position::from(&SqOne::new())
+    }
+
+    /// Twists the top layer by top and
+    /// the bottom layer by bottom using
+    /// the standard Square One notation.
+    pub(crate) fn twist(&self, top: i8, bottom: i8) -> Position {
+        let mut next = *self;
+        for i in 0..12 {
+            next.top[i] = self.top[(i as i8 - top).rem_euclid(12) as usize];
+            next.bottom[i] = self.bottom[(i as i8 + bottom).rem_euclid(12) as usize];
+        }
+
+        next
+    }
+
+    /// Returns true if no corner
+    /// crosses the slash line in
+    /// either layer.
+    pub(crate) fn can_slash(&self) -> bool {
+        self.can_slash_top() && self.can_slash_bottom()
+    }
+
+    /// Returns true if no corner crosses
+    /// the slash line in the top layer.
+    fn can_slash_top(&self) -> bool {
+        self.top[0] != self.top[11] && self.top[6] != self.top[5]
+    }
+
+    /// Returns true if no corner crosses
+    /// the slash line in the bottom layer.
+    fn can_slash_bottom(&self) -> bool {
+        self.bottom[0] != self.bottom[11] && self.bottom[6] != self.bottom[5]
+    }
+
+    /// Swaps the right half of the
+    /// top and bottom layers. The
+    /// caller must check can_slash.
+    pub(crate) fn slash(&self) -> Position {
+        let mut next = *self;
+        for k in 0..6 {
+            next.top[6 + k] = self.bottom[11 - k];
+            next.bottom[11 - k] = self.top[6 + k];
+        }
+        next.middle = !self.middle;
+
+        next
+    }
+
+    /// Returns a bitmask of the slots
+    /// where a piece starts, with the
+    /// top layer in the low 12 bits.
+    pub(crate) fn shape(&self) -> u32 {
+        let mut mask = 0;
+        for i in 0..12 {
+            if self.top[i] != self.top[(i + 11) % 12] {
+                mask |= 1 << i;
+            }
+            if self.bottom[i] != self.bottom[(i + 11) % 12] {
+                mask |= 1 << (i + 12);
+            }
+        }
+
+        mask
+    }
+
+    /// Returns the pieces in the order
+    /// they appear reading the top and
+    /// then the bottom layer from slot 0.
+    fn sequence(&self) -> [u8; 16] {
+        let mut sequence = [0; 16];
+        let mut n = 0;
+        for layer in [&self.top, &self.bottom] {
+            for i in 0..12 {
+                if layer[i] != layer[(i + 11) % 12] {
+                    sequence[n] = layer[i];
+                    n += 1;
+                }
+            }
+        }
+
+        sequence
+    }
+
+    /// Returns true if the piece sequence
+    /// is an odd permutation of the
+    /// solved sequence.
+    fn parity(&self) -> bool {
+        let mut rank = [0; 16];
+        for (i, &piece) in SOLVED_SEQUENCE.iter().enumerate() {
+            rank[piece as usize] = i;
+        }
+
+        let sequence = self.sequence();
+        let mut odd = false;
+        for i in 0..16 {
+            for j in i + 1..16 {
+                if rank[sequence[i] as usize] > rank[sequence[j] as usize] {
+                    odd = !odd;
+                }
+            }
+        }
+
+        odd
+    }
+
+    /// Returns the key used to look up
+    /// the position in the shape table.
+    fn shape_key(&self) -> u32 {
+        self.shape() | (self.parity() as u32) << 24
+    }
+
+    /// Returns the twist that solves the
+    /// position, if the position is only
+    /// a single twist away from solved.
+    fn solving_twist(&self, solved: &Position) -> Option<(i8, i8)> {
+        if self.middle {
+            return None;
+        }
+
+        // Find where the first corner of
+        // each layer starts and rotate it
+        // back to slot 0.
+        let start = |layer: &[u8; 12], piece: u8| {
+            (0..12).find(|&i| layer[i] == piece && layer[(i + 11) % 12] != piece)
+        };
+        let top = start(&self.top, solved.top[0])? as i8;
+        let bottom = start(&self.bottom, solved.bottom[0])? as i8;
+        let twist = (normalize(-top), normalize(bottom));
+
+        if self.twist(twist.0, twist.1) == *solved {
+            Some(twist)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<&SqOne> for Position {
+    fn from(cube: &SqOne) -> Position {
+        let (top, bottom, middle) = cube.slots();
+        Position { top, bottom, middle }
+    }
+}
+
+/// Returns the lexicographic rank
+/// of a permutation of 8 elements.
+fn perm_rank(perm: &[u8; 8]) -> u16 {
+    let mut rank = 0;
+    for i in 0..8 {
+        let smaller = perm[i + 1..].iter().filter(|&&p| p < perm[i]).count();
+        rank = rank * (8 - i as u16) + smaller as u16;
+    }
+
+    rank
+}
+
+/// Returns the permutation of 8
+/// elements with the given rank.
+fn perm_unrank(rank: u16) -> [u8; 8] {
+    let mut digits = [0; 8];
+    let mut rank = rank;
+    for i in (0..8).rev() {
+        digits[i] = (rank % (8 - i as u16)) as u8;
+        rank /= 8 - i as u16;
+    }
+
+    let mut remaining: Vec<u8> = (0..8).collect();
+    let mut perm = [0; 8];
+    for i in 0..8 {
+        perm[i] = remaining.remove(digits[i] as usize);
+    }
+
+    perm
+}
+
+/// Number of permutations of the 8
+/// corners or the 8 edges.
+const PERMS: usize = 40320;
+
+/// Number of ways to choose which 4
+/// of the 8 corner or edge slots hold
+/// pieces from the top layer.
+const SPLITS: usize = 70;
+
+/// Returns the index of the split of
+/// top and bottom layer pieces in a
+/// permutation of 8 pieces.
+fn split_index(perm: &[u8; 8]) -> u8 {
+    let mask = perm.iter()
+        .enumerate()
+        .filter(|(_, &p)| p < 4)
+        .fold(0u8, |mask, (i, _)| mask | 1 << i);
+
+    (0..mask).filter(|m| m.count_ones() == 4).count() as u8
+}
+
+/// Coordinates of a position in cube
+/// shape with the top layer aligned to
+/// slot 0 and the bottom layer to slot
+/// 1, where every twist by a multiple
+/// of 3 keeps the cube slashable.
+///
+/// Slashing in this alignment keeps
+/// each corner next to the same edge,
+/// so the square moves also include an
+/// offset slash, made with both layers
+/// turned one slot further.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Square {
+    corners: u16,
+    edges: u16,
+    middle: bool,
+}
+
+impl Square {
+
+    /// Returns the coordinates of an
+    /// aligned cube shape position.
+    fn from_position(position: &Position) -> Square {
+        let mut corners = [0; 8];
+        let mut edges = [0; 8];
+        for i in 0..4 {
+            corners[i] = position.top[3 * i];
+            corners[i + 4] = position.bottom[3 * i + 1];
+            edges[i] = position.top[3 * i + 2] - 8;
+            edges[i + 4] = position.bottom[3 * i] - 8;
+        }
+
+        Square {
+            corners: perm_rank(&corners),
+            edges: perm_rank(&edges),
+            middle: position.middle,
+        }
+    }
+
+    /// Returns the position described
+    /// by the coordinates.
+    fn to_position(self) -> Position {
+        let corners = perm_unrank(self.corners);
+        let edges = perm_unrank(self.edges);
+        let mut position = Position {
+            top: [0; 12],
+            bottom: [0; 12],
+            middle: self.middle,
+        };
+        for i in 0..4 {
+            position.top[3 * i] = corners[i];
+            position.top[3 * i + 1] = corners[i];
+            position.top[3 * i + 2] = edges[i] + 8;
+            position.bottom[3 * i] = edges[i + 4] + 8;
+            position.bottom[3 * i + 1] = corners[i + 4];
+            position.bottom[3 * i + 2] = corners[i + 4];
+        }
+
+        position
+    }
+
+    /// Returns true if the position is
+    /// solved apart from a final twist.
+    fn is_goal(tables: &Tables, square: &Square) -> bool {
+        if square.middle {
+            return false;
+        }
+
+        let mut top = *square;
+        for _ in 0..4 {
+            let mut bottom = top;
+            for _ in 0..4 {
+                if bottom.corners == tables.goal.corners && bottom.edges == tables.goal.edges {
+                    return true;
+                }
+                bottom = tables.square_move(&bottom, BOTTOM_TWIST);
+            }
+            top = tables.square_move(&top, TOP_TWIST);
+        }
+
+        false
+    }
+}
+
+/// Move tables and pruning tables
+/// shared by every solver.
+struct Tables {
+
+    /// Slashes needed to reach cube
+    /// shape with solvable parity,
+    /// keyed by Position::shape_key.
+    shape: HashMap<u32, u8>,
+
+    /// Effect of each square move on
+    /// each permutation rank of the
+    /// corners and the edges.
+    corner_moves: Vec<[u16; SQUARE_MOVES]>,
+    edge_moves: Vec<[u16; SQUARE_MOVES]>,
+
+    /// Split of top and bottom layer
+    /// pieces for each permutation rank.
+    splits: Vec<u8>,
+
+    /// Slashes needed to solve the
+    /// corners along with the split of
+    /// the edges, and the edges along
+    /// with the split of the corners,
+    /// indexed by (rank * SPLITS + split)
+    /// * 2 + middle.
+    corner_prune: Prune,
+    edge_prune: Prune,
+
+    /// Coordinates of the solved cube
+    /// twisted into alignment.
+    goal: Square,
+}
+
+/// Pruning table with one entry a byte,
+/// or two a byte with the low nibble
+/// first when its depths are capped at
+/// PACKED_DEPTH. A table left out holds
+/// no entries and gives 0 for every
+/// index.
+struct Prune {
+    entries: Vec<u8>,
+    packed: bool,
+}
+
+/// Deepest prune_depth of a MemoryBudget
+/// whose pruning tables are packed.
+pub const PACKED_DEPTH: u8 = 14;
+
+impl Prune {
+
+    /// Returns a table of len entries
+    /// that are all unseen.
+    fn new(len: usize, packed: bool) -> Prune {
+        if packed {
+            Prune { entries: vec![0xff; len.div_ceil(2)], packed }
+        } else {
+            Prune { entries: vec![u8::MAX; len], packed }
+        }
+    }
+
+    /// Returns a table left out.
+    fn skipped() -> Prune {
+        Prune { entries: vec![], packed: false }
+    }
+
+    /// Returns the value of entries
+    /// not yet filled.
+    fn unseen(&self) -> u8 {
+        if self.packed { 0xf } else { u8::MAX }
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        match (self.entries.is_empty(), self.packed) {
+            (true, _) => 0,
+            (false, true) => self.entries[index / 2] >> (index % 2 * 4) & 0xf,
+            (false, false) => self.entries[index],
+        }
+    }
+
+    fn set(&mut self, index: usize, depth: u8) {
+        if self.packed {
+            let shift = index % 2 * 4;
+            let entry = &mut self.entries[index / 2];
+            *entry = *entry & !(0xf << shift) | depth << shift;
+        } else {
+            self.entries[index] = depth;
+        }
+    }
+}
+
+/// Memory the solver tables may use,
+/// set with set_memory_budget before
+/// they are generated. Smaller tables
+/// bound the search less tightly, so
+/// solving takes longer but still finds
+/// the same solutions.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct MemoryBudget {
+
+    /// Deepest slash count the pruning
+    /// tables hold, with deeper entries
+    /// stored as one more. Up to
+    /// PACKED_DEPTH the tables are packed
+    /// two entries a byte.
+    pub prune_depth: u8,
+
+    /// Whether the edge pruning table is
+    /// generated. Without it only the
+    /// corners bound the search.
+    pub edge_table: bool,
+}
+
+impl MemoryBudget {
+
+    /// Returns the budget with every
+    /// table at full size, the default.
+    pub fn full() -> MemoryBudget {
+        MemoryBudget { prune_depth: u8::MAX - 1, edge_table: true }
+    }
+
+    /// Returns the budget with both
+    /// pruning tables packed, about half
+    /// the size of full.
+    pub fn compact() -> MemoryBudget {
+        MemoryBudget { prune_depth: PACKED_DEPTH, edge_table: true }
+    }
+
+    /// Returns the budget with only the
+    /// corner pruning table, packed, for
+    /// the least memory.
+    pub fn minimal() -> MemoryBudget {
+        MemoryBudget { prune_depth: PACKED_DEPTH, edge_table: false }
+    }
+
+    /// Returns the largest of full, compact
+    /// and minimal within bytes, or minimal
+    /// if none fits.
+    pub fn within(bytes: usize) -> MemoryBudget {
+        [MemoryBudget::full(), MemoryBudget::compact()]
+            .into_iter()
+            .find(|budget| budget.table_bytes() <= bytes)
+            .unwrap_or(MemoryBudget::minimal())
+    }
+
+    /// Returns about how many bytes the
+    /// move and pruning tables take.
+    pub fn table_bytes(&self) -> usize {
+        let moves = PERMS * (SQUARE_MOVES * 2 * 2 + 1);
+        let entries = PERMS * SPLITS * 2;
+        let prune = if self.prune_depth <= PACKED_DEPTH { entries.div_ceil(2) } else { entries };
+
+        moves + prune * (1 + self.edge_table as usize)
+    }
+}
+
+impl Default for MemoryBudget {
+    fn default() -> MemoryBudget {
+        MemoryBudget::full()
+    }
+}
+
+/// Budget the tables are generated
+/// with, fixed once they are.
+static BUDGET: OnceLock<MemoryBudget> = OnceLock::new();
+
+/// Sets the memory budget of the solver
+/// tables for the rest of the program.
+/// Returns the budget back if one is
+/// set or the tables are generated.
+pub fn set_memory_budget(budget: MemoryBudget) -> Result<(), MemoryBudget> {
+    BUDGET.set(budget)
+}
+
+/// Returns the memory budget the
+/// tables are generated with.
+pub fn memory_budget() -> MemoryBudget {
+    BUDGET.get().copied().unwrap_or_default()
+}
+
+/// Version of the table format
+/// written by write_tables.
+pub const TABLES_VERSION: u8 = 1;
+
+/// Reasons tables cannot be loaded
+/// by load_tables.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TablesError {
+
+    /// The bytes are not tables written
+    /// by write_tables, failing at
+    /// this byte offset.
+    Invalid(usize),
+
+    /// The tables were written in
+    /// another version of the format.
+    UnsupportedVersion(u8),
+
+    /// Tables are already generated
+    /// or loaded.
+    AlreadyBuilt,
+}
+
+impl fmt::Display for TablesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TablesError::Invalid(offset) => write!(f, "invalid solver tables at byte {}", offset),
+            TablesError::UnsupportedVersion(version) => write!(f, "unsupported solver table version {}", version),
+            TablesError::AlreadyBuilt => write!(f, "solver tables are already built"),
+        }
+    }
+}
+
+impl Error for TablesError {}
+
+/// Writes the tables, generating them
+/// first if needed, so they can be saved
+/// at build time and loaded later with
+/// load_tables or built into a binary
+/// with the embedded-tables feature.
+pub fn write_tables<W: Write>(out: W) -> io::Result<()> {
+    tables().write(out)
+}
+
+/// Uses tables written by write_tables
+/// rather than generating them, which
+/// makes the first solve start straight
+/// away. The memory budget becomes the
+/// one the tables were written with.
+pub fn load_tables(bytes: &[u8]) -> Result<(), TablesError> {
+    if TABLES.get().is_some() {
+        return Err(TablesError::AlreadyBuilt);
+    }
+
+    let tables = Tables::read(bytes)?;
+    let _ = BUDGET.set(tables.budget());
+    TABLES.set(tables).map_err(|_| TablesError::AlreadyBuilt)
+}
+
+/// Reader over the bytes of tables,
+/// tracking the offset for errors.
+struct TableReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> TableReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], TablesError> {
+        let taken = self.bytes.get(self.pos..self.pos + len).ok_or(TablesError::Invalid(self.pos))?;
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, TablesError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, TablesError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, TablesError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    /// Reads a permutation rank,
+    /// which must be below PERMS.
+    fn rank(&mut self) -> Result<u16, TablesError> {
+        let pos = self.pos;
+        match self.u16()? {
+            rank if (rank as usize) < PERMS => Ok(rank),
+            _ => Err(TablesError::Invalid(pos)),
+        }
+    }
+
+    fn moves(&mut self) -> Result<Vec<[u16; SQUARE_MOVES]>, TablesError> {
+        (0..PERMS).map(|_| Ok([self.rank()?, self.rank()?, self.rank()?, self.rank()?])).collect()
+    }
+
+    /// Reads the split of each
+    /// permutation, which must be
+    /// below SPLITS.
+    fn splits(&mut self) -> Result<Vec<u8>, TablesError> {
+        let pos = self.pos;
+        let splits = self.take(PERMS)?;
+        match splits.iter().position(|&split| split as usize >= SPLITS) {
+            Some(i) => Err(TablesError::Invalid(pos + i)),
+            None => Ok(splits.to_vec()),
+        }
+    }
+
+    /// Reads a pruning table, which
+    /// must hold every entry or none.
+    fn prune(&mut self) -> Result<Prune, TablesError> {
+        let packed = self.u8()? != 0;
+        let pos = self.pos;
+        let len = self.u32()? as usize;
+        let entries = PERMS * SPLITS * 2;
+        if len != 0 && len != if packed { entries.div_ceil(2) } else { entries } {
+            return Err(TablesError::Invalid(pos));
+        }
+
+        Ok(Prune { entries: self.take(len)?.to_vec(), packed })
+    }
+}
+
+impl Tables {
+
+    /// Returns the budget the tables
+    /// were generated within.
+    fn budget(&self) -> MemoryBudget {
+        let prune_depth = if self.corner_prune.packed { PACKED_DEPTH } else { MemoryBudget::full().prune_depth };
+        MemoryBudget { prune_depth, edge_table: !self.edge_prune.entries.is_empty() }
+    }
+
+    /// Writes the tables as the bytes
+    /// SQ1T and TABLES_VERSION, then each
+    /// table in little endian order with
+    /// the shape table sorted by key.
+    fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(b"SQ1T")?;
+        out.write_all(&[TABLES_VERSION])?;
+
+        let mut shape: Vec<(&u32, &u8)> = self.shape.iter().collect();
+        shape.sort_unstable();
+        out.write_all(&(shape.len() as u32).to_le_bytes())?;
+        for (key, depth) in shape {
+            out.write_all(&key.to_le_bytes())?;
+            out.write_all(&[*depth])?;
+        }
+
+        for moves in [&self.corner_moves, &self.edge_moves] {
+            for amounts in moves {
+                for amount in amounts {
+                    out.write_all(&amount.to_le_bytes())?;
+                }
+            }
+        }
+        out.write_all(&self.splits)?;
+
+        for prune in [&self.corner_prune, &self.edge_prune] {
+            out.write_all(&[prune.packed as u8])?;
+            out.write_all(&(prune.entries.len() as u32).to_le_bytes())?;
+            out.write_all(&prune.entries)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads tables written by write.
+    fn read(bytes: &[u8]) -> Result<Tables, TablesError> {
+        let mut reader = TableReader { bytes, pos: 0 };
+        if reader.take(4)? != b"SQ1T" {
+            return Err(TablesError::Invalid(0));
+        }
+        match reader.u8()? {
+            TABLES_VERSION => {},
+            version => return Err(TablesError::UnsupportedVersion(version)),
+        }
+
+        // Every shape is looked up during
+        // a search, so none may be missing.
+        let mut shape = HashMap::new();
+        if reader.u32()? as usize != SHAPE_KEYS {
+            return Err(TablesError::Invalid(5));
+        }
+        for _ in 0..SHAPE_KEYS {
+            shape.insert(reader.u32()?, reader.u8()?);
+        }
+        let corner_moves = reader.moves()?;
+        let edge_moves = reader.moves()?;
+        let splits = reader.splits()?;
+        let corner_prune = reader.prune()?;
+        let edge_prune = reader.prune()?;
+        if reader.pos != bytes.len() {
+            return Err(TablesError::Invalid(reader.pos));
+        }
+
+        Ok(Tables {
+            shape,
+            corner_moves,
+            edge_moves,
+            splits,
+            corner_prune,
+            edge_prune,
+            goal: Square::from_position(&Position::solved().twist(0, -1)),
+        })
+    }
+}
+
+/// Moves of an aligned cube shape
+/// position: a top quarter twist, a
+/// bottom quarter twist, a slash and
+/// an offset slash.
+const TOP_TWIST: usize = 0;
+const BOTTOM_TWIST: usize = 1;
+const SLASH: usize = 2;
+const OFFSET_SLASH: usize = 3;
+const SQUARE_MOVES: usize = 4;
+
+/// Number of shape keys in the
+/// shape table.
+const SHAPE_KEYS: usize = 38610;
+
+/// Tables shared by every search,
+/// generated once.
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+/// Returns the tables, generating
+/// them on first use.
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(Tables::new)
+}
+
+/// Returns the tables, generating
+/// them with progress on first use.
+fn tables_with(progress: &mut Progress) -> Result<&'static Tables, Cancelled> {
+    if let Some(tables) = TABLES.get() {
+        return Ok(tables);
+    }
+
+    // Another thread may finish first,
+    // in which case its tables are kept.
+    let _ = TABLES.set(Tables::build(*BUDGET.get_or_init(MemoryBudget::default), progress)?);
+    Ok(TABLES.get().unwrap())
+}
+
+/// Generates the pruning tables now
+/// rather than on the first solve,
+/// which otherwise takes the time.
+pub fn prepare() {
+    tables();
+}
+
+/// Generates the pruning tables like
+/// prepare, reporting each stage to
+/// the sink and stopping early if the
+/// token is cancelled. Tables already
+/// generated are reported as done.
+pub fn prepare_with(sink: &mut dyn ProgressSink, cancel: &CancelToken) -> Result<(), Cancelled> {
+    let mut progress = Progress::new(sink, cancel);
+    if TABLES.get().is_none() {
+        return tables_with(&mut progress).map(|_| ());
+    }
+
+    progress.report(Stage::ShapeTable, SHAPE_KEYS, SHAPE_KEYS)?;
+    progress.report(Stage::MoveTables, PERMS, PERMS)?;
+    progress.report(Stage::CornerTable, PERMS * SPLITS * 2, PERMS * SPLITS * 2)?;
+    progress.report(Stage::EdgeTable, PERMS * SPLITS * 2, PERMS * SPLITS * 2)
+}
+
+impl Tables {
+
+    /// Generates every table within
+    /// the memory budget.
+    #[cfg(not(feature = "embedded-tables"))]
+    fn new() -> Tables {
+        let budget = *BUDGET.get_or_init(MemoryBudget::default);
+        Tables::build(budget, &mut Progress::new(&mut (), &CancelToken::new())).unwrap()
+    }
+
+    /// Reads the tables built into the
+    /// binary, from the file named by the
+    /// SQ1_TABLES environment variable at
+    /// compile time or written by build.rs.
+    #[cfg(feature = "embedded-tables")]
+    fn new() -> Tables {
+        let tables = Tables::read(include_bytes!(env!("SQ1_TABLES_FILE"))).expect("the embedded solver tables are valid");
+        let _ = BUDGET.set(tables.budget());
+        tables
+    }
+
+    /// Generates every table within the
+    /// budget, reporting progress.
+    fn build(budget: MemoryBudget, progress: &mut Progress) -> Result<Tables, Cancelled> {
+        trace_span!("build_tables");
+        let shape = Tables::gen_shape(progress)?;
+        let goal = Square::from_position(&Position::solved().twist(0, -1));
+
+        let mut corner_moves = vec![[0; SQUARE_MOVES]; PERMS];
+        let mut edge_moves = vec![[0; SQUARE_MOVES]; PERMS];
+        let mut splits = vec![0; PERMS];
+        for rank in 0..PERMS as u16 {
+            if rank % 4096 == 0 {
+                progress.report(Stage::MoveTables, rank as usize, PERMS)?;
+            }
+            splits[rank as usize] = split_index(&perm_unrank(rank));
+            let position = Square { corners: rank, edges: rank, middle: false }.to_position();
+            let moved = [
+                position.twist(3, 0),
+                position.twist(0, 3),
+                position.slash(),
+                position.twist(1, 1).slash().twist(-1, -1),
+            ];
+            for (m, next) in moved.iter().enumerate() {
+                let next = Square::from_position(next);
+                corner_moves[rank as usize][m] = next.corners;
+                edge_moves[rank as usize][m] = next.edges;
+            }
+        }
+
+        // Every permutation with the same
+        // split moves to the same split.
+        let mut corner_split_moves = vec![[0; SQUARE_MOVES]; SPLITS];
+        let mut edge_split_moves = vec![[0; SQUARE_MOVES]; SPLITS];
+        for rank in 0..PERMS {
+            for m in 0..SQUARE_MOVES {
+                corner_split_moves[splits[rank] as usize][m] = splits[corner_moves[rank][m] as usize];
+                edge_split_moves[splits[rank] as usize][m] = splits[edge_moves[rank][m] as usize];
+            }
+        }
+
+        progress.report(Stage::MoveTables, PERMS, PERMS)?;
+
+        let corner_prune = Tables::gen_prune(
+            &corner_moves,
+            &edge_split_moves,
+            goal.corners,
+            splits[goal.edges as usize],
+            budget.prune_depth,
+            (progress, Stage::CornerTable),
+        )?;
+        let edge_prune = if budget.edge_table {
+            Tables::gen_prune(
+                &edge_moves,
+                &corner_split_moves,
+                goal.edges,
+                splits[goal.corners as usize],
+                budget.prune_depth,
+                (progress, Stage::EdgeTable),
+            )?
+        } else {
+            progress.report(Stage::EdgeTable, PERMS * SPLITS * 2, PERMS * SPLITS * 2)?;
+            Prune::skipped()
+        };
+
+        Ok(Tables {
+            shape,
+            corner_moves,
+            edge_moves,
+            splits,
+            corner_prune,
+            edge_prune,
+            goal,
+        })
+    }
+
+    /// Runs a breadth first search over
+    /// the shapes of the cube, counting
+    /// only slashes since any twist can
+    /// be applied for free.
+    fn gen_shape(progress: &mut Progress) -> Result<HashMap<u32, u8>, Cancelled> {
+        trace_span!("shape_table");
+        let mut table = HashMap::new();
+        let mut frontier = vec![];
+        let mut depth = 0;
+
+        // Every position one twist away
+        // from a visited position is at
+        // the same distance.
+        let visit = |table: &mut HashMap<u32, u8>, position: &Position, depth: u8, frontier: &mut Vec<Position>| {
+            if table.contains_key(&position.shape_key()) {
+                return;
+            }
+            for top in 0..12 {
+                for bottom in 0..12 {
+                    let next = position.twist(top, bottom);
+                    if table.insert(next.shape_key(), depth).is_none() && next.can_slash() {
+                        frontier.push(next);
+                    }
+                }
+            }
+        };
+
+        visit(&mut table, &Position::solved(), depth, &mut frontier);
+        while !frontier.is_empty() {
+            depth += 1;
+            let mut next_frontier = vec![];
+            for position in frontier {
+                visit(&mut table, &position.slash(), depth, &mut next_frontier);
+            }
+            frontier = next_frontier;
+            progress.report(Stage::ShapeTable, table.len(), SHAPE_KEYS)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Runs a breadth first search over
+    /// a permutation coordinate, a split
+    /// coordinate and the middle layer,
+    /// counting only slashes, down to
+    /// max_depth.
+    fn gen_prune(
+        perm_moves: &[[u16; SQUARE_MOVES]],
+        split_moves: &[[u8; SQUARE_MOVES]],
+        perm: u16,
+        split: u8,
+        max_depth: u8,
+        (progress, stage): (&mut Progress, Stage),
+    ) -> Result<Prune, Cancelled> {
+        trace_span!("prune_table", stage = %stage);
+        let mut table = Prune::new(PERMS * SPLITS * 2, max_depth <= PACKED_DEPTH);
+        let unseen = table.unseen();
+        let mut frontier = vec![];
+        let mut depth = 0;
+
+        let mut visit = |perm: u16, split: u8, middle: bool, depth: u8, frontier: &mut Vec<usize>| {
+            if table.get((perm as usize * SPLITS + split as usize) * 2 + middle as usize) != unseen {
+                return;
+            }
+
+            let (mut top_perm, mut top_split) = (perm, split);
+            for _ in 0..4 {
+                let (mut perm, mut split) = (top_perm, top_split);
+                for _ in 0..4 {
+                    let index = (perm as usize * SPLITS + split as usize) * 2 + middle as usize;
+                    if table.get(index) == unseen {
+                        table.set(index, depth);
+                        frontier.push(index);
+                    }
+                    perm = perm_moves[perm as usize][BOTTOM_TWIST];
+                    split = split_moves[split as usize][BOTTOM_TWIST];
+                }
+                top_perm = perm_moves[top_perm as usize][TOP_TWIST];
+                top_split = split_moves[top_split as usize][TOP_TWIST];
+            }
+        };
+
+        // Each index on a frontier was
+        // filled when it was pushed.
+        visit(perm, split, false, depth, &mut frontier);
+        let mut filled = frontier.len();
+        while !frontier.is_empty() && depth < max_depth {
+            depth += 1;
+            let mut next_frontier = vec![];
+            for index in frontier {
+                for slash in [SLASH, OFFSET_SLASH] {
+                    let perm = perm_moves[index / 2 / SPLITS][slash];
+                    let split = split_moves[index / 2 % SPLITS][slash];
+                    visit(perm, split, index % 2 == 0, depth, &mut next_frontier);
+                }
+            }
+            frontier = next_frontier;
+            filled += frontier.len();
+            progress.report(stage, filled, PERMS * SPLITS * 2)?;
+        }
+
+        // Entries left unseen need more
+        // slashes than the deepest found.
+        if !frontier.is_empty() {
+            for index in 0..PERMS * SPLITS * 2 {
+                if table.get(index) == unseen {
+                    table.set(index, depth + 1);
+                }
+            }
+            progress.report(stage, PERMS * SPLITS * 2, PERMS * SPLITS * 2)?;
+        }
+
+        Ok(table)
+    }
+
+    /// Returns the number of slashes
+    /// needed to reach cube shape.
+    fn shape_distance(&self, position: &Position) -> u8 {
+        self.shape[&position.shape_key()]
+    }
+
+    /// Returns a lower bound on the
+    /// slashes needed to solve an aligned
+    /// cube shape position.
+    fn square_distance(&self, square: &Square) -> u8 {
+        let middle = square.middle as usize;
+        let corners = square.corners as usize;
+        let edges = square.edges as usize;
+        let corner_index = (corners * SPLITS + self.splits[edges] as usize) * 2 + middle;
+        let edge_index = (edges * SPLITS + self.splits[corners] as usize) * 2 + middle;
+
+        self.corner_prune.get(corner_index).max(self.edge_prune.get(edge_index))
+    }
+
+    /// Applies a square move to an
+    /// aligned position.
+    fn square_move(&self, square: &Square, m: usize) -> Square {
+        Square {
+            corners: self.corner_moves[square.corners as usize][m],
+            edges: self.edge_moves[square.edges as usize][m],
+            middle: square.middle != (m >= SLASH),
+        }
+    }
+}
+
+/// Coordinate of a position in any
+/// shape keeping the order of the pieces
+/// of one kind around each layer, the
+/// places of the other kind without
+/// telling them apart, and the middle
+/// layer, but not the twist of either
+/// layer. Twists cost no slashes, so the
+/// slashes to solve the coordinate bound
+/// the slashes to solve the cube.
+struct LayerCoord {
+
+    /// Tracks the corners if set and
+    /// the edges otherwise.
+    corners: bool,
+
+    /// Start of the indices of the
+    /// positions with each number of
+    /// tracked pieces in the top layer,
+    /// with the length last.
+    base: [usize; 10],
+
+    /// Number of top and bottom patterns,
+    /// top sets and top and bottom orders
+    /// for each number of tracked pieces
+    /// in the top layer.
+    dims: [[usize; 5]; 9],
+
+    /// Rank of each mask of 12 bits
+    /// among the masks with as many bits
+    /// set, and the masks with each
+    /// number of bits set in order.
+    mask_rank: Vec<u16>,
+    masks: Vec<Vec<u16>>,
+}
+
+/// Pruning tables of the layer
+/// coordinates of the corners and the
+/// edges, bounding the optimal search.
+struct LayerTables {
+    corners: (LayerCoord, Prune),
+    edges: (LayerCoord, Prune),
+}
+
+static LAYER_TABLES: OnceLock<LayerTables> = OnceLock::new();
+
+/// Returns the layer tables, generating
+/// them with progress on first use.
+fn layer_tables_with(progress: &mut Progress) -> Result<&'static LayerTables, Cancelled> {
+    if let Some(tables) = LAYER_TABLES.get() {
+        return Ok(tables);
+    }
+
+    let _ = LAYER_TABLES.set(LayerTables::build(progress)?);
+    Ok(LAYER_TABLES.get().unwrap())
+}
+
+/// Returns n choose k.
+fn choose(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |c, i| c * (n - i) / (i + 1))
+}
+
+/// Returns the factorial of n.
+fn factorial(n: usize) -> usize {
+    (1..=n).product()
+}
+
+/// Returns the rank of the order of
+/// distinct values among their orders.
+fn order_rank(values: &[u8]) -> usize {
+    let mut rank = 0;
+    for (i, value) in values.iter().enumerate() {
+        let smaller = values[i + 1..].iter().filter(|&other| other < value).count();
+        rank = rank * (values.len() - i) + smaller;
+    }
+
+    rank
+}
+
+/// Returns the sorted values in
+/// the order of the given rank.
+fn order_unrank(mut rank: usize, mut values: Vec<u8>) -> Vec<u8> {
+    let mut digits = vec![0; values.len()];
+    for i in (0..values.len()).rev() {
+        digits[i] = rank % (values.len() - i);
+        rank /= values.len() - i;
+    }
+
+    digits.into_iter().map(|digit| values.remove(digit)).collect()
+}
+
+impl LayerCoord {
+
+    /// Returns the coordinate tracking
+    /// the corners or the edges.
+    fn new(corners: bool) -> LayerCoord {
+        let mut masks = vec![vec![]; 13];
+        let mut mask_rank = vec![0; 1 << 12];
+        for mask in 0..1u16 << 12 {
+            let count = &mut masks[mask.count_ones() as usize];
+            mask_rank[mask as usize] = count.len() as u16;
+            count.push(mask);
+        }
+
+        let mut coord = LayerCoord { corners, base: [0; 10], dims: [[0; 5]; 9], mask_rank, masks };
+        for k in 0..=8 {
+            if let (Some(top), Some(bottom)) = (coord.pieces(k), coord.pieces(8 - k)) {
+                let orders = |k: usize| factorial(k.saturating_sub(1));
+                coord.dims[k] = [
+                    LayerCoord::patterns(top, k),
+                    LayerCoord::patterns(bottom, 8 - k),
+                    choose(8, k),
+                    orders(k),
+                    orders(8 - k),
+                ];
+            }
+            coord.base[k + 1] = coord.base[k] + coord.dims[k].iter().product::<usize>() * 2;
+        }
+
+        coord
+    }
+
+    /// Returns the number of indices.
+    fn len(&self) -> usize {
+        self.base[9]
+    }
+
+    /// Returns the number of pieces in
+    /// a layer holding k tracked pieces,
+    /// or None if no layer holds k.
+    fn pieces(&self, k: usize) -> Option<usize> {
+        match self.corners {
+            true if (2..=6).contains(&k) => Some(12 - k),
+            false if k.is_multiple_of(2) && k <= 8 => Some((12 + k) / 2),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of orders of
+    /// the pieces of a layer with n pieces
+    /// and k tracked, once the lowest
+    /// tracked piece is put first.
+    fn patterns(n: usize, k: usize) -> usize {
+        if k == 0 { 1 } else { choose(n - 1, k - 1) }
+    }
+
+    fn tracked(&self, piece: u8) -> bool {
+        (piece < 8) == self.corners
+    }
+
+    /// Returns the pattern of tracked
+    /// pieces after the first of a layer
+    /// and the tracked pieces in order,
+    /// reading from the lowest one, with
+    /// their number.
+    fn read(&self, layer: &[u8; 12]) -> (u16, [u8; 8], usize) {
+        let mut pieces = [0; 12];
+        let mut n = 0;
+        let mut first = None;
+        for i in 0..12 {
+            let piece = layer[i];
+            if piece != layer[(i + 11) % 12] {
+                if self.tracked(piece) && first.is_none_or(|first: usize| piece < pieces[first]) {
+                    first = Some(n);
+                }
+                pieces[n] = piece;
+                n += 1;
+            }
+        }
+
+        let first = first.unwrap_or(0);
+        let mut pattern = 0;
+        let mut tracked = [0; 8];
+        let mut k = 0;
+        for (i, &piece) in pieces[first..n].iter().chain(&pieces[..first]).enumerate() {
+            if self.tracked(piece) {
+                if i > 0 {
+                    pattern |= 1 << (i - 1);
+                }
+                tracked[k] = piece % 8;
+                k += 1;
+            }
+        }
+
+        (pattern, tracked, k)
+    }
+
+    /// Returns the index of the position.
+    fn index(&self, position: &Position) -> usize {
+        let (top_pattern, top, k) = self.read(&position.top);
+        let (bottom_pattern, bottom, _) = self.read(&position.bottom);
+        let set = top[..k].iter().fold(0, |set, &piece| set | 1 << piece);
+        let rank = |pattern: u16| self.mask_rank[pattern as usize] as usize;
+        let [_, bottom_patterns, sets, top_orders, bottom_orders] = self.dims[k];
+
+        let mut index = rank(top_pattern) * bottom_patterns + rank(bottom_pattern);
+        index = index * sets + rank(set);
+        index = index * top_orders + order_rank(top.get(1..k).unwrap_or(&[]));
+        index = index * bottom_orders + order_rank(bottom.get(1..8 - k).unwrap_or(&[]));
+
+        self.base[k] + index * 2 + position.middle as usize
+    }
+
+    /// Returns a position with the index,
+    /// telling apart the pieces of the
+    /// other kind in any order.
+    fn position(&self, index: usize) -> Position {
+        let k = (0..=8).rfind(|&k| self.base[k] <= index && self.base[k + 1] > index).unwrap();
+        let (top_pieces, bottom_pieces) = (self.pieces(k).unwrap(), self.pieces(8 - k).unwrap());
+        let [_, bottom_patterns, sets, top_orders, bottom_orders] = self.dims[k];
+        let mut rest = index - self.base[k];
+        let mut digit = |base: usize| {
+            let digit = rest % base;
+            rest /= base;
+            digit
+        };
+
+        let middle = digit(2) == 1;
+        let bottom_order = digit(bottom_orders);
+        let top_order = digit(top_orders);
+        let set = self.masks[k][digit(sets)];
+        let bottom_pattern = self.masks[(8 - k).saturating_sub(1)][digit(bottom_patterns)];
+        let top_pattern = self.masks[k.saturating_sub(1)][rest];
+
+        let (mut top, mut bottom) = (vec![], vec![]);
+        for piece in 0..8 {
+            if set & 1 << piece != 0 { top.push(piece) } else { bottom.push(piece) }
+        }
+        let order = |rank: usize, mut pieces: Vec<u8>| {
+            if pieces.is_empty() {
+                return pieces;
+            }
+            let first = pieces.remove(0);
+            let mut order = vec![first];
+            order.extend(order_unrank(rank, pieces));
+            order
+        };
+
+        let mut others = (0..8).map(|piece| if self.corners { piece + 8 } else { piece });
+        let mut layer = |n: usize, pattern: u16, tracked: Vec<u8>| {
+            let first = !tracked.is_empty();
+            let mut tracked = tracked.into_iter();
+            let mut slots = [0; 12];
+            let mut slot = 0;
+            for i in 0..n {
+                let is_tracked = if i == 0 { first } else { pattern & 1 << (i - 1) != 0 };
+                let piece = match is_tracked {
+                    true if self.corners => tracked.next().unwrap(),
+                    true => tracked.next().unwrap() + 8,
+                    false => others.next().unwrap(),
+                };
+                slots[slot] = piece;
+                if piece < 8 {
+                    slot += 1;
+                    slots[slot] = piece;
+                }
+                slot += 1;
+            }
+            slots
+        };
+
+        Position {
+            top: layer(top_pieces, top_pattern, order(top_order, top)),
+            bottom: layer(bottom_pieces, bottom_pattern, order(bottom_order, bottom)),
+            middle,
+        }
+    }
+}
+
+impl LayerTables {
+
+    /// Generates the tables of the corners
+    /// and the edges, reporting progress.
+    fn build(progress: &mut Progress) -> Result<LayerTables, Cancelled> {
+        trace_span!("layer_tables");
+        let corners = LayerCoord::new(true);
+        let edges = LayerCoord::new(false);
+        let total = corners.len() + edges.len();
+        let corner_prune = LayerTables::gen(&corners, progress, 0, total)?;
+        let edge_prune = LayerTables::gen(&edges, progress, corners.len(), total)?;
+        progress.report(Stage::LayerTables, total, total)?;
+
+        Ok(LayerTables { corners: (corners, corner_prune), edges: (edges, edge_prune) })
+    }
+
+    /// Runs a breadth first search over
+    /// the coordinate, counting only
+    /// slashes, down to PACKED_DEPTH. Once
+    /// fewer entries are left unseen than
+    /// were just filled, each depth is found
+    /// by looking back from the unseen
+    /// entries instead.
+    fn gen(coord: &LayerCoord, progress: &mut Progress, done: usize, total: usize) -> Result<Prune, Cancelled> {
+        let mut table = Prune::new(coord.len(), true);
+        let unseen = table.unseen();
+        table.set(coord.index(&Position::solved()), 0);
+
+        let mut filled = 1;
+        let mut frontier = 1;
+        let mut depth = 0;
+        while frontier > 0 && depth < PACKED_DEPTH {
+            let backward = coord.len() - filled < frontier;
+            frontier = 0;
+            for index in 0..coord.len() {
+                if table.get(index) != if backward { unseen } else { depth } {
+                    continue;
+                }
+
+                LayerTables::neighbors(&coord.position(index), |next| {
+                    let next = coord.index(next);
+                    if backward && table.get(next) == depth {
+                        table.set(index, depth + 1);
+                        frontier += 1;
+                        return true;
+                    }
+                    if !backward && table.get(next) == unseen {
+                        table.set(next, depth + 1);
+                        frontier += 1;
+                    }
+                    false
+                });
+            }
+            filled += frontier;
+            depth += 1;
+            progress.report(Stage::LayerTables, done + filled, total)?;
+        }
+
+        // Entries left unseen need more
+        // slashes than the deepest found.
+        for index in 0..coord.len() {
+            if table.get(index) == unseen {
+                table.set(index, depth + 1);
+            }
+        }
+
+        Ok(table)
+    }
+
+    /// Passes each position one twist
+    /// and slash from the position to
+    /// visit, until visit returns true.
+    fn neighbors(position: &Position, mut visit: impl FnMut(&Position) -> bool) {
+        let twists: [Position; 12] = core::array::from_fn(|t| position.twist(t as i8, t as i8));
+        for top in twists.iter().filter(|twisted| twisted.can_slash_top()) {
+            for bottom in twists.iter().filter(|twisted| twisted.can_slash_bottom()) {
+                let twisted = Position { top: top.top, bottom: bottom.bottom, middle: position.middle };
+                if visit(&twisted.slash()) {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Returns a lower bound on the
+    /// slashes needed to solve the
+    /// position.
+    fn distance(&self, position: &Position) -> u8 {
+        let (corners, corner_prune) = &self.corners;
+        let (edges, edge_prune) = &self.edges;
+        corner_prune.get(corners.index(position)).max(edge_prune.get(edges.index(position)))
+    }
+}
+
+/// Builds the list of turns of a
+/// solution, merging consecutive
+/// twists and cancelling slashes
+/// that undo each other.
+#[derive(Clone)]
+struct Solution {
+    turns: Vec<(i8, i8)>,
+}
+
+impl Solution {
+
+    /// Starts an empty solution.
+    fn new() -> Solution {
+        Solution { turns: vec![(0, 0)] }
+    }
+
+    /// Adds a twist to the last turn.
+    fn twist(&mut self, top: i8, bottom: i8) {
+        let last = self.turns.last_mut().unwrap();
+        *last = (normalize(last.0 + top), normalize(last.1 + bottom));
+    }
+
+    /// Adds a slash, removing the last
+    /// slash instead if no twist was
+    /// made since it.
+    fn slash(&mut self) {
+        if self.turns.len() > 1 && self.turns.last() == Some(&(0, 0)) {
+            self.turns.pop();
+        } else {
+            self.turns.push((0, 0));
+        }
+    }
+
+    /// Returns the turns as a Scramble.
+    fn into_scramble(self) -> Scramble {
+        Scramble { turns: self.turns }
+    }
+}
+
+/// Finds a solution for the cube
+/// using a two-phase approach. The
+/// first phase brings the cube into
+/// cube shape, and the second phase
+/// solves the pieces using only moves
+/// that keep the cube shape.
+///
+/// The solution is returned as a
+/// Scramble, with a slash between
+/// each pair of turns.
+pub fn solve(cube: &SqOne) -> Scramble {
+    solve_position(&Position::from(cube))
+}
+
+/// Finds the solution solve gives for
+/// each cube, in the order of the cubes.
+/// With the parallel feature the cubes
+/// are shared across every core, and a
+/// thread that finishes takes the next
+/// cube so slow solves do not hold the
+/// others up.
+pub fn solve_batch(cubes: &[SqOne]) -> Vec<Scramble> {
+    trace_span!("solve_batch");
+    let positions: Vec<Position> = cubes.iter().map(Position::from).collect();
+
+    map_batch(&positions, solve_position)
+}
+
+/// Finds the best solution for the
+/// cube in the slash metric within the
+/// time limit, which is optimal if the
+/// search finishes in time and otherwise
+/// the shortest of the solutions from
+/// solve_n, as Quality::Balanced finds.
+/// Returns None if not even a two-phase
+/// solution was found.
+pub fn solve_with_limit(cube: &SqOne, limit: Duration) -> Option<Scramble> {
+    solve_until(cube, Metric::Slash, &CancelToken::with_timeout(limit))
+}
+
+/// Finds the best solution for the cube
+/// in the metric like solve_with_limit,
+/// searching until the token is
+/// cancelled.
+pub fn solve_until(cube: &SqOne, metric: Metric, cancel: &CancelToken) -> Option<Scramble> {
+    let position = Position::from(cube);
+    let mut sink = ();
+    let mut progress = Progress::new(&mut sink, cancel);
+    let Ok(solution) = solve_position_with(&position, &mut progress) else {
+        trace_event!("solve_until", "cancelled before a two-phase solution was found");
+        return None;
+    };
+    let best = solve_n(cube, BALANCED_CANDIDATES)
+        .into_iter()
+        .chain([solution])
+        .min_by_key(|solution| solution.len_in(metric))
+        .unwrap();
+    let upper = best.len_in(metric);
+    match solve_position_optimal(&position, metric, upper, &mut progress) {
+        Ok(optimal) => Some(optimal),
+        Err(Cancelled) => {
+            trace_event!("solve_until", "cancelled in the optimal search, keeping {} moves", upper);
+            Some(best)
+        },
+    }
+}
+
+/// How hard the solver works for a
+/// shorter solution. All levels share
+/// the same tables.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Quality {
+
+    /// The two-phase solution from solve,
+    /// the quickest to find. Suits hints
+    /// in interactive apps.
+    Fast,
+
+    /// The shortest of BALANCED_CANDIDATES
+    /// solutions from solve_n, a little
+    /// shorter and slower than Fast. Suits
+    /// scramble generation.
+    Balanced,
+
+    /// A shortest solution in the slash
+    /// metric, which can take seconds or
+    /// much longer for deep states. Suits
+    /// analysis.
+    Optimal,
+}
+
+/// Solutions solve_n gathers for
+/// Quality::Balanced.
+pub const BALANCED_CANDIDATES: usize = 8;
+
+/// Finds a solution for the cube
+/// at the quality level.
+pub fn solve_quality(cube: &SqOne, quality: Quality) -> Scramble {
+    solve_quality_until(cube, quality, &CancelToken::new())
+}
+
+/// Finds a solution for the cube at the
+/// quality level, falling back to the
+/// balanced solution if the token is
+/// cancelled during the optimal search.
+/// The other levels are quick enough
+/// that the token is not checked.
+pub fn solve_quality_until(cube: &SqOne, quality: Quality, cancel: &CancelToken) -> Scramble {
+    if quality == Quality::Fast {
+        return solve(cube);
+    }
+
+    let balanced = solve_n(cube, BALANCED_CANDIDATES).into_iter().next().unwrap_or_else(|| solve(cube));
+    if quality == Quality::Balanced {
+        return balanced;
+    }
+
+    let upper = balanced.len_in(Metric::Slash);
+    let mut sink = ();
+    match solve_position_optimal(&Position::from(cube), Metric::Slash, upper, &mut Progress::new(&mut sink, cancel)) {
+        Ok(optimal) => optimal,
+        Err(Cancelled) => {
+            trace_event!("solve_quality", "cancelled in the optimal search, keeping {} slashes", upper);
+            balanced
+        },
+    }
+}
+
+/// Solver for a run of closely related
+/// states, such as hints after each move
+/// in a simulator. It remembers the
+/// solutions it finds with the states
+/// along them, so a state on or one move
+/// off an earlier solution is answered
+/// without searching.
+#[derive(Clone, Default, Debug)]
+pub struct SolverSession {
+    solutions: HashMap<Position, Scramble>,
+    capacity: Option<usize>,
+}
+
+impl SolverSession {
+
+    /// Creates a session remembering
+    /// no solutions.
+    pub fn new() -> SolverSession {
+        SolverSession::default()
+    }
+
+    /// Creates a session remembering
+    /// solutions for at most states
+    /// states. When full, it forgets
+    /// every solution before taking
+    /// new ones.
+    pub fn with_capacity(states: usize) -> SolverSession {
+        SolverSession { solutions: HashMap::new(), capacity: Some(states) }
+    }
+
+    /// Finds a solution for the cube. The
+    /// rest of an earlier solution is used
+    /// when the cube is on it, with the
+    /// move back onto it added when the
+    /// cube is one move off, so a solution
+    /// may be a move longer than solve
+    /// gives. Other cubes are solved with
+    /// solve and remembered.
+    pub fn solve(&mut self, cube: &SqOne) -> Scramble {
+        let position = Position::from(cube);
+        if let Some(solution) = self.solutions.get(&position) {
+            return solution.clone();
+        }
+
+        let twists = (-5..=6).flat_map(|top| (-5..=6).map(move |bottom| (top, bottom)));
+        for (top, bottom) in twists {
+            if let Some(solution) = self.solutions.get(&position.twist(top, bottom)) {
+                return Scramble { turns: vec![(top, bottom)] }.join(solution.clone());
+            }
+        }
+        if position.can_slash() {
+            if let Some(solution) = self.solutions.get(&position.slash()) {
+                return Scramble { turns: vec![(0, 0), (0, 0)] }.join(solution.clone());
+            }
+        }
+
+        let solution = solve_position(&position);
+        self.remember(&position, &solution);
+        solution
+    }
+
+    /// Remembers the solution for the
+    /// position and the rest of it for the
+    /// position after each of its slashes.
+    fn remember(&mut self, position: &Position, solution: &Scramble) {
+        if self.capacity.is_some_and(|capacity| self.solutions.len() + solution.turns.len() > capacity) {
+            self.solutions.clear();
+        }
+        let mut position = *position;
+        for k in 0..solution.turns.len() {
+            if self.capacity.is_some_and(|capacity| self.solutions.len() >= capacity) {
+                break;
+            }
+            self.solutions.entry(position).or_insert_with(|| Scramble { turns: solution.turns[k..].to_vec() });
+            let (top, bottom) = solution.turns[k];
+            position = position.twist(top, bottom);
+            if k + 1 < solution.turns.len() {
+                position = position.slash();
+            }
+        }
+    }
+
+    /// Returns the number of states
+    /// with a remembered solution.
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    /// Returns true if no solution
+    /// is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+
+    /// Forgets every solution.
+    pub fn clear(&mut self) {
+        self.solutions.clear();
+    }
+}
+
+/// Phases of the two-phase solver.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum SolverPhase {
+
+    /// Brings the cube into cube shape
+    /// with a parity the second phase
+    /// can solve.
+    CubeShape,
+
+    /// Solves the pieces using only
+    /// moves that keep the cube shape.
+    Pieces,
+}
+
+impl SolverPhase {
+
+    /// Returns the name of the phase
+    /// used in JSON.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolverPhase::CubeShape => "cube_shape",
+            SolverPhase::Pieces => "pieces",
+        }
+    }
+
+    /// Returns the sub-goal of the
+    /// phase, written for learners.
+    pub fn goal(&self) -> &'static str {
+        match self {
+            SolverPhase::CubeShape => "Bring both layers into squares, one slash closer each time.",
+            SolverPhase::Pieces => "Solve the pieces with square moves that keep the cube shape.",
+        }
+    }
+}
+
+/// Moves of one phase of a solution
+/// from solve_explained.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct ExplainedStep {
+    pub phase: SolverPhase,
+
+    /// Moves of the phase. The first
+    /// phase ends with its last slash and
+    /// the second starts with the twist
+    /// after it.
+    pub moves: Scramble,
+
+    /// State string from fen::to_fen of
+    /// the cube after the phase.
+    pub state: String,
+}
+
+/// Two-phase solution split into
+/// the moves of each phase.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Explanation {
+    pub steps: Vec<ExplainedStep>,
+}
+
+impl Explanation {
+
+    /// Returns the moves of every
+    /// step joined together, which
+    /// solve gives for the cube.
+    pub fn scramble(&self) -> Scramble {
+        self.steps.iter().fold(Scramble { turns: vec![] }, |joined, step| joined.join(step.moves.clone()))
+    }
+
+    /// Returns the explanation as JSON,
+    /// with the phase name, goal, moves
+    /// and state after it of each step.
+    pub fn to_json(&self) -> String {
+        let steps: Vec<String> = self.steps
+            .iter()
+            .map(|step| {
+                format!(
+                    "{{\"phase\":\"{}\",\"goal\":{},\"moves\":{},\"state\":{}}}",
+                    step.phase.name(),
+                    json::json_string(step.phase.goal()),
+                    json::json_string(&step.moves.to_string()),
+                    json::json_string(&step.state),
+                )
+            })
+            .collect();
+
+        format!("{{\"steps\":[{}]}}", steps.join(","))
+    }
+}
+
+/// Finds the same solution as solve,
+/// split into the moves of each phase
+/// with the state each phase leaves,
+/// so a frontend can show why each
+/// part of the solution is there.
+pub fn solve_explained(cube: &SqOne) -> Explanation {
+    let position = Position::from(cube);
+    let slashes = tables().shape_distance(&position) as usize;
+    let turns = solve_position(&position).turns;
+
+    // The first phase is the slashes down
+    // to cube shape, and the twist after
+    // its last slash already lines the
+    // cube up for the second phase.
+    let split = slashes.min(turns.len() - 1);
+    let mut shape_turns = turns[..split].to_vec();
+    if split > 0 {
+        shape_turns.push((0, 0));
+    }
+    let phases = [
+        (SolverPhase::CubeShape, Scramble { turns: shape_turns }),
+        (SolverPhase::Pieces, Scramble { turns: turns[split..].to_vec() }),
+    ];
+
+    let mut cube = cube.clone();
+    let steps = phases
+        .into_iter()
+        .map(|(phase, moves)| {
+            cube.apply(&moves).unwrap();
+            ExplainedStep { phase, moves, state: fen::to_fen(&cube) }
+        })
+        .collect();
+
+    Explanation { steps }
+}
+
+/// Quarter twists of the top and
+/// bottom layers before a square move
+/// slash, and the kind of slash.
+type SquareStep = (usize, usize, usize);
+
+/// Finds a two-phase solution for
+/// the position.
+fn solve_position(position: &Position) -> Scramble {
+    let (mut sink, cancel) = ((), CancelToken::new());
+    solve_position_with(position, &mut Progress::new(&mut sink, &cancel)).unwrap()
+}
+
+/// Finds a two-phase solution for the
+/// position, stopping early if the
+/// token of progress is cancelled.
+fn solve_position_with(position: &Position, progress: &mut Progress) -> Result<Scramble, Cancelled> {
+    trace_span!("solve");
+    let tables = tables_with(progress)?;
+    let mut position = *position;
+    let mut solution = Solution::new();
+
+    // Phase 1: follow the shape table
+    // down to cube shape with a parity
+    // that can be solved in phase 2.
+    let mut distance = tables.shape_distance(&position);
+    while distance > 0 {
+        let (top, bottom, next) = shape_steps(tables, &position).next().unwrap();
+        solution.twist(top, bottom);
+        solution.slash();
+        position = next;
+        distance -= 1;
+    }
+
+    // Phase 2: search the square
+    // moves for the shortest way to
+    // solve the pieces.
+    let (position, square) = align(&position, &mut solution);
+    let mut path = vec![];
+    let mut depth = tables.square_distance(&square);
+    while !search_square(tables, &square, depth, &mut path, progress, &mut |_| true)? {
+        depth += 2;
+    }
+
+    Ok(finish_square(&position, solution, &path))
+}
+
+/// Returns each twist and slash from
+/// the position that brings it one
+/// slash closer to cube shape, with
+/// the position it reaches.
+fn shape_steps<'a>(tables: &'a Tables, position: &'a Position) -> impl Iterator<Item = (i8, i8, Position)> + 'a {
+    let distance = tables.shape_distance(position);
+    (-5..=6)
+        .flat_map(|top| (-5..=6).map(move |bottom| (top, bottom)))
+        .map(|(top, bottom)| (top, bottom, position.twist(top, bottom)))
+        .filter(|(_, _, twisted)| twisted.can_slash())
+        .map(|(top, bottom, twisted)| (top, bottom, twisted.slash()))
+        .filter(move |(_, _, next)| tables.shape_distance(next) < distance)
+}
+
+/// Aligns the layers of a cube shape
+/// position so the top starts with a
+/// corner at slot 0 and the bottom at
+/// slot 1, adding the twist to the
+/// solution.
+fn align(position: &Position, solution: &mut Solution) -> (Position, Square) {
+    let top = (0..3).find(|&i| position.top[i] != position.top[(i + 11) % 12]
+        && position.top[i] < 8).unwrap() as i8;
+    let bottom = (0..3).find(|&i| position.bottom[i] != position.bottom[(i + 11) % 12]
+        && position.bottom[i] < 8).unwrap() as i8;
+    solution.twist(-top, bottom - 1);
+    let position = position.twist(-top, bottom - 1);
+
+    (position, Square::from_position(&position))
+}
+
+/// Adds the square moves of the path
+/// and the final twist to the solution
+/// of the aligned position.
+fn finish_square(position: &Position, mut solution: Solution, path: &[SquareStep]) -> Scramble {
+    let mut position = *position;
+    for &(top, bottom, slash) in path {
+        let (top, bottom) = (3 * top as i8, 3 * bottom as i8);
+        let offset = (slash == OFFSET_SLASH) as i8;
+        position = position.twist(top + offset, bottom + offset).slash().twist(-offset, -offset);
+        solution.twist(top + offset, bottom + offset);
+        solution.slash();
+        solution.twist(-offset, -offset);
+    }
+
+    let (top, bottom) = position.solving_twist(&Position::solved()).unwrap();
+    solution.twist(top, bottom);
+
+    solution.into_scramble()
+}
+
+/// Candidates gathered for each
+/// solution solve_n returns, before
+/// keeping the shortest.
+const CANDIDATES: usize = 4;
+
+/// Returns up to k distinct solutions
+/// for the cube, shortest first in the
+/// slash metric and then the twist
+/// metric. The solutions follow the
+/// different shortest ways to cube shape
+/// and the shortest ways on from there,
+/// so none is guaranteed optimal.
+pub fn solve_n(cube: &SqOne, k: usize) -> Vec<Scramble> {
+    trace_span!("solve_n");
+    let tables = tables();
+    let mut solutions = vec![];
+    if k > 0 {
+        gather(tables, &Position::from(cube), Solution::new(), k, &mut solutions);
+    }
+
+    solutions.sort_by_key(|solution: &Scramble| (solution.len_in(Metric::Slash), solution.len_in(Metric::Twist)));
+    solutions.truncate(k);
+    solutions
+}
+
+/// Follows every shortest way from the
+/// position to cube shape, adding the
+/// solutions found from each until
+/// there are enough candidates for k.
+fn gather(tables: &Tables, position: &Position, solution: Solution, k: usize, solutions: &mut Vec<Scramble>) {
+    if solutions.len() >= k * CANDIDATES {
+        return;
+    }
+    if tables.shape_distance(position) > 0 {
+        for (top, bottom, next) in shape_steps(tables, position) {
+            let mut solution = solution.clone();
+            solution.twist(top, bottom);
+            solution.slash();
+            gather(tables, &next, solution, k, solutions);
+        }
+        return;
+    }
+
+    // Take the shortest square paths, and
+    // the ones two slashes longer if that
+    // is not enough.
+    let mut solution = solution;
+    let (position, square) = align(position, &mut solution);
+    let (mut sink, cancel) = ((), CancelToken::new());
+    let progress = Progress::new(&mut sink, &cancel);
+    let shortest = tables.square_distance(&square);
+    let mut depth = shortest;
+    let mut found = 0;
+    while found < k && depth <= shortest + 2 {
+        let mut path = vec![];
+        let mut add = |path: &[SquareStep]| {
+            let scramble = finish_square(&position, solution.clone(), path);
+            if !solutions.contains(&scramble) {
+                solutions.push(scramble);
+                found += 1;
+            }
+            found >= k
+        };
+        search_square(tables, &square, depth, &mut path, &progress, &mut add).unwrap();
+        depth += 2;
+    }
+}
+
+/// Searches for a sequence of depth
+/// slashes that solves the position,
+/// storing the number of quarter twists
+/// before each slash and its kind. Each
+/// sequence is passed to found, and the
+/// search stops once found accepts one.
+fn search_square(
+    tables: &Tables,
+    square: &Square,
+    depth: u8,
+    path: &mut Vec<SquareStep>,
+    progress: &Progress,
+    found: &mut dyn FnMut(&[SquareStep]) -> bool,
+) -> Result<bool, Cancelled> {
+    progress.poll()?;
+    if depth == 0 {
+        return Ok(Square::is_goal(tables, square) && found(path));
+    }
+
+    // The middle layer is only solved
+    // after an even number of slashes.
+    if (depth % 2 == 1) != square.middle {
+        return Ok(false);
+    }
+
+    let mut top_twisted = *square;
+    for top in 0..4 {
+        let mut twisted = top_twisted;
+        for bottom in 0..4 {
+            for slash in [SLASH, OFFSET_SLASH] {
+
+                // The same slash twice in
+                // a row cancels itself.
+                let last = path.last().map(|&(_, _, last)| last);
+                if top == 0 && bottom == 0 && last == Some(slash) {
+                    continue;
+                }
+
+                let next = tables.square_move(&twisted, slash);
+                if tables.square_distance(&next) >= depth {
+                    continue;
+                }
+
+                path.push((top, bottom, slash));
+                if search_square(tables, &next, depth - 1, path, progress, found)? {
+                    return Ok(true);
+                }
+                path.pop();
+            }
+            twisted = tables.square_move(&twisted, BOTTOM_TWIST);
+        }
+        top_twisted = tables.square_move(&top_twisted, TOP_TWIST);
+    }
+
+    Ok(false)
+}
+
+/// Finds a shortest solution for the
+/// cube in the given metric using
+/// iterative deepening A* search,
+/// pruned by the shape table and by
+/// tables of the corners and the edges
+/// in every shape.
+///
+/// Random states take seconds each.
+/// The first call also generates about
+/// 11 MB of tables for the corners and
+/// edges, which takes around half a
+/// minute with optimizations.
+pub fn solve_optimal(cube: &SqOne, metric: Metric) -> Scramble {
+    solve_optimal_with(cube, metric, &mut (), &CancelToken::new()).unwrap()
+}
+
+/// Finds a shortest solution like
+/// solve_optimal, reporting each bound
+/// searched to the sink out of the
+/// length of the two-phase solution,
+/// and stopping early if the token
+/// is cancelled.
+pub fn solve_optimal_with(
+    cube: &SqOne,
+    metric: Metric,
+    sink: &mut dyn ProgressSink,
+    cancel: &CancelToken,
+) -> Result<Scramble, Cancelled> {
+    let position = Position::from(cube);
+    let upper = solve_position(&position).len_in(metric);
+    solve_position_optimal(&position, metric, upper, &mut Progress::new(sink, cancel))
+}
+
+/// Longest two-phase solution, in
+/// slashes, that distance checks with
+/// the optimal solver.
+const OPTIMAL_SLASHES: usize = 4;
+
+/// Returns the number of moves needed
+/// to solve the cube in the metric. The
+/// count is optimal when the two-phase
+/// solution has at most OPTIMAL_SLASHES
+/// slashes or meets the lower bound from
+/// the shape table, and otherwise is the
+/// length of the two-phase solution.
+pub fn distance(cube: &SqOne, metric: Metric) -> usize {
+    let position = Position::from(cube);
+    let solution = solve_position(&position);
+    let upper = solution.len_in(metric);
+    let lower = slash_bound(tables().shape_distance(&position), metric) as usize;
+    if upper == lower || solution.len_in(Metric::Slash) > OPTIMAL_SLASHES {
+        return upper;
+    }
+
+    let (mut sink, cancel) = ((), CancelToken::new());
+    solve_position_optimal(&position, metric, upper, &mut Progress::new(&mut sink, &cancel)).unwrap().len_in(metric)
+}
+
+/// Returns true if every solution of
+/// the cube has at least slashes slashes.
+/// The lower bounds of the optimal
+/// solver prove most cubes, and the rest
+/// are proven by searching every shorter
+/// solution, which like solve_optimal
+/// can take seconds.
+pub fn needs_slashes(cube: &SqOne, slashes: usize) -> bool {
+    let position = Position::from(cube);
+    let tables = tables();
+    let (mut sink, cancel) = ((), CancelToken::new());
+    let mut progress = Progress::new(&mut sink, &cancel);
+    let layers = layer_tables_with(&mut progress).unwrap();
+    let solved = Position::solved();
+
+    let lower = lower_bound(tables, layers, &position, Metric::Slash) as usize;
+    (lower..slashes).all(|bound| {
+        let found = search_optimal((tables, layers), &solved, &position, Metric::Slash, 0, bound as u8, &mut vec![], &progress);
+        found.unwrap().is_none()
+    })
+}
+
+/// Returns true if the fewest slashes
+/// that solve the cube are from least to
+/// most. No cube needs more than
+/// MAX_SLASHES, and the two-phase
+/// solution shows most others need no
+/// more than most. The rest are proven
+/// like needs_slashes.
+pub fn slashes_within(cube: &SqOne, least: usize, most: usize) -> bool {
+    let within_most = || most >= MAX_SLASHES || solve(cube).len_in(Metric::Slash) <= most || !needs_slashes(cube, most + 1);
+    needs_slashes(cube, least) && within_most()
+}
+
+/// Finds a shortest solution for
+/// the position in the metric, given
+/// the length upper of a known one.
+fn solve_position_optimal(
+    start: &Position,
+    metric: Metric,
+    upper: usize,
+    progress: &mut Progress,
+) -> Result<Scramble, Cancelled> {
+    trace_span!("solve_optimal");
+    let tables = tables();
+    let layers = layer_tables_with(progress)?;
+    let solved = Position::solved();
+    let mut path = vec![];
+
+    let mut bound = lower_bound(tables, layers, start, metric);
+    let last = loop {
+        progress.report(Stage::Search, bound as usize, upper)?;
+        if let Some(last) = search_optimal((tables, layers), &solved, start, metric, 0, bound, &mut path, progress)? {
+            break last;
+        }
+        bound += 1;
+    };
+    progress.report(Stage::Search, upper, upper)?;
+
+    let mut solution = Solution::new();
+    for (top, bottom) in path {
+        solution.twist(top, bottom);
+        solution.slash();
+    }
+    solution.twist(last.0, last.1);
+
+    Ok(solution.into_scramble())
+}
+
+/// Returns a lower bound on the
+/// length of a solution in the metric,
+/// from the slashes to reach cube shape
+/// and to solve the corners and edges.
+fn lower_bound(tables: &Tables, layers: &LayerTables, position: &Position, metric: Metric) -> u8 {
+    slash_bound(tables.shape_distance(position).max(layers.distance(position)), metric)
+}
+
+/// Returns a lower bound on the length
+/// in the metric of a solution needing
+/// at least slashes slashes.
+fn slash_bound(slashes: u8, metric: Metric) -> u8 {
+    match metric {
+        Metric::Slash => slashes,
+
+        // Each pair of slashes needs
+        // a twist between them.
+        Metric::Twist => (2 * slashes).saturating_sub(1),
+    }
+}
+
+/// Searches for a solution costing
+/// at most bound moves in the metric,
+/// returning the final twist.
+#[allow(clippy::too_many_arguments)]
+fn search_optimal(
+    tables: (&Tables, &LayerTables),
+    solved: &Position,
+    position: &Position,
+    metric: Metric,
+    cost: u8,
+    bound: u8,
+    path: &mut Vec<(i8, i8)>,
+    progress: &Progress,
+) -> Result<Option<(i8, i8)>, Cancelled> {
+    progress.poll()?;
+    if let Some(twist) = position.solving_twist(solved) {
+        let twist_cost = match metric {
+            Metric::Twist if twist != (0, 0) => 1,
+            _ => 0,
+        };
+        if cost + twist_cost <= bound {
+            return Ok(Some(twist));
+        }
+    }
+
+    if cost + lower_bound(tables.0, tables.1, position, metric) > bound {
+        return Ok(None);
+    }
+
+    // The middle layer is only solved
+    // after an even number of slashes.
+    let slashes_left = match metric {
+        Metric::Slash => bound - cost,
+        Metric::Twist => (bound - cost).div_ceil(2),
+    };
+    if metric == Metric::Slash && (slashes_left % 2 == 1) != position.middle {
+        return Ok(None);
+    }
+    if slashes_left == 0 {
+        return Ok(None);
+    }
+
+    for top in -5..=6 {
+        for bottom in -5..=6 {
+
+            // Two slashes in a row
+            // cancel each other.
+            let twist_cost = match (top, bottom) {
+                (0, 0) if !path.is_empty() => continue,
+                (0, 0) => 0,
+                _ if metric == Metric::Twist => 1,
+                _ => 0,
+            };
+
+            let twisted = position.twist(top, bottom);
+            if !twisted.can_slash() || cost + twist_cost + 1 > bound {
+                continue;
+            }
+
+            path.push((top, bottom));
+            let next = twisted.slash();
+            let found = search_optimal(tables, solved, &next, metric, cost + twist_cost + 1, bound, path, progress)?;
+            if found.is_some() {
+                return Ok(found);
+            }
+            path.pop();
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Applies the turns of a scramble
+    // to a position, slashing between
+    // each pair of turns.
+    fn apply(position: &Position, scramble: &Scramble) -> Position {
+        let mut position = *position;
+        for (i, &(top, bottom)) in scramble.turns.iter().enumerate() {
+            if i > 0 {
+                assert!(position.can_slash());
+                position = position.slash();
+            }
+            position = position.twist(top, bottom);
+        }
+
+        position
+    }
+
+    // Checks that positions follow the
+    // same moves as the cube when it
+    // is scrambled.
+    #[test]
+    fn position_matches_cube() {
+        let mut cube = SqOne::new();
+        let scramble = cube.scramble();
+        let position = apply(&Position::solved(), &scramble);
+        assert_eq!(position, Position::from(&cube));
+    }
+
+    #[test]
+    fn rank_permutations() {
+        for rank in [0, 1, 719, 20000, PERMS as u16 - 1] {
+            assert_eq!(perm_rank(&perm_unrank(rank)), rank);
+        }
+    }
+
+    #[test]
+    fn solve_solved_cube() {
+        let cube = SqOne::new();
+        assert_eq!(solve(&cube).turns, vec![(0, 0)]);
+        assert_eq!(solve_optimal(&cube, Metric::Slash).turns, vec![(0, 0)]);
+    }
+
+    #[test]
+    fn solve_scrambled_cube() {
+        let mut cube = SqOne::new();
+        cube.scramble();
+        let solution = solve(&cube);
+        assert_eq!(apply(&Position::from(&cube), &solution), Position::solved());
+    }
+
+    // Finds a shortest solution for a
+    // position without a sink or token.
+    fn optimal(start: &Position, metric: Metric) -> Scramble {
+        let (mut sink, cancel) = ((), CancelToken::new());
+        let upper = solve_position(start).len_in(metric);
+        solve_position_optimal(start, metric, upper, &mut Progress::new(&mut sink, &cancel)).unwrap()
+    }
+
+    #[test]
+    fn solve_optimal_short_scramble() {
+        let scramble = Scramble { turns: vec![(1, 0), (2, -3), (3, 3), (3, 2)] };
+        let start = apply(&Position::solved(), &scramble);
+
+        let solution = optimal(&start, Metric::Slash);
+        assert_eq!(apply(&start, &solution), Position::solved());
+        assert!(solution.turns.len() - 1 <= 3);
+
+        let solution = optimal(&start, Metric::Twist);
+        assert_eq!(apply(&start, &solution), Position::solved());
+        assert!(solution.turns.iter().filter(|&&turn| turn != (0, 0)).count() <= 4);
+    }
+
+    // The tables of the corners and edges
+    // bound the search closely enough to
+    // solve a random state in seconds.
+    #[test]
+    fn solve_random_state_optimally() {
+        use std::time::Instant;
+
+        let cube = SqOne::random_state(&mut StdRng::seed_from_u64(1));
+        layer_tables_with(&mut Progress::new(&mut (), &CancelToken::new())).unwrap();
+        let start = Instant::now();
+        let solution = solve_optimal(&cube, Metric::Slash);
+        assert!(start.elapsed() < Duration::from_secs(20));
+
+        let position = Position::from(&cube);
+        assert_eq!(apply(&position, &solution), Position::solved());
+        assert_eq!(solution.len_in(Metric::Slash), 11);
+        assert!(solve(&cube).len_in(Metric::Slash) > 11);
+    }
+
+    // The two-phase solution of the state
+    // has 12 slashes, but it can be solved
+    // in 10.
+    #[test]
+    fn prove_slash_count() {
+        let cube = SqOne::random_state(&mut StdRng::seed_from_u64(2));
+        assert!(solve(&cube).len_in(Metric::Slash) >= 11);
+        assert!(!needs_slashes(&cube, 11));
+        assert!(needs_slashes(&cube, 10));
+        assert!(needs_slashes(&SqOne::random_state(&mut StdRng::seed_from_u64(1)), 11));
+    }
+
+    #[test]
+    fn solve_several_ways() {
+        let mut cube = SqOne::new();
+        cube.apply(&Scramble { turns: vec![(1, 0), (2, -3), (3, 3), (3, 2), (-1, 4)] }).unwrap();
+        let solutions = solve_n(&cube, 5);
+        assert_eq!(solutions.len(), 5);
+        for (i, solution) in solutions.iter().enumerate() {
+            assert_eq!(apply(&Position::from(&cube), solution), Position::solved());
+            assert!(!solutions[..i].contains(solution));
+        }
+        assert!(solutions.windows(2).all(|pair| pair[0].len_in(Metric::Slash) <= pair[1].len_in(Metric::Slash)));
+        assert!(solve_n(&cube, 0).is_empty());
+    }
+
+    #[test]
+    fn report_progress() {
+        let mut finished = vec![];
+        let mut sink = |stage, done, total| {
+            if done == total {
+                finished.push(stage);
+            }
+        };
+        prepare_with(&mut sink, &CancelToken::new()).unwrap();
+        finished.dedup();
+        assert_eq!(finished, [Stage::ShapeTable, Stage::MoveTables, Stage::CornerTable, Stage::EdgeTable]);
+
+        let mut cube = SqOne::new();
+        cube.scramble();
+        let cancel = CancelToken::new();
+        cancel.clone().cancel();
+        assert_eq!(solve_optimal_with(&cube, Metric::Slash, &mut (), &cancel), Err(Cancelled));
+    }
+
+    // Tables written out read back
+    // to the same bytes.
+    #[test]
+    fn tables_round_trip() {
+        let mut bytes = vec![];
+        write_tables(&mut bytes).unwrap();
+        assert_eq!(&bytes[..5], b"SQ1T\x01");
+
+        let mut written = vec![];
+        Tables::read(&bytes).unwrap().write(&mut written).unwrap();
+        assert!(written == bytes);
+        assert!(matches!(Tables::read(&bytes[..bytes.len() - 1]), Err(TablesError::Invalid(_))));
+        assert!(matches!(Tables::read(b"SQ1T\x02"), Err(TablesError::UnsupportedVersion(2))));
+
+        // Entries pointing outside the
+        // tables are rejected.
+        let splits = 9 + SHAPE_KEYS * 5 + 2 * PERMS * SQUARE_MOVES * 2;
+        let mut corrupted = bytes.clone();
+        corrupted[splits + 3] = SPLITS as u8;
+        assert_eq!(Tables::read(&corrupted).err(), Some(TablesError::Invalid(splits + 3)));
+        let mut corrupted = bytes.clone();
+        corrupted[splits - 2..splits].copy_from_slice(&(PERMS as u16).to_le_bytes());
+        assert_eq!(Tables::read(&corrupted).err(), Some(TablesError::Invalid(splits - 2)));
+        let mut corrupted = bytes.clone();
+        corrupted[splits + PERMS + 1..splits + PERMS + 5].copy_from_slice(&7u32.to_le_bytes());
+        assert_eq!(Tables::read(&corrupted).err(), Some(TablesError::Invalid(splits + PERMS + 1)));
+        assert_eq!(load_tables(&bytes), Err(TablesError::AlreadyBuilt));
+    }
+
+    #[test]
+    fn solve_within_limit() {
+        let mut cube = SqOne::new();
+        cube.apply(&Scramble { turns: vec![(1, 0), (2, -3), (3, 3), (3, 2)] }).unwrap();
+        let solution = solve_with_limit(&cube, Duration::from_secs(60)).unwrap();
+        assert_eq!(solution.len_in(Metric::Slash), distance(&cube, Metric::Slash));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(solve_until(&cube, Metric::Slash, &cancel), None);
+
+        // Stopped in the optimal search,
+        // the best of solve_n is kept.
+        let cube = SqOne::random_state(&mut StdRng::seed_from_u64(1));
+        layer_tables_with(&mut Progress::new(&mut (), &CancelToken::new())).unwrap();
+        let balanced = solve_n(&cube, BALANCED_CANDIDATES)[0].len_in(Metric::Slash);
+        let solution = solve_with_limit(&cube, Duration::from_millis(200)).unwrap();
+        assert!(solution.len_in(Metric::Slash) <= balanced);
+        assert!(balanced < solve(&cube).len_in(Metric::Slash));
+    }
+
+    #[test]
+    fn solve_at_quality() {
+        let mut cube = SqOne::new();
+        cube.apply(&Scramble { turns: vec![(1, 0), (2, -3), (3, 3), (3, 2)] }).unwrap();
+        let lengths: Vec<usize> = [Quality::Fast, Quality::Balanced, Quality::Optimal]
+            .into_iter()
+            .map(|quality| {
+                let solution = solve_quality(&cube, quality);
+                assert_eq!(apply(&Position::from(&cube), &solution), Position::solved());
+                solution.len_in(Metric::Slash)
+            })
+            .collect();
+        assert!(lengths.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert_eq!(lengths[2], distance(&cube, Metric::Slash));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert_eq!(solve_quality_until(&cube, Quality::Optimal, &cancel), solve_quality(&cube, Quality::Balanced));
+    }
+
+    #[test]
+    fn distance_of_short_scramble() {
+        let mut cube = SqOne::new();
+        assert_eq!(distance(&cube, Metric::Slash), 0);
+
+        // A slash and its undoing twist
+        // only need the slash back.
+        cube.apply(&Scramble { turns: vec![(1, 0), (0, 0)] }).unwrap();
+        assert_eq!(distance(&cube, Metric::Slash), 1);
+        assert_eq!(distance(&cube, Metric::Twist), 2);
+    }
+
+    #[test]
+    fn explain_solution() {
+        let mut cube = SqOne::new();
+        cube.scramble();
+        let explanation = solve_explained(&cube);
+        assert_eq!(explanation.scramble(), solve(&cube));
+
+        let [shape, pieces] = &explanation.steps[..] else {
+            panic!("{:?}", explanation);
+        };
+        let after_shape = fen::from_fen(&shape.state).unwrap();
+        assert!(crate::shape::classify(&after_shape).is_cube());
+        assert!(fen::from_fen(&pieces.state).unwrap().is_solved());
+
+        let json = explanation.to_json();
+        assert!(json.starts_with("{\"steps\":[{\"phase\":\"cube_shape\""));
+        assert!(json::parse_json(&json).is_ok());
+    }
+
+    #[test]
+    fn reuse_session_solutions() {
+        let mut session = SolverSession::new();
+        let mut cube = SqOne::new();
+        cube.scramble();
+        let solution = session.solve(&cube);
+        assert_eq!(solution, solve(&cube));
+        let remembered = session.len();
+        assert_eq!(remembered, solution.len());
+
+        // Following the solution and going
+        // one move astray are both answered
+        // from what the session remembers.
+        let (top, bottom) = solution.turns[0];
+        cube.try_twist(top, bottom).unwrap();
+        cube.try_slash().unwrap();
+        assert_eq!(session.solve(&cube), Scramble { turns: solution.turns[1..].to_vec() });
+        cube.try_twist(2, -1).unwrap();
+        let astray = session.solve(&cube);
+        assert_eq!(session.len(), remembered);
+
+        cube.apply(&astray).unwrap();
+        assert!(cube.is_solved());
+        session.clear();
+        assert!(session.is_empty());
+    }
+
+    #[test]
+    fn tables_within_budget() {
+        assert!(MemoryBudget::minimal().table_bytes() < MemoryBudget::compact().table_bytes());
+        assert!(MemoryBudget::compact().table_bytes() < MemoryBudget::full().table_bytes());
+        assert_eq!(MemoryBudget::within(MemoryBudget::compact().table_bytes()), MemoryBudget::compact());
+        assert_eq!(MemoryBudget::within(0), MemoryBudget::minimal());
+
+        // Smaller tables give lower bounds
+        // no larger than the full tables.
+        let full = tables();
+        let budget = MemoryBudget { prune_depth: 6, edge_table: false };
+        let (mut sink, cancel) = ((), CancelToken::new());
+        let small = Tables::build(budget, &mut Progress::new(&mut sink, &cancel)).unwrap();
+        assert!(small.corner_prune.packed && small.edge_prune.entries.is_empty());
+        for index in (0..PERMS * SPLITS * 2).step_by(97) {
+            assert_eq!(small.corner_prune.get(index), full.corner_prune.get(index).min(7));
+            assert_eq!(small.edge_prune.get(index), 0);
+        }
+
+        let mut session = SolverSession::with_capacity(3);
+        let mut cube = SqOne::new();
+        cube.scramble();
+        session.solve(&cube);
+        assert!(session.len() <= 3);
+    }
+
+    #[test]
+    fn solve_in_batch() {
+        assert_eq!(solve_batch(&[]), vec![]);
+
+        let mut cubes = vec![SqOne::new()];
+        for _ in 0..6 {
+            let mut cube = SqOne::new();
+            cube.scramble();
+            cubes.push(cube);
+        }
+        let solutions = solve_batch(&cubes);
+        assert_eq!(solutions, cubes.iter().map(solve).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,136 @@
+//! Module for simulating and scrambling
+//! a Square Two, whose corners are each
+//! cut into two pieces the width of an
+//! edge, so every twist and slash can
+//! always be made.
+
+use rand::Rng;
+
+use crate::solver::Position;
+use crate::{Move, Scramble};
+
+/// Square Two, kept as the slots of
+/// each layer like the solver keeps a
+/// Square One. Every slot holds its
+/// own piece, numbered 0-11 in the
+/// top layer and 12-23 in the bottom.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Square2 {
+    position: Position,
+}
+
+impl Default for Square2 {
+    fn default() -> Square2 {
+        Square2::new()
+    }
+}
+
+impl Square2 {
+
+    /// Produces a solved Square Two.
+    pub fn new() -> Square2 {
+        let mut top = [0; 12];
+        let mut bottom = [0; 12];
+        for i in 0..12 {
+            top[i] = i as u8;
+            bottom[i] = 12 + i as u8;
+        }
+
+        Square2 { position: Position { top, bottom, middle: false } }
+    }
+
+    /// Returns the piece in each slot
+    /// of the top and bottom layers and
+    /// whether the middle layer is flipped.
+    pub fn slots(&self) -> ([u8; 12], [u8; 12], bool) {
+        (self.position.top, self.position.bottom, self.position.middle)
+    }
+
+    /// Returns true if every piece is in
+    /// its solved slot and the middle
+    /// layer is solved.
+    pub fn is_solved(&self) -> bool {
+        *self == Square2::new()
+    }
+
+    /// Makes a single move. Square Two
+    /// has no corners to block a slash,
+    /// so every move can be made.
+    pub fn apply_move(&mut self, next: Move) {
+        self.position = match next {
+            Move::Twist(top, bottom) => self.position.twist(top, bottom),
+            Move::Slash => self.position.slash(),
+        };
+    }
+
+    /// Applies each turn of the scramble
+    /// with a slash between each.
+    pub fn apply(&mut self, scramble: &Scramble) {
+        for next in scramble.moves() {
+            self.apply_move(next);
+        }
+    }
+
+    /// Scrambles the puzzle with random
+    /// moves, drawing random numbers from
+    /// rng so a seeded rng gives the
+    /// same scramble.
+    pub fn scramble_rng<R: Rng>(&mut self, rng: &mut R) -> Scramble {
+        const NUM_FLIPS: u32 = 20;
+        let mut turns = vec![];
+        for i in 0..NUM_FLIPS {
+
+            // Any twist can be followed by a
+            // slash, so only twisting neither
+            // layer is left out.
+            let mut turn = (0, 0);
+            while turn == (0, 0) {
+                turn = (rng.gen_range(-5..=6), rng.gen_range(-5..=6));
+            }
+            self.position = self.position.twist(turn.0, turn.1);
+            if i + 1 < NUM_FLIPS {
+                self.position = self.position.slash();
+            }
+            turns.push(turn);
+        }
+
+        Scramble { turns }
+    }
+
+    /// Scrambles the puzzle with random
+    /// moves from the thread rng.
+    pub fn scramble(&mut self) -> Scramble {
+        self.scramble_rng(&mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cstimer;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // A scramble written out and read
+    // back gives the same puzzle.
+    #[test]
+    fn scramble_square_two() {
+        let mut square = Square2::new();
+        let scramble = square.scramble_rng(&mut StdRng::seed_from_u64(5));
+        assert!(!square.is_solved());
+
+        let mut applied = Square2::new();
+        applied.apply(&cstimer::parse(&cstimer::format(&scramble)).unwrap());
+        assert_eq!(applied, square);
+
+        // Slashes blocked on a Square One
+        // can be made on a Square Two.
+        let mut square = Square2::new();
+        square.apply(&Scramble { turns: vec![(1, 0), (0, 0), (1, 0), (0, 0)] });
+        assert!(square.slots().2);
+        let before = square;
+        square.apply(&scramble);
+        square.apply(&scramble.inverse());
+        assert_eq!(square, before);
+    }
+}
@@ -0,0 +1,261 @@
+//! Module for simulating and scrambling
+//! a Super Square One, which adds two
+//! more twisting layers inside the top
+//! and bottom layers.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use rand::Rng;
+
+use crate::cstimer::ParseError;
+use crate::cube::{twist_amount, SqOne, SqOneError};
+
+/// Single move of a
+/// Super Square One.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SuperMove {
+
+    /// Twist of the top, upper, lower
+    /// and bottom layers.
+    Twist([i8; 4]),
+
+    /// Slash of the right half.
+    Slash,
+}
+
+/// Sequence of turns of the four
+/// layers, with a slash between each
+/// pair of turns. Each turn is written
+/// (top, upper, lower, bottom), where
+/// upper and lower are the inner layers
+/// below the top and above the bottom.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SuperScramble {
+    turns: Vec<[i8; 4]>,
+}
+
+impl SuperScramble {
+
+    /// Returns the turns of the scramble.
+    pub fn turns(&self) -> &[[i8; 4]] {
+        &self.turns
+    }
+
+    /// Reads a scramble in the extended
+    /// notation, as in (1,0,0,-1) / (3,3,0,0).
+    /// Spacing is ignored, and a slash with
+    /// no turn before or after it stands
+    /// for a turn of (0,0,0,0).
+    pub fn parse(text: &str) -> Result<SuperScramble, ParseError> {
+        let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+        if text.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        let turns = text.split('/').map(parse_turn).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SuperScramble { turns })
+    }
+}
+
+impl fmt::Display for SuperScramble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let turns: Vec<String> = self.turns
+            .iter()
+            .map(|[top, upper, lower, bottom]| format!("({},{},{},{})", top, upper, lower, bottom))
+            .collect();
+        write!(f, "{}", turns.join(" / "))
+    }
+}
+
+/// Reads a single turn with its spaces
+/// removed. An empty turn is (0,0,0,0).
+fn parse_turn(turn: &str) -> Result<[i8; 4], ParseError> {
+    if turn.is_empty() {
+        return Ok([0; 4]);
+    }
+
+    let invalid = || ParseError::InvalidTurn(turn.to_string());
+    let amounts = turn
+        .strip_prefix('(')
+        .and_then(|turn| turn.strip_suffix(')'))
+        .ok_or_else(invalid)?
+        .split(',')
+        .map(|amount| match amount.parse::<i8>() {
+            Ok(amount) if (-6..=6).contains(&amount) => Ok(amount),
+            _ => Err(invalid()),
+        })
+        .collect::<Result<Vec<i8>, _>>()?;
+
+    amounts.try_into().map_err(|_| invalid())
+}
+
+/// Super Square One, simulated as an
+/// outer Square One made of the top and
+/// bottom layers and an inner one made
+/// of the upper and lower layers. A
+/// slash turns the right half of both.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct SuperSqOne {
+    outer: SqOne,
+    inner: SqOne,
+}
+
+impl Default for SuperSqOne {
+    fn default() -> SuperSqOne {
+        SuperSqOne::new()
+    }
+}
+
+impl SuperSqOne {
+
+    /// Produces a solved
+    /// Super Square One.
+    pub fn new() -> SuperSqOne {
+        SuperSqOne { outer: SqOne::new(), inner: SqOne::new() }
+    }
+
+    /// Returns the top and bottom
+    /// layers as a Square One.
+    pub fn outer(&self) -> &SqOne {
+        &self.outer
+    }
+
+    /// Returns the upper and lower
+    /// layers as a Square One. Its middle
+    /// layer is the same as the outer one.
+    pub fn inner(&self) -> &SqOne {
+        &self.inner
+    }
+
+    /// Returns true if every layer
+    /// is solved.
+    pub fn is_solved(&self) -> bool {
+        self.outer.is_solved() && self.inner.is_solved()
+    }
+
+    /// Twists the four layers, or returns
+    /// an error if any amount is outside
+    /// -6 to 6. Nothing is twisted if
+    /// there is an error.
+    pub fn try_twist(&mut self, turn: [i8; 4]) -> Result<(), SqOneError> {
+        if let Some(&amount) = turn.iter().find(|&&amount| twist_amount(amount).is_none()) {
+            return Err(SqOneError::TwistOutOfRange(amount));
+        }
+        let [top, upper, lower, bottom] = turn.map(|amount| twist_amount(amount).unwrap());
+        self.outer.twist(top, bottom);
+        self.inner.twist(upper, lower);
+
+        Ok(())
+    }
+
+    /// Slashes the puzzle, or returns an
+    /// error if a corner of any layer
+    /// crosses the slash line.
+    pub fn try_slash(&mut self) -> Result<(), SqOneError> {
+        if !self.outer.can_flip() || !self.inner.can_flip() {
+            return Err(SqOneError::SlashBlocked);
+        }
+        self.outer.flip();
+        self.inner.flip();
+
+        Ok(())
+    }
+
+    /// Applies a single move, or returns
+    /// an error if it cannot be made.
+    pub fn apply_move(&mut self, next: SuperMove) -> Result<(), SqOneError> {
+        match next {
+            SuperMove::Twist(turn) => self.try_twist(turn),
+            SuperMove::Slash => self.try_slash(),
+        }
+    }
+
+    /// Applies each turn of the scramble
+    /// with a slash between each, stopping
+    /// at the first move that cannot
+    /// be made.
+    pub fn apply(&mut self, scramble: &SuperScramble) -> Result<(), SqOneError> {
+        for (i, &turn) in scramble.turns.iter().enumerate() {
+            if i > 0 {
+                self.try_slash()?;
+            }
+
+            // A half turn may be written as
+            // either 6 or -6.
+            self.try_twist(turn.map(|amount| if amount == -6 { 6 } else { amount }))?;
+        }
+
+        Ok(())
+    }
+
+    /// Scrambles the puzzle with random
+    /// moves, drawing random numbers from
+    /// rng so a seeded rng gives the
+    /// same scramble.
+    pub fn scramble_rng<R: Rng>(&mut self, rng: &mut R) -> SuperScramble {
+        const NUM_FLIPS: u32 = 20;
+        let mut turns = vec![];
+        for i in 0..NUM_FLIPS {
+
+            // Both halves are twisted to a
+            // random alignment that allows
+            // the next slash.
+            let (top, bottom) = self.outer.random_turn(rng);
+            let (upper, lower) = self.inner.random_turn(rng);
+            self.outer.twist(top, bottom);
+            self.inner.twist(upper, lower);
+            if i + 1 < NUM_FLIPS {
+                self.outer.flip();
+                self.inner.flip();
+            }
+            turns.push([top, upper, lower, bottom]);
+        }
+
+        SuperScramble { turns }
+    }
+
+    /// Scrambles the puzzle with random
+    /// moves from the thread rng.
+    #[cfg(feature = "std")]
+    pub fn scramble(&mut self) -> SuperScramble {
+        self.scramble_rng(&mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // A scramble written out and read
+    // back gives the same puzzle.
+    #[test]
+    fn scramble_super_square_one() {
+        let mut cube = SuperSqOne::new();
+        let scramble = cube.scramble_rng(&mut StdRng::seed_from_u64(3));
+        assert!(!cube.is_solved());
+
+        let mut applied = SuperSqOne::new();
+        applied.apply(&SuperScramble::parse(&scramble.to_string()).unwrap()).unwrap();
+        assert_eq!(applied, cube);
+        assert_eq!(applied.outer().middle_flipped(), applied.inner().middle_flipped());
+    }
+
+    #[test]
+    fn parse_super_scramble() {
+        let scramble = SuperScramble::parse("(1,0,0,-1) / (3, 3,0,-6)/").unwrap();
+        assert_eq!(scramble.turns(), [[1, 0, 0, -1], [3, 3, 0, -6], [0, 0, 0, 0]]);
+        assert_eq!(SuperScramble::parse("(1,0)"), Err(ParseError::InvalidTurn("(1,0)".to_string())));
+
+        // Only the inner layers block
+        // the second slash.
+        let blocked = SuperScramble::parse("(1,0,0,0)/(0,1,0,0)/").unwrap();
+        assert_eq!(SuperSqOne::new().apply(&blocked), Err(SqOneError::SlashBlocked));
+    }
+}
@@ -0,0 +1,203 @@
+//! Module for reducing cubes to a
+//! canonical representative under the
+//! symmetries of the puzzle, so states
+//! that only differ by how the cube is
+//! held share one representative.
+
+use crate::cube::SqOne;
+
+/// Slots of a cube, as returned by
+/// SqOne::slots.
+type Slots = ([u8; 12], [u8; 12], bool);
+
+/// Twist taking a solved cube to the
+/// solved cube turned upside down or
+/// mirrored, since the slot model is
+/// not symmetric about the slash line.
+pub(crate) const REFLECTION_TWIST: (i8, i8) = (1, -1);
+
+/// Symmetry mapping one cube to another,
+/// applied in the order of the fields:
+/// turning the cube upside down,
+/// mirroring it left to right, turning
+/// the pieces of each layer as if the
+/// layer was twisted before the state,
+/// and twisting the layers after it.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+pub struct Symmetry {
+
+    /// Whether the cube is turned
+    /// upside down (z2).
+    pub flipped: bool,
+
+    /// Whether the cube is mirrored.
+    pub mirrored: bool,
+
+    /// Quarter turns the pieces of the
+    /// top and bottom layers are
+    /// renumbered by, which moves the
+    /// whole state around the layer.
+    pub relabel: (u8, u8),
+
+    /// Twist of the top and bottom
+    /// layers, as in a scramble.
+    pub twist: (i8, i8),
+}
+
+impl Symmetry {
+
+    /// Returns the cube the symmetry
+    /// maps the cube to.
+    pub fn apply(&self, cube: &SqOne) -> SqOne {
+        let (top, bottom, middle) = self.apply_slots(&cube.slots());
+        let mut mapped = SqOne::from_slots(&top, &bottom, middle);
+        mapped.set_scheme(cube.scheme());
+
+        mapped
+    }
+
+    /// Returns the slots the symmetry
+    /// maps the slots to.
+    fn apply_slots(&self, slots: &Slots) -> Slots {
+        let (mut top, mut bottom, middle) = *slots;
+
+        // Both reflections keep the slash
+        // line in place and reverse the
+        // order of the slots and pieces.
+        if self.flipped {
+            (top, bottom) = (reflect(&bottom, flip_piece), reflect(&top, flip_piece));
+        }
+        if self.mirrored {
+            (top, bottom) = (reflect(&top, mirror_piece), reflect(&bottom, mirror_piece));
+        }
+
+        let relabel = |piece: u8| {
+            let turns = if piece & 4 == 0 { self.relabel.0 } else { self.relabel.1 };
+            (piece & !3) | ((piece + turns) & 3)
+        };
+        let (x, y) = self.twist;
+        let mut mapped = ([0; 12], [0; 12], middle);
+        for i in 0..12 {
+            mapped.0[i] = relabel(top[(i as i8 - x).rem_euclid(12) as usize]);
+            mapped.1[i] = relabel(bottom[(i as i8 + y).rem_euclid(12) as usize]);
+        }
+
+        mapped
+    }
+
+    /// Returns every symmetry, each
+    /// mapping a cube to a different
+    /// arrangement unless the cube is
+    /// itself symmetric.
+    pub fn all() -> impl Iterator<Item = Symmetry> {
+        (0..4).flat_map(|reflection| {
+            (0..16).flat_map(move |relabel| {
+                (0..144u8).map(move |twist| Symmetry {
+                    flipped: reflection & 1 == 1,
+                    mirrored: reflection & 2 == 2,
+                    relabel: (relabel / 4, relabel % 4),
+                    twist: ((twist / 12) as i8 - 5, (twist % 12) as i8 - 5),
+                })
+            })
+        })
+    }
+}
+
+/// Returns the canonical representative
+/// of the cube's symmetry class with the
+/// symmetry mapping the cube to it. The
+/// representative has the smallest slots
+/// of every cube the symmetries reach.
+pub fn canonicalize(cube: &SqOne) -> (SqOne, Symmetry) {
+    let slots = cube.slots();
+    let symmetry = Symmetry::all()
+        .min_by_key(|symmetry| symmetry.apply_slots(&slots))
+        .unwrap();
+
+    (symmetry.apply(cube), symmetry)
+}
+
+/// Returns the layer reflected so slot i
+/// moves to slot 11 - i, mapping each
+/// piece with map.
+fn reflect(layer: &[u8; 12], map: fn(u8) -> u8) -> [u8; 12] {
+    let mut reflected = [0; 12];
+    for i in 0..12 {
+        reflected[11 - i] = map(layer[i]);
+    }
+
+    reflected
+}
+
+/// Returns the piece that takes the
+/// place of the piece in a mirrored
+/// solved cube. Each group of four
+/// pieces is reversed.
+fn mirror_piece(piece: u8) -> u8 {
+    let k = piece & 3;
+    let reversed = if piece < 8 { 3 - k } else { (6 - k) & 3 };
+
+    (piece & !3) | reversed
+}
+
+/// Returns the piece that takes the
+/// place of the piece in a solved cube
+/// turned upside down, which is the
+/// mirrored piece in the other layer.
+fn flip_piece(piece: u8) -> u8 {
+    mirror_piece(piece) ^ 4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scramble;
+
+    fn twisted(cube: &SqOne, top: i8, bottom: i8) -> SqOne {
+        let mut cube = cube.clone();
+        cube.try_twist(top, bottom).unwrap();
+        cube
+    }
+
+    // Each reflection maps a solved cube
+    // to a solved cube twisted by a slot,
+    // so no piece is left half in a slot.
+    #[test]
+    fn reflections_keep_solved_cube() {
+        let solved = SqOne::new();
+        for (flipped, mirrored) in [(true, false), (false, true), (true, true)] {
+            let symmetry = Symmetry { flipped, mirrored, ..Symmetry::default() };
+            let mapped = symmetry.apply(&solved);
+            assert!((-5..=6).any(|top| (-5..=6).any(|bottom| twisted(&solved, top, bottom).slots() == mapped.slots())));
+        }
+    }
+
+    #[test]
+    fn reflection_twist() {
+        let (top, bottom) = REFLECTION_TWIST;
+        let solved = twisted(&SqOne::new(), top, bottom);
+        for (flipped, mirrored) in [(true, false), (false, true)] {
+            let symmetry = Symmetry { flipped, mirrored, ..Symmetry::default() };
+            assert_eq!(symmetry.apply(&SqOne::new()).slots(), solved.slots());
+        }
+    }
+
+    #[test]
+    fn canonical_cube_is_shared() {
+        let mut cube = SqOne::new();
+        let scramble = Scramble { turns: vec![(1, 0), (2, -3), (3, 3), (-1, 2)] };
+        cube.apply(&scramble).unwrap();
+        let (canonical, symmetry) = canonicalize(&cube);
+        assert_eq!(symmetry.apply(&cube).slots(), canonical.slots());
+
+        for symmetry in [
+            Symmetry { twist: (3, -1), ..Symmetry::default() },
+            Symmetry { relabel: (1, 2), ..Symmetry::default() },
+            Symmetry { flipped: true, ..Symmetry::default() },
+            Symmetry { mirrored: true, relabel: (3, 0), twist: (1, 1), ..Symmetry::default() },
+        ] {
+            assert_eq!(canonicalize(&symmetry.apply(&cube)).0.slots(), canonical.slots());
+        }
+        assert_ne!(canonical.slots(), canonicalize(&SqOne::new()).0.slots());
+    }
+}
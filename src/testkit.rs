@@ -0,0 +1,110 @@
+//! Module of golden test vectors, pairs
+//! of scrambles and the states they give
+//! from a solved cube, so programs built
+//! on the crate can check in their own
+//! tests that an upgrade has not changed
+//! what moves do.
+//!
+//! States are written as SqOne::to_fen
+//! strings, which fix the piece in every
+//! slot and the middle layer.
+
+use alloc::string::String;
+use core::error::Error;
+use core::fmt;
+
+use crate::cstimer;
+use crate::cube::SqOne;
+
+/// Scramble in csTimer notation and the
+/// state it gives from a solved cube.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Vector {
+    pub scramble: &'static str,
+    pub state: &'static str,
+}
+
+/// The golden vectors. They cover single
+/// turns of each layer, a slash from
+/// solved, half turns written as 6 and -6
+/// and a full random move scramble.
+pub const VECTORS: &[Vector] = &[
+    Vector { scramble: "(0,0)", state: "b6db6d 08192a3b4c5d6e7f -" },
+    Vector { scramble: "(1,0)", state: "6dbb6d b08192a34c5d6e7f -" },
+    Vector { scramble: "/", state: "6ed6ed 0819f7e64c5db3a2 m" },
+    Vector { scramble: "(0,-1) /", state: "b6d6db 08197e6df4c5b3a2 m" },
+    Vector { scramble: "(1,0) / (-3,2) /", state: "b5bb5b 81f7492ac5d30b6e -" },
+    Vector { scramble: "(6,6) / (6,-6) /", state: "6db6db d5c4f7e69180b3a2 -" },
+    Vector { scramble: "(-2,0) / (0,3) / (3,3) / (-1,-1) / (4,0) /", state: "b5bb5b c4e35f7a90b62d18 m" },
+    Vector {
+        scramble: "(4,-6) / (3,3) / (0,3) / (-3,-4) / (6,-6) / (3,-1) / (1,3) / (6,0) / (-3,3) / (0,1) / \
+            (-2,2) / (-5,0) / (6,-2) / (2,-6) / (2,1) / (-4,-2) / (0,-6) / (0,-6) / (4,2) / (-2,2)",
+        state: "d756d7 67ef20b98a43d5c1 m",
+    },
+];
+
+/// Vector whose state differs from
+/// the state given by an integration.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Mismatch {
+    pub vector: Vector,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "scramble {} gave {} but should give {}",
+            self.vector.scramble, self.actual, self.vector.state,
+        )
+    }
+}
+
+impl Error for Mismatch {}
+
+/// Checks an integration against every
+/// vector. state_of is given each scramble
+/// and returns the state the integration
+/// reaches from a solved cube, written as
+/// a to_fen string. Returns the first
+/// vector whose state differs.
+pub fn verify(mut state_of: impl FnMut(&str) -> String) -> Result<(), Mismatch> {
+    for &vector in VECTORS {
+        let actual = state_of(vector.scramble);
+        if SqOne::from_fen(&actual).ok() != SqOne::from_fen(vector.state).ok() {
+            return Err(Mismatch { vector, actual });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks this crate against every
+/// vector, reading scrambles with
+/// cstimer::parse.
+pub fn verify_crate() -> Result<(), Mismatch> {
+    verify(|scramble| {
+        let mut cube = SqOne::new();
+        match cstimer::parse(scramble).map(|scramble| cube.apply(&scramble)) {
+            Ok(Ok(())) => cube.to_fen(),
+            _ => String::new(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_vectors() {
+        assert_eq!(verify_crate(), Ok(()));
+
+        // A state that does not match,
+        // or cannot be read, is reported.
+        let mismatch = verify(|_| SqOne::new().to_fen()).unwrap_err();
+        assert_eq!(mismatch.vector, VECTORS[1]);
+        assert_eq!(verify(|_| String::from("?")).unwrap_err().vector, VECTORS[0]);
+    }
+}
@@ -0,0 +1,327 @@
+//! Module for timing solves from any
+//! input, such as the keyboard or a
+//! Stackmat timer, and adding them to
+//! a session with a fresh scramble
+//! for each solve.
+
+use std::io::{self, BufRead};
+#[cfg(feature = "serial")]
+use std::io::Read;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::cube::SqOne;
+use crate::session::{Penalty, Session, Solve};
+use crate::Scramble;
+
+/// Event sent by a timer input.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TimerEvent {
+
+    /// The timer started.
+    Start,
+
+    /// The timer stopped after
+    /// this many milliseconds.
+    Stop { millis: u32 },
+
+    /// The penalty is given to
+    /// the last solve.
+    Penalty(Penalty),
+
+    /// The running solve is
+    /// thrown away.
+    Cancel,
+}
+
+/// Source of timer events, such as
+/// a keyboard or a Stackmat.
+pub trait TimerSource {
+
+    /// Waits for the next event, or
+    /// returns None when the input ends.
+    fn next_event(&mut self) -> io::Result<Option<TimerEvent>>;
+}
+
+/// Session that takes timer events,
+/// adding a solve of the current
+/// scramble each time the timer stops.
+pub struct TimedSession {
+    session: Session,
+    scramble: Scramble,
+    running: bool,
+    next_scramble: Box<dyn FnMut() -> Scramble + Send>,
+}
+
+impl TimedSession {
+
+    /// Creates a session scrambling with
+    /// random moves from a solved cube.
+    pub fn new() -> TimedSession {
+        TimedSession::with_scrambler(|| SqOne::new().scramble())
+    }
+
+    /// Creates a session taking each
+    /// scramble from next_scramble.
+    pub fn with_scrambler(mut next_scramble: impl FnMut() -> Scramble + Send + 'static) -> TimedSession {
+        TimedSession {
+            session: Session::new(),
+            scramble: next_scramble(),
+            running: false,
+            next_scramble: Box::new(next_scramble),
+        }
+    }
+
+    /// Returns the scramble for the
+    /// next solve.
+    pub fn scramble(&self) -> &Scramble {
+        &self.scramble
+    }
+
+    /// Returns the solves so far.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Returns true if the timer
+    /// is running.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Updates the session for the
+    /// event. A stop with no start, or a
+    /// penalty with no solve, is ignored.
+    pub fn handle(&mut self, event: TimerEvent) {
+        match event {
+            TimerEvent::Start => self.running = true,
+            TimerEvent::Stop { millis } if self.running => {
+                self.running = false;
+                let scramble = std::mem::replace(&mut self.scramble, (self.next_scramble)());
+                self.session.push(Solve {
+                    scramble,
+                    millis,
+                    penalty: Penalty::None,
+                    timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()),
+                });
+            },
+            TimerEvent::Stop { .. } => {},
+            TimerEvent::Penalty(penalty) => {
+                if let Some(solve) = self.session.solves_mut().last_mut() {
+                    solve.penalty = penalty;
+                }
+            },
+            TimerEvent::Cancel => self.running = false,
+        }
+    }
+
+    /// Handles every event from the source
+    /// until its input ends.
+    pub fn run(&mut self, source: &mut impl TimerSource) -> io::Result<()> {
+        while let Some(event) = source.next_event()? {
+            self.handle(event);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for TimedSession {
+    fn default() -> TimedSession {
+        TimedSession::new()
+    }
+}
+
+/// Timer driven by lines of text, such
+/// as a terminal in line mode. An empty
+/// line starts or stops the timer, and
+/// +2, dnf, ok and x give a penalty,
+/// clear it or cancel the solve.
+pub struct KeyboardSource<R> {
+    input: R,
+    started: Option<Instant>,
+}
+
+impl<R: BufRead> KeyboardSource<R> {
+
+    /// Creates a timer reading lines
+    /// from the input.
+    pub fn new(input: R) -> KeyboardSource<R> {
+        KeyboardSource { input, started: None }
+    }
+}
+
+impl<R: BufRead> TimerSource for KeyboardSource<R> {
+    fn next_event(&mut self) -> io::Result<Option<TimerEvent>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+
+            let event = match line.trim().to_ascii_lowercase().as_str() {
+                "" => match self.started.take() {
+                    Some(started) => TimerEvent::Stop { millis: started.elapsed().as_millis() as u32 },
+                    None => {
+                        self.started = Some(Instant::now());
+                        TimerEvent::Start
+                    },
+                },
+                "+2" => TimerEvent::Penalty(Penalty::PlusTwo),
+                "dnf" => TimerEvent::Penalty(Penalty::Dnf),
+                "ok" => TimerEvent::Penalty(Penalty::None),
+                "x" => {
+                    self.started = None;
+                    TimerEvent::Cancel
+                },
+                _ => continue,
+            };
+            return Ok(Some(event));
+        }
+    }
+}
+
+/// State shown by a Stackmat in
+/// each packet it sends.
+#[cfg(feature = "serial")]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct StackmatPacket {
+
+    /// Status byte, ' ' while running,
+    /// 'S' when stopped, 'I' after a
+    /// reset and 'A', 'C', 'L' or 'R'
+    /// while hands are on the pads.
+    pub status: u8,
+
+    /// Time on the display.
+    pub millis: u32,
+}
+
+/// Reads a Stackmat packet: the status,
+/// five or six digits of the time, a
+/// checksum of 64 plus the sum of the
+/// digits, and a line feed and carriage
+/// return. Returns None if the packet
+/// is damaged.
+#[cfg(feature = "serial")]
+pub fn parse_packet(packet: &[u8]) -> Option<StackmatPacket> {
+    let (&status, rest) = packet.split_first()?;
+    let body = rest.strip_suffix(b"\n\r")?;
+    let (&checksum, digits) = body.split_last()?;
+    if !(5..=6).contains(&digits.len()) || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let digits: Vec<u32> = digits.iter().map(|digit| (digit - b'0') as u32).collect();
+    if checksum as u32 != 64 + digits.iter().sum::<u32>() {
+        return None;
+    }
+
+    // Older timers send hundredths and
+    // newer ones send thousandths.
+    let thousandths = if digits.len() == 6 { digits[5] } else { 0 };
+    let millis = digits[0] * 60_000 + (digits[1] * 10 + digits[2]) * 1000 + digits[3] * 100 + digits[4] * 10 + thousandths;
+
+    Some(StackmatPacket { status, millis })
+}
+
+/// Timer reading the packets a Stackmat
+/// sends over its data port, from a
+/// serial device set to 1200 baud.
+#[cfg(feature = "serial")]
+pub struct StackmatSource<R> {
+    input: R,
+    buffer: Vec<u8>,
+    last: Option<u8>,
+}
+
+#[cfg(feature = "serial")]
+impl<R: Read> StackmatSource<R> {
+
+    /// Creates a timer reading packets
+    /// from the input.
+    pub fn new(input: R) -> StackmatSource<R> {
+        StackmatSource { input, buffer: Vec::new(), last: None }
+    }
+
+    /// Returns the event for a change
+    /// of status, if there is one.
+    fn event(&self, packet: StackmatPacket) -> Option<TimerEvent> {
+        let running = |status| status == b' ';
+        match self.last {
+            Some(last) if !running(last) && running(packet.status) => Some(TimerEvent::Start),
+            Some(last) if running(last) && packet.status == b'S' => Some(TimerEvent::Stop { millis: packet.millis }),
+            Some(last) if running(last) && packet.status == b'I' => Some(TimerEvent::Cancel),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serial")]
+impl<R: Read> TimerSource for StackmatSource<R> {
+    fn next_event(&mut self) -> io::Result<Option<TimerEvent>> {
+        let mut byte = [0];
+        loop {
+            if self.input.read(&mut byte)? == 0 {
+                return Ok(None);
+            }
+            self.buffer.push(byte[0]);
+            if byte[0] != b'\r' {
+                continue;
+            }
+
+            // Damaged packets are dropped
+            // and the next one is waited for.
+            let packet = parse_packet(&std::mem::take(&mut self.buffer));
+            if let Some(packet) = packet {
+                let event = self.event(packet);
+                self.last = Some(packet.status);
+                if event.is_some() {
+                    return Ok(event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_keyboard_solves() {
+        let mut source = KeyboardSource::new(&b"\n\n+2\n\nx\n\n\nhello\n"[..]);
+        let mut session = TimedSession::with_scrambler(|| Scramble { turns: vec![(1, 0)] });
+        session.run(&mut source).unwrap();
+
+        let solves = session.session().solves();
+        assert_eq!(solves.len(), 2);
+        assert_eq!(solves[0].penalty, Penalty::PlusTwo);
+        assert_eq!(solves[1].penalty, Penalty::None);
+        assert!(!session.is_running());
+    }
+
+    #[cfg(feature = "serial")]
+    #[test]
+    fn read_stackmat_packets() {
+        let packet = |status: u8, digits: &[u8]| {
+            let sum: u32 = digits.iter().map(|digit| (digit - b'0') as u32).sum();
+            let mut packet = vec![status];
+            packet.extend(digits);
+            packet.push(64 + sum as u8);
+            packet.extend(b"\n\r");
+            packet
+        };
+        assert_eq!(parse_packet(&packet(b'S', b"012345")), Some(StackmatPacket { status: b'S', millis: 12345 }));
+        assert_eq!(parse_packet(&packet(b' ', b"10234")).unwrap().millis, 60000 + 2340);
+        assert_eq!(parse_packet(b"S00000A\n\r"), None);
+
+        let mut input = vec![];
+        for (status, digits) in [(b'I', b"00000"), (b' ', b"00010"), (b' ', b"00500"), (b'S', b"00987")] {
+            input.extend(packet(status, digits));
+        }
+        let mut source = StackmatSource::new(&input[..]);
+        assert_eq!(source.next_event().unwrap(), Some(TimerEvent::Start));
+        assert_eq!(source.next_event().unwrap(), Some(TimerEvent::Stop { millis: 9870 }));
+        assert_eq!(source.next_event().unwrap(), None);
+    }
+}
@@ -0,0 +1,84 @@
+//! Module describing the tracing output
+//! of the crate, for finding where time
+//! goes in scramble generation, solving
+//! and table building. Spans and events
+//! are sent to whatever tracing
+//! subscriber the program installs, such
+//! as tracing_subscriber::fmt.
+//!
+//! Spans, all at the info level:
+//!
+//! - `scramble` around each scramble
+//!   made by SqOne::scramble_with
+//! - `solve`, `solve_n`, `solve_optimal`
+//!   and `solve_batch` around solves
+//! - `build_tables`, `shape_table`,
+//!   `prune_table` with its `stage`, and
+//!   `layer_tables` around table building
+//!
+//! Events named `solve_until` and
+//! `solve_quality` are sent when a search
+//! is cancelled and keeps the best
+//! solution found so far.
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::SqOne;
+    use crate::progress::CancelToken;
+    use crate::solver;
+    use crate::Metric;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Subscriber keeping the names of
+    /// the spans and events it gets.
+    #[derive(Default)]
+    struct Recorder {
+        names: Arc<Mutex<Vec<&'static str>>>,
+        next: AtomicU64,
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            self.names.lock().unwrap().push(span.metadata().name());
+            Id::from_u64(self.next.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            self.names.lock().unwrap().push(event.metadata().name());
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    #[test]
+    fn trace_solve() {
+        let recorder = Recorder::default();
+        let names = Arc::clone(&recorder.names);
+        tracing::subscriber::with_default(recorder, || {
+            let mut cube = SqOne::new();
+            cube.scramble();
+            solver::solve(&cube);
+
+            let cancel = CancelToken::new();
+            cancel.cancel();
+            assert_eq!(solver::solve_until(&cube, Metric::Slash, &cancel), None);
+        });
+
+        let names = names.lock().unwrap();
+        assert!(names.contains(&"solve"));
+        assert!(names.contains(&"solve_until"));
+    }
+}
@@ -0,0 +1,275 @@
+//! Module for generating scrambles that
+//! set up a chosen case to practice.
+
+use std::collections::HashSet;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::algs::{self, Alg, AlgCase};
+use crate::analysis::Phase;
+use crate::cases::{self, CornerCase, PblCase};
+use crate::cube::SqOne;
+use crate::shape::ShapeState;
+use crate::{solver, Scramble};
+
+/// Case a scramble should set up.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Case {
+
+    /// A PBL case, with the cube in
+    /// cube shape.
+    Pbl(PblCase),
+
+    /// A permutation of the corners of
+    /// each layer, with the edges of
+    /// each layer at random.
+    Corners { top: CornerCase, bottom: CornerCase },
+
+    /// A shape, with every piece at
+    /// random.
+    Shape(ShapeState),
+}
+
+/// Options for drilling the cases
+/// of the algs database.
+#[derive(Copy, Clone, Eq, PartialEq, Default, Debug)]
+pub struct DrillOptions {
+    auf: bool,
+    random_pieces: bool,
+}
+
+impl DrillOptions {
+
+    /// Returns options that set up each
+    /// case exactly as its algorithm
+    /// solves it.
+    pub fn new() -> DrillOptions {
+        DrillOptions::default()
+    }
+
+    /// Turns each layer at random after
+    /// the case is set up, by a multiple
+    /// of a quarter turn in cube shape.
+    pub fn auf(mut self, auf: bool) -> DrillOptions {
+        self.auf = auf;
+        self
+    }
+
+    /// Swaps pieces the case does not
+    /// depend on at random, so the case
+    /// is set up with different pieces.
+    pub fn random_pieces(mut self, random: bool) -> DrillOptions {
+        self.random_pieces = random;
+        self
+    }
+}
+
+/// Returns a scramble from a solved cube
+/// to a random cube with the given case.
+/// The cube is set up directly and then
+/// solved, so the scramble is the
+/// inverse of the solution.
+///
+/// Panics if the shape cannot be
+/// reached.
+pub fn scramble_for(case: Case) -> Scramble {
+    let cube = match case {
+        Case::Pbl(pbl) => random_pbl(|found| found == pbl),
+        Case::Corners { top, bottom } => {
+            random_pbl(|found| found.top.corners() == top && found.bottom.corners() == bottom)
+        },
+        Case::Shape(shape) => {
+            let mut cube = SqOne::new();
+            cube.scramble();
+            cube.scramble_shape_only(Some(shape));
+            cube
+        },
+    };
+
+    solver::solve(&cube).inverse()
+}
+
+/// Returns a scramble from a solved cube
+/// to a cube with the case of the
+/// algorithm. Without random pieces the
+/// scramble is the inverse of the
+/// algorithm, and otherwise the cube is
+/// set up directly and then solved.
+pub fn scramble_for_alg(alg: &Alg, options: &DrillOptions) -> Scramble {
+    let mut rng = rand::thread_rng();
+    let setup = alg.scramble().inverse();
+    let mut scramble = if options.random_pieces {
+        let mut cube = SqOne::new();
+        cube.apply(&setup).unwrap();
+        solver::solve(&relabel(&cube, alg.case, &mut rng)).inverse()
+    } else {
+        setup
+    };
+
+    if options.auf {
+        let step = if alg.case.phase() == Phase::CubeShape { 1 } else { 3 };
+        let mut turn = || rng.gen_range(0..12 / step) * step;
+        let (top, bottom) = (turn(), turn());
+        let last = scramble.turns.last_mut().unwrap();
+        *last = ((last.0 + top + 5) % 12 - 5, (last.1 + bottom + 5) % 12 - 5);
+    }
+
+    scramble
+}
+
+/// Returns a scramble for each of count
+/// cases picked at random from the
+/// algorithms, with the algorithm
+/// it sets up.
+pub fn drill<'a>(algs: &[&'a Alg], count: usize, options: &DrillOptions) -> Vec<(&'a Alg, Scramble)> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .filter_map(|_| algs.choose(&mut rng))
+        .map(|&alg| (alg, scramble_for_alg(alg, options)))
+        .collect()
+}
+
+/// Returns a scramble for every case of
+/// the phase in the algs database, each
+/// case exactly once, in random order,
+/// so a learner can sweep through all of
+/// them. A case with more than one
+/// algorithm is set up by the first.
+pub fn sweep(phase: Phase, options: &DrillOptions) -> Vec<(&'static Alg, Scramble)> {
+    let mut seen = HashSet::new();
+    let mut algs: Vec<&'static Alg> = algs::for_phase(phase).filter(|alg| seen.insert(alg.case)).collect();
+    algs.shuffle(&mut rand::thread_rng());
+
+    algs.into_iter().map(|alg| (alg, scramble_for_alg(alg, options))).collect()
+}
+
+/// Returns the cube with the pieces the
+/// case does not depend on swapped at
+/// random between themselves.
+fn relabel<R: Rng>(cube: &SqOne, case: AlgCase, rng: &mut R) -> SqOne {
+    let mut pieces: [u8; 16] = core::array::from_fn(|i| i as u8);
+    let groups: &[core::ops::Range<usize>] = match case.phase() {
+        Phase::CubeShape => &[0..8, 8..16],
+        Phase::CornerOrientation => &[0..4, 4..8, 8..16],
+        Phase::EdgeOrientation => &[0..4, 4..8, 8..12, 12..16],
+        Phase::CornerPermutation => &[8..12, 12..16],
+        Phase::EdgePermutation => &[],
+    };
+    for group in groups {
+        pieces[group.clone()].shuffle(rng);
+    }
+
+    // Corner permutation cases only allow
+    // the corners of a layer to be moved
+    // around the layer.
+    if case.phase() == Phase::CornerPermutation {
+        for start in [0, 4] {
+            let turn = rng.gen_range(0..4);
+            for i in 0..4 {
+                pieces[start + i] = (start + (i + turn) % 4) as u8;
+            }
+        }
+    }
+
+    let (top, bottom, middle) = cube.slots();
+    let middle = if case.phase() == Phase::EdgePermutation { middle } else { rng.gen() };
+    SqOne::from_slots(&top.map(|piece| pieces[piece as usize]), &bottom.map(|piece| pieces[piece as usize]), middle)
+}
+
+/// Returns a random cube in cube shape
+/// with each piece in its own layer,
+/// whose PBL case is accepted.
+fn random_pbl<F: Fn(PblCase) -> bool>(accept: F) -> SqOne {
+    let mut rng = rand::thread_rng();
+    let (solved_top, solved_bottom, _) = SqOne::new().slots();
+
+    // Shuffle the corners and edges of
+    // each layer between themselves.
+    let shuffle = |slots: &[u8; 12], rng: &mut rand::rngs::ThreadRng| {
+        let mut corners: Vec<u8> = (0..12).filter(|&i| i % 3 == 0).map(|i| slots[i]).collect();
+        let mut edges: Vec<u8> = (0..12).filter(|&i| i % 3 == 2).map(|i| slots[i]).collect();
+        corners.shuffle(rng);
+        edges.shuffle(rng);
+        let mut shuffled = [0; 12];
+        for i in 0..4 {
+            shuffled[i * 3] = corners[i];
+            shuffled[i * 3 + 1] = corners[i];
+            shuffled[i * 3 + 2] = edges[i];
+        }
+        shuffled
+    };
+
+    loop {
+        let top = shuffle(&solved_top, &mut rng);
+        let bottom = shuffle(&solved_bottom, &mut rng);
+        let cube = SqOne::from_slots(&top, &bottom, false);
+        if cases::identify(&cube).is_some_and(&accept) {
+            return cube;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cases::LayerCase;
+    use crate::shape::{self, Shape};
+
+    #[test]
+    fn scramble_for_pbl() {
+        let pbl = PblCase { top: LayerCase::Jb, bottom: LayerCase::Parity(CornerCase::Opposite) };
+        let scramble = scramble_for(Case::Pbl(pbl));
+        let mut cube = SqOne::new();
+        cube.apply(&scramble).unwrap();
+        assert_eq!(cases::identify(&cube), Some(pbl));
+    }
+
+    // Each option keeps the case, from
+    // every phase of the database.
+    #[test]
+    fn scramble_for_algs() {
+        let options = DrillOptions::new().auf(true).random_pieces(true);
+        for phase in Phase::all() {
+            let alg = algs::for_phase(phase).last().unwrap();
+            for options in [DrillOptions::new(), options] {
+                let mut cube = SqOne::new();
+                cube.apply(&scramble_for_alg(alg, &options)).unwrap();
+                assert_eq!(AlgCase::recognize(&cube), Some(alg.case));
+            }
+        }
+
+        let chosen: Vec<&Alg> = algs::for_phase(Phase::EdgeOrientation).collect();
+        let drilled = drill(&chosen, 3, &DrillOptions::new());
+        assert_eq!(drilled.len(), 3);
+        assert!(drilled.iter().all(|(alg, _)| alg.case.phase() == Phase::EdgeOrientation));
+        assert!(drill(&[], 3, &options).is_empty());
+    }
+
+    // A sweep sets up every case of the
+    // phase once.
+    #[test]
+    fn sweep_phase() {
+        let swept = sweep(Phase::CornerPermutation, &DrillOptions::new().auf(true));
+        let cases: HashSet<AlgCase> = swept
+            .iter()
+            .map(|(alg, scramble)| {
+                let mut cube = SqOne::new();
+                cube.apply(scramble).unwrap();
+                assert_eq!(AlgCase::recognize(&cube), Some(alg.case));
+                alg.case
+            })
+            .collect();
+        assert_eq!(cases.len(), swept.len());
+        assert_eq!(cases, algs::for_phase(Phase::CornerPermutation).map(|alg| alg.case).collect());
+    }
+
+    #[test]
+    fn scramble_for_shape() {
+        let state = ShapeState { top: Shape::Kite, bottom: Shape::Scallop };
+        let scramble = scramble_for(Case::Shape(state));
+        let mut cube = SqOne::new();
+        cube.apply(&scramble).unwrap();
+        assert_eq!(shape::classify(&cube), state);
+    }
+}
@@ -0,0 +1,191 @@
+//! Module for practicing with a cube in
+//! the terminal. The arrow keys twist
+//! the layers, space slashes, s scrambles
+//! and starts the timer, u undoes and q
+//! quits. The terminal is driven with
+//! crossterm, so any terminal it supports
+//! works.
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+
+use crate::cube::{SqOne, SqOneError};
+use crate::shape;
+
+/// How often the running timer is
+/// redrawn while no key is pressed.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Action taken for a key press.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum Action {
+    Twist(i8, i8),
+    Slash,
+    Scramble,
+    Undo,
+    Quit,
+}
+
+/// Returns the action for a key
+/// press, if there is one.
+pub(crate) fn action(key: &KeyEvent) -> Option<Action> {
+    match key.code {
+        KeyCode::Left => Some(Action::Twist(-1, 0)),
+        KeyCode::Right => Some(Action::Twist(1, 0)),
+        KeyCode::Up => Some(Action::Twist(0, 1)),
+        KeyCode::Down => Some(Action::Twist(0, -1)),
+        KeyCode::Char(' ') => Some(Action::Slash),
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Some(Action::Quit),
+        KeyCode::Char('s') => Some(Action::Scramble),
+        KeyCode::Char('u') => Some(Action::Undo),
+        KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+        _ => None,
+    }
+}
+
+/// Returns the cube drawn as text, with
+/// a letter for each slot. Corners are
+/// upper case and edges lower case, and
+/// the slash line is drawn as a bar.
+pub fn render(cube: &SqOne) -> String {
+    let (top, bottom, middle) = cube.slots();
+    let layer = |slots: [u8; 12]| {
+        let mut line = String::new();
+        for (i, &piece) in slots.iter().enumerate() {
+            if i == 6 {
+                line.push('|');
+            }
+            line.push(if piece < 8 { (b'A' + piece) as char } else { (b'a' + piece - 8) as char });
+        }
+        line
+    };
+
+    let state = shape::classify(cube);
+    format!(
+        "top    {}\r\nbottom {}\r\nmiddle {}\r\nshape  {:?} / {:?}\r\n",
+        layer(top),
+        layer(bottom),
+        if middle { "flipped" } else { "solved" },
+        state.top,
+        state.bottom,
+    )
+}
+
+/// Terminal in raw mode on the alternate
+/// screen, restored when dropped, so it
+/// is restored after an error or panic.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        let raw = RawTerminal;
+        execute!(io::stdout(), EnterAlternateScreen, Hide)?;
+
+        Ok(raw)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Runs the simulator until q is
+/// pressed, restoring the terminal
+/// afterwards.
+pub fn run() -> io::Result<()> {
+    let _raw = RawTerminal::enter()?;
+    let mut cube = SqOne::with_history();
+    let mut started: Option<Instant> = None;
+    let mut message = String::new();
+    let mut stdout = io::stdout();
+    let mut changed = true;
+
+    loop {
+        // The cube is only drawn again
+        // after a key, or each tick while
+        // the timer is running.
+        if changed || started.is_some() {
+            let timer = match started {
+                Some(start) => format!("{:.1}s\r\n", start.elapsed().as_secs_f64()),
+                None => String::new(),
+            };
+            execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+            write!(stdout, "{}{}{}\r\n", render(&cube), timer, message)?;
+            stdout.flush()?;
+            changed = false;
+        }
+
+        // Each event is one key, so keys
+        // pressed faster than the cube is
+        // drawn are all handled in turn.
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            Event::Resize(..) => {
+                changed = true;
+                continue;
+            },
+            _ => continue,
+        };
+        changed = true;
+        message.clear();
+        let result = match action(&key) {
+            Some(Action::Twist(top, bottom)) => cube.try_twist(top, bottom),
+            Some(Action::Slash) => cube.try_slash(),
+            Some(Action::Undo) => {
+                cube.undo();
+                Ok(())
+            },
+            Some(Action::Scramble) => {
+                cube = SqOne::with_history();
+                message = cube.scramble().to_string();
+                started = Some(Instant::now());
+                continue;
+            },
+            Some(Action::Quit) => return Ok(()),
+            None => continue,
+        };
+        if let Err(SqOneError::SlashBlocked) = result {
+            message = String::from("a corner is blocking the slash");
+        }
+
+        // Stop the timer once the
+        // scrambled cube is solved.
+        if cube.is_solved() {
+            if let Some(start) = started.take() {
+                message = format!("solved in {:.2}s", start.elapsed().as_secs_f64());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_keys() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        assert_eq!(action(&key(KeyCode::Right)), Some(Action::Twist(1, 0)));
+        assert_eq!(action(&key(KeyCode::Char(' '))), Some(Action::Slash));
+        assert_eq!(action(&key(KeyCode::Char('x'))), None);
+        assert_eq!(action(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Action::Quit));
+    }
+
+    #[test]
+    fn render_solved_cube() {
+        let text = render(&SqOne::new());
+        assert!(text.starts_with("top    AAaBBb|CCcDDd\r\nbottom EEeFFf|GGgHHh\r\n"));
+    }
+}
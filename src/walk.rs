@@ -0,0 +1,166 @@
+//! Module for random walks over the
+//! states of the cube, for studying how
+//! many random moves a scramble needs
+//! before its states are close to
+//! uniformly random.
+
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::cube::SqOne;
+use crate::policy::{MovePolicy, UniformLayers};
+use crate::shape::{self, ShapeState};
+
+/// Iterator over the states of a random
+/// walk, created by random_walk.
+pub struct RandomWalk<'a, R: Rng, P: MovePolicy = UniformLayers> {
+    cube: SqOne,
+    steps: usize,
+    policy: P,
+    rng: &'a mut R,
+}
+
+impl<R: Rng, P: MovePolicy> Iterator for RandomWalk<'_, R, P> {
+    type Item = SqOne;
+
+    fn next(&mut self) -> Option<SqOne> {
+        if self.steps == 0 {
+            return None;
+        }
+        self.steps -= 1;
+
+        let (top, bottom) = self.policy.turn(&self.cube, self.rng);
+        self.cube.twist(top, bottom);
+        self.cube.flip();
+
+        Some(self.cube.clone())
+    }
+}
+
+/// Returns the states visited by a walk
+/// of random steps from start. Each step
+/// turns the layers as the random move
+/// scrambler does and then slashes, so
+/// the states are those of scrambles
+/// with 1 to steps slashes.
+pub fn random_walk<'a, R: Rng>(start: &SqOne, steps: usize, rng: &'a mut R) -> RandomWalk<'a, R> {
+    walk_with_policy(start, steps, UniformLayers, rng)
+}
+
+/// Returns a random walk like
+/// random_walk, with the policy
+/// picking each turn.
+pub fn walk_with_policy<'a, R: Rng, P: MovePolicy>(
+    start: &SqOne,
+    steps: usize,
+    policy: P,
+    rng: &'a mut R,
+) -> RandomWalk<'a, R, P> {
+    RandomWalk { cube: start.clone(), steps, policy, rng }
+}
+
+/// Features of a state compared between
+/// walks and uniformly random states:
+/// the shape and the parity in cube
+/// shape. The middle layer is left out,
+/// since each slash flips it, so after
+/// a number of steps it never varies.
+type Features = (ShapeState, Option<bool>);
+
+fn features(cube: &SqOne) -> Features {
+    (shape::classify(cube), cube.has_parity())
+}
+
+/// Counts of the features of samples
+/// states, each from a call to state.
+fn count(samples: usize, mut state: impl FnMut() -> SqOne) -> HashMap<Features, usize> {
+    let mut counts = HashMap::new();
+    for _ in 0..samples {
+        *counts.entry(features(&state())).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Returns the state after steps random
+/// steps from solved.
+fn walked<R: Rng>(steps: usize, rng: &mut R) -> SqOne {
+    random_walk(&SqOne::new(), steps, rng).last().unwrap_or_default()
+}
+
+/// Returns the total variation distance
+/// between two counts of samples states.
+fn distance(a: &HashMap<Features, usize>, b: &HashMap<Features, usize>, samples: usize) -> f64 {
+    let difference: usize = a.keys()
+        .chain(b.keys().filter(|key| !a.contains_key(key)))
+        .map(|key| a.get(key).copied().unwrap_or(0).abs_diff(b.get(key).copied().unwrap_or(0)))
+        .sum();
+    difference as f64 / (2 * samples.max(1)) as f64
+}
+
+/// Returns the total variation distance
+/// between the features of the states
+/// after steps random steps from solved
+/// and those of uniformly random states,
+/// from samples of each. The distance
+/// is 0 for the same distribution and 1
+/// for ones that never overlap.
+///
+/// Walks only stop where a slash can be
+/// made, which favors shapes with more
+/// ways to line up for a slash, so the
+/// distance levels off above 0 however
+/// long the walk. Sampling adds noise
+/// of roughly the square root of
+/// 50 / samples.
+pub fn feature_distance<R: Rng>(steps: usize, samples: usize, rng: &mut R) -> f64 {
+    let walks = count(samples, || walked(steps, rng));
+    let uniform = count(samples, || SqOne::random_state(rng));
+    distance(&walks, &uniform, samples)
+}
+
+/// Returns the total variation distance
+/// between the features of walks of
+/// two lengths, like feature_distance.
+pub fn walk_distance<R: Rng>(steps: usize, other_steps: usize, samples: usize, rng: &mut R) -> f64 {
+    let walks = count(samples, || walked(steps, rng));
+    let others = count(samples, || walked(other_steps, rng));
+    distance(&walks, &others, samples)
+}
+
+/// Returns the fewest steps, up to
+/// max_steps, whose walk_distance from
+/// walks of max_steps is at most the
+/// threshold, estimating how many
+/// steps random move scrambles need
+/// before more stop changing the
+/// states they give.
+pub fn mixing_time<R: Rng>(threshold: f64, max_steps: usize, samples: usize, rng: &mut R) -> usize {
+    let limit = count(samples, || walked(max_steps, rng));
+    (0..max_steps)
+        .find(|&steps| distance(&count(samples, || walked(steps, rng)), &limit, samples) <= threshold)
+        .unwrap_or(max_steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn walk_states() {
+        let mut rng = StdRng::seed_from_u64(605);
+        let states: Vec<SqOne> = random_walk(&SqOne::new(), 5, &mut rng).collect();
+        assert_eq!(states.len(), 5);
+        assert!(states.iter().all(|cube| cube.invariants().is_ok()));
+
+        // A solved cube is far from random,
+        // and 20 steps come much closer.
+        assert!(feature_distance(0, 500, &mut rng) > 0.9);
+        assert!(feature_distance(20, 500, &mut rng) < 0.5);
+        assert!(walk_distance(1, 30, 500, &mut rng) > 0.9);
+        assert!((3..20).contains(&mixing_time(0.4, 20, 500, &mut rng)));
+    }
+}
@@ -0,0 +1,178 @@
+//! Module exporting the scrambler to
+//! JavaScript when built for wasm32.
+//! The exports are made with wasm-bindgen,
+//! so strings and errors cross into JS
+//! through the glue it generates, and
+//! wasm/sq1.js wraps them for JS with
+//! types in wasm/sq1.d.ts.
+//!
+//! A cube state is written as the piece
+//! in each slot of the top and bottom
+//! layers as hex digits, where corners
+//! fill two slots, followed by 0 or 1
+//! for a solved or flipped middle:
+//! `00811922a33b/44c55d66e77f/0` with
+//! the bottom read from slot 0 like
+//! `SqOne::slots`.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use wasm_bindgen::prelude::*;
+
+use crate::cstimer;
+use crate::cube::SqOne;
+use crate::render;
+use crate::ScrambleOptions;
+
+/// Returns a scramble generated with
+/// the options, given as a comma
+/// separated list of `wca`,
+/// `random-state`, `even`, `odd` and
+/// `min-slashes=N`. The seed picks the
+/// scramble, since wasm32 has no source
+/// of randomness without JS.
+pub fn generate_scramble(options: &str, seed: u64) -> Result<String, String> {
+    let mut parsed = ScrambleOptions::new();
+    for option in options.split(',').map(str::trim).filter(|o| !o.is_empty()) {
+        parsed = match option {
+            "wca" => ScrambleOptions::wca(),
+            "random-state" => parsed.random_state(true),
+            "even" => parsed.require_parity(false),
+            "odd" => parsed.require_parity(true),
+            _ => match option.strip_prefix("min-slashes=").map(str::parse) {
+                Some(Ok(slashes)) => parsed.min_slashes(slashes).map_err(|error| error.to_string())?,
+                _ => return Err(format!("unknown option {}", option)),
+            },
+        };
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let scramble = SqOne::new().try_scramble_with_rng(&parsed, &mut rng).map_err(|error| error.to_string())?;
+    Ok(cstimer::format(&scramble))
+}
+
+/// Returns the state reached by applying
+/// the scramble, in csTimer notation,
+/// to the state.
+pub fn apply_scramble(state: &str, scramble: &str) -> Result<String, String> {
+    let mut cube = parse_state(state)?;
+    let scramble = cstimer::parse(scramble).map_err(|error| error.to_string())?;
+    cube.apply(&scramble).map_err(|slash| format!("slash {} is blocked", slash + 1))?;
+    Ok(format_state(&cube))
+}
+
+/// Returns the state drawn as an
+/// SVG image.
+pub fn render_svg(state: &str) -> Result<String, String> {
+    Ok(render::svg(&parse_state(state)?))
+}
+
+/// Returns the state of the cube.
+pub fn format_state(cube: &SqOne) -> String {
+    let (top, bottom, middle) = cube.slots();
+    let layer = |slots: [u8; 12]| -> String {
+        slots.iter().map(|&piece| char::from_digit(piece as u32, 16).unwrap()).collect()
+    };
+
+    format!("{}/{}/{}", layer(top), layer(bottom), middle as u8)
+}
+
+/// Returns the cube of a state, or an
+/// error if it is not a possible state.
+pub fn parse_state(state: &str) -> Result<SqOne, String> {
+    let invalid = || format!("invalid state {}", state);
+    let parts: Vec<&str> = state.trim().split('/').collect();
+    let [top, bottom, middle] = parts[..] else {
+        return Err(invalid());
+    };
+    let layer = |text: &str| -> Option<[u8; 12]> {
+        let digits: Vec<u8> = text.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>()?;
+        digits.try_into().ok()
+    };
+    let (top, bottom) = layer(top).zip(layer(bottom)).ok_or_else(invalid)?;
+    let middle = match middle {
+        "0" => false,
+        "1" => true,
+        _ => return Err(invalid()),
+    };
+
+    // Each corner fills two neighbouring
+    // slots of one layer and each edge
+    // fills a single slot.
+    for slots in [&top, &bottom] {
+        for i in 0..12 {
+            let piece = slots[i];
+            let pair = piece == slots[(i + 1) % 12] || piece == slots[(i + 11) % 12];
+            if (piece < 8) != pair {
+                return Err(invalid());
+            }
+        }
+    }
+    let mut counts = [0; 16];
+    for &piece in top.iter().chain(bottom.iter()) {
+        counts[piece as usize] += 1;
+    }
+    if (0..16).any(|piece| counts[piece] != if piece < 8 { 2 } else { 1 }) {
+        return Err(invalid());
+    }
+
+    let cube = SqOne::from_slots(&top, &bottom, middle);
+    if cube.slots() != (top, bottom, middle) {
+        return Err(invalid());
+    }
+
+    Ok(cube)
+}
+
+/// Exports generate_scramble to JS
+/// as generateScramble, throwing the
+/// error message.
+#[wasm_bindgen(js_name = generateScramble)]
+pub fn export_generate_scramble(options: &str, seed: u64) -> Result<String, JsError> {
+    generate_scramble(options, seed).map_err(|message| JsError::new(&message))
+}
+
+/// Exports apply_scramble to JS
+/// as applyScramble.
+#[wasm_bindgen(js_name = applyScramble)]
+pub fn export_apply_scramble(state: &str, scramble: &str) -> Result<String, JsError> {
+    apply_scramble(state, scramble).map_err(|message| JsError::new(&message))
+}
+
+/// Exports render_svg to JS
+/// as renderSvg.
+#[wasm_bindgen(js_name = renderSvg)]
+pub fn export_render_svg(state: &str) -> Result<String, JsError> {
+    render_svg(state).map_err(|message| JsError::new(&message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_round_trip() {
+        assert_eq!(format_state(&SqOne::new()), "00811922a33b/44c55d66e77f/0");
+        let mut cube = SqOne::new();
+        cube.scramble();
+        let state = format_state(&cube);
+        assert_eq!(format_state(&parse_state(&state).unwrap()), state);
+    }
+
+    #[test]
+    fn reject_impossible_states() {
+        let solved = format_state(&SqOne::new());
+        assert!(parse_state(&solved.replacen('8', "9", 1)).is_err());
+        assert!(parse_state(&solved[1..]).is_err());
+        assert!(parse_state(&solved.replace("/0", "/2")).is_err());
+    }
+
+    #[test]
+    fn apply_generated_scramble() {
+        let scramble = generate_scramble("random-state, even", 7).unwrap();
+        assert_eq!(generate_scramble("random-state,even", 7).unwrap(), scramble);
+        let state = apply_scramble(&format_state(&SqOne::new()), &scramble).unwrap();
+        assert!(render_svg(&state).unwrap().starts_with("<svg"));
+        assert!(generate_scramble("fast", 7).is_err());
+    }
+}
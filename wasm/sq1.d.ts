@@ -0,0 +1,29 @@
+/**
+ * A cube state: the piece in each slot of
+ * the top and bottom layers as hex digits,
+ * then 0 or 1 for the middle layer.
+ */
+export type State = string;
+
+export interface ScrambleOptions {
+  wca?: boolean;
+  randomState?: boolean;
+  parity?: "even" | "odd";
+  minSlashes?: number;
+  seed?: number | bigint;
+}
+
+export interface Sq1 {
+  readonly SOLVED: State;
+
+  /** Returns a scramble in csTimer notation. */
+  generateScramble(options?: ScrambleOptions): string;
+
+  /** Returns the state after the scramble. */
+  applyScramble(state: State, scramble: string): State;
+
+  /** Returns the state drawn as SVG. */
+  renderSvg(state: State): string;
+}
+
+export function load(bytes: BufferSource): Promise<Sq1>;
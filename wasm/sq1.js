@@ -0,0 +1,33 @@
+// Wrapper around the wasm exports of
+// sq1-scrambler. Build the module and its
+// glue with
+//
+//   cargo rustc --release --lib --features wasm \
+//     --target wasm32-unknown-unknown --crate-type cdylib
+//   wasm-bindgen --target web --out-dir wasm/pkg \
+//     target/wasm32-unknown-unknown/release/sq1_scrambler.wasm
+//
+// and pass the bytes of the .wasm file to load.
+
+import init, { applyScramble, generateScramble, renderSvg } from "./pkg/sq1_scrambler.js";
+
+export async function load(bytes) {
+  await init({ module_or_path: bytes });
+
+  const SOLVED = "00811922a33b/44c55d66e77f/0";
+
+  return {
+    SOLVED,
+    generateScramble: (options = {}) => {
+      const list = [];
+      if (options.wca) list.push("wca");
+      if (options.randomState) list.push("random-state");
+      if (options.parity === "even" || options.parity === "odd") list.push(options.parity);
+      if (options.minSlashes !== undefined) list.push(`min-slashes=${options.minSlashes}`);
+      const seed = options.seed ?? BigInt(Math.floor(Math.random() * 2 ** 53));
+      return generateScramble(list.join(","), BigInt(seed));
+    },
+    applyScramble: (state, scramble) => applyScramble(state, scramble),
+    renderSvg: (state) => renderSvg(state),
+  };
+}